@@ -0,0 +1,13 @@
+#ifndef _STDARG_H
+#define _STDARG_H 1
+
+#include <va_list.h>
+
+typedef __gnuc_va_list va_list;
+
+#define va_start(ap, last) __builtin_va_start(ap, last)
+#define va_arg(ap, type) __builtin_va_arg(ap, type)
+#define va_end(ap) __builtin_va_end(ap)
+#define va_copy(dest, src) __builtin_va_copy(dest, src)
+
+#endif
@@ -1,4 +1,5 @@
 #include <FILE.h>
+#include <va_list.h>
 
 #define EOF (-1)
 
@@ -13,4 +14,10 @@ extern int printf (char *__format, ...);
 extern int scanf (char *__format, ...);
 
 extern int dprintf (int __fd, char * __fmt, ...);
-extern int fprintf (FILE * __stream, char * __format, ...);
\ No newline at end of file
+extern int fprintf (FILE * __stream, char * __format, ...);
+
+extern int vprintf (char *__format, __gnuc_va_list __arg);
+extern int vfprintf (FILE *__s, char *__format, __gnuc_va_list __arg);
+extern int vsprintf (char *__s, char *__format, __gnuc_va_list __arg);
+extern int vsnprintf (char *__s, unsigned long __maxlen, char *__format, __gnuc_va_list __arg);
+extern int vdprintf (int __fd, char *__fmt, __gnuc_va_list __arg);
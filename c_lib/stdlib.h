@@ -1 +1,5 @@
-extern int atoi (char *__nptr);
\ No newline at end of file
+extern int atoi (char *__nptr);
+
+extern _Noreturn void abort (void);
+extern _Noreturn void exit (int __status);
+extern _Noreturn void _Exit (int __status);
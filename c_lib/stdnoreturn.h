@@ -0,0 +1 @@
+#define noreturn _Noreturn
@@ -0,0 +1,12 @@
+#ifndef __va_list_defined
+#define __va_list_defined 1
+
+/* Layout of a va_list, as per the SysV x86-64 ABI.  */
+typedef struct __va_list_tag {
+    unsigned int gp_offset;		/* Offset of the next unnamed arg in the GP registers.  */
+    unsigned int fp_offset;		/* Offset of the next unnamed arg in the vector registers.  */
+    void *overflow_arg_area;	/* Next unnamed arg passed on the stack.  */
+    void *reg_save_area;		/* Where the arg registers were saved.  */
+} __gnuc_va_list[1];
+
+#endif
@@ -2,8 +2,8 @@ use memory_size::MemorySize;
 
 use crate::{args_handling::location_classification::{PreferredParamLocation, StructEightbytePreferredLocation}, asm_gen_data::GetStructUnion, assembly::operand::{register::{GPRegister, MMRegister}, Storage}, data_type::{base_type::BaseType, recursive_data_type::DataType}};
 
-const MAX_GP_REGS: u64 = 6;
-const MAX_XMM_REGS: u64 = 8;
+pub const MAX_GP_REGS: u64 = 6;
+pub const MAX_XMM_REGS: u64 = 8;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum EightByteLocation {
@@ -34,9 +34,8 @@ where ArgIter: IntoIterator<Item = &'a DataType>
     let return_loc = generate_only_return_location(return_type, get_struct_union);
     if return_loc == Some(ReturnLocation::HiddenPointer) {
         arg_alloc.integer_regs_used += 1;//first register is a hidden pointer
-        todo!();
     }
-    
+
 
     let params_loc = 
         arg_types.into_iter()
@@ -137,7 +136,7 @@ impl ArgAllocator {
     }
 }
 
-fn gp_arg(idx: u64) -> GPRegister {
+pub fn gp_arg(idx: u64) -> GPRegister {
     match idx {
         0 => GPRegister::_DI,//starts at 1 because I have already incremented the counter
         1 => GPRegister::_SI,
@@ -148,7 +147,7 @@ fn gp_arg(idx: u64) -> GPRegister {
         _ => panic!("this param should be on the stack.")
     }
 }
-fn xmm_arg(idx: u64) -> MMRegister {
+pub fn xmm_arg(idx: u64) -> MMRegister {
     match idx {
         0 => MMRegister::XMM0,
         1 => MMRegister::XMM1,
@@ -27,11 +27,13 @@ impl PreferredParamLocation {
     pub fn param_from_type(data_type: &DataType, get_struct_union: &dyn GetStructUnion) -> PreferredParamLocation {
         match data_type {
             DataType::UNKNOWNSIZEARRAY { .. } |
-            DataType::ARRAY {..} => PreferredParamLocation::InGP,//decays to a pointer, which is integer
+            DataType::ARRAY {..} |
+            DataType::FUNCTION {..} => PreferredParamLocation::InGP,//decays to a pointer, which is integer
 
             DataType::POINTER(_) => PreferredParamLocation::InGP,// pointer can be passed as an integer type
             DataType::RAW(base_type) => 
                 Self::param_from_base_type(base_type, get_struct_union),
+            DataType::QUALIFIED { inner, .. } => Self::param_from_type(inner, get_struct_union),
         }
     }
 
@@ -1,2 +1,3 @@
 pub mod location_classification;
 pub mod location_allocation;
+pub mod va_args;
@@ -0,0 +1,211 @@
+//! This file handles `va_list`s, so that variadic functions can read args that were not named in their declaration
+
+use memory_size::MemorySize;
+use stack_management::{simple_stack_frame::SimpleStackFrame, stack_item::StackItemKey};
+use uuid::Uuid;
+
+use crate::{args_handling::{location_allocation::{AllocatedLocation, EightByteLocation, MAX_GP_REGS, MAX_XMM_REGS}, location_classification::{PreferredParamLocation, StructEightbytePreferredLocation}}, asm_gen_data::AsmData, assembly::{assembly::IRCode, comparison::AsmComparison, operand::{IRMemOperand, IROperand, Storage, PTR_SIZE}, operation::{IROperation, Label}}, data_type::{base_type::{IntegerType, ScalarType}, recursive_data_type::DataType}, number_literal::typed_value::NumberLiteral};
+
+/// Each GP register takes up 8 bytes at the start of the register save area
+const GP_SAVE_AREA_SIZE: u64 = 8 * MAX_GP_REGS;
+/// Each vector register takes up 16 bytes, after the GP registers
+const XMM_SAVE_AREA_SIZE: u64 = 16 * MAX_XMM_REGS;
+/// Where every register that could hold an arg is stored, at the start of a variadic function
+pub const REG_SAVE_AREA_SIZE: MemorySize = MemorySize::from_bytes(GP_SAVE_AREA_SIZE + XMM_SAVE_AREA_SIZE);
+
+//offsets of each member of a va_list, as per the SYSV ABI:
+//struct { unsigned int gp_offset; unsigned int fp_offset; void* overflow_arg_area; void* reg_save_area; }
+const GP_OFFSET_MEMBER: MemorySize = MemorySize::from_bytes(0);
+const FP_OFFSET_MEMBER: MemorySize = MemorySize::from_bytes(4);
+const OVERFLOW_ARG_AREA_MEMBER: MemorySize = MemorySize::from_bytes(8);
+const REG_SAVE_AREA_MEMBER: MemorySize = MemorySize::from_bytes(16);
+const VA_LIST_SIZE: MemorySize = MemorySize::from_bytes(24);
+
+/// skips the pushed rbp and the return address, to find the first memory arg
+const FIRST_MEMORY_ARG: MemorySize = MemorySize::from_bytes(16);
+
+/// Where the first unnamed arg of a variadic function could be, so that `va_start` can point a `va_list` at it
+#[derive(Clone)]
+pub struct VaStartInfo {
+    /// where each arg register was saved in the function's stack frame
+    reg_save_area: StackItemKey,
+    /// offset into the register save area of the first unused GP register
+    gp_offset: u64,
+    /// offset into the register save area of the first unused vector register
+    fp_offset: u64,
+    /// how far past the first memory arg the unnamed memory args start
+    overflow_arg_offset: MemorySize,
+}
+
+impl VaStartInfo {
+    /// Counts the registers and memory used by the named params, as unnamed args are stored after them
+    pub fn new<'a, ParamIter>(named_params: ParamIter, reg_save_area: StackItemKey) -> VaStartInfo
+    where ParamIter: IntoIterator<Item = (&'a AllocatedLocation, MemorySize)>
+    {
+        let mut result = VaStartInfo { reg_save_area, gp_offset: 0, fp_offset: GP_SAVE_AREA_SIZE, overflow_arg_offset: MemorySize::new() };
+
+        for (location, param_size) in named_params {
+            match location {
+                AllocatedLocation::Regs(eightbyte_locations) => {
+                    for eightbyte in eightbyte_locations {
+                        match eightbyte {
+                            EightByteLocation::GP(_) => result.gp_offset += 8,
+                            EightByteLocation::XMM(_) => result.fp_offset += 16,
+                        }
+                    }
+                },
+                AllocatedLocation::Memory => result.overflow_arg_offset += param_size.align_up(&MemorySize::from_bytes(8)),//memory args are padded to eightbytes
+            }
+        }
+
+        result
+    }
+}
+
+/// Initialises the `va_list` pointed to by `va_list_ptr`, so that the next `va_arg` gets the first unnamed arg
+pub fn generate_va_start(va_list_ptr: StackItemKey, va_start_info: &VaStartInfo) -> IRCode {
+    let mut result = IRCode::make_empty();
+
+    result.add_comment("va_start");
+    result.add_instruction(IROperation::MOV {
+        from: IROperand::Constant(offset_literal(va_start_info.gp_offset)),
+        to: va_list_member(va_list_ptr, GP_OFFSET_MEMBER),
+        size: MemorySize::from_bytes(4),
+    });
+    result.add_instruction(IROperation::MOV {
+        from: IROperand::Constant(offset_literal(va_start_info.fp_offset)),
+        to: va_list_member(va_list_ptr, FP_OFFSET_MEMBER),
+        size: MemorySize::from_bytes(4),
+    });
+    result.add_instruction(IROperation::LEA {
+        from: IRMemOperand::PreviousStackFrame { add_to_rbp: FIRST_MEMORY_ARG + va_start_info.overflow_arg_offset },
+        to: va_list_member(va_list_ptr, OVERFLOW_ARG_AREA_MEMBER),
+    });
+    result.add_instruction(IROperation::LEA {
+        from: IRMemOperand::Stack { base: va_start_info.reg_save_area },
+        to: va_list_member(va_list_ptr, REG_SAVE_AREA_MEMBER),
+    });
+
+    result
+}
+
+/// Reads the next unnamed arg as `data_type` from the `va_list` pointed to by `va_list_ptr`, and moves the `va_list` past it
+///
+/// Returns the IR and the location of the arg
+pub fn generate_va_arg(va_list_ptr: StackItemKey, data_type: &DataType, asm_data: &AsmData, stack_data: &mut SimpleStackFrame) -> (IRCode, StackItemKey) {
+    let mut result = IRCode::make_empty();
+
+    let arg_size = data_type.memory_size(asm_data);
+    let arg_value = stack_data.allocate(arg_size);
+
+    let generic_label = Uuid::new_v4().simple().to_string();
+    let in_memory_label = Label::Local(format!("{}_va_arg_memory", generic_label));//when the arg was passed on the stack
+    let va_arg_end_label = Label::Local(format!("{}_va_arg_end", generic_label));//rendevous point for register and memory args
+
+    result.add_comment(format!("va_arg as {}", data_type));
+
+    //the arg would have been in these registers if it was a named param
+    let eightbyte_locations = match PreferredParamLocation::param_from_type(data_type, asm_data) {
+        PreferredParamLocation::InGP => Some(vec![StructEightbytePreferredLocation::InGP]),
+        PreferredParamLocation::InMMX => Some(vec![StructEightbytePreferredLocation::InMMX]),
+        PreferredParamLocation::Struct { l, r } => Some(vec![l, r]),
+        PreferredParamLocation::InMemory => None,
+    };
+
+    if let Some(eightbyte_locations) = eightbyte_locations {
+        let gp_regs_required: u64 = eightbyte_locations.iter().filter(|x| **x == StructEightbytePreferredLocation::InGP).count().try_into().unwrap();
+        let xmm_regs_required: u64 = eightbyte_locations.iter().filter(|x| **x == StructEightbytePreferredLocation::InMMX).count().try_into().unwrap();
+
+        let gp_offset = stack_data.allocate(MemorySize::from_bytes(4));
+        let fp_offset = stack_data.allocate(MemorySize::from_bytes(4));
+        result.add_instruction(IROperation::MOV { from: IROperand::Memory(va_list_member(va_list_ptr, GP_OFFSET_MEMBER)), to: IRMemOperand::Stack { base: gp_offset }, size: MemorySize::from_bytes(4) });
+        result.add_instruction(IROperation::MOV { from: IROperand::Memory(va_list_member(va_list_ptr, FP_OFFSET_MEMBER)), to: IRMemOperand::Stack { base: fp_offset }, size: MemorySize::from_bytes(4) });
+
+        //the whole arg is in memory if there were not enough registers left for it
+        if gp_regs_required > 0 {
+            result.add_instruction(IROperation::CMP { lhs: Storage::Stack(gp_offset), rhs: Storage::Constant(offset_literal(GP_SAVE_AREA_SIZE - 8 * gp_regs_required)), data_type: ScalarType::Integer(IntegerType::U32) });
+            result.add_instruction(IROperation::JMPCC { label: in_memory_label.clone(), comparison: AsmComparison::G { signed: false } });
+        }
+        if xmm_regs_required > 0 {
+            result.add_instruction(IROperation::CMP { lhs: Storage::Stack(fp_offset), rhs: Storage::Constant(offset_literal(REG_SAVE_AREA_SIZE.size_bytes() - 16 * xmm_regs_required)), data_type: ScalarType::Integer(IntegerType::U32) });
+            result.add_instruction(IROperation::JMPCC { label: in_memory_label.clone(), comparison: AsmComparison::G { signed: false } });
+        }
+
+        let reg_save_area = stack_data.allocate(PTR_SIZE);
+        result.add_instruction(IROperation::MOV { from: IROperand::Memory(va_list_member(va_list_ptr, REG_SAVE_AREA_MEMBER)), to: IRMemOperand::Stack { base: reg_save_area }, size: PTR_SIZE });
+
+        let extended_offset = stack_data.allocate(PTR_SIZE);
+        let eightbyte_ptr = stack_data.allocate(PTR_SIZE);
+        //structs split across GP and vector registers are not contiguous in the register save area, so copy each eightbyte separately
+        for (i, eightbyte_location) in eightbyte_locations.iter().enumerate() {
+            let (offset, register_size) = match eightbyte_location {
+                StructEightbytePreferredLocation::InGP => (gp_offset, 8),
+                StructEightbytePreferredLocation::InMMX => (fp_offset, 16),
+            };
+            let eightbyte_offset = MemorySize::from_bytes((8*i).try_into().unwrap());
+            let eightbyte_size = (arg_size - eightbyte_offset).min(MemorySize::from_bytes(8));//last eightbyte could be a few bytes remaining of a struct
+
+            //find where the register was saved
+            result.add_instruction(IROperation::CAST { from: Storage::Stack(offset), from_type: ScalarType::Integer(IntegerType::U32), to: Storage::Stack(extended_offset), to_type: ScalarType::Integer(IntegerType::U64) });
+            result.add_instruction(IROperation::ADD { lhs: Storage::Stack(reg_save_area), rhs: Storage::Stack(extended_offset), to: Storage::Stack(eightbyte_ptr), data_type: ScalarType::Integer(IntegerType::U64) });
+            //copy this eightbyte of the arg
+            result.add_instruction(IROperation::MOV {
+                from: IROperand::Memory(IRMemOperand::IndirectAddress { pointer_location: Box::new(IRMemOperand::Stack { base: eightbyte_ptr }) }),
+                to: IRMemOperand::OffsetAddress { base: Box::new(IRMemOperand::Stack { base: arg_value }), displacement: eightbyte_offset },
+                size: eightbyte_size,
+            });
+            //consume the register
+            result.add_instruction(IROperation::ADD { lhs: Storage::Stack(offset), rhs: Storage::Constant(offset_literal(register_size)), to: Storage::Stack(offset), data_type: ScalarType::Integer(IntegerType::U32) });
+        }
+
+        //save the registers consumed
+        result.add_instruction(IROperation::MOV { from: IROperand::Memory(IRMemOperand::Stack { base: gp_offset }), to: va_list_member(va_list_ptr, GP_OFFSET_MEMBER), size: MemorySize::from_bytes(4) });
+        result.add_instruction(IROperation::MOV { from: IROperand::Memory(IRMemOperand::Stack { base: fp_offset }), to: va_list_member(va_list_ptr, FP_OFFSET_MEMBER), size: MemorySize::from_bytes(4) });
+
+        result.add_instruction(IROperation::JMPCC { label: va_arg_end_label.clone(), comparison: AsmComparison::ALWAYS });
+    }
+
+    result.add_instruction(IROperation::Label(in_memory_label));
+    let overflow_arg_area = stack_data.allocate(PTR_SIZE);
+    result.add_instruction(IROperation::MOV { from: IROperand::Memory(va_list_member(va_list_ptr, OVERFLOW_ARG_AREA_MEMBER)), to: IRMemOperand::Stack { base: overflow_arg_area }, size: PTR_SIZE });
+    //copy the arg from the stack
+    result.add_instruction(IROperation::MOV {
+        from: IROperand::Memory(IRMemOperand::IndirectAddress { pointer_location: Box::new(IRMemOperand::Stack { base: overflow_arg_area }) }),
+        to: IRMemOperand::Stack { base: arg_value },
+        size: arg_size,
+    });
+    //skip past the arg, which is padded to an eightbyte
+    let padded_arg_size = arg_size.align_up(&MemorySize::from_bytes(8)).size_bytes();
+    result.add_instruction(IROperation::ADD { lhs: Storage::Stack(overflow_arg_area), rhs: Storage::Constant(NumberLiteral::INTEGER { data: padded_arg_size.into(), data_type: IntegerType::U64 }), to: Storage::Stack(overflow_arg_area), data_type: ScalarType::Integer(IntegerType::U64) });
+    result.add_instruction(IROperation::MOV { from: IROperand::Memory(IRMemOperand::Stack { base: overflow_arg_area }), to: va_list_member(va_list_ptr, OVERFLOW_ARG_AREA_MEMBER), size: PTR_SIZE });
+
+    result.add_instruction(IROperation::Label(va_arg_end_label));
+
+    (result, arg_value)
+}
+
+/// Copies the `va_list` pointed to by `src_ptr` to the one pointed to by `dest_ptr`, so both read the same remaining args
+pub fn generate_va_copy(dest_ptr: StackItemKey, src_ptr: StackItemKey) -> IRCode {
+    let mut result = IRCode::make_empty();
+
+    result.add_commented_instruction(IROperation::MOV {
+        from: IROperand::Memory(IRMemOperand::IndirectAddress { pointer_location: Box::new(IRMemOperand::Stack { base: src_ptr }) }),
+        to: IRMemOperand::IndirectAddress { pointer_location: Box::new(IRMemOperand::Stack { base: dest_ptr }) },
+        size: VA_LIST_SIZE,
+    }, "va_copy");
+
+    result
+}
+
+/// Finds the member of the `va_list` pointed to by `va_list_ptr`, at the specified offset
+fn va_list_member(va_list_ptr: StackItemKey, member_offset: MemorySize) -> IRMemOperand {
+    IRMemOperand::OffsetAddress {
+        base: Box::new(IRMemOperand::IndirectAddress { pointer_location: Box::new(IRMemOperand::Stack { base: va_list_ptr }) }),
+        displacement: member_offset
+    }
+}
+
+/// gp_offset and fp_offset are both unsigned ints
+fn offset_literal(offset: u64) -> NumberLiteral {
+    NumberLiteral::INTEGER { data: offset.into(), data_type: IntegerType::U32 }
+}
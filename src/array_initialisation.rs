@@ -1,8 +1,10 @@
-use crate::{data_type::recursive_data_type::DataType, debugging::{ASTDisplay, TreeDisplayInfo}, expression::expression::{self, Expression}, generate_ir_traits::GetType, lexer::{punctuator::Punctuator, token::Token, token_savepoint::TokenQueueSlice, token_walk::{TokenQueue, TokenSearchType}}, number_literal::typed_value::NumberLiteral, parse_data::ParseData};
+use serde::Serialize;
+use crate::{data_type::recursive_data_type::DataType, debugging::{ASTDisplay, TreeDisplayInfo}, expression::expression::{self, Expression}, generate_ir_traits::GetType, lexer::{punctuator::Punctuator, source_span::SourceSpan, token::Token, token_savepoint::TokenQueueSlice, token_walk::{TokenQueue, TokenSearchType}}, number_literal::typed_value::NumberLiteral, parse_data::ParseData};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct ArrayInitialisation {
-    elements: Vec<Expression>
+    elements: Vec<Expression>,
+    span: SourceSpan,
 }
 
 impl ArrayInitialisation {
@@ -12,6 +14,8 @@ impl ArrayInitialisation {
             return None;//initialisation must be the whole slice
         }
 
+        let span = tokens_queue.get_span(previous_queue_idx);
+
         //strip the { }
         let curr_queue_idx = TokenQueueSlice {
             index: previous_queue_idx.index + 1,
@@ -23,6 +27,7 @@ impl ArrayInitialisation {
             //int x[4] = {};
             return Some(Self {
                 elements: Vec::new(),
+                span,
             });
         }
 
@@ -38,10 +43,17 @@ impl ArrayInitialisation {
         }
 
         Some(
-            ArrayInitialisation{ elements: parsed }
+            ArrayInitialisation{ elements: parsed, span }
         )
     }
 
+    pub fn get_span(&self) -> &SourceSpan {
+        &self.span
+    }
+    pub fn get_elements(&self) -> &[Expression] {
+        &self.elements
+    }
+
     pub fn calculate_element_count(&self) -> u64 {
         self.elements.iter()
         .map(|x| {
@@ -78,7 +90,7 @@ impl ArrayInitialisation {
                 let zero_padded = flattened_elements.into_iter()
                     // int x[4] = {1,2} => {1,2,0,0}
                     .chain(std::iter::repeat_n(
-                        Expression::NUMBERLITERAL(NumberLiteral::from(0)), extra_zeroes_required)
+                        Expression::NUMBERLITERAL(NumberLiteral::from(0), self.span.clone()), extra_zeroes_required)//the zeroes come from the initialisation being too short
                     )
                     .collect();
                 
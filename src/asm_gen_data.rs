@@ -1,6 +1,6 @@
-use stack_management::simple_stack_frame::SimpleStackFrame;
+use stack_management::{simple_stack_frame::SimpleStackFrame, stack_item::StackItemKey};
 
-use crate::{args_handling::location_allocation::ReturnLocation, assembly::{operand::Storage, operation::Label}, data_type::recursive_data_type::DataType, function_declaration::FunctionDeclaration, parse_data::ParseData, struct_definition::{StructDefinition, StructIdentifier}, union_definition::{UnionDefinition, UnionIdentifier}};
+use crate::{args_handling::{location_allocation::ReturnLocation, va_args::VaStartInfo}, assembly::{operand::Storage, operation::Label}, data_type::recursive_data_type::DataType, function_declaration::FunctionDeclaration, parse_data::ParseData, struct_definition::{StructDefinition, StructIdentifier}, union_definition::{UnionDefinition, UnionIdentifier}};
 
 pub trait GetStructUnion {
     fn get_struct(&self, name: &StructIdentifier) -> &StructDefinition;
@@ -21,13 +21,16 @@ pub struct AsmData {
     struct_list: Vec<(StructIdentifier, StructDefinition)>,//needs to be ordered since some structs need previously declared structs as members
     union_list: Vec<(UnionIdentifier, UnionDefinition)>,
     break_label: Option<Label>,//which label to jump to on a "break;" statement
+    continue_label: Option<Label>,//which label to jump to on a "continue;" statement
+    va_start_info: Option<VaStartInfo>,//where the unnamed args are, if this function is variadic
+    hidden_pointer_location: Option<StackItemKey>,//where the pointer to write the return value to is saved, if returning in memory
 }
 
 /// Stores information that is required globally and does not change when entering new scopes, like the list of accessible functions
 pub struct GlobalAsmData {
     function_decls: Vec<FunctionDeclaration>,
     /// any variable that is accessed via a label, like extern and static variables.
-    /// static variables in functions are also stored here
+    /// static variables in functions are also stored here, as well as functions so that they can be used as function designators
     global_variables: Vec<(String, AddressedDeclaration)>,
     /// all structs declared at a global scope
     global_structs: Vec<(StructIdentifier, StructDefinition)>,
@@ -39,6 +42,7 @@ impl GlobalAsmData {
         let global_variables = global_parse_data.get_symbol_table()
             .iter()
             .map(generate_global_variable_decl)
+            .chain(global_parse_data.func_declarations_as_vec().iter().map(generate_function_designator_decl))
             .collect();
 
         //generate a partially complete self, so that structs can be padded using myself
@@ -68,7 +72,7 @@ impl GlobalAsmData {
 }
 
 impl AsmData {
-    pub fn for_new_function(global_asm_data: &GlobalAsmData, parse_data: &ParseData, current_function_return_type: DataType, current_function_return_addr: Option<ReturnLocation>, stack_data: &mut SimpleStackFrame) -> AsmData {
+    pub fn for_new_function(global_asm_data: &GlobalAsmData, parse_data: &ParseData, current_function_return_type: DataType, current_function_return_addr: Option<ReturnLocation>, va_start_info: Option<VaStartInfo>, hidden_pointer_location: Option<StackItemKey>, stack_data: &mut SimpleStackFrame) -> AsmData {
         let mut result = Self {
             variables: global_asm_data.global_variables.clone(),
             return_type: current_function_return_type,
@@ -76,6 +80,9 @@ impl AsmData {
             struct_list: global_asm_data.global_structs.clone(),
             union_list: global_asm_data.global_unions.clone(),
             break_label: None,
+            continue_label: None,
+            va_start_info,
+            hidden_pointer_location,
         };
 
         //when creating local variables, I need struct data beforehand
@@ -120,7 +127,16 @@ impl AsmData {
         result
     }
 
-    pub fn clone_for_new_loop(&self, break_jump_label: Label) -> AsmData {
+    pub fn clone_for_new_loop(&self, break_jump_label: Label, continue_jump_label: Label) -> AsmData {
+        let mut result = self.clone();
+        result.break_label = Some(break_jump_label);
+        result.continue_label = Some(continue_jump_label);
+
+        result
+    }
+
+    /// Switch statements can be broken out of, but "continue;" still refers to the enclosing loop
+    pub fn clone_for_new_switch(&self, break_jump_label: Label) -> AsmData {
         let mut result = self.clone();
         result.break_label = Some(break_jump_label);
 
@@ -128,12 +144,15 @@ impl AsmData {
     }
 
     pub fn get_variable(&self, name: &str) -> &AddressedDeclaration {
-        &self.variables
+        self.try_get_variable(name)
+        .expect(&format!("cannot find variable {}", name))
+    }
+    pub fn try_get_variable(&self, name: &str) -> Option<&AddressedDeclaration> {
+        self.variables
         .iter()
         .rev()
         .find(|(n, _)| n == name)
-        .expect(&format!("cannot find variable {}", name))
-        .1
+        .map(|(_, decl)| decl)
     }
     pub fn get_function_return_type(&self) -> &DataType {
         &self.return_type
@@ -142,6 +161,15 @@ impl AsmData {
     pub fn get_break_label(&self) -> Option<&Label> {
         self.break_label.as_ref()
     }
+    pub fn get_continue_label(&self) -> Option<&Label> {
+        self.continue_label.as_ref()
+    }
+    pub fn get_va_start_info(&self) -> Option<&VaStartInfo> {
+        self.va_start_info.as_ref()
+    }
+    pub fn get_hidden_pointer_location(&self) -> Option<StackItemKey> {
+        self.hidden_pointer_location
+    }
 }
 
 impl GetStructUnion for AsmData {
@@ -187,6 +215,13 @@ impl GetStructUnion for GlobalAsmData {
  */
 fn generate_global_variable_decl(data: &(String, DataType)) -> (String, AddressedDeclaration) {
     let (var_name, var_type) = data;
-    panic!()
-    // (var_name.to_string(), AddressedDeclaration{ data_type: var_type.clone(), location: Storage(var_name.to_string()) })
+    //the data is saved under a label of the same name by the translation unit
+    (var_name.to_string(), AddressedDeclaration{ data_type: var_type.clone(), location: Storage::Label(Label::Global(var_name.to_string())) })
+}
+
+/**
+ * functions are accessed via their label, and decay to a pointer to that label when used as a value
+ */
+fn generate_function_designator_decl(decl: &FunctionDeclaration) -> (String, AddressedDeclaration) {
+    (decl.function_name.clone(), AddressedDeclaration { data_type: decl.as_data_type(), location: Storage::Label(Label::Global(decl.function_name.clone())) })
 }
\ No newline at end of file
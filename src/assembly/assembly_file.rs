@@ -1,16 +1,19 @@
 
+use std::path::Path;
+
 use stack_management::{baked_stack_frame::BakedSimpleStackFrame, simple_stack_frame::SimpleStackFrame};
 
-use crate::assembly::operand::STACK_ALIGN;
+use crate::{assembly::{global_data::GlobalData, ir_text::{self, IR_TEXT_VERSION}, operand::STACK_ALIGN, operation::{IROperation, Label}}, diagnostics::diagnostic::Diagnostic};
 
 use super::assembly::IRCode;
 
 
 pub struct AssemblyFile {
-    string_literal_lines: Vec<String>,//raw assembly lines defining string literals
+    string_literals: Vec<GlobalData>,//the bytes of each string literal
     global_labels: Vec<String>,//function names that are exported
     extern_labels: Vec<String>,//function names that are imported
-    global_variable_init: Vec<String>,//initialise static and auto variables
+    global_variables: Vec<GlobalData>,//initialise static and auto variables
+    read_only_variables: Vec<GlobalData>,//initialise const variables, which are never written to
     functions: Vec<(IRCode, SimpleStackFrame)>,//list of each function
 }
 
@@ -30,9 +33,33 @@ impl AssemblyFile {
             .map(|label| format!("extern {}\n", label))
             .collect();
 
-        let string_literals = self.string_literal_lines.join("\n");
+        let data_lines = |data: &[GlobalData]| data.iter()
+            .map(|x| x.to_nasm())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let string_literals = data_lines(&self.string_literals);
 
-        let var_init = self.global_variable_init.join("\n");
+        let var_init = data_lines(&self.global_variables);
+        let read_only_var_init = data_lines(&self.read_only_variables);
+
+        let jump_tables = self.functions
+            .iter()
+            .map(|(asm, _)| {
+                let mut function_label = "";//jump tables use local labels, so they need to know which function they are in
+                let mut tables = Vec::new();
+                for line in asm.get_lines() {
+                    match line.get_operation() {
+                        IROperation::Label(Label::Global(name)) => function_label = name.as_str(),
+                        IROperation::JMPTABLE { table, .. } => tables.push(table.generate_rodata(function_label)),
+                        _ => {}
+                    }
+                }
+                tables
+            })
+            .flatten()
+            .collect::<Vec<_>>()
+            .join("\n");
 
         let instructions = self.functions
             .iter()
@@ -47,7 +74,7 @@ impl AssemblyFile {
             .collect::<Vec<_>>()//get each line
             .join("\n");
 
-        format!(
+        let assembly_code = format!(
 "
 {}
 {}
@@ -57,31 +84,70 @@ FLOAT_NEGATE dd 0x80000000, 0, 0, 0
 align 16
 DOUBLE_NEGATE dq 0x8000000000000000, 0
 
+{}
+{}
 {}
 SECTION .data
 {}
 SECTION .note.GNU-stack ;disable executing the stack
 SECTION .text
-{}",global_label_text, extern_label_text, string_literals, var_init, instructions)
+{}",global_label_text, extern_label_text, string_literals, jump_tables, read_only_var_init, var_init, instructions);
+
+        let banned_registers = ["rbx", "r12", "r13", "r14", "r15"];//these ones are callee saved and could cause problems
+        assert!(!banned_registers.iter()
+            .any(|reg| assembly_code.contains(reg)));//ensure my code does not contain the bad registers
+
+        assembly_code
+    }
+
+    /// Writes the file as textual IR, which [AssemblyFile::from_ir_text] reads back
+    pub fn to_ir_text(&self) -> String {
+        let header = format!("stucc-ir {}", IR_TEXT_VERSION);
+
+        let labels = self.global_labels.iter()
+            .map(|label| format!("global {}", label))
+            .chain(self.extern_labels.iter().map(|label| format!("extern {}", label)));
+
+        let data = self.string_literals.iter()
+            .map(|x| ir_text::write_global_data("string", x))
+            .chain(self.global_variables.iter().map(|x| ir_text::write_global_data("data", x)))
+            .chain(self.read_only_variables.iter().map(|x| ir_text::write_global_data("rodata", x)));
+
+        let functions = self.functions.iter()
+            .map(|(asm, stack)| ir_text::write_function(asm, stack));
+
+        std::iter::once(header)
+            .chain(labels)
+            .chain(data)
+            .chain(functions)
+            .collect::<Vec<_>>()
+            .join("\n") + "\n"
+    }
+
+    /// Reads textual IR from a file at `path`, as written by [AssemblyFile::to_ir_text]
+    pub fn from_ir_text(text: &str, path: &Path) -> Result<AssemblyFile, Diagnostic> {
+        ir_text::parse_ir_file(text, path)
     }
 }
 
 #[derive(Default)]//adds ::default() which sets all vectors to empty
 pub struct AssemblyFileBuilder {
-    string_literal_lines: Vec<String>,
+    string_literals: Vec<GlobalData>,
     ///labels that must be marked global to be exported
     global_label_lines: Vec<String>,
     ///labels that must be marked extern to be imported
     extern_label_lines: Vec<String>,
 
-    /// assembly lines for initialising static or auto variables
-    global_variable_init: Vec<String>,
+    /// the starting values of static or auto variables
+    global_variables: Vec<GlobalData>,
+    /// the values of const variables, which go in `.rodata`
+    read_only_variables: Vec<GlobalData>,
     functions: Vec<(IRCode, SimpleStackFrame)>,
 }
 
 impl AssemblyFileBuilder {
-    pub fn string_literal_lines(mut self, lines: Vec<String>) -> Self {
-        self.string_literal_lines = lines;
+    pub fn string_literals(mut self, data: Vec<GlobalData>) -> Self {
+        self.string_literals = data;
         self
     }
 
@@ -100,17 +166,23 @@ impl AssemblyFileBuilder {
         self
     }
     
-    pub fn global_variable_init(mut self, inits: Vec<String>) -> Self {
-        self.global_variable_init = inits;
+    pub fn global_variables(mut self, data: Vec<GlobalData>) -> Self {
+        self.global_variables = data;
+        self
+    }
+
+    pub fn read_only_variables(mut self, data: Vec<GlobalData>) -> Self {
+        self.read_only_variables = data;
         self
     }
 
     pub fn build(self) -> AssemblyFile {
         AssemblyFile {
-            string_literal_lines: self.string_literal_lines,
+            string_literals: self.string_literals,
             global_labels: self.global_label_lines,
             extern_labels: self.extern_label_lines,
-            global_variable_init: self.global_variable_init,
+            global_variables: self.global_variables,
+            read_only_variables: self.read_only_variables,
             functions: self.functions,
         }
     }
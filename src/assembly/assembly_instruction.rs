@@ -23,6 +23,13 @@ impl IRInstruction {
         IRInstruction { comment: Some(comment), operation }
     }
 
+    pub fn get_operation(&self) -> &IROperation {
+        &self.operation
+    }
+    pub fn get_comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
+
     pub fn emit_assembly(&self, stack: &BakedSimpleStackFrame) -> String{
         if let Some(comment) = &self.comment {
             format!("{} ; {}", self.operation.to_text(stack), comment)
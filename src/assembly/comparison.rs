@@ -1,3 +1,5 @@
+use crate::data_type::base_type::ScalarType;
+
 #[derive(Clone)]
 pub enum AsmComparison {
     ALWAYS,//always jump or set to true
@@ -11,8 +13,11 @@ pub enum AsmComparison {
     L {signed: bool},
     ///greater than
     G {signed: bool},
+    /// comparison after comparing floats, where an unordered result (NaN) is only not equal
+    FLOAT(ComparisonKind),
 }
 
+#[derive(Clone)]
 pub enum ComparisonKind {
     ALWAYS,//always jump or set to true
     NE,//not equal
@@ -38,6 +43,7 @@ impl AsmComparison {
             Self::GE { signed:_ } => ComparisonKind::GE,
             Self::L { signed:_ } => ComparisonKind::L,
             Self::G { signed:_ } => ComparisonKind::G,
+            Self::FLOAT(kind) => kind.clone(),
         }
     }
 }
@@ -54,4 +60,12 @@ impl ComparisonKind {
             Self::G => AsmComparison::G { signed },
         }
     }
+
+    /// converts to an asm comparison that is correct after comparing two values of type `data_type`
+    pub fn to_asm_comparison_for(&self, data_type: &ScalarType) -> AsmComparison {
+        match data_type {
+            ScalarType::Float(_) => AsmComparison::FLOAT(self.clone()),
+            ScalarType::Integer(integer_type) => self.to_asm_comparison(!integer_type.is_unsigned()),
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,28 @@
+use memory_size::MemorySize;
+
+/// A labelled piece of data in `.data` or `.rodata`, like a global variable or string literal
+#[derive(Debug, Clone, PartialEq)]
+pub struct GlobalData {
+    pub label: String,
+    pub contents: DataContents,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DataContents {
+    /// each byte of the data, in order
+    Bytes(Vec<u8>),
+    /// this many zero bytes
+    Zeroes(MemorySize),
+    /// the address of `label`, plus `offset` bytes
+    Pointer { label: String, offset: i128 },
+}
+
+impl GlobalData {
+    pub fn to_nasm(&self) -> String {
+        match &self.contents {
+            DataContents::Bytes(bytes) => format!("{} db {}", self.label, bytes.iter().map(|x| x.to_string()).collect::<Vec<_>>().join(",")),
+            DataContents::Zeroes(size) => format!("{} TIMES {} db 0", self.label, size.size_bytes()),
+            DataContents::Pointer { label, offset } => format!("{} dq {} + {}", self.label, label, offset),
+        }
+    }
+}
@@ -0,0 +1,918 @@
+//! A plain text format for the IR, so that it can be saved with `--emit-ir` and compiled later from a `.ir` file
+//!
+//! Each line is one item, `#` starts a comment, and the first line is the header `stucc-ir <version>`. The file-level lines are:
+//! - `global <name>` and `extern <name>`: labels to export and import
+//! - `string <name> <contents>`, `data <name> <contents>` and `rodata <name> <contents>`: string literals, writable globals and const globals,
+//!   where `<contents>` is `bytes=[<byte>, ...]`, `zeroes=<bytes>` or `pointer=@<label> offset=<bytes>`
+//! - `function`, then the function's stack slots and instructions, then `end`
+//!
+//! Inside a function, `slot %<n> size=<bytes>` declares stack slot `%n`, and must come before the first instruction.
+//! The slots are allocated in the order they are declared, starting next to rbp.
+//!
+//! Each instruction is a mnemonic then `name=value` fields in a fixed order, with an optional `; "comment"`:
+//! ```text
+//! mov to=<mem> from=<operand> size=<bytes>
+//! lea to=<mem> from=<mem>
+//! cmp lhs=<storage> rhs=<storage> type=<scalar>
+//! setcc to=<storage> type=<integer> cond=<comparison>
+//! jmpcc label=<label> cond=<comparison>
+//! jmptable value=<storage> type=<integer> table=<label> lowest=<value> targets=[<label>, ...] out_of_range=<label>
+//! cast to=<storage> to_type=<scalar> from=<storage> from_type=<scalar>
+//! add|sub|mul|div to=<storage> lhs=<storage> rhs=<storage> type=<scalar>
+//! mod to=<storage> lhs=<storage> rhs=<storage> type=<integer>
+//! shl|shr to=<storage> from=<storage> from_type=<integer> amount=<storage>
+//! neg to=<storage> from=<storage> type=<scalar>
+//! not to=<storage> from=<storage> size=<bytes>
+//! bitop to=<storage> lhs=<storage> rhs=<storage> size=<bytes> op=and|or|xor
+//! label name=<label>
+//! create_stack_frame
+//! ret [value=%<n> size=<bytes> (regs=[<reg>, ...] | hidden_pointer=<storage>)]
+//! call (direct=<label> | indirect=<storage>) params=[{<storage> <bytes> <location>}, ...] variadic=true|false [return={<return location> %<n> <bytes>}]
+//! read_params regs=[{[<reg>, ...] <bytes> %<n>}, ...] mem=[{<bytes> %<n>}, ...]
+//! save_va_arg_regs area=%<n>
+//! blank
+//! ```
+//! Where:
+//! - `<storage>` is `%n`, `(%n + <bytes>)`, `*%n` (the memory pointed to by `%n`), a label or a constant
+//! - `<mem>` is `%n`, `*<mem>`, `(<mem> + <bytes>)`, `(rbp + <bytes>)` (the caller's stack frame) or a label
+//! - `<operand>` is a `<mem>` or a constant
+//! - labels are `@name`, or `@.name` for local labels
+//! - constants are `<type>:<value>`, like `i32:-5` or `f64:0.5`, and types are `bool`, `i8`, `u8`... `u64`, `f32` and `f64`
+//! - comparisons are `always`, `eq`, `ne`, `l`, `le`, `g` and `ge`, with a `u` prefix for unsigned, like `ule`, or a `float-` prefix after comparing floats
+//! - registers are `ax`, `bx`, `cx`, `dx`, `si`, `di`, `r8`, `r9`, `sp`, `bp` and `xmm0` to `xmm7`
+//! - `<location>` is `memory` or a list of registers, and `<return location>` is `hidden` or a list of registers
+
+use std::{fmt::Display, path::{Path, PathBuf}, rc::Rc};
+
+use memory_size::MemorySize;
+use stack_management::{baked_stack_frame::BakedSimpleStackFrame, simple_stack_frame::SimpleStackFrame, stack_item::StackItemKey};
+
+use crate::{args_handling::location_allocation::{AllocatedLocation, EightByteLocation, ReturnLocation}, assembly::{assembly::IRCode, assembly_file::AssemblyFile, global_data::{DataContents, GlobalData}, comparison::{AsmComparison, ComparisonKind}, jump_table::JumpTable, operand::{register::{GPRegister, MMRegister}, IRMemOperand, IROperand, Storage, STACK_ALIGN}, operation::{CallTarget, CalleeReturnData, CallerParamData, CallerReturnData, IROperation, Label, LogicalOperation, ReadParamFromMem, ReadParamFromReg}}, data_type::base_type::{FloatType, IntegerType, ScalarType}, diagnostics::diagnostic::Diagnostic, lexer::source_span::{SourcePosition, SourceSpan}, number_literal::typed_value::NumberLiteral};
+
+/// Goes up whenever the format changes, so that old files are rejected instead of misread
+pub const IR_TEXT_VERSION: u32 = 1;
+
+const GP_REGISTERS: [(GPRegister, &str); 10] = [
+    (GPRegister::_AX, "ax"),
+    (GPRegister::_BX, "bx"),
+    (GPRegister::_CX, "cx"),
+    (GPRegister::_DX, "dx"),
+    (GPRegister::_SI, "si"),
+    (GPRegister::_DI, "di"),
+    (GPRegister::R8, "r8"),
+    (GPRegister::R9, "r9"),
+    (GPRegister::_SP, "sp"),
+    (GPRegister::_BP, "bp"),
+];
+
+const XMM_REGISTERS: [(MMRegister, &str); 8] = [
+    (MMRegister::XMM0, "xmm0"),
+    (MMRegister::XMM1, "xmm1"),
+    (MMRegister::XMM2, "xmm2"),
+    (MMRegister::XMM3, "xmm3"),
+    (MMRegister::XMM4, "xmm4"),
+    (MMRegister::XMM5, "xmm5"),
+    (MMRegister::XMM6, "xmm6"),
+    (MMRegister::XMM7, "xmm7"),
+];
+
+const INTEGER_TYPES: [IntegerType; 9] = [IntegerType::_BOOL, IntegerType::I8, IntegerType::U8, IntegerType::I16, IntegerType::U16, IntegerType::I32, IntegerType::U32, IntegerType::I64, IntegerType::U64];
+
+/// Puts `text` in speechmarks, escaping it so that it stays on one line
+fn quote(text: &str) -> String {
+    let mut result = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\t' => result.push_str("\\t"),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+/// Writes one function's IR, including the stack slots it uses
+pub fn write_function(code: &IRCode, stack: &SimpleStackFrame) -> String {
+    let baked_stack = BakedSimpleStackFrame::new(stack, STACK_ALIGN);
+    let mut writer = IRTextWriter { slots: Vec::new() };
+
+    let instructions: Vec<String> = code.get_lines()
+        .iter()
+        .map(|line| {
+            let operation = writer.operation(line.get_operation());
+            match line.get_comment() {
+                Some(comment) => format!("    {} ; {}", operation, quote(comment)),
+                None => format!("    {}", operation),
+            }
+        })
+        .collect();
+
+    //slots are only known once every instruction has been written
+    //they are declared in the order they are laid out, and each one's size is the room it has before the next slot
+    let mut slot_offsets: Vec<(usize, u64)> = writer.slots
+        .iter()
+        .enumerate()
+        .map(|(index, key)| (index, baked_stack.get(key).offset_from_bp.size_bytes()))
+        .collect();
+    slot_offsets.sort_by_key(|(_, offset)| *offset);
+
+    let mut previous_offset = 0;
+    let slots = slot_offsets
+        .into_iter()
+        .map(|(index, offset)| {
+            let size = offset - previous_offset;
+            previous_offset = offset;
+            format!("slot %{} size={}", index, size)
+        })
+        .collect::<Vec<_>>();
+
+    std::iter::once("function".to_string())
+        .chain(slots)
+        .chain(instructions)
+        .chain(std::iter::once("end".to_string()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Writes a string literal or global variable, where `directive` is the section it goes in
+pub fn write_global_data(directive: &str, data: &GlobalData) -> String {
+    let contents = match &data.contents {
+        DataContents::Bytes(bytes) => format!("bytes=[{}]", bytes.iter().map(|x| x.to_string()).collect::<Vec<_>>().join(", ")),
+        DataContents::Zeroes(size) => format!("zeroes={}", size.size_bytes()),
+        DataContents::Pointer { label, offset } => format!("pointer=@{} offset={}", label, offset),
+    };
+    format!("{} {} {}", directive, data.label, contents)
+}
+
+/// Names each stack slot `%0`, `%1`... in the order they are first used
+struct IRTextWriter {
+    slots: Vec<StackItemKey>,
+}
+
+impl IRTextWriter {
+    fn slot(&mut self, key: &StackItemKey) -> String {
+        let index = match self.slots.iter().position(|x| x == key) {
+            Some(index) => index,
+            None => {
+                self.slots.push(*key);
+                self.slots.len() - 1
+            }
+        };
+        format!("%{}", index)
+    }
+
+    fn storage(&mut self, storage: &Storage) -> String {
+        match storage {
+            Storage::Stack(key) => self.slot(key),
+            Storage::StackWithOffset { stack, offset } => format!("({} + {})", self.slot(stack), offset.size_bytes()),
+            Storage::Constant(number) => constant(number),
+            Storage::IndirectAddress(key) => format!("*{}", self.slot(key)),
+            Storage::Label(label) => format!("@{}", label),
+        }
+    }
+
+    fn mem_operand(&mut self, operand: &IRMemOperand) -> String {
+        match operand {
+            IRMemOperand::Stack { base } => self.slot(base),
+            IRMemOperand::IndirectAddress { pointer_location } => format!("*{}", self.mem_operand(pointer_location)),
+            IRMemOperand::OffsetAddress { base, displacement } => format!("({} + {})", self.mem_operand(base), displacement.size_bytes()),
+            IRMemOperand::Label { label } => format!("@{}", label),
+            IRMemOperand::PreviousStackFrame { add_to_rbp } => format!("(rbp + {})", add_to_rbp.size_bytes()),
+        }
+    }
+
+    fn operand(&mut self, operand: &IROperand) -> String {
+        match operand {
+            IROperand::Memory(memory) => self.mem_operand(memory),
+            IROperand::Constant(number) => constant(number),
+        }
+    }
+
+    fn operation(&mut self, operation: &IROperation) -> String {
+        match operation {
+            IROperation::MOV { from, to, size } => format!("mov to={} from={} size={}", self.mem_operand(to), self.operand(from), size.size_bytes()),
+            IROperation::LEA { from, to } => format!("lea to={} from={}", self.mem_operand(to), self.mem_operand(from)),
+            IROperation::CMP { lhs, rhs, data_type } => format!("cmp lhs={} rhs={} type={}", self.storage(lhs), self.storage(rhs), data_type),
+            IROperation::SETCC { to, data_type, comparison } => format!("setcc to={} type={} cond={}", self.storage(to), data_type, comparison_name(comparison)),
+            IROperation::JMPCC { label, comparison } => format!("jmpcc label=@{} cond={}", label, comparison_name(comparison)),
+            IROperation::JMPTABLE { value, data_type, table } => format!("jmptable value={} type={} table=@{} lowest={} targets=[{}] out_of_range=@{}",
+                self.storage(value),
+                data_type,
+                table.get_table_label(),
+                table.get_lowest_value(),
+                table.get_targets().iter().map(|x| format!("@{}", x)).collect::<Vec<_>>().join(", "),
+                table.get_out_of_range()
+            ),
+            IROperation::CAST { from, from_type, to, to_type } => format!("cast to={} to_type={} from={} from_type={}", self.storage(to), to_type, self.storage(from), from_type),
+            IROperation::ADD { lhs, rhs, to, data_type } => self.arithmetic("add", to, lhs, rhs, data_type),
+            IROperation::SUB { lhs, rhs, to, data_type } => self.arithmetic("sub", to, lhs, rhs, data_type),
+            IROperation::MUL { lhs, rhs, to, data_type } => self.arithmetic("mul", to, lhs, rhs, data_type),
+            IROperation::DIV { lhs, rhs, to, data_type } => self.arithmetic("div", to, lhs, rhs, data_type),
+            IROperation::MOD { lhs, rhs, to, data_type } => format!("mod to={} lhs={} rhs={} type={}", self.storage(to), self.storage(lhs), self.storage(rhs), data_type),
+            IROperation::SHL { from, from_type, amount, to } => format!("shl to={} from={} from_type={} amount={}", self.storage(to), self.storage(from), from_type, self.storage(amount)),
+            IROperation::SHR { from, from_type, amount, to } => format!("shr to={} from={} from_type={} amount={}", self.storage(to), self.storage(from), from_type, self.storage(amount)),
+            IROperation::NEG { from, to, data_type } => format!("neg to={} from={} type={}", self.storage(to), self.storage(from), data_type),
+            IROperation::BitwiseNot { from, to, size } => format!("not to={} from={} size={}", self.storage(to), self.storage(from), size.size_bytes()),
+            IROperation::BitwiseOp { lhs, rhs, to, size, operation } => format!("bitop to={} lhs={} rhs={} size={} op={}", self.storage(to), self.storage(lhs), self.storage(rhs), size.size_bytes(), logical_operation_name(operation)),
+            IROperation::Label(label) => format!("label name=@{}", label),
+            IROperation::CreateStackFrame => "create_stack_frame".to_string(),
+            IROperation::Return { return_data: None } => "ret".to_string(),
+            IROperation::Return { return_data: Some((return_data, value, size)) } => {
+                let location = match return_data {
+                    CalleeReturnData::InRegs { regs_used } => format!("regs={}", eightbyte_list(regs_used)),
+                    CalleeReturnData::InMemory { hidden_pointer_location } => format!("hidden_pointer={}", self.storage(hidden_pointer_location)),
+                };
+                format!("ret value={} size={} {}", self.slot(value), size.size_bytes(), location)
+            },
+            IROperation::CALL { target, params, return_data, variadic } => {
+                let target = match target {
+                    CallTarget::Direct(label) => format!("direct=@{}", label),
+                    CallTarget::Indirect(pointer) => format!("indirect={}", self.storage(pointer)),
+                };
+                let params = params.iter()
+                    .map(|param| {
+                        let location = match &param.location {
+                            AllocatedLocation::Regs(regs) => eightbyte_list(regs),
+                            AllocatedLocation::Memory => "memory".to_string(),
+                        };
+                        format!("{{{} {} {}}}", self.storage(&param.data), param.data_size.size_bytes(), location)
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let return_data = match return_data {
+                    Some(return_data) => {
+                        let location = match &return_data.return_location_info {
+                            ReturnLocation::InRegs(regs) => eightbyte_list(regs),
+                            ReturnLocation::HiddenPointer => "hidden".to_string(),
+                        };
+                        format!(" return={{{} {} {}}}", location, self.slot(&return_data.return_location), return_data.return_location_size.size_bytes())
+                    },
+                    None => String::new(),
+                };
+                format!("call {} params=[{}] variadic={}{}", target, params, variadic, return_data)
+            },
+            IROperation::ReadParams { regs, mem } => {
+                let regs = regs.iter()
+                    .map(|param| format!("{{{} {} {}}}", eightbyte_list(&param.eightbyte_locations), param.param_size.size_bytes(), self.slot(&param.param_destination)))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let mem = mem.iter()
+                    .map(|param| format!("{{{} {}}}", param.param_size.size_bytes(), self.slot(&param.param_destination)))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("read_params regs=[{}] mem=[{}]", regs, mem)
+            },
+            IROperation::SaveVaArgRegs { reg_save_area } => format!("save_va_arg_regs area={}", self.slot(reg_save_area)),
+            IROperation::BLANK => "blank".to_string(),
+        }
+    }
+
+    fn arithmetic(&mut self, mnemonic: &str, to: &Storage, lhs: &Storage, rhs: &Storage, data_type: &ScalarType) -> String {
+        format!("{} to={} lhs={} rhs={} type={}", mnemonic, self.storage(to), self.storage(lhs), self.storage(rhs), data_type)
+    }
+}
+
+fn constant(number: &NumberLiteral) -> String {
+    match number {
+        NumberLiteral::INTEGER { data, data_type } => format!("{}:{}", data_type, data),
+        NumberLiteral::FLOAT { data, data_type } => format!("{}:{:?}", data_type, data),//debug formatting reads back as exactly the same number
+    }
+}
+
+fn comparison_name(comparison: &AsmComparison) -> String {
+    let signed_name = |name: &str, signed: bool| if signed {name.to_string()} else {format!("u{}", name)};
+    match comparison {
+        AsmComparison::ALWAYS => "always".to_string(),
+        AsmComparison::NE => "ne".to_string(),
+        AsmComparison::EQ => "eq".to_string(),
+        AsmComparison::LE { signed } => signed_name("le", *signed),
+        AsmComparison::GE { signed } => signed_name("ge", *signed),
+        AsmComparison::L { signed } => signed_name("l", *signed),
+        AsmComparison::G { signed } => signed_name("g", *signed),
+        AsmComparison::FLOAT(kind) => format!("float-{}", comparison_name(&kind.to_asm_comparison(true))),
+    }
+}
+
+fn logical_operation_name(operation: &LogicalOperation) -> &'static str {
+    match operation {
+        LogicalOperation::AND => "and",
+        LogicalOperation::OR => "or",
+        LogicalOperation::XOR => "xor",
+    }
+}
+
+fn eightbyte_list(locations: &[EightByteLocation]) -> String {
+    let names = locations.iter()
+        .map(|location| match location {
+            EightByteLocation::GP(reg) => GP_REGISTERS.iter().find(|(x, _)| x == reg).unwrap().1,
+            EightByteLocation::XMM(reg) => XMM_REGISTERS.iter().find(|(x, _)| x == reg).unwrap().1,
+        })
+        .collect::<Vec<_>>();
+
+    format!("[{}]", names.join(", "))
+}
+
+/// Reads a whole IR file, as written by [AssemblyFile::to_ir_text]
+pub fn parse_ir_file(text: &str, path: &Path) -> Result<AssemblyFile, Diagnostic> {
+    let file = Rc::new(path.to_path_buf());
+    let mut lines = text.lines().zip(1..);
+
+    let mut seen_header = false;
+    let mut global_labels = Vec::new();
+    let mut extern_labels = Vec::new();
+    let mut string_literals = Vec::new();
+    let mut global_variables = Vec::new();
+    let mut read_only_variables = Vec::new();
+    let mut functions = Vec::new();
+
+    while let Some((line, line_number)) = lines.next() {
+        let tokens = tokenise(line, line_number, &file)?;
+        if tokens.is_empty() {
+            continue;
+        }
+        let mut parser = LineParser { tokens, index: 0, line_number, line_length: line.len() as u32, file: &file, slots: &[] };
+
+        if !seen_header {
+            parser.keyword("stucc-ir")?;
+            let version = parser.parse_word("a version number", |x| x.parse::<u32>().ok())?;
+            if version != IR_TEXT_VERSION {
+                return Err(Diagnostic::error(format!("this is version {} of the IR format, but only version {} can be read", version, IR_TEXT_VERSION))
+                    .with_primary(parser.previous_span(), ""));
+            }
+            parser.finish()?;
+            seen_header = true;
+            continue;
+        }
+
+        let directive = parser.parse_word("`global`, `extern`, `string`, `data`, `rodata` or `function`", |x| Some(x.to_string()))?;
+        match directive.as_str() {
+            "global" => global_labels.push(parser.parse_word("a label name", |x| Some(x.to_string()))?),
+            "extern" => extern_labels.push(parser.parse_word("a label name", |x| Some(x.to_string()))?),
+            "string" => string_literals.push(parser.global_data()?),
+            "data" => global_variables.push(parser.global_data()?),
+            "rodata" => read_only_variables.push(parser.global_data()?),
+            "function" => {
+                parser.finish()?;
+                functions.push(parse_function(&mut lines, &file, parser.previous_span())?);
+                continue;
+            },
+            _ => return Err(Diagnostic::error(format!("unknown IR directive `{}`", directive))
+                .with_primary(parser.previous_span(), "")
+                .with_help("each line outside a function starts with `global`, `extern`, `string`, `data`, `rodata` or `function`")),
+        }
+        parser.finish()?;
+    }
+
+    if !seen_header {
+        return Err(Diagnostic::error(format!("{} is empty, so is not an IR file", path.display())));
+    }
+
+    Ok(AssemblyFile::builder()
+        .global_label_lines(global_labels)
+        .extern_label_lines(extern_labels)
+        .string_literals(string_literals)
+        .global_variables(global_variables)
+        .read_only_variables(read_only_variables)
+        .functions(functions)
+        .build())
+}
+
+/// Reads the slots and instructions of a function, up to and including its `end`
+fn parse_function<'a>(lines: &mut impl Iterator<Item = (&'a str, u32)>, file: &Rc<PathBuf>, function_span: SourceSpan) -> Result<(IRCode, SimpleStackFrame), Diagnostic> {
+    let mut slot_sizes: Vec<(usize, MemorySize)> = Vec::new();//in the order they are declared
+    let mut stack = None;//allocated once every slot is known, at the first instruction
+    let mut code = IRCode::make_empty();
+
+    for (line, line_number) in lines {
+        let tokens = tokenise(line, line_number, file)?;
+        let first_word = match tokens.first() {
+            Some(Located { token: IRToken::Word(word), .. }) => word.clone(),
+            Some(_) => String::new(),
+            None => continue,
+        };
+
+        if first_word == "slot" && stack.is_none() {
+            let mut parser = LineParser { tokens, index: 1, line_number, line_length: line.len() as u32, file, slots: &[] };
+            parser.punct('%')?;
+            let slot = parser.parse_word("a stack slot number", |x| x.parse::<usize>().ok())?;
+            let slot_span = parser.previous_span();
+            let size = parser.field("size", |x| x.size())?;
+            parser.finish()?;
+
+            if slot_sizes.iter().any(|(x, _)| *x == slot) {
+                return Err(Diagnostic::error(format!("stack slot %{} is declared twice", slot)).with_primary(slot_span, ""));
+            }
+            slot_sizes.push((slot, size));
+            continue;
+        }
+
+        if first_word == "end" {
+            let parser = LineParser { tokens, index: 1, line_number, line_length: line.len() as u32, file, slots: &[] };
+            parser.finish()?;
+            let (stack_frame, _) = stack.unwrap_or_else(|| allocate_slots(&slot_sizes));
+            return Ok((code, stack_frame));
+        }
+
+        let (_, slots) = stack.get_or_insert_with(|| allocate_slots(&slot_sizes));
+        let mut parser = LineParser { tokens, index: 0, line_number, line_length: line.len() as u32, file, slots };
+
+        if first_word == "slot" {
+            return Err(Diagnostic::error("stack slots must be declared before the first instruction").with_primary(parser.current_span(), ""));
+        }
+
+        let operation = parser.operation()?;
+        if parser.try_punct(';') {
+            let comment = parser.string()?;
+            code.add_commented_instruction(operation, comment);
+        } else {
+            code.add_instruction(operation);
+        }
+        parser.finish()?;
+    }
+
+    Err(Diagnostic::error("this function has no `end`").with_primary(function_span, ""))
+}
+
+/// Allocates each declared slot in the order they were declared, returning the key for each slot number
+fn allocate_slots(slot_sizes: &[(usize, MemorySize)]) -> (SimpleStackFrame, Vec<Option<StackItemKey>>) {
+    let slot_count = slot_sizes.iter().map(|(slot, _)| slot + 1).max().unwrap_or(0);
+
+    let mut stack = SimpleStackFrame::new(STACK_ALIGN);
+    let mut keys = vec![None; slot_count];
+    for (slot, size) in slot_sizes {
+        keys[*slot] = Some(stack.allocate(*size));
+    }
+
+    (stack, keys)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum IRToken {
+    Word(String),
+    Str(String),
+    Punct(char),
+}
+
+impl Display for IRToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IRToken::Word(word) => write!(f, "`{}`", word),
+            IRToken::Str(_) => write!(f, "a string"),
+            IRToken::Punct(c) => write!(f, "`{}`", c),
+        }
+    }
+}
+
+/// A token, and the columns it covers
+struct Located {
+    token: IRToken,
+    start: u32,
+    end: u32,
+}
+
+const PUNCTUATION: &str = "[]{}(),=*+:@%;";
+
+fn is_word_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "_.-$".contains(c)
+}
+
+fn tokenise(line: &str, line_number: u32, file: &Rc<PathBuf>) -> Result<Vec<Located>, Diagnostic> {
+    let mut tokens = Vec::new();
+    let mut chars = line.char_indices().peekable();
+    let column = |index: usize| index as u32 + 1;
+    let span = |start: usize, end: usize| SourceSpan {
+        file: file.clone(),
+        start: SourcePosition { line: line_number, column: column(start) },
+        end: SourcePosition { line: line_number, column: column(end) },
+        expanded_from: None,
+    };
+
+    while let Some((start, c)) = chars.next() {
+        let token = match c {
+            '#' => break,//comment until the end of the line
+            c if c.is_whitespace() => continue,
+            c if PUNCTUATION.contains(c) => IRToken::Punct(c),
+            c if is_word_char(c) => {
+                let mut word = c.to_string();
+                while let Some((_, c)) = chars.next_if(|(_, c)| is_word_char(*c)) {
+                    word.push(c);
+                }
+                IRToken::Word(word)
+            },
+            '"' => {
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((index, '\\')) => match chars.next() {
+                            Some((_, '"')) => text.push('"'),
+                            Some((_, '\\')) => text.push('\\'),
+                            Some((_, 'n')) => text.push('\n'),
+                            Some((_, 't')) => text.push('\t'),
+                            _ => return Err(Diagnostic::error("unknown escape sequence").with_primary(span(index, index + 2), "").with_help("only \\\", \\\\, \\n and \\t are allowed")),
+                        },
+                        Some((_, c)) => text.push(c),
+                        None => return Err(Diagnostic::error("string is missing its closing `\"`").with_primary(span(start, line.len()), "")),
+                    }
+                }
+                IRToken::Str(text)
+            },
+            c => return Err(Diagnostic::error(format!("unexpected character `{}`", c)).with_primary(span(start, start + c.len_utf8()), "")),
+        };
+
+        let end = chars.peek().map(|(index, _)| *index).unwrap_or(line.len());
+        tokens.push(Located { token, start: column(start), end: column(end) });
+    }
+
+    Ok(tokens)
+}
+
+/// Parses the tokens of one line
+struct LineParser<'a> {
+    tokens: Vec<Located>,
+    index: usize,
+    line_number: u32,
+    line_length: u32,
+    file: &'a Rc<PathBuf>,
+    /// the key for each stack slot number, or None if that number was not declared
+    slots: &'a [Option<StackItemKey>],
+}
+
+impl<'a> LineParser<'a> {
+    fn span(&self, start: u32, end: u32) -> SourceSpan {
+        SourceSpan {
+            file: self.file.clone(),
+            start: SourcePosition { line: self.line_number, column: start },
+            end: SourcePosition { line: self.line_number, column: end },
+            expanded_from: None,
+        }
+    }
+    /// The span of the next token, or the end of the line
+    fn current_span(&self) -> SourceSpan {
+        match self.tokens.get(self.index) {
+            Some(token) => self.span(token.start, token.end),
+            None => self.span(self.line_length + 1, self.line_length + 2),
+        }
+    }
+    fn previous_span(&self) -> SourceSpan {
+        let token = &self.tokens[self.index - 1];
+        self.span(token.start, token.end)
+    }
+
+    fn peek(&self) -> Option<&IRToken> {
+        self.tokens.get(self.index).map(|x| &x.token)
+    }
+    fn peek_word(&self) -> Option<&str> {
+        match self.peek() {
+            Some(IRToken::Word(word)) => Some(word),
+            _ => None,
+        }
+    }
+
+    fn expected(&self, what: &str) -> Diagnostic {
+        let found = match self.peek() {
+            Some(token) => token.to_string(),
+            None => "the end of the line".to_string(),
+        };
+        Diagnostic::error(format!("expected {}, found {}", what, found)).with_primary(self.current_span(), "")
+    }
+
+    fn finish(&self) -> Result<(), Diagnostic> {
+        match self.peek() {
+            Some(_) => Err(self.expected("the end of the line")),
+            None => Ok(()),
+        }
+    }
+    /// whether the operation has no more fields, as the line has ended or the comment has started
+    fn at_end_of_operation(&self) -> bool {
+        matches!(self.peek(), None | Some(IRToken::Punct(';')))
+    }
+
+    fn try_punct(&mut self, c: char) -> bool {
+        if self.peek() == Some(&IRToken::Punct(c)) {
+            self.index += 1;
+            true
+        } else {
+            false
+        }
+    }
+    fn punct(&mut self, c: char) -> Result<(), Diagnostic> {
+        if self.try_punct(c) {
+            Ok(())
+        } else {
+            Err(self.expected(&format!("`{}`", c)))
+        }
+    }
+
+    /// Consumes a word if `convert` accepts it
+    fn parse_word<T>(&mut self, what: &str, convert: impl FnOnce(&str) -> Option<T>) -> Result<T, Diagnostic> {
+        match self.peek_word().and_then(convert) {
+            Some(result) => {
+                self.index += 1;
+                Ok(result)
+            },
+            None => Err(self.expected(what)),
+        }
+    }
+    fn keyword(&mut self, keyword: &str) -> Result<(), Diagnostic> {
+        self.parse_word(&format!("`{}`", keyword), |x| (x == keyword).then_some(()))
+    }
+    fn string(&mut self) -> Result<String, Diagnostic> {
+        match self.peek().cloned() {
+            Some(IRToken::Str(text)) => {
+                self.index += 1;
+                Ok(text)
+            },
+            _ => Err(self.expected("a string")),
+        }
+    }
+
+    /// Parses `name=value`
+    fn field<T>(&mut self, name: &str, parse_value: impl FnOnce(&mut Self) -> Result<T, Diagnostic>) -> Result<T, Diagnostic> {
+        self.keyword(name)?;
+        self.punct('=')?;
+        parse_value(self)
+    }
+    /// Parses `[item, item, ...]`
+    fn list<T>(&mut self, mut parse_item: impl FnMut(&mut Self) -> Result<T, Diagnostic>) -> Result<Vec<T>, Diagnostic> {
+        self.punct('[')?;
+        let mut items = Vec::new();
+        if self.try_punct(']') {
+            return Ok(items);
+        }
+        loop {
+            items.push(parse_item(self)?);
+            if self.try_punct(']') {
+                return Ok(items);
+            }
+            self.punct(',')?;
+        }
+    }
+
+    fn size(&mut self) -> Result<MemorySize, Diagnostic> {
+        self.parse_word("a number of bytes", |x| x.parse::<u64>().ok().map(MemorySize::from_bytes))
+    }
+    fn boolean(&mut self) -> Result<bool, Diagnostic> {
+        self.parse_word("`true` or `false`", |x| x.parse::<bool>().ok())
+    }
+
+    fn slot(&mut self) -> Result<StackItemKey, Diagnostic> {
+        self.punct('%')?;
+        let slot = self.parse_word("a stack slot number", |x| x.parse::<usize>().ok())?;
+        match self.slots.get(slot) {
+            Some(Some(key)) => Ok(*key),
+            _ => Err(Diagnostic::error(format!("stack slot %{} is not declared", slot))
+                .with_primary(self.previous_span(), "")
+                .with_help(format!("add `slot %{} size=<bytes>` before the first instruction of the function", slot))),
+        }
+    }
+    /// Parses `<name> <contents>` after `string`, `data` or `rodata`
+    fn global_data(&mut self) -> Result<GlobalData, Diagnostic> {
+        let label = self.parse_word("a label name", |x| Some(x.to_string()))?;
+        let kind = self.peek_word().map(|x| x.to_string());
+        let contents = match kind.as_deref() {
+            Some("bytes") => DataContents::Bytes(self.field("bytes", |x| x.list(|x| x.parse_word("a byte", |x| x.parse::<u8>().ok())))?),
+            Some("zeroes") => DataContents::Zeroes(self.field("zeroes", |x| x.size())?),
+            Some("pointer") => {
+                let label = self.field("pointer", |x| {
+                    x.punct('@')?;
+                    x.parse_word("a label name", |x| Some(x.to_string()))
+                })?;
+                let offset = self.field("offset", |x| x.parse_word("a number of bytes", |x| x.parse::<i128>().ok()))?;
+                DataContents::Pointer { label, offset }
+            },
+            _ => return Err(self.expected("`bytes`, `zeroes` or `pointer`")),
+        };
+        Ok(GlobalData { label, contents })
+    }
+    fn label(&mut self) -> Result<Label, Diagnostic> {
+        self.punct('@')?;
+        self.parse_word("a label name", |x| match x.strip_prefix('.') {
+            Some(local) => Some(Label::Local(local.to_string())),
+            None => Some(Label::Global(x.to_string())),
+        })
+    }
+
+    fn scalar_type(&mut self) -> Result<ScalarType, Diagnostic> {
+        self.parse_word("a type", |x| match x {
+            "f32" => Some(ScalarType::Float(FloatType::F32)),
+            "f64" => Some(ScalarType::Float(FloatType::F64)),
+            x => INTEGER_TYPES.iter().find(|integer| integer.to_string() == x).map(|integer| ScalarType::Integer(*integer)),
+        })
+    }
+    fn integer_type(&mut self) -> Result<IntegerType, Diagnostic> {
+        self.parse_word("an integer type", |x| INTEGER_TYPES.iter().find(|integer| integer.to_string() == x).copied())
+    }
+    fn constant(&mut self) -> Result<NumberLiteral, Diagnostic> {
+        let data_type = self.scalar_type()?;
+        self.punct(':')?;
+        match data_type {
+            ScalarType::Integer(data_type) => self.parse_word("an integer", |x| x.parse::<i128>().ok().map(|data| NumberLiteral::INTEGER { data, data_type })),
+            ScalarType::Float(data_type) => self.parse_word("a number", |x| x.parse::<f64>().ok().map(|data| NumberLiteral::FLOAT { data, data_type })),
+        }
+    }
+    fn comparison(&mut self) -> Result<AsmComparison, Diagnostic> {
+        self.parse_word("a comparison", |x| {
+            let (name, float) = match x.strip_prefix("float-") {
+                Some(name) => (name, true),
+                None => (x, false),
+            };
+            let (name, signed) = match name.strip_prefix('u') {
+                Some(name) if !float => (name, false),
+                _ => (name, true),
+            };
+            let kind = match name {
+                "always" => ComparisonKind::ALWAYS,
+                "ne" => ComparisonKind::NE,
+                "eq" => ComparisonKind::EQ,
+                "le" => ComparisonKind::LE,
+                "ge" => ComparisonKind::GE,
+                "l" => ComparisonKind::L,
+                "g" => ComparisonKind::G,
+                _ => return None,
+            };
+            Some(if float {AsmComparison::FLOAT(kind)} else {kind.to_asm_comparison(signed)})
+        })
+    }
+    fn eightbyte_location(&mut self) -> Result<EightByteLocation, Diagnostic> {
+        self.parse_word("a register", |x| {
+            GP_REGISTERS.iter().find(|(_, name)| *name == x).map(|(reg, _)| EightByteLocation::GP(*reg))
+            .or_else(|| XMM_REGISTERS.iter().find(|(_, name)| *name == x).map(|(reg, _)| EightByteLocation::XMM(*reg)))
+        })
+    }
+
+    fn storage(&mut self) -> Result<Storage, Diagnostic> {
+        match self.peek() {
+            Some(IRToken::Punct('%')) => Ok(Storage::Stack(self.slot()?)),
+            Some(IRToken::Punct('*')) => {
+                self.index += 1;
+                Ok(Storage::IndirectAddress(self.slot()?))
+            },
+            Some(IRToken::Punct('(')) => {
+                self.index += 1;
+                let stack = self.slot()?;
+                self.punct('+')?;
+                let offset = self.size()?;
+                self.punct(')')?;
+                Ok(Storage::StackWithOffset { stack, offset })
+            },
+            Some(IRToken::Punct('@')) => Ok(Storage::Label(self.label()?)),
+            Some(IRToken::Word(_)) => Ok(Storage::Constant(self.constant()?)),
+            _ => Err(self.expected("a stack slot, label or constant")),
+        }
+    }
+    fn mem_operand(&mut self) -> Result<IRMemOperand, Diagnostic> {
+        match self.peek() {
+            Some(IRToken::Punct('%')) => Ok(IRMemOperand::Stack { base: self.slot()? }),
+            Some(IRToken::Punct('*')) => {
+                self.index += 1;
+                Ok(IRMemOperand::IndirectAddress { pointer_location: Box::new(self.mem_operand()?) })
+            },
+            Some(IRToken::Punct('(')) => {
+                self.index += 1;
+                let result = if self.peek_word() == Some("rbp") {
+                    self.index += 1;
+                    self.punct('+')?;
+                    IRMemOperand::PreviousStackFrame { add_to_rbp: self.size()? }
+                } else {
+                    let base = Box::new(self.mem_operand()?);
+                    self.punct('+')?;
+                    IRMemOperand::OffsetAddress { base, displacement: self.size()? }
+                };
+                self.punct(')')?;
+                Ok(result)
+            },
+            Some(IRToken::Punct('@')) => Ok(IRMemOperand::Label { label: self.label()? }),
+            _ => Err(self.expected("a stack slot, label or memory address")),
+        }
+    }
+    fn operand(&mut self) -> Result<IROperand, Diagnostic> {
+        match self.peek() {
+            Some(IRToken::Word(_)) => Ok(IROperand::Constant(self.constant()?)),
+            _ => Ok(IROperand::Memory(self.mem_operand()?)),
+        }
+    }
+
+    fn operation(&mut self) -> Result<IROperation, Diagnostic> {
+        let mnemonic = self.parse_word("an instruction", |x| Some(x.to_string()))?;
+        let mnemonic_span = self.previous_span();
+
+        //struct fields are evaluated in the order they are written, which is the order of the fields in the text
+        Ok(match mnemonic.as_str() {
+            "mov" => IROperation::MOV { to: self.field("to", Self::mem_operand)?, from: self.field("from", Self::operand)?, size: self.field("size", Self::size)? },
+            "lea" => IROperation::LEA { to: self.field("to", Self::mem_operand)?, from: self.field("from", Self::mem_operand)? },
+            "cmp" => IROperation::CMP { lhs: self.field("lhs", Self::storage)?, rhs: self.field("rhs", Self::storage)?, data_type: self.field("type", Self::scalar_type)? },
+            "setcc" => IROperation::SETCC { to: self.field("to", Self::storage)?, data_type: self.field("type", Self::integer_type)?, comparison: self.field("cond", Self::comparison)? },
+            "jmpcc" => IROperation::JMPCC { label: self.field("label", Self::label)?, comparison: self.field("cond", Self::comparison)? },
+            "jmptable" => {
+                let value = self.field("value", Self::storage)?;
+                let data_type = self.field("type", Self::integer_type)?;
+                let table_label = self.field("table", Self::label)?;
+                let lowest_value = self.field("lowest", |p| p.parse_word("an integer", |x| x.parse::<i128>().ok()))?;
+                let targets = self.field("targets", |p| p.list(Self::label))?;
+                let targets_span = self.previous_span();
+                let out_of_range = self.field("out_of_range", Self::label)?;
+                let table = JumpTable::new(table_label, lowest_value, targets, out_of_range)
+                    .ok_or_else(|| Diagnostic::error("a jump table needs at least one target").with_primary(targets_span, ""))?;
+                IROperation::JMPTABLE { value, data_type, table }
+            },
+            "cast" => IROperation::CAST { to: self.field("to", Self::storage)?, to_type: self.field("to_type", Self::scalar_type)?, from: self.field("from", Self::storage)?, from_type: self.field("from_type", Self::scalar_type)? },
+            "add" => IROperation::ADD { to: self.field("to", Self::storage)?, lhs: self.field("lhs", Self::storage)?, rhs: self.field("rhs", Self::storage)?, data_type: self.field("type", Self::scalar_type)? },
+            "sub" => IROperation::SUB { to: self.field("to", Self::storage)?, lhs: self.field("lhs", Self::storage)?, rhs: self.field("rhs", Self::storage)?, data_type: self.field("type", Self::scalar_type)? },
+            "mul" => IROperation::MUL { to: self.field("to", Self::storage)?, lhs: self.field("lhs", Self::storage)?, rhs: self.field("rhs", Self::storage)?, data_type: self.field("type", Self::scalar_type)? },
+            "div" => IROperation::DIV { to: self.field("to", Self::storage)?, lhs: self.field("lhs", Self::storage)?, rhs: self.field("rhs", Self::storage)?, data_type: self.field("type", Self::scalar_type)? },
+            "mod" => IROperation::MOD { to: self.field("to", Self::storage)?, lhs: self.field("lhs", Self::storage)?, rhs: self.field("rhs", Self::storage)?, data_type: self.field("type", Self::integer_type)? },
+            "shl" => IROperation::SHL { to: self.field("to", Self::storage)?, from: self.field("from", Self::storage)?, from_type: self.field("from_type", Self::integer_type)?, amount: self.field("amount", Self::storage)? },
+            "shr" => IROperation::SHR { to: self.field("to", Self::storage)?, from: self.field("from", Self::storage)?, from_type: self.field("from_type", Self::integer_type)?, amount: self.field("amount", Self::storage)? },
+            "neg" => IROperation::NEG { to: self.field("to", Self::storage)?, from: self.field("from", Self::storage)?, data_type: self.field("type", Self::scalar_type)? },
+            "not" => IROperation::BitwiseNot { to: self.field("to", Self::storage)?, from: self.field("from", Self::storage)?, size: self.field("size", Self::size)? },
+            "bitop" => IROperation::BitwiseOp {
+                to: self.field("to", Self::storage)?,
+                lhs: self.field("lhs", Self::storage)?,
+                rhs: self.field("rhs", Self::storage)?,
+                size: self.field("size", Self::size)?,
+                operation: self.field("op", |p| p.parse_word("`and`, `or` or `xor`", |x| match x {
+                    "and" => Some(LogicalOperation::AND),
+                    "or" => Some(LogicalOperation::OR),
+                    "xor" => Some(LogicalOperation::XOR),
+                    _ => None,
+                }))?,
+            },
+            "label" => IROperation::Label(self.field("name", Self::label)?),
+            "create_stack_frame" => IROperation::CreateStackFrame,
+            "ret" if self.at_end_of_operation() => IROperation::Return { return_data: None },
+            "ret" => {
+                let value = self.field("value", Self::slot)?;
+                let size = self.field("size", Self::size)?;
+                let return_data = if self.peek_word() == Some("regs") {
+                    CalleeReturnData::InRegs { regs_used: self.field("regs", |p| p.list(Self::eightbyte_location))? }
+                } else {
+                    CalleeReturnData::InMemory { hidden_pointer_location: self.field("hidden_pointer", Self::storage)? }
+                };
+                IROperation::Return { return_data: Some((return_data, value, size)) }
+            },
+            "call" => {
+                let target = if self.peek_word() == Some("direct") {
+                    CallTarget::Direct(self.field("direct", Self::label)?)
+                } else {
+                    CallTarget::Indirect(self.field("indirect", Self::storage)?)
+                };
+                let params = self.field("params", |p| p.list(|p| {
+                    p.punct('{')?;
+                    let data = p.storage()?;
+                    let data_size = p.size()?;
+                    let location = if p.peek_word() == Some("memory") {
+                        p.index += 1;
+                        AllocatedLocation::Memory
+                    } else {
+                        AllocatedLocation::Regs(p.list(Self::eightbyte_location)?)
+                    };
+                    p.punct('}')?;
+                    Ok(CallerParamData { data, data_size, location })
+                }))?;
+                let variadic = self.field("variadic", Self::boolean)?;
+                let return_data = if self.at_end_of_operation() {
+                    None
+                } else {
+                    Some(self.field("return", |p| {
+                        p.punct('{')?;
+                        let return_location_info = if p.peek_word() == Some("hidden") {
+                            p.index += 1;
+                            ReturnLocation::HiddenPointer
+                        } else {
+                            ReturnLocation::InRegs(p.list(Self::eightbyte_location)?)
+                        };
+                        let return_location = p.slot()?;
+                        let return_location_size = p.size()?;
+                        p.punct('}')?;
+                        Ok(CallerReturnData { return_location_info, return_location, return_location_size })
+                    })?)
+                };
+                IROperation::CALL { target, params, return_data, variadic }
+            },
+            "read_params" => IROperation::ReadParams {
+                regs: self.field("regs", |p| p.list(|p| {
+                    p.punct('{')?;
+                    let eightbyte_locations = p.list(Self::eightbyte_location)?;
+                    let param_size = p.size()?;
+                    let param_destination = p.slot()?;
+                    p.punct('}')?;
+                    Ok(ReadParamFromReg { eightbyte_locations, param_size, param_destination })
+                }))?,
+                mem: self.field("mem", |p| p.list(|p| {
+                    p.punct('{')?;
+                    let param_size = p.size()?;
+                    let param_destination = p.slot()?;
+                    p.punct('}')?;
+                    Ok(ReadParamFromMem { param_size, param_destination })
+                }))?,
+            },
+            "save_va_arg_regs" => IROperation::SaveVaArgRegs { reg_save_area: self.field("area", Self::slot)? },
+            "blank" => IROperation::BLANK,
+            _ => return Err(Diagnostic::error(format!("unknown instruction `{}`", mnemonic)).with_primary(mnemonic_span, "")),
+        })
+    }
+}
\ No newline at end of file
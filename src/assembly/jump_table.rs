@@ -0,0 +1,55 @@
+use crate::assembly::operation::Label;
+
+/// Jump table for a dense switch statement
+///
+/// Entry `i` of the table is the label to jump to when the switch value is `lowest_value + i`
+#[derive(Clone)]
+pub struct JumpTable {
+    /// label of the table itself, which is stored in .rodata
+    table_label: Label,
+    /// the switch value that maps to the first entry of the table
+    lowest_value: i128,
+    targets: Vec<Label>,
+    /// where to jump if the value is not covered by the table
+    out_of_range: Label,
+}
+
+impl JumpTable {
+    /// Returns None if there are no targets, as the table would be empty
+    pub fn new(table_label: Label, lowest_value: i128, targets: Vec<Label>, out_of_range: Label) -> Option<Self> {
+        if targets.is_empty() {
+            return None;
+        }
+        Some(Self { table_label, lowest_value, targets, out_of_range })
+    }
+
+    pub fn get_table_label(&self) -> &Label {
+        &self.table_label
+    }
+    pub fn get_lowest_value(&self) -> i128 {
+        self.lowest_value
+    }
+    pub fn get_targets(&self) -> &[Label] {
+        &self.targets
+    }
+    pub fn get_out_of_range(&self) -> &Label {
+        &self.out_of_range
+    }
+
+    /// Generates the table to be put in .rodata, where each entry is the 32 bit offset from the start of the table to the target
+    ///
+    /// Offsets are used instead of addresses so that the table needs no relocations, which keeps the code position independent
+    ///
+    /// `function_label` is required as the table (and its entries) are local labels, which would otherwise attach to the wrong global label
+    pub fn generate_rodata(&self, function_label: &str) -> String {
+        //`$` is the address of each entry, which is 4*i bytes after the start of the table
+        let entries = self.targets
+            .iter()
+            .enumerate()
+            .map(|(i, label)| format!("dd {}{} - $ + {}", function_label, label, 4 * i))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        format!("align 4\n{}{}:\n{}", function_label, self.table_label, entries)
+    }
+}
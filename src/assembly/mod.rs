@@ -4,4 +4,7 @@ pub mod assembly;
 pub mod operand;
 pub mod operation;
 pub mod comparison;
-pub mod assembly_text;
\ No newline at end of file
+pub mod assembly_text;
+pub mod jump_table;
+pub mod ir_text;
+pub mod global_data;
\ No newline at end of file
@@ -7,7 +7,7 @@ use colored::Colorize;
 use memory_size::MemorySize;
 use stack_management::stack_item::StackItemKey;
 
-use crate::number_literal::typed_value::NumberLiteral;
+use crate::{assembly::operation::Label, number_literal::typed_value::NumberLiteral};
 
 pub const PTR_SIZE: MemorySize = MemorySize::from_bytes(8);
 /// Alignment of the stack before calling a function in SysV ABI
@@ -22,6 +22,8 @@ pub enum Storage {
     Constant(NumberLiteral),
     /// Dereferences the pointer at `self.0`
     IndirectAddress(StackItemKey),
+    /// The memory at a label, like a function
+    Label(Label),
 }
 
 impl Debug for Storage {
@@ -31,6 +33,7 @@ impl Debug for Storage {
             Storage::StackWithOffset{stack,offset}=>format!("[{:?} + {}]",stack,offset),
             Storage::Constant(immediate_value)=>immediate_value.to_string(),
             Storage::IndirectAddress(stack_item_key) => format!("[[{:?}]]", stack_item_key),
+            Storage::Label(label) => format!("[{}]", label),
         }.blue())
     }
 }
@@ -42,6 +45,7 @@ impl Into<IROperand> for Storage {
             Storage::StackWithOffset { stack, offset } => IROperand::Memory(IRMemOperand::OffsetAddress { base: Box::new(IRMemOperand::Stack { base: stack}), displacement: offset }),
             Storage::Constant(number_literal) => IROperand::Constant(number_literal),
             Storage::IndirectAddress(stack_item_key) => IROperand::Memory(IRMemOperand::IndirectAddress { pointer_location: Box::new(IRMemOperand::Stack { base: stack_item_key})}),
+            Storage::Label(label) => IROperand::Memory(IRMemOperand::Label { label }),
         }
     }
 }
@@ -54,6 +58,7 @@ impl TryInto<IRMemOperand> for Storage {
             Storage::StackWithOffset { stack, offset } => Ok(IRMemOperand::OffsetAddress { base: Box::new(IRMemOperand::Stack { base: stack}), displacement: offset }),
             Storage::Constant(_) => Err(()),
             Storage::IndirectAddress(stack_item_key) => Ok(IRMemOperand::IndirectAddress { pointer_location: Box::new(IRMemOperand::Stack { base: stack_item_key})}),
+            Storage::Label(label) => Ok(IRMemOperand::Label { label }),
         }
     }
 }
@@ -68,7 +73,10 @@ pub enum IRMemOperand {
         base: Box<IRMemOperand>,
         displacement: MemorySize
     },
-    //TODO label access (no displacement?)
+    /// The memory at a label (no displacement?)
+    Label {label: Label},
+    /// Memory in the caller's stack frame, like args passed in memory
+    PreviousStackFrame {add_to_rbp: MemorySize},
 }
 
 /// Operand for the IR that relates to something with value
@@ -83,7 +91,9 @@ impl Debug for IRMemOperand {
         match self {
             Self::Stack { base } => write!(f, "{:?}", base),
             Self::IndirectAddress { pointer_location} => write!(f, "[{:?}]", pointer_location),
-            Self::OffsetAddress { base, displacement } => write!(f, "[{} + &{:?}]", displacement.size_bytes(), base)
+            Self::OffsetAddress { base, displacement } => write!(f, "[{} + &{:?}]", displacement.size_bytes(), base),
+            Self::Label { label } => write!(f, "{}", label),
+            Self::PreviousStackFrame { add_to_rbp } => write!(f, "[rbp + {}]", add_to_rbp.size_bytes()),
         }
     }
 }
@@ -1,9 +1,8 @@
 use std::{fmt::Display, mem};
-use crate::{args_handling::location_allocation::{AllocatedLocation, EightByteLocation, ReturnLocation}, assembly::{assembly_text::RawAssembly, comparison::AsmComparison, operand::{register::GPRegister, IRMemOperand, IROperand, Storage}}, data_type::base_type::{FloatType, IntegerType, ScalarType}, debugging::IRDisplay};
+use crate::{args_handling::location_allocation::{gp_arg, xmm_arg, AllocatedLocation, EightByteLocation, ReturnLocation, MAX_GP_REGS, MAX_XMM_REGS}, assembly::{assembly_text::RawAssembly, comparison::{AsmComparison, ComparisonKind}, jump_table::JumpTable, operand::{register::{GPRegister, MMRegister}, IRMemOperand, IROperand, Storage, PTR_SIZE}}, data_type::base_type::{FloatType, IntegerType, ScalarType}, debugging::IRDisplay};
 use itertools::{Either, Itertools};
 use memory_size::MemorySize;
 use stack_management::{baked_stack_frame::BakedSimpleStackFrame, stack_item::StackItemKey};
-use unwrap_let::unwrap_let;
 
 #[derive(Clone)]
 pub enum IROperation {
@@ -22,6 +21,9 @@ pub enum IROperation {
     ///based on the comparison, conditionally jump to the label
     JMPCC {label: Label, comparison: AsmComparison},
 
+    /// jumps to the label in `table` that corresponds to `value`, or the table's out of range label if there is none
+    JMPTABLE {value: Storage, data_type: IntegerType, table: JumpTable},
+
     //casts and moves from -> to
     CAST {from: Storage, from_type: ScalarType, to: Storage, to_type: ScalarType},
 
@@ -61,12 +63,13 @@ pub enum IROperation {
     ///calls a subroutine (you must handle the parameters though)
     /// 
     /// - Sets up registers and the stack correctly
-    /// - 
-    /// 
-    /// TODO should this call a Storage ?
-    CALL {label: String, params: Vec<CallerParamData>, return_data: Option<CallerReturnData>},
+    /// - Calls the label directly, or the function pointed to for function pointers
+    /// - Tells `variadic` functions how many vector registers hold args
+    CALL {target: CallTarget, params: Vec<CallerParamData>, return_data: Option<CallerReturnData>, variadic: bool},
 
     ReadParams {regs: Vec<ReadParamFromReg>, mem: Vec<ReadParamFromMem>},
+    /// Dumps every arg register in the register save area, so that `va_arg` can find unnamed args passed in registers
+    SaveVaArgRegs {reg_save_area: StackItemKey},
 
     ///not even a nop, just a blank line of assembly
     BLANK,
@@ -128,6 +131,14 @@ pub struct MemoryArg {
     pub size: MemorySize
 }
 
+#[derive(Clone)]
+pub enum CallTarget {
+    /// Calls the function at this label
+    Direct(Label),
+    /// Calls the function pointer stored here
+    Indirect(Storage),
+}
+
 #[derive(Clone)]
 pub enum LogicalOperation {
     AND,
@@ -172,12 +183,9 @@ impl IROperation {
             IROperation::CMP { rhs, data_type, lhs } => {
                 match data_type {
                     ScalarType::Float(float_type) => {
-                        result.add(put_rhs_xmm0_lhs_xmm1(lhs, rhs, float_type, stack));
-                        //compare
-                        result.add(match float_type {
-                            FloatType::F32 => "ucomiss xmm1, xmm0",
-                            FloatType::F64 => "ucomisd xmm1, xmm0",
-                        }.to_string());
+                        result.merge(put_rhs_xmm0_lhs_xmm1(lhs, rhs, float_type, stack));
+                        //compare, setting the parity flag if either is NaN
+                        result.add(format!("{} xmm1, xmm0", float_instruction("ucomis", float_type)));
                     },
                     ScalarType::Integer(integer_type) => {
                         result.merge(put_lhs_ax_rhs_cx(lhs, rhs, integer_type, stack));
@@ -187,19 +195,28 @@ impl IROperation {
                 }
             },
             IROperation::SETCC { comparison, to, data_type } => {
-                let reg_name = GPRegister::acc().generate_name(MemorySize::from_bytes(1));//setting 1 byte boolean
+                let truncated_rcx = GPRegister::_CX.generate_name(data_type.memory_size());
 
-                let comparison_instr = match comparison {
-                    AsmComparison::NE => "setne",
-                    AsmComparison::EQ => "sete",
-                    AsmComparison::ALWAYS => panic!(),//return format!("mov al, 1"),//for set always, there is no command, so quickly return a mov command
-                    AsmComparison::LE {signed} => if *signed {"setle"} else {"setbe"},
-                    AsmComparison::GE {signed} => if *signed {"setge"} else {"setae"},
-                    AsmComparison::L {signed} => if *signed {"setl"} else {"setb"},
-                    AsmComparison::G {signed} => if *signed {"setg"} else {"seta"},
-                };
-
-                result.add(format!("{} al", comparison_instr));
+                //calculate the boolean
+                result.merge(set_al_from_flags(comparison));
+                result.add("movzx ecx, al".to_string());
+                //store it
+                result.merge(put_pointer_in_rax(&to.clone().try_into().unwrap(), stack));
+                result.add(format!("mov [rax], {}", truncated_rcx));
+            },
+            IROperation::JMPCC { label, comparison: AsmComparison::FLOAT(kind) } => {
+                match kind {
+                    ComparisonKind::ALWAYS => result.add(format!("jmp {}", label)),
+                    //unordered results set CF, so these are already false for NaN
+                    ComparisonKind::G => result.add(format!("ja {}", label)),
+                    ComparisonKind::GE => result.add(format!("jae {}", label)),
+                    //the rest need the parity flag checking too
+                    _ => {
+                        result.merge(set_al_from_flags(&AsmComparison::FLOAT(kind.clone())));
+                        result.add("test al, al".to_string());
+                        result.add(format!("jnz {}", label));
+                    }
+                }
             },
             IROperation::JMPCC { label, comparison } => {
                 let comparison_instr = match comparison {
@@ -210,13 +227,35 @@ impl IROperation {
                     AsmComparison::GE {signed}  => if *signed {"jge"} else {"jae"},
                     AsmComparison::L {signed}  => if *signed {"jl"} else {"jb"},
                     AsmComparison::G {signed}  => if *signed {"jg"} else {"ja"},
+                    AsmComparison::FLOAT(_) => unreachable!(),//handled above
                 };
         
                 result.add(format!("{} {}", comparison_instr, label));
             },
+            IROperation::JMPTABLE { value, data_type, table } => {
+                //get the value, extended to 64 bits
+                result.merge(put_value_in_rax(&value.clone().try_into().unwrap(), data_type, stack));
+                //find the index into the table
+                result.add(format!("mov rcx, {}", table.get_lowest_value()));
+                result.add("sub rax, rcx".to_string());
+                //values below the lowest value wrap round to huge unsigned numbers, so one unsigned comparison checks both bounds
+                result.add(format!("mov rcx, {}", table.get_targets().len() - 1));
+                result.add("cmp rax, rcx".to_string());
+                result.add(format!("ja {}", table.get_out_of_range()));
+                //each entry is the offset of the target from the start of the table
+                result.add(format!("lea rcx, [rel {}]", table.get_table_label()));
+                result.add("movsxd rax, dword [rcx+rax*4]".to_string());
+                result.add("add rax, rcx".to_string());
+                result.add("jmp rax".to_string());
+            },
             IROperation::ADD { data_type, lhs, rhs, to } => {
                 match data_type {
-                    ScalarType::Float(float_type) => {todo!()},
+                    ScalarType::Float(float_type) => {
+                        result.merge(put_rhs_xmm0_lhs_xmm1(lhs, rhs, float_type, stack));
+                        //sum and put the result in xmm1
+                        result.add(format!("{} xmm1, xmm0", float_instruction("add", float_type)));
+                        result.merge(store_xmm(MMRegister::XMM1, to, float_type, stack));
+                    },
                     ScalarType::Integer(integer_type) => {
                         let truncated_rcx = GPRegister::_CX.generate_name(integer_type.memory_size());
                         result.merge(put_lhs_ax_rhs_cx(lhs, rhs, integer_type, stack));
@@ -230,7 +269,12 @@ impl IROperation {
                 }
             },
             IROperation::SUB { data_type, lhs, rhs, to } => match data_type {
-                ScalarType::Float(float_type) => {todo!()},
+                ScalarType::Float(float_type) => {
+                    result.merge(put_rhs_xmm0_lhs_xmm1(lhs, rhs, float_type, stack));
+                    //find the difference and put the result in xmm1
+                    result.add(format!("{} xmm1, xmm0", float_instruction("sub", float_type)));
+                    result.merge(store_xmm(MMRegister::XMM1, to, float_type, stack));
+                },
                 ScalarType::Integer(integer_type) => {
                     let truncated_rcx = GPRegister::_CX.generate_name(integer_type.memory_size());
                     result.merge(put_lhs_ax_rhs_cx(lhs, rhs, integer_type, stack));
@@ -243,7 +287,16 @@ impl IROperation {
                 },
             }
             IROperation::NEG { data_type, from, to } => match data_type {
-                ScalarType::Float(float_type) => todo!(),
+                ScalarType::Float(float_type) => {
+                    let sign_mask = match float_type {
+                        FloatType::F32 => "FLOAT_NEGATE",
+                        FloatType::F64 => "DOUBLE_NEGATE",
+                    };
+                    result.merge(put_value_in_xmm0(from, float_type, stack));
+                    //flip the sign bit
+                    result.add(format!("xorps xmm0, [rel {}]", sign_mask));
+                    result.merge(store_xmm(MMRegister::XMM0, to, float_type, stack));
+                },
                 ScalarType::Integer(integer_type) => {
                     let truncated_rcx = GPRegister::_CX.generate_name(integer_type.memory_size());
                     //get value
@@ -263,20 +316,27 @@ impl IROperation {
                 match return_data {
                     None => {},//returns void, do nothing
                     Some((CalleeReturnData::InMemory { hidden_pointer_location }, return_value, size)) => {
-                        todo!("copy return_value to the location pointed by hidden_pointer_location, and put hidden_pointer_location's value in RAX")
+                        let hidden_pointer: IRMemOperand = hidden_pointer_location.clone().try_into().unwrap();
+                        //copy the return value to wherever the caller asked
+                        result.merge(instruction_mov(
+                            &IROperand::Memory(IRMemOperand::Stack { base: *return_value }),
+                            &IRMemOperand::IndirectAddress { pointer_location: Box::new(hidden_pointer.clone()) },
+                            *size,
+                            stack
+                        ));
+                        //the caller expects the hidden pointer back in rax
+                        result.merge(put_value_in_rax(&IROperand::Memory(hidden_pointer), &IntegerType::U64, stack));
                     }
                     Some((CalleeReturnData::InRegs{ regs_used }, return_value, size)) => {
-                        assert!(size.size_bytes().is_power_of_two());//can't do weird number of bytes read
-                        
-                        //put in other registers first, as rax gets clobbered many times
+                        //each register is loaded straight from the stack, so nothing gets clobbered
                         for(i, eightbyte_destination) in regs_used.iter().enumerate() {
                             let offset = MemorySize::from_bytes((8*i).try_into().unwrap());
-                            let reg_size = best_reg_size(*size);
-                            let from = stack.get(return_value).offset_from_bp + offset;
+                            let load_size = eightbyte_load_size(*size - offset);
+                            let from = stack.get(return_value).offset_from_bp - offset;//higher addresses are closer to rbp
 
                             let mov_instr = match eightbyte_destination {
-                                EightByteLocation::GP(gpregister) => format!("mov {}, [rbp-{}]", gpregister.generate_name(reg_size), from.size_bytes()),
-                                EightByteLocation::XMM(mmregister) => todo!(),
+                                EightByteLocation::GP(gpregister) => format!("mov {}, [rbp-{}]", gpregister.generate_name(load_size), from.size_bytes()),
+                                EightByteLocation::XMM(mmregister) => format!("{} {}, [rbp-{}]", xmm_mov_instruction(load_size), mmregister.generate_name(load_size), from.size_bytes()),
                             };
                             result.add(mov_instr);
                         }
@@ -292,7 +352,12 @@ impl IROperation {
             },
             IROperation::BLANK => {},
             IROperation::MUL { data_type, lhs, rhs, to } => match data_type {
-                ScalarType::Float(float_type) => todo!(),
+                ScalarType::Float(float_type) => {
+                    result.merge(put_rhs_xmm0_lhs_xmm1(lhs, rhs, float_type, stack));
+                    //multiply and put the result in xmm1
+                    result.add(format!("{} xmm1, xmm0", float_instruction("mul", float_type)));
+                    result.merge(store_xmm(MMRegister::XMM1, to, float_type, stack));
+                },
                 ScalarType::Integer(integer_type) => {
                     let truncated_rcx = GPRegister::_CX.generate_name(integer_type.memory_size());
                     result.merge(put_lhs_ax_rhs_cx(lhs, rhs, integer_type, stack));
@@ -306,7 +371,12 @@ impl IROperation {
                 },
             },
             IROperation::DIV { data_type, lhs, rhs, to } => match data_type {
-                ScalarType::Float(float_type) => todo!(),
+                ScalarType::Float(float_type) => {
+                    result.merge(put_rhs_xmm0_lhs_xmm1(lhs, rhs, float_type, stack));
+                    //divide lhs by rhs and put the result in xmm1
+                    result.add(format!("{} xmm1, xmm0", float_instruction("div", float_type)));
+                    result.merge(store_xmm(MMRegister::XMM1, to, float_type, stack));
+                },
                 ScalarType::Integer(integer_type) => {
                     let truncated_rcx = GPRegister::_CX.generate_name(integer_type.memory_size());
                     result.merge(put_lhs_ax_rhs_cx(lhs, rhs, integer_type, stack));
@@ -319,8 +389,24 @@ impl IROperation {
                     result.add(format!("mov [rax], {}", truncated_rcx));
                 },
             },
-            IROperation::BitwiseOp { operation, lhs, rhs, to, size } => todo!(),
-            IROperation::CALL { label, params, return_data } => {
+            IROperation::BitwiseOp { operation, lhs, rhs, to, size } => {
+                let integer_type = unsigned_integer_of_size(*size);//bitwise operations ignore signedness
+                let truncated_rcx = GPRegister::_CX.generate_name(*size);
+                let op_asm = match operation {
+                    LogicalOperation::AND => "and",
+                    LogicalOperation::OR => "or",
+                    LogicalOperation::XOR => "xor",
+                };
+                result.merge(put_lhs_ax_rhs_cx(lhs, rhs, &integer_type, stack));
+                //apply the operation and put the result in rcx
+                result.add(format!("{} rax, rcx", op_asm));
+                result.add("mov rcx, rax".to_string());
+                //point to the destination
+                result.merge(put_pointer_in_rax(&to.clone().try_into().unwrap(), stack));
+                //truncate and store
+                result.add(format!("mov [rax], {}", truncated_rcx));
+            },
+            IROperation::CALL { target, params, return_data, variadic } => {
 
                 let mut extra_stack = MemorySize::new();
                 //split reg and mem args
@@ -330,99 +416,226 @@ impl IROperation {
                         match location {
                             AllocatedLocation::Regs(eight_byte_locations) => Either::Left((data, data_size, eight_byte_locations)),
                             AllocatedLocation::Memory => {
-                                let aligned_up_size = data_size.align_up(&MemorySize::from_bytes(8));
-                                extra_stack += aligned_up_size;//allocate room
+                                let sp_offset = extra_stack;
+                                extra_stack += data_size.align_up(&MemorySize::from_bytes(8));//allocate room, as each arg is padded to an eightbyte
 
-                                Either::Right((data, data_size, extra_stack))
+                                Either::Right(MemoryArg { value: data.clone(), sp_offset, size: *data_size })
                             },
                         }
                     });
+                //rsp must still be 16 byte aligned when the call happens
+                let extra_stack = extra_stack.align_up(&MemorySize::from_bytes(16));
+
+                if extra_stack.size_bytes() > 0 {
+                    result.add_commented(&format!("sub rsp, {}", extra_stack.size_bytes()), "make room for memory args");
+                }
                 
                 result.add_comment("putting memory args in the right place");
-                for (data, data_size, extra_stack) in mem_args {
-                    todo!()
+                for MemoryArg { value, sp_offset, size } in mem_args {
+                    //point to the arg's data
+                    result.merge(put_pointer_in_rax(&value.try_into().unwrap(), stack));
+                    //copy the biggest parts that fit to the top of the stack, so a 19 byte struct is copied as 8+8+2+1 bytes
+                    let mut offset = MemorySize::new();
+                    while offset < size {
+                        let part_size = best_reg_size(size - offset);
+                        let cx_name = GPRegister::_CX.generate_name(part_size);
+                        result.add(format!("mov {}, [rax+{}]", cx_name, offset.size_bytes()));
+                        result.add(format!("mov [rsp+{}], {}", (sp_offset + offset).size_bytes(), cx_name));
+                        offset += part_size;
+                    }
                 }
+                let vector_regs_used = reg_args.iter()
+                    .flat_map(|(_, _, registers)| registers.iter())
+                    .filter(|reg| matches!(reg, EightByteLocation::XMM(_)))
+                    .count();
+
                 result.add_comment("putting register args in the right place");
                 for(data, data_size, registers) in reg_args {
                     for (i, reg) in registers.iter().enumerate() {
                         assert_ne!(*reg, EightByteLocation::GP(GPRegister::_AX));//accumulator gets clobbered tons, don't use it please
 
                         let offset = MemorySize::from_bytes((8*i).try_into().unwrap());
+                        //structs may not fill their last eightbyte, so read up to the next register size
+                        let t = unsigned_integer_of_size(eightbyte_load_size(*data_size - offset));
+
+                        let from: IROperand = match data.clone() {
+                            Storage::Stack(stack_item_key) => Storage::StackWithOffset { stack: stack_item_key, offset }.into(),
+                            Storage::StackWithOffset { stack, offset: off } => Storage::StackWithOffset { stack: stack, offset: off + offset }.into(),
+                            Storage::Constant(number_literal) => {
+                                assert!(offset == MemorySize::default());
+                                IROperand::Constant(number_literal)
+                            },
+                            //like a dereferenced pointer or a global, so read the eightbyte from wherever the arg points
+                            arg @ (Storage::IndirectAddress(_) | Storage::Label(_)) => IROperand::Memory(IRMemOperand::OffsetAddress {
+                                base: Box::new(arg.try_into().unwrap()),
+                                displacement: offset
+                            }),
+                        };
+                        //put and zero extend number in rax
+                        result.merge(put_value_in_rax(&from, &t, stack));
 
                         match reg {
                             EightByteLocation::GP(gpregister) => {
-                                //TODO n byte integer type
-                                let t = match data_size.size_bytes() {
-                                    1 => IntegerType::U8,
-                                    2 => IntegerType::U16,
-                                    4 => IntegerType::U32,
-                                    8 => IntegerType::U64,
-                                    _ => panic!(),
-                                };
-                                let from = match data.clone() {
-                                    Storage::Stack(stack_item_key) => Storage::StackWithOffset { stack: stack_item_key, offset },
-                                    Storage::StackWithOffset { stack, offset: off } => Storage::StackWithOffset { stack: stack, offset: off + offset },
-                                    Storage::Constant(number_literal) => {
-                                        assert!(offset == MemorySize::default());
-                                        Storage::Constant(number_literal)
-                                    },
-                                    Storage::IndirectAddress(stack_item_key) => todo!(),
-                                };
-                                //put and zero extend number in rax
-                                result.merge(put_value_in_rax(&from.try_into().unwrap(), &t, stack));
-                                
                                 let sized_dest = gpregister.generate_name(MemorySize::from_bytes(8));
                                 result.add_commented(&format!("mov {}, rax", sized_dest), "dumping value in register");
                             },
-                            EightByteLocation::XMM(mmregister) => todo!(),
+                            EightByteLocation::XMM(mmregister) => {
+                                //the bit pattern is moved, so the float type doesn't matter
+                                result.add_commented(&format!("movq {}, rax", mmregister.generate_name(MemorySize::from_bytes(8))), "dumping value in register");
+                            },
                         }
                     }
                 }
 
-                if let Some(CallerReturnData{ return_location_info: ReturnLocation::HiddenPointer, return_location, return_location_size }) = return_data {
-                    todo!("put in first reg spot (hidden pointer)");
+                if let Some(CallerReturnData{ return_location_info: ReturnLocation::HiddenPointer, return_location, .. }) = return_data {
+                    //the callee writes the return value wherever the first GP register points
+                    result.merge(put_pointer_in_rax(&IRMemOperand::Stack { base: *return_location }, stack));
+                    result.add_commented(&format!("mov {}, rax", gp_arg(0).generate_name(PTR_SIZE)), "hidden pointer to the return value");
                 }
 
-                result.add(format!("call {}", label));
+                let call_instr = match target {
+                    CallTarget::Direct(label) => format!("call {}", label),
+                    CallTarget::Indirect(function_pointer) => {
+                        //args are already in their registers, so only rax is free to load the pointer with
+                        result.merge(put_value_in_rax(&function_pointer.clone().into(), &IntegerType::U64, stack));
+                        result.add_commented("mov r11, rax", "r11 is never used for args");
+                        "call r11".to_string()
+                    },
+                };
+
+                if *variadic {
+                    //al is an upper bound on the number of vector registers used, so the callee knows which ones to save
+                    result.add_commented(&format!("mov eax, {}", vector_regs_used), "number of vector registers used by variadic args");
+                }
+
+                result.add(call_instr);
+
+                if extra_stack.size_bytes() > 0 {
+                    result.add_commented(&format!("add rsp, {}", extra_stack.size_bytes()), "remove memory args");
+                }
 
                 match return_data {
                     Some(CallerReturnData { return_location_info: ReturnLocation::HiddenPointer, return_location, return_location_size }) => {},//result is already in the hidden pointer location
-                    Some(CallerReturnData { return_location_info: ReturnLocation::InRegs(regs), return_location, return_location_size }) => {
-                        unwrap_let!([EightByteLocation::GP(gpregister)] = &regs[..]);
-
-                        result.add_commented(&format!("mov [rbp-{}], {}", stack.get(return_location).offset_from_bp.size_bytes(), gpregister.generate_name(MemorySize::from_bytes(8))), "dumping return value from register");
+                    Some(CallerReturnData { return_location_info: ReturnLocation::InRegs(regs), return_location, .. }) => {
+                        //the return location is padded to eightbytes, so whole registers can be stored
+                        for (i, eightbyte) in regs.iter().enumerate() {
+                            let to = stack.get(return_location).offset_from_bp - MemorySize::from_bytes((8*i).try_into().unwrap());
+                            let mov_instr = match eightbyte {
+                                EightByteLocation::GP(gpregister) => format!("mov [rbp-{}], {}", to.size_bytes(), gpregister.generate_name(MemorySize::from_bytes(8))),
+                                EightByteLocation::XMM(mmregister) => format!("movq [rbp-{}], {}", to.size_bytes(), mmregister.generate_name(MemorySize::from_bytes(8))),
+                            };
+                            result.add_commented(&mov_instr, "dumping return value from register");
+                        }
                     },
                     None => {},
                 }
             },
-            IROperation::SHL { amount, from, from_type, to } => todo!(),
-            IROperation::SHR { amount, from, from_type, to } => todo!(),
-            IROperation::BitwiseNot{ from, to, size } => todo!(),
+            IROperation::SHL { amount, from, from_type, to } => {
+                let truncated_rcx = GPRegister::_CX.generate_name(from_type.memory_size());
+                result.merge(put_value_amount_cx(from, from_type, amount, stack));
+                //shift and put the result in rcx
+                result.add("shl rax, cl".to_string());
+                result.add("mov rcx, rax".to_string());
+                //point to the destination
+                result.merge(put_pointer_in_rax(&to.clone().try_into().unwrap(), stack));
+                //truncate and store
+                result.add(format!("mov [rax], {}", truncated_rcx));
+            },
+            IROperation::SHR { amount, from, from_type, to } => {
+                let truncated_rcx = GPRegister::_CX.generate_name(from_type.memory_size());
+                result.merge(put_value_amount_cx(from, from_type, amount, stack));
+                //signed numbers keep their sign bit, unsigned numbers shift in zeroes
+                result.add(if from_type.is_unsigned() {"shr rax, cl"} else {"sar rax, cl"}.to_string());
+                result.add("mov rcx, rax".to_string());
+                //point to the destination
+                result.merge(put_pointer_in_rax(&to.clone().try_into().unwrap(), stack));
+                //truncate and store
+                result.add(format!("mov [rax], {}", truncated_rcx));
+            },
+            IROperation::BitwiseNot{ from, to, size } => {
+                let truncated_rcx = GPRegister::_CX.generate_name(*size);
+                //get value
+                result.merge(put_value_in_rax(&from.clone().try_into().unwrap(), &unsigned_integer_of_size(*size), stack));
+                //flip the bits and put in rcx
+                result.add("not rax".to_string());
+                result.add("mov rcx, rax".to_string());
+                //truncate and store
+                result.merge(put_pointer_in_rax(&to.clone().try_into().unwrap(), stack));
+                result.add(format!("mov [rax], {}", truncated_rcx));
+            },
             IROperation::CAST { from_type, to_type, from, to } => {
                 result.merge(instruction_cast(from_type, to_type, from, to, stack))
             },
-            IROperation::MOD { lhs, rhs, to, data_type } => todo!(),
+            IROperation::MOD { lhs, rhs, to, data_type } => {
+                let truncated_rcx = GPRegister::_CX.generate_name(data_type.memory_size());
+                result.merge(put_lhs_ax_rhs_cx(lhs, rhs, data_type, stack));
+                //divide using the correct signedness and put the remainder in rcx
+                result.add(if data_type.is_unsigned() {"mov rdx, 0\ndiv rcx"} else {"cqo\nidiv rcx"}.to_string());
+                result.add("mov rcx, rdx".to_string());
+                //point to the destination
+                result.merge(put_pointer_in_rax(&to.clone().try_into().unwrap(), stack));
+                //truncate and store
+                result.add(format!("mov [rax], {}", truncated_rcx));
+            },
             IROperation::ReadParams { regs, mem } => {
                 for ReadParamFromReg { eightbyte_locations, param_size, param_destination } in regs {
-                    for eightbyte in eightbyte_locations {
+                    for (i, eightbyte) in eightbyte_locations.iter().enumerate() {
+                        //the last eightbyte of a struct may only be partly used
+                        let offset = MemorySize::from_bytes((8*i).try_into().unwrap());
+                        let eightbyte_size = (*param_size - offset).min(MemorySize::from_bytes(8));
+
+                        //rax is not used for args, so it can point to the destination
+                        result.merge(put_pointer_in_rax(&IRMemOperand::Stack{base:param_destination.clone()}, stack));
+
                         match eightbyte {
                             //this one is special, since almost all function clobber rax, so I need to do some magic
                             EightByteLocation::GP(GPRegister::_AX) => panic!("magic not implemented - perhaps use some fancy instruction like 'pop [rax]'"),
 
                             EightByteLocation::GP(gp_register) => {
-                                assert!(param_size.size_bytes().is_power_of_two());//can do some clever bit-shifting here, to turn a 3 byte store into a 2 byte and 1 byte store, but this is not implemented yet
-                                let sized_register = gp_register.generate_name(*param_size);
-                                result.merge(put_pointer_in_rax(&IRMemOperand::Stack{base:param_destination.clone()}, stack));
-                                result.add(format!("mov [rax], {}", sized_register));
+                                //store the biggest part that fits, then shift the rest down, for sizes like 3 bytes
+                                let mut stored = MemorySize::new();
+                                while stored < eightbyte_size {
+                                    let part_size = best_reg_size(eightbyte_size - stored);
+                                    result.add(format!("mov [rax+{}], {}", (offset + stored).size_bytes(), gp_register.generate_name(part_size)));
+                                    stored += part_size;
+
+                                    if stored < eightbyte_size {
+                                        result.add(format!("shr {}, {}", gp_register.generate_name(MemorySize::from_bytes(8)), part_size.size_bits()));
+                                    }
+                                }
                             }
 
-                            EightByteLocation::XMM(xmm_register) => todo!()
+                            EightByteLocation::XMM(xmm_register) => {
+                                result.add(format!("{} [rax+{}], {}", xmm_mov_instruction(eightbyte_size), offset.size_bytes(), xmm_register.generate_name(eightbyte_size)));
+                            }
                         }
                     }
                 }
 
+                //memory args are after the pushed rbp and return address, in order
+                let mut memory_offset_tracker = MemorySize::from_bytes(16);
                 for ReadParamFromMem { param_size, param_destination } in mem {
-                    todo!();//keep track of stack offsets and things
+                    result.merge(instruction_mov(
+                        &IROperand::Memory(IRMemOperand::PreviousStackFrame { add_to_rbp: memory_offset_tracker }),
+                        &IRMemOperand::Stack { base: *param_destination },
+                        *param_size,
+                        stack
+                    ));
+                    memory_offset_tracker += param_size.align_up(&MemorySize::from_bytes(8));//args are padded to eightbytes
+                }
+            },
+            IROperation::SaveVaArgRegs { reg_save_area } => {
+                let save_area_start = stack.get(reg_save_area).offset_from_bp;
+
+                //GP registers go first, in the order they are allocated
+                for i in 0..MAX_GP_REGS {
+                    let reg_location = save_area_start - MemorySize::from_bytes(8 * i);
+                    result.add(format!("mov [rbp-{}], {}", reg_location.size_bytes(), gp_arg(i).generate_name(MemorySize::from_bytes(8))));
+                }
+                //then each vector register gets 16 bytes
+                for i in 0..MAX_XMM_REGS {
+                    let reg_location = save_area_start - MemorySize::from_bytes(8 * MAX_GP_REGS + 16 * i);
+                    result.add(format!("movdqu [rbp-{}], {}", reg_location.size_bytes(), xmm_arg(i).generate_name(MemorySize::from_bytes(8))));
                 }
             },
         }
@@ -447,19 +660,46 @@ fn put_lhs_ax_rhs_cx(lhs:&Storage, rhs:&Storage, integer_type: &IntegerType, sta
     result
 }
 
+/// Puts `from` extended to 64 bits in rax, and the u8 shift `amount` in rcx
+/// 
+/// ### Clobbers
+/// - RAX
+/// - RCX
+fn put_value_amount_cx(from: &Storage, from_type: &IntegerType, amount: &Storage, stack: &BakedSimpleStackFrame) -> RawAssembly {
+    let mut result = RawAssembly::default();
+    //put amount in rcx, as shifts can only use cl
+    result.merge(put_value_in_rax(&amount.clone().try_into().unwrap(), &IntegerType::U8, stack));
+    result.add(format!("mov rcx, rax"));
+    //put the value in rax
+    result.merge(put_value_in_rax(&from.clone().try_into().unwrap(), from_type, stack));
+
+    result
+}
+
+/// Finds the unsigned integer type that is `size` bytes, for operations that work on raw bytes
+fn unsigned_integer_of_size(size: MemorySize) -> IntegerType {
+    match size.size_bytes() {
+        1 => IntegerType::U8,
+        2 => IntegerType::U16,
+        4 => IntegerType::U32,
+        8 => IntegerType::U64,
+        _ => panic!("no integer type is {} bytes", size.size_bytes()),
+    }
+}
+
 /// Says it on the tin
 /// 
 /// ### Clobbers
 /// - RAX
 /// - XMM0
 /// - XMM1
-fn put_rhs_xmm0_lhs_xmm1(lhs:&Storage, rhs:&Storage, float_type: &FloatType, stack: &BakedSimpleStackFrame) -> String {
-    let mut result = String::new();
+fn put_rhs_xmm0_lhs_xmm1(lhs:&Storage, rhs:&Storage, float_type: &FloatType, stack: &BakedSimpleStackFrame) -> RawAssembly {
+    let mut result = RawAssembly::default();
     //put lhs in XMM1
-    result += &put_value_in_xmm0(lhs, float_type, stack);
-    result += &format!("movq xmm1, xmm0");
+    result.merge(put_value_in_xmm0(lhs, float_type, stack));
+    result.add("movq xmm1, xmm0".to_string());
     //put rhs in XMM0
-    result += &put_value_in_xmm0(rhs, float_type, stack);
+    result.merge(put_value_in_xmm0(rhs, float_type, stack));
 
     result
 }
@@ -490,6 +730,14 @@ fn put_pointer_in_rax(storage: &IRMemOperand, stack: &BakedSimpleStackFrame) ->
             //get the offset
             result.add(format!("add rax, {}", displacement.size_bytes()));
         }
+
+        IRMemOperand::Label { label } => {
+            result.add(format!("lea rax, [rel {}]", label));
+        }
+
+        IRMemOperand::PreviousStackFrame { add_to_rbp } => {
+            result.add(format!("lea rax, [rbp+{}]", add_to_rbp.size_bytes()));
+        }
     };
 
     result
@@ -521,6 +769,12 @@ fn put_value_in_rax(storage: &IROperand, data_type: &IntegerType, stack: &BakedS
             result.merge(put_value_in_rax(&IROperand::Memory((**pointer_location).clone()), &IntegerType::U64, stack));
             result.add(format!("mov {}, [rax]", register));
         }
+        IROperand::Memory(IRMemOperand::Label { label }) => {
+            result.add(format!("mov {}, [rel {}]", register, label));
+        }
+        IROperand::Memory(IRMemOperand::PreviousStackFrame { add_to_rbp }) => {
+            result.add(format!("mov {}, [rbp+{}]", register, add_to_rbp.size_bytes()));
+        }
     };
 
     //sign extend
@@ -538,26 +792,89 @@ fn put_value_in_rax(storage: &IROperand, data_type: &IntegerType, stack: &BakedS
 /// ### Clobbers
 /// - RAX
 /// - XMM0
-fn put_value_in_xmm0(storage: &Storage, data_type: &FloatType, stack: &BakedSimpleStackFrame) -> String {
-    let mov_from_mem = match data_type {
-        FloatType::F32 => "movss",
-        FloatType::F64 => "movsd",
-    };
-    let mov_from_reg = match data_type {
-        FloatType::F32 => "movd",
-        FloatType::F64 => "movq",
-    };
+fn put_value_in_xmm0(storage: &Storage, data_type: &FloatType, stack: &BakedSimpleStackFrame) -> RawAssembly {
+    let mut result = RawAssembly::default();
+    let mov_from_mem = float_instruction("mov", data_type);
 
     match storage {
-        Storage::Stack(stack_item_key) => 
-            format!("{} xmm0, [rbp-{}]", mov_from_mem, stack.get(stack_item_key).offset_from_bp.size_bytes()),
-        Storage::StackWithOffset { stack: stack_item_key, offset } => 
-            format!("{} xmm0, [rbp-{}]", mov_from_mem, (stack.get(stack_item_key).offset_from_bp + *offset).size_bytes()),//+ offset right?
-        Storage::Constant(number_literal) => 
-            format!("mov rax, {}\n{} xmm0, rax", number_literal.generate_nasm_literal(), mov_from_reg),//pass the raw bitpattern via rax
-        Storage::IndirectAddress(stack_item_key) => 
-            format!("mov rax, [rbp-{}]\n{} xmm0, [rax]", stack.get(stack_item_key).offset_from_bp.size_bytes(), mov_from_mem),
+        Storage::Constant(number_literal) => {
+            //pass the raw bitpattern via rax
+            let bits = number_literal.cast(&ScalarType::Float(*data_type)).generate_nasm_literal();
+            result.add(format!("mov rax, {}", bits));
+            result.add("movq xmm0, rax".to_string());
+        }
+        memory => {
+            result.merge(put_pointer_in_rax(&memory.clone().try_into().unwrap(), stack));
+            result.add(format!("{} xmm0, [rax]", mov_from_mem));
+        }
     }
+
+    result
+}
+
+/// Stores the float in `register` to `to`
+/// 
+/// ### Clobbers
+/// - RAX
+fn store_xmm(register: MMRegister, to: &Storage, data_type: &FloatType, stack: &BakedSimpleStackFrame) -> RawAssembly {
+    let mut result = RawAssembly::default();
+    result.merge(put_pointer_in_rax(&to.clone().try_into().unwrap(), stack));
+    result.add(format!("{} [rax], {}", float_instruction("mov", data_type), register.generate_name(data_type.memory_size())));
+
+    result
+}
+
+/// Appends the scalar single or double suffix to `instruction`, for example "add" becomes "addss" or "addsd"
+fn float_instruction(instruction: &str, data_type: &FloatType) -> String {
+    match data_type {
+        FloatType::F32 => format!("{}ss", instruction),
+        FloatType::F64 => format!("{}sd", instruction),
+    }
+}
+
+/// Sets AL to 1 if the flags (from a previous CMP) match `comparison`, else 0
+/// 
+/// ### Clobbers
+/// - RAX
+/// - RCX
+fn set_al_from_flags(comparison: &AsmComparison) -> RawAssembly {
+    let mut result = RawAssembly::default();
+
+    match comparison {
+        AsmComparison::ALWAYS => result.add("mov al, 1".to_string()),//there is no set-always instruction
+        AsmComparison::NE => result.add("setne al".to_string()),
+        AsmComparison::EQ => result.add("sete al".to_string()),
+        AsmComparison::LE {signed} => result.add(if *signed {"setle al"} else {"setbe al"}.to_string()),
+        AsmComparison::GE {signed} => result.add(if *signed {"setge al"} else {"setae al"}.to_string()),
+        AsmComparison::L {signed} => result.add(if *signed {"setl al"} else {"setb al"}.to_string()),
+        AsmComparison::G {signed} => result.add(if *signed {"setg al"} else {"seta al"}.to_string()),
+
+        //ucomiss/ucomisd set ZF, PF and CF if either side is NaN
+        AsmComparison::FLOAT(kind) => match kind {
+            ComparisonKind::ALWAYS => result.add("mov al, 1".to_string()),
+            //CF set when unordered, so these are false for NaN anyway
+            ComparisonKind::G => result.add("seta al".to_string()),
+            ComparisonKind::GE => result.add("setae al".to_string()),
+            //these would be true for NaN, so must also check it is ordered
+            ComparisonKind::EQ | ComparisonKind::L | ComparisonKind::LE => {
+                result.add(match kind {
+                    ComparisonKind::EQ => "sete al",
+                    ComparisonKind::L => "setb al",
+                    _ => "setbe al",
+                }.to_string());
+                result.add("setnp cl".to_string());
+                result.add("and al, cl".to_string());
+            }
+            //NaN is not equal to anything
+            ComparisonKind::NE => {
+                result.add("setne al".to_string());
+                result.add("setp cl".to_string());
+                result.add("or al, cl".to_string());
+            }
+        },
+    }
+
+    result
 }
 
 fn instruction_cast(from_type: &ScalarType, to_type: &ScalarType, from: &Storage, to: &Storage, stack: &BakedSimpleStackFrame) -> RawAssembly {
@@ -578,7 +895,132 @@ fn instruction_cast(from_type: &ScalarType, to_type: &ScalarType, from: &Storage
             result
         }
 
-        _ => todo!()
+        (ScalarType::Float(x), ScalarType::Float(y)) => {
+            let mut result = RawAssembly::default();
+            result.merge(put_value_in_xmm0(from, x, stack));
+            result.add(match (x, y) {
+                (FloatType::F32, FloatType::F64) => "cvtss2sd xmm0, xmm0",
+                (FloatType::F64, FloatType::F32) => "cvtsd2ss xmm0, xmm0",
+                _ => unreachable!(),//same types are handled above
+            }.to_string());
+            result.merge(store_xmm(MMRegister::XMM0, to, y, stack));
+
+            result
+        }
+
+        (ScalarType::Float(x), ScalarType::Integer(IntegerType::_BOOL)) => {
+            let mut result = RawAssembly::default();
+            //compare with 0.0
+            result.merge(put_value_in_xmm0(from, x, stack));
+            result.add("xorps xmm1, xmm1".to_string());
+            result.add(format!("{} xmm0, xmm1", float_instruction("ucomis", x)));
+            //NaN is truthy, so use a float comparison
+            result.merge(set_al_from_flags(&AsmComparison::FLOAT(ComparisonKind::NE)));
+            result.add("mov cl, al".to_string());
+            //store the boolean
+            result.merge(put_pointer_in_rax(&to.clone().try_into().unwrap(), stack));
+            result.add("mov [rax], cl".to_string());
+
+            result
+        }
+
+        (ScalarType::Float(x), ScalarType::Integer(IntegerType::U64)) => {
+            let mut result = RawAssembly::default();
+            let convert_instr = float_to_int_instruction(x);
+            //2^63, the smallest value that is too big for a signed conversion
+            let two_pow_63 = match x {
+                FloatType::F32 => "0x5F000000",
+                FloatType::F64 => "0x43E0000000000000",
+            };
+
+            result.merge(put_value_in_xmm0(from, x, stack));
+            result.add_commented(&format!("{} rcx, xmm0", convert_instr), "correct if below 2^63, else 0x8000000000000000");
+            result.add(format!("mov rax, {}", two_pow_63));
+            result.add("movq xmm1, rax".to_string());
+            result.add(format!("{} xmm0, xmm1", float_instruction("sub", x)));
+            result.add_commented(&format!("{} rax, xmm0", convert_instr), "value - 2^63, for when the first conversion overflowed");
+            //if the first conversion overflowed, combine the upper bit from it with the reduced value
+            result.add("mov rdx, rcx".to_string());
+            result.add("sar rdx, 63".to_string());
+            result.add("and rax, rdx".to_string());
+            result.add("or rax, rcx".to_string());
+            //store
+            result.add("mov rcx, rax".to_string());
+            result.merge(put_pointer_in_rax(&to.clone().try_into().unwrap(), stack));
+            result.add("mov [rax], rcx".to_string());
+
+            result
+        }
+
+        (ScalarType::Float(x), ScalarType::Integer(y)) => {
+            let mut result = RawAssembly::default();
+            let rcx_sized = GPRegister::_CX.generate_name(y.memory_size());
+
+            //every other integer fits in an i64, so convert to that and truncate
+            result.merge(put_value_in_xmm0(from, x, stack));
+            result.add(format!("{} rax, xmm0", float_to_int_instruction(x)));
+            result.add("mov rcx, rax".to_string());
+            result.merge(put_pointer_in_rax(&to.clone().try_into().unwrap(), stack));
+            result.add(format!("mov [rax], {}", rcx_sized));
+
+            result
+        }
+
+        (ScalarType::Integer(IntegerType::U64), ScalarType::Float(y)) => {
+            let mut result = RawAssembly::default();
+            let convert_instr = int_to_float_instruction(y);
+            //bit patterns of 1.0 and 2.0
+            let (one, two) = match y {
+                FloatType::F32 => ("0x3F800000", "0x40000000"),
+                FloatType::F64 => ("0x3FF0000000000000", "0x4000000000000000"),
+            };
+
+            result.merge(put_value_in_rax(&from.clone().try_into().unwrap(), &IntegerType::U64, stack));
+            //values with the top bit set are halved, keeping the lowest bit so that rounding is still correct
+            result.add("mov rcx, rax".to_string());
+            result.add("shr rcx, 1".to_string());
+            result.add("mov rdx, rax".to_string());
+            result.add("and rdx, 1".to_string());
+            result.add("or rcx, rdx".to_string());
+            result.add("test rax, rax".to_string());
+            result.add_commented("cmovns rcx, rax", "small enough to convert directly");
+            result.add(format!("{} xmm0, rcx", convert_instr));
+            //double the result if it was halved
+            result.add(format!("mov rdx, {}", one));
+            result.add(format!("mov rcx, {}", two));
+            result.add("cmovs rdx, rcx".to_string());
+            result.add("movq xmm1, rdx".to_string());
+            result.add(format!("{} xmm0, xmm1", float_instruction("mul", y)));
+            result.merge(store_xmm(MMRegister::XMM0, to, y, stack));
+
+            result
+        }
+
+        (ScalarType::Integer(x), ScalarType::Float(y)) => {
+            let mut result = RawAssembly::default();
+            //definitely not u64, so extending to i64 keeps the value
+            result.merge(put_value_in_rax(&from.clone().try_into().unwrap(), x, stack));
+            result.add(format!("{} xmm0, rax", int_to_float_instruction(y)));
+            result.merge(store_xmm(MMRegister::XMM0, to, y, stack));
+
+            result
+        }
+    }
+}
+
+/// Truncating conversion from a float in an XMM register to a 64 bit signed integer
+fn float_to_int_instruction(data_type: &FloatType) -> &'static str {
+    match data_type {
+        FloatType::F32 => "cvttss2si",
+        FloatType::F64 => "cvttsd2si",
+    }
+}
+
+/// Conversion from a 64 bit signed integer to a float in an XMM register
+fn int_to_float_instruction(data_type: &FloatType) -> &'static str {
+    match data_type {
+        FloatType::F32 => "cvtsi2ss",
+        FloatType::F64 => "cvtsi2sd",
     }
 }
 
@@ -588,9 +1030,9 @@ fn instruction_mov(from: &IROperand, to: &IRMemOperand, size: MemorySize, stack:
         //data comes from a memory address
         IROperand::Memory(mem_location)=> {
             result.add_comment(format!("moving {} bytes", size.size_bytes()));
-            //loop through each eightbyte(or smaller)
-            for i in (0..size.size_bytes()).step_by(8) {
-                let offset = MemorySize::from_bytes(i);//find the offset into `from` and `to` that I am copying
+            //copy the biggest part that fits each time, until every byte has been copied
+            let mut offset = MemorySize::new();//the offset into `from` and `to` that I am copying
+            while offset < size {
                 let remaining_bytes = size - offset;//find the number of bytes left to copy
                 let best_reg_size = best_reg_size(remaining_bytes);//find the biggest register size to move the next part of the data
                 let cx_name = GPRegister::_CX.generate_name(best_reg_size);//to store the bytes temporarily
@@ -603,6 +1045,8 @@ fn instruction_mov(from: &IROperand, to: &IRMemOperand, size: MemorySize, stack:
                 result.merge(put_pointer_in_rax(&to.clone().try_into().unwrap(), stack));
                 //store the next few bytes from RCX
                 result.add(format!("mov [rax+{}], {}", offset.size_bytes(), cx_name));
+
+                offset += best_reg_size;
             }
         }
 
@@ -661,7 +1105,7 @@ fn instruction_mov(from: &IROperand, to: &IRMemOperand, size: MemorySize, stack:
 fn sign_extend(original: MemorySize) -> &'static str {
     match original.size_bytes() {
         1 => "cbw\ncwde\ncdqe",
-        2 => "cwde\ncqde",
+        2 => "cwde\ncdqe",
         4 => "cdqe",
         8 => "",
         _ => panic!("tried to sign extend unknown size")
@@ -764,6 +1208,23 @@ fn zero_extend(original: MemorySize) -> &'static str {
 //     }
 // }
 
+/// Finds how many bytes to read for an eightbyte that only has `remaining` bytes of data left, rounding up to a register size
+/// 
+/// Reading past the end of a struct only gets padding in the upper bytes of the register, which is allowed for args and returns
+fn eightbyte_load_size(remaining: MemorySize) -> MemorySize {
+    MemorySize::from_bytes(remaining.size_bytes().next_power_of_two())
+    .min(MemorySize::from_bytes(8))
+}
+
+/// Picks the instruction to move the raw bits of an SSE eightbyte between memory and a vector register
+fn xmm_mov_instruction(size: MemorySize) -> &'static str {
+    match size.size_bytes() {
+        4 => "movd",//a float, or a float and padding
+        8 => "movq",//a double, or two floats
+        x => panic!("SSE eightbytes are made of floats, so can't be {} bytes", x)
+    }
+}
+
 fn best_reg_size(x: MemorySize) -> MemorySize {
     assert_ne!(x, MemorySize::default());//cannot have a 0 register size
 
@@ -782,6 +1243,7 @@ impl IRDisplay for IROperation {
             IROperation::CMP { lhs, rhs, data_type } => format!("compare {:?}, {:?} ({})", lhs, rhs, data_type),
             IROperation::SETCC { to, data_type, comparison } => format!("set-{} {:?} ({})", comparison, to, data_type),
             IROperation::JMPCC { label, comparison } => format!("jump-{} to {}", comparison, label),
+            IROperation::JMPTABLE { value, data_type, table } => format!("jump via table {} indexed by {:?} - {} ({})", table.get_table_label(), value, table.get_lowest_value(), data_type),
             IROperation::CAST { from, from_type, to, to_type } => format!("cast {} -> {} ({:?} = {:?})", from_type, to_type, to, from),
             IROperation::ADD { lhs, rhs, to, data_type } => format!("{:?} = {:?} + {:?} ({})", to, lhs, rhs, data_type),
             IROperation::SUB { lhs, rhs, to, data_type } => format!("{:?} = {:?} - {:?} ({})", to, lhs, rhs, data_type),
@@ -797,8 +1259,18 @@ impl IRDisplay for IROperation {
             IROperation::CreateStackFrame => format!("create stack frame and reserve stack space"),
             IROperation::Return { return_data: None } => format!("return"),
             IROperation::Return { return_data: Some((return_location, storage, size)) } => format!("return {:?}", storage),
-            IROperation::CALL { label, params, return_data } => format!("call {}", label),
+            IROperation::CALL { target, params, return_data, variadic } => format!("call {}", target),
             IROperation::ReadParams { regs, mem } => format!("load params"),
+            IROperation::SaveVaArgRegs { reg_save_area } => format!("save arg registers to {:?}", reg_save_area),
+        }
+    }
+}
+
+impl Display for CallTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CallTarget::Direct(label) => write!(f, "{}", label),
+            CallTarget::Indirect(function_pointer) => write!(f, "*{:?}", function_pointer),
         }
     }
 }
@@ -815,6 +1287,11 @@ impl Display for LogicalOperation {
 
 impl Display for AsmComparison {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let AsmComparison::FLOAT(kind) = self {
+            //signedness is irrelevant when displaying
+            return write!(f, "float-{}", kind.to_asm_comparison(false));
+        }
+
         write!(f, "{}", match self {
             AsmComparison::ALWAYS => "always",
             AsmComparison::NE => "ne",
@@ -823,6 +1300,7 @@ impl Display for AsmComparison {
             AsmComparison::GE {..} => "ge",
             AsmComparison::L {..} => "l",
             AsmComparison::G {..} => "g",
+            AsmComparison::FLOAT(_) => unreachable!(),
         })
     }
 }
@@ -3,13 +3,15 @@ use colored::Colorize;
 use stack_management::{simple_stack_frame::SimpleStackFrame, stack_item::StackItemKey};
 use unwrap_let::unwrap_let;
 use memory_size::MemorySize;
-use crate::{asm_gen_data::{AsmData, GlobalAsmData}, assembly::{assembly::IRCode, operand::{immediate::ToImmediate,register::GPRegister, Storage, PTR_SIZE}, operation::IROperation}, data_type::{base_type::{BaseType, IntegerType, ScalarType}, recursive_data_type::{calculate_promoted_type_arithmetic, calculate_unary_type_arithmetic, DataType}}, debugging::ASTDisplay, expression::{binary_expression_operator::BinaryExpressionOperator, expression::{generate_assembly_for_assignment, promote, Expression}}, expression_visitors::{expr_visitor::ExprVisitor}, generate_ir_traits::{GenerateIR, GetType}, number_literal::typed_value::NumberLiteral};
+use serde::Serialize;
+use crate::{asm_gen_data::{AsmData, GlobalAsmData}, assembly::{assembly::IRCode, operand::{immediate::ToImmediate,register::GPRegister, IRMemOperand, IROperand, Storage, PTR_SIZE}, operation::IROperation}, data_type::{base_type::{BaseType, IntegerType, ScalarType}, recursive_data_type::{calculate_promoted_type_arithmetic, calculate_unary_type_arithmetic, DataType}}, debugging::ASTDisplay, expression::{binary_expression_operator::BinaryExpressionOperator, expression::{generate_assembly_for_assignment, promote, Expression}}, expression_visitors::{expr_visitor::ExprVisitor}, generate_ir_traits::{GenerateIR, GetAddress, GetType}, lexer::source_span::SourceSpan, number_literal::typed_value::NumberLiteral};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct BinaryExpression {
     pub lhs: Box<Expression>,
     pub operator: BinaryExpressionOperator,
     pub rhs: Box<Expression>,
+    pub span: SourceSpan,
 }
 
 impl BinaryExpression {
@@ -17,47 +19,35 @@ impl BinaryExpression {
         visitor.visit_binary_expression(self)
     }
 
-    pub fn new(lhs: Expression, operator: BinaryExpressionOperator, rhs: Expression) -> BinaryExpression {
+    pub fn new(lhs: Expression, operator: BinaryExpressionOperator, rhs: Expression, span: SourceSpan) -> BinaryExpression {
         BinaryExpression {
             lhs: Box::new(lhs),
             operator,
             rhs: Box::new(rhs),
+            span,
         }
     }
+
+    pub fn get_span(&self) -> &SourceSpan {
+        &self.span
+    }
 }
 impl GetType for BinaryExpression {
     fn get_type(&self, asm_data: &AsmData) -> DataType {
         match self.operator {
-            BinaryExpressionOperator::BitwiseOr |
-            BinaryExpressionOperator::BitwiseAnd |
-            BinaryExpressionOperator::BitwiseXor |
-            BinaryExpressionOperator::Add |
-            BinaryExpressionOperator::Subtract |
-            BinaryExpressionOperator::Multiply | 
-            BinaryExpressionOperator::Divide | 
-            BinaryExpressionOperator::Mod => {
-                calculate_promoted_type_arithmetic(//calculate type when data types:
-                    &self.lhs.get_type(asm_data),//type of lhs
-                    &self.rhs.get_type(asm_data),//type of rhs
-                )
-            },
-
             BinaryExpressionOperator::Assign |
             BinaryExpressionOperator::AdditionCombination |
-            BinaryExpressionOperator::SubtractionCombination => self.lhs.get_type(asm_data),//assigning, rhs must be converted to lhs
-
-            //bit shifts have lhs promoted, then resultant type is the same as promoted lhs
-            BinaryExpressionOperator::BitshiftLeft |
-            BinaryExpressionOperator::BitshiftRight => calculate_unary_type_arithmetic(&self.lhs.get_type(asm_data)),
-
-            BinaryExpressionOperator::CmpLess |
-            BinaryExpressionOperator::CmpGreater |
-            BinaryExpressionOperator::CmpGreaterEqual |
-            BinaryExpressionOperator::CmpLessEqual |
-            BinaryExpressionOperator::CmpEqual |
-            BinaryExpressionOperator::CmpNotEqual |
-            BinaryExpressionOperator::BooleanOr |
-            BinaryExpressionOperator::BooleanAnd  => DataType::RAW(BaseType::Scalar(ScalarType::Integer(IntegerType::_BOOL))),
+            BinaryExpressionOperator::SubtractionCombination |
+            BinaryExpressionOperator::MultiplicationCombination |
+            BinaryExpressionOperator::DivisionCombination |
+            BinaryExpressionOperator::ModCombination |
+            BinaryExpressionOperator::BitshiftLeftCombination |
+            BinaryExpressionOperator::BitshiftRightCombination |
+            BinaryExpressionOperator::BitwiseAndCombination |
+            BinaryExpressionOperator::BitwiseOrCombination |
+            BinaryExpressionOperator::BitwiseXorCombination => self.lhs.get_type(asm_data),//assigning, rhs must be converted to lhs
+
+            _ => calculate_binary_operation_type(&self.operator, &self.lhs.get_type(asm_data), &self.rhs.get_type(asm_data)),
         }
     }
 }
@@ -69,6 +59,10 @@ impl GenerateIR for BinaryExpression {
             return generate_assembly_for_assignment(&self.lhs, &self.rhs, asm_data, stack_data, global_asm_data);
         }
 
+        if let Some(operation) = self.operator.as_compound_assignment_base() {
+            return generate_assembly_for_compound_assignment(&self.lhs, &operation, &self.rhs, asm_data, stack_data, global_asm_data);
+        }
+
         //todo maybe decay these?
         let lhs_type = self.lhs.get_type(asm_data).decay();
         let rhs_type = self.rhs.get_type(asm_data).decay();
@@ -79,418 +73,9 @@ impl GenerateIR for BinaryExpression {
         result.add_comment("generating rhs of binary expression");
         let (rhs_asm, rhs_result) = self.rhs.generate_ir(asm_data, stack_data, global_asm_data);
         result.merge(&rhs_asm);
-        
-        //what type the result is
-        let resultant_type = self.get_type(asm_data);
-
-        //the type lhs and rhs have to be promoted to (sometimes rhs doesn't get promoted to this, as in bit shifts)
-        let promoted_type = match &self.operator {
-            BinaryExpressionOperator::Assign => unreachable!(),
-
-            BinaryExpressionOperator::BitshiftLeft |
-            BinaryExpressionOperator::BitshiftRight => calculate_unary_type_arithmetic(&lhs_type),//bit shift type is related to the number being shifted
-            x if x.as_boolean_instr().is_some() => DataType::RAW(BaseType::Scalar(ScalarType::Integer(IntegerType::_BOOL))),//is a boolean operator, operands are booleans
-            BinaryExpressionOperator::AdditionCombination => lhs_type.clone(),
-            _ => calculate_promoted_type_arithmetic(&lhs_type, &rhs_type)//else find a common meeting ground
-        };
-        
-        //where the result of the computation goes
-        let resultant_location = stack_data.allocate(resultant_type.memory_size(asm_data));
-        //use these as lhs and rhs, as they have correct promotions (or correctly have no promotion)
-        let (lhs_promoted, rhs_promoted) = match &self.operator {
-            BinaryExpressionOperator::Add |
-            BinaryExpressionOperator::Subtract |
-
-            BinaryExpressionOperator::Multiply |
-            BinaryExpressionOperator::Divide |
-            BinaryExpressionOperator::Mod |
-            BinaryExpressionOperator::CmpEqual |
-            BinaryExpressionOperator::CmpGreater |
-            BinaryExpressionOperator::CmpGreaterEqual |
-            BinaryExpressionOperator::CmpLess |
-            BinaryExpressionOperator::CmpLessEqual |
-            BinaryExpressionOperator::CmpNotEqual |
-            BinaryExpressionOperator::BooleanOr |
-            BinaryExpressionOperator::BooleanAnd |
-            BinaryExpressionOperator::AdditionCombination => {
-                let (promote_lhs_op, lhs_promoted) = promote(lhs_result.unwrap(), lhs_type.clone(), promoted_type.clone(), stack_data, asm_data);
-                let (promote_rhs_op, rhs_promoted) = promote(rhs_result.unwrap(), rhs_type.clone(), promoted_type.clone(), stack_data, asm_data);
-
-                result.add_instruction(promote_lhs_op);
-                result.add_instruction(promote_rhs_op);
-
-                (lhs_promoted, rhs_promoted)
-            }
-
-            BinaryExpressionOperator::BitshiftLeft |
-            BinaryExpressionOperator::BitshiftRight => {
-                let (promote_lhs_op, lhs_promoted) = promote(lhs_result.unwrap(), lhs_type.clone(), promoted_type.clone(), stack_data, asm_data);
-                let (promote_rhs_op, rhs_promoted) = promote(rhs_result.unwrap(), rhs_type.clone(), DataType::RAW(BaseType::Scalar(ScalarType::Integer(IntegerType::U8))), stack_data, asm_data);//can only shift by u8 in assembly
-
-                result.add_instruction(promote_lhs_op);
-                result.add_instruction(promote_rhs_op);
-
-                (lhs_promoted, rhs_promoted)
-            }
-
-
-
-            _ => todo!()
-        };
-
-        //do different work based on the operator
-        match &self.operator {
-            BinaryExpressionOperator::Add => {
-                result.add_comment(format!("adding {} numbers", promoted_type.memory_size(asm_data)));
-
-                let (ptr_scale_asm, lhs_scaled, rhs_scaled) = apply_pointer_scaling(lhs_promoted, &lhs_type, rhs_promoted, &rhs_type, &promoted_type, asm_data);
-                result.merge(&ptr_scale_asm);
-
-                result.add_instruction(IROperation::ADD {
-                    lhs: Storage::Stack(lhs_scaled),
-                    rhs: Storage::Stack(rhs_scaled),
-                    to: Storage::Stack(resultant_location),
-                    data_type: promoted_type.decay_to_primative(),
-                });
-                
-            },
-            BinaryExpressionOperator::Subtract => {
-                result.add_comment(format!("subtracting {} numbers", promoted_type.memory_size(asm_data)));
-
-                let (ptr_scale_asm, lhs_scaled, rhs_scaled) = apply_pointer_scaling(lhs_promoted, &lhs_type, rhs_promoted, &rhs_type, &promoted_type, asm_data);
-                result.merge(&ptr_scale_asm);
-
-                result.add_instruction(IROperation::SUB {
-                    lhs: Storage::Stack(lhs_scaled),
-                    rhs: Storage::Stack(rhs_scaled),
-                    to: Storage::Stack(resultant_location),
-                    data_type: promoted_type.decay_to_primative(),
-                });
-
-            }
-            BinaryExpressionOperator::Multiply => {
-                result.add_comment("mulitplying numbers");
-
-                unwrap_let!(DataType::RAW(BaseType::Scalar(promoted_underlying)) = promoted_type);
-                result.add_instruction(IROperation::MUL {
-                    data_type: promoted_underlying,
-                    lhs: Storage::Stack(lhs_promoted),
-                    rhs: Storage::Stack(rhs_promoted),
-                    to: Storage::Stack(resultant_location),
-                });
-
-            },
-            BinaryExpressionOperator::Divide => {
-                result.add_comment("dividing numbers");
-
-                unwrap_let!(DataType::RAW(BaseType::Scalar(promoted_base)) = promoted_type);
-                result.add_instruction(IROperation::DIV {
-                    data_type: promoted_base,
-                    lhs: Storage::Stack(lhs_promoted),
-                    rhs: Storage::Stack(rhs_promoted),
-                    to: Storage::Stack(resultant_location),
-                });
-            },
-
-            BinaryExpressionOperator::Mod => {
-                result.add_comment("calculating modulus");
-
-                unwrap_let!(DataType::RAW(BaseType::Scalar(ScalarType::Integer(promoted_base))) = promoted_type);
-                result.add_instruction(IROperation::MOD {
-                    data_type: promoted_base,
-                    lhs: Storage::Stack(lhs_promoted),
-                    rhs: Storage::Stack(rhs_promoted),
-                    to: Storage::Stack(resultant_location),
-                });
-            }
-
-            comparison if comparison.as_comparator_instr().is_some() => { // >, <, ==, >=, <=
-                result.add_comment("comparing numbers");
-
-                let promoted_base = promoted_type.decay_to_primative();
-                result.add_instruction(IROperation::CMP {
-                    lhs: Storage::Stack(lhs_promoted),
-                    rhs: Storage::Stack(rhs_promoted),
-                    data_type: promoted_base,
-                });
-
-                let asm_comparison = comparison
-                    .as_comparator_instr()
-                    .unwrap()
-                    .to_asm_comparison(match promoted_type.decay_to_primative() {
-                        ScalarType::Float(_) => false,//float comparisons need unsigned setcc/jmpcc instructions for some reason
-                        ScalarType::Integer(integer_type) => !integer_type.is_unsigned(),
-                    });//take signedness and convert comparison kind to an asm comparison
-
-                //create the correct setcc instruction
-                result.add_instruction(IROperation::SETCC {
-                    comparison: asm_comparison,
-                    to: Storage::Stack(resultant_location),
-                    data_type: IntegerType::_BOOL//returns a bool, not taking into account the promoted type of lhs and rhs (obviously)
-                });
-
-            },
-
-            operator if operator.as_boolean_instr().is_some() => {
-                assert_eq!(promoted_type, DataType::RAW(BaseType::Scalar(ScalarType::Integer(IntegerType::_BOOL))));
-                result.add_comment("applying boolean operator");
-
-                let instruction = operator.as_boolean_instr().unwrap();
-                //operands should have been converted into booleans as this is a boolean instruction
-                result.add_instruction(IROperation::BitwiseOp {
-                    operation: instruction,
-                    lhs: Storage::Stack(lhs_promoted),
-                    rhs: Storage::Stack(rhs_promoted),
-                    to: Storage::Stack(resultant_location),
-                    size: promoted_type.memory_size(asm_data),
-                });
-            },
-
-            operator if operator.as_bitwise_binary_instr().is_some() => {
-                result.add_comment("applying bitwise operator");
-
-                let instruction = operator.as_bitwise_binary_instr().unwrap();
-
-                result.add_instruction(IROperation::BitwiseOp {
-                    operation: instruction,
-                    lhs: Storage::Stack(lhs_promoted),
-                    rhs: Storage::Stack(rhs_promoted),
-                    to: Storage::Stack(resultant_location),
-                    size: promoted_type.memory_size(asm_data),
-                });
-            },
-
-            //bit shifts left or right
-            BinaryExpressionOperator::BitshiftRight => {
-                result.add_comment("bitwise shift right");
-                
-                unwrap_let!(DataType::RAW(BaseType::Scalar(ScalarType::Integer(lhs_type))) = promoted_type);
-                result.add_instruction(IROperation::SHR {
-                    from: Storage::Stack(lhs_promoted),
-                    from_type: lhs_type,
-                    amount: Storage::Stack(rhs_promoted),
-                    to: Storage::Stack(resultant_location),
-                });
-            }
-            BinaryExpressionOperator::BitshiftLeft => {
-                result.add_comment("bitwise shift left");
-                
-                unwrap_let!(DataType::RAW(BaseType::Scalar(ScalarType::Integer(lhs_type))) = promoted_type);
-                result.add_instruction(IROperation::SHL {
-                    from: Storage::Stack(lhs_promoted),
-                    from_type: lhs_type,
-                    amount: Storage::Stack(rhs_promoted),
-                    to: Storage::Stack(resultant_location),
-                });
-            }
-
-            // BinaryExpressionOperator::AdditionCombination => {
-            //     let promoted_type = ();
-            //     let promoted_size = ();
-            //     result.add_comment("addition combination (+=)");
-
-            //     // *a++ += b is not the same as *a++ = *a++ + b because a might be incremented once or twice
-            //     // so I need a custom implementation
-            //     let lhs_type = self.lhs.get_type(asm_data);
-            //     let rhs_type = self.rhs.get_type(asm_data);
-
-            //     let lhs_ptr_asm = self.lhs.accept(&mut ReferenceVisitor {asm_data, stack_data, global_asm_data});
-            //     result.merge(&lhs_ptr_asm);//put pointer to lhs in acc
-            //     //save the pointer
-            //     let lhs_ptr_temporary_address = stack_data.allocate(PTR_SIZE);
-            //     result.add_instruction(AsmOperation::MOV {
-            //         to: RegOrMem::Mem(MemoryOperand::SubFromBP(lhs_ptr_temporary_address)),
-            //         from: Operand::GPReg(GPRegister::acc()),
-            //         size: PTR_SIZE
-            //     });
-
-            //     //calculate and cast rhs value
-            //     let rhs_asm = self.rhs.accept(&mut ScalarInAccVisitor {asm_data, stack_data, global_asm_data});
-            //     let rhs_cast_asm = cast_from_acc(&rhs_type, &lhs_type, asm_data);
-            //     result.merge(&rhs_asm);
-            //     result.merge(&rhs_cast_asm);//cast to lhs as that will be incremented
-
-            //     if let DataType::POINTER(_) = lhs_type.decay() {
-            //         //you can only add pointer and number here, as per the C standard
-            //         let lhs_deref_size = lhs_type.remove_outer_modifier().memory_size(asm_data);
-
-            //         result.add_comment(format!("lhs is a pointer. make rhs {} times bigger", lhs_deref_size.size_bytes()));
-
-            //         //get the size of value pointed to by rhs
-            //         result.add_instruction(AsmOperation::MOV {
-            //             to: RegOrMem::GPReg(GPRegister::_CX),
-            //             from: Operand::Imm(lhs_deref_size.as_imm()),
-            //             size: MemorySize::from_bytes(8),
-            //         });
-
-            //         //multiply lhs by the size of value pointed to by rhs, so that +1 would skip along 1 value, not 1 byte
-            //         result.add_instruction(AsmOperation::MUL {
-            //             multiplier: RegOrMem::GPReg(GPRegister::_CX),
-            //             data_type: lhs_type.decay_to_primative(),//rhs has been promoted to lhs's type
-            //         });
-            //     }
-
-            //     //put RHS in secondary
-            //     result.add_commented_instruction(
-            //         AsmOperation::MOV { to: RegOrMem::GPReg(GPRegister::secondary()), from: Operand::GPReg(GPRegister::acc()), size: MemorySize::from_bytes(8)},
-            //         "put RHS in secondary"
-            //     );
-
-            //     // put a pointer to lhs to acc
-            //     result.add_commented_instruction(
-            //         AsmOperation::MOV { 
-            //             to: RegOrMem::GPReg(GPRegister::acc()),
-            //             from: Operand::Mem(MemoryOperand::SubFromBP(lhs_ptr_temporary_address)),
-            //             size: PTR_SIZE
-            //         },
-            //         "put a pointer to lhs in acc"
-            //     );
-            //     //dereference the pointer
-            //     result.add_commented_instruction(
-            //         AsmOperation::MOV {
-            //         to: RegOrMem::GPReg(GPRegister::acc()), 
-            //             from: Operand::Mem(MemoryOperand::MemoryAddress { pointer_reg: GPRegister::acc()}),
-            //             size: lhs_type.memory_size(asm_data)
-            //         },
-            //         "dereference pointer to lhs"
-            //     );
-            //     //increment lhs by rhs
-            //     result.add_commented_instruction(AsmOperation::ADD {
-            //         increment: Operand::GPReg(GPRegister::secondary()),
-            //         data_type: lhs_type.decay_to_primative()
-            //     }, "increment lhs by rhs");
-            //     //put the addition result in secondary
-            //     result.add_commented_instruction(
-            //         AsmOperation::MOV { to: RegOrMem::GPReg(GPRegister::secondary()), from: Operand::GPReg(GPRegister::acc()), size: lhs_type.memory_size(asm_data) },
-            //         "move the result to secondary"
-            //     );
-
-            //     //get a pointer to lhs again
-            //     result.add_commented_instruction(
-            //         AsmOperation::MOV { 
-            //             to: RegOrMem::GPReg(GPRegister::acc()),
-            //             from: Operand::Mem(MemoryOperand::SubFromBP(lhs_ptr_temporary_address)),
-            //             size: PTR_SIZE
-            //         },
-            //         "get a pointer to lhs again"
-            //     );
-            //     //save the results
-            //     result.add_commented_instruction(
-            //         AsmOperation::MOV { to: RegOrMem::Mem(MemoryOperand::MemoryAddress { pointer_reg: GPRegister::acc() }), from: Operand::GPReg(GPRegister::secondary()), size: lhs_type.memory_size(asm_data) },
-            //         "save the results back to lhs"
-            //     );
-
-            //     result.add_commented_instruction(
-            //         AsmOperation::MOV { to: RegOrMem::GPReg(GPRegister::acc()), from: Operand::GPReg(GPRegister::secondary()), size: lhs_type.memory_size(asm_data) },
-            //         "leave the result in acc"
-            //     );
-            // }
-
-            // BinaryExpressionOperator::SubtractionCombination => {
-            //     let promoted_type = ();
-            //     let promoted_size = ();
-            //     result.add_comment("addition combination (+=)");
-
-            //     // *a++ += b is not the same as *a++ = *a++ + b because a might be incremented once or twice
-            //     // so I need a custom implementation
-            //     let lhs_type = self.lhs.get_type(asm_data);
-            //     let rhs_type = self.rhs.get_type(asm_data);
-
-            //     let lhs_ptr_asm = self.lhs.accept(&mut ReferenceVisitor {asm_data, stack_data, global_asm_data});
-            //     result.merge(&lhs_ptr_asm);//put pointer to lhs in acc
-            //     //save the pointer
-            //     let lhs_ptr_temporary_address = stack_data.allocate(PTR_SIZE);
-            //     result.add_instruction(AsmOperation::MOV {
-            //         to: RegOrMem::Mem(MemoryOperand::SubFromBP(lhs_ptr_temporary_address)),
-            //         from: Operand::GPReg(GPRegister::acc()),
-            //         size: PTR_SIZE
-            //     });
-
-            //     //calculate and cast rhs value
-            //     let rhs_asm = self.rhs.accept(&mut ScalarInAccVisitor {asm_data, stack_data, global_asm_data});
-            //     let rhs_cast_asm = cast_from_acc(&rhs_type, &lhs_type, asm_data);
-            //     result.merge(&rhs_asm);
-            //     result.merge(&rhs_cast_asm);//cast to lhs as that will be incremented
-
-            //     if let DataType::POINTER(_) = lhs_type.decay() {
-            //         //you can only add pointer and number here, as per the C standard
-            //         let lhs_deref_size = lhs_type.remove_outer_modifier().memory_size(asm_data);
-
-            //         result.add_comment(format!("lhs is a pointer. make rhs {} times bigger", lhs_deref_size.size_bytes()));
-
-            //         //get the size of value pointed to by rhs
-            //         result.add_instruction(AsmOperation::MOV {
-            //             to: RegOrMem::GPReg(GPRegister::_CX),
-            //             from: Operand::Imm(lhs_deref_size.as_imm()),
-            //             size: MemorySize::from_bytes(8),
-            //         });
-
-            //         //multiply lhs by the size of value pointed to by rhs, so that +1 would skip along 1 value, not 1 byte
-            //         result.add_instruction(AsmOperation::MUL {
-            //             multiplier: RegOrMem::GPReg(GPRegister::_CX),
-            //             data_type: lhs_type.decay_to_primative(),//rhs has been promoted to lhs's type
-            //         });
-                    
-            //     }
-
-            //     //put RHS in secondary
-            //     result.add_commented_instruction(
-            //         AsmOperation::MOV { to: RegOrMem::GPReg(GPRegister::secondary()), from: Operand::GPReg(GPRegister::acc()), size: MemorySize::from_bytes(8)},
-            //         "put RHS in secondary"
-            //     );
-
-            //     // put a pointer to lhs to acc
-            //     result.add_commented_instruction(
-            //         AsmOperation::MOV { 
-            //             to: RegOrMem::GPReg(GPRegister::acc()),
-            //             from: Operand::Mem(MemoryOperand::SubFromBP(lhs_ptr_temporary_address)),
-            //             size: PTR_SIZE
-            //         },
-            //         "put a pointer to lhs in acc"
-            //     );
-            //     //dereference the pointer
-            //     result.add_commented_instruction(
-            //         AsmOperation::MOV {
-            //         to: RegOrMem::GPReg(GPRegister::acc()), 
-            //             from: Operand::Mem(MemoryOperand::MemoryAddress { pointer_reg: GPRegister::acc()}),
-            //             size: lhs_type.memory_size(asm_data)
-            //         },
-            //         "dereference pointer to lhs"
-            //     );
-            //     //increment lhs by rhs
-            //     result.add_commented_instruction(AsmOperation::SUB {
-            //         decrement: Operand::GPReg(GPRegister::secondary()),
-            //         data_type: lhs_type.decay_to_primative()
-            //     }, "increment lhs by rhs");
-            //     //put the addition result in secondary
-            //     result.add_commented_instruction(
-            //         AsmOperation::MOV { to: RegOrMem::GPReg(GPRegister::secondary()), from: Operand::GPReg(GPRegister::acc()), size: lhs_type.memory_size(asm_data) },
-            //         "move the result to secondary"
-            //     );
-
-            //     //get a pointer to lhs again
-            //     result.add_commented_instruction(
-            //         AsmOperation::MOV { 
-            //             to: RegOrMem::GPReg(GPRegister::acc()),
-            //             from: Operand::Mem(MemoryOperand::SubFromBP(lhs_ptr_temporary_address)),
-            //             size: PTR_SIZE
-            //         },
-            //         "get a pointer to lhs again"
-            //     );
-            //     //save the results
-            //     result.add_commented_instruction(
-            //         AsmOperation::MOV { to: RegOrMem::Mem(MemoryOperand::MemoryAddress { pointer_reg: GPRegister::acc() }), from: Operand::GPReg(GPRegister::secondary()), size: lhs_type.memory_size(asm_data) },
-            //         "save the results back to lhs"
-            //     );
-
-            //     result.add_commented_instruction(
-            //         AsmOperation::MOV { to: RegOrMem::GPReg(GPRegister::acc()), from: Operand::GPReg(GPRegister::secondary()), size: lhs_type.memory_size(asm_data) },
-            //         "leave the result in acc"
-            //     );
-            // }
-
-            _ => panic!("assignment must be done beforehand")
-        }
+
+        let (operation_asm, resultant_location) = generate_binary_operation(&self.operator, lhs_result.unwrap(), lhs_type, rhs_result.unwrap(), rhs_type, asm_data, stack_data);
+        result.merge(&operation_asm);
 
         (result, Some(resultant_location))
     }
@@ -507,6 +92,304 @@ impl ASTDisplay for BinaryExpression {
     }
 }
 
+/// Calculates the type of `lhs operator rhs`, for operators that do not assign
+fn calculate_binary_operation_type(operator: &BinaryExpressionOperator, lhs_type: &DataType, rhs_type: &DataType) -> DataType {
+    match operator {
+        BinaryExpressionOperator::BitwiseOr |
+        BinaryExpressionOperator::BitwiseAnd |
+        BinaryExpressionOperator::BitwiseXor |
+        BinaryExpressionOperator::Add |
+        BinaryExpressionOperator::Subtract |
+        BinaryExpressionOperator::Multiply | 
+        BinaryExpressionOperator::Divide | 
+        BinaryExpressionOperator::Mod => {
+            calculate_promoted_type_arithmetic(lhs_type, rhs_type)
+        },
+
+        //bit shifts have lhs promoted, then resultant type is the same as promoted lhs
+        BinaryExpressionOperator::BitshiftLeft |
+        BinaryExpressionOperator::BitshiftRight => calculate_unary_type_arithmetic(lhs_type),
+
+        BinaryExpressionOperator::CmpLess |
+        BinaryExpressionOperator::CmpGreater |
+        BinaryExpressionOperator::CmpGreaterEqual |
+        BinaryExpressionOperator::CmpLessEqual |
+        BinaryExpressionOperator::CmpEqual |
+        BinaryExpressionOperator::CmpNotEqual |
+        BinaryExpressionOperator::BooleanOr |
+        BinaryExpressionOperator::BooleanAnd  => DataType::RAW(BaseType::Scalar(ScalarType::Integer(IntegerType::_BOOL))),
+
+        x => panic!("{:?} assigns, so has the type of its lhs", x)
+    }
+}
+
+/// Generates `lhs op= rhs`, calculating the address of lhs only once
+/// 
+/// `*a++ += b` is not the same as `*a++ = *a++ + b` because a would be incremented twice
+fn generate_assembly_for_compound_assignment(lhs: &Expression, operator: &BinaryExpressionOperator, rhs: &Expression, asm_data: &AsmData, stack_data: &mut SimpleStackFrame, global_asm_data: &GlobalAsmData) -> (IRCode, Option<StackItemKey>) {
+    let mut result = IRCode::make_empty();
+
+    let lhs_type = lhs.get_type(asm_data);
+    let rhs_type = rhs.get_type(asm_data).decay();
+    let lhs_size = lhs_type.memory_size(asm_data);
+
+    //put address of lvalue on stack
+    result.add_comment("getting address of lhs of compound assignment");
+    let (lhs_addr_asm, lhs_addr_ptr) = lhs.get_address(asm_data, stack_data, global_asm_data);
+    result.merge(&lhs_addr_asm);
+
+    //read the current value of lhs
+    let lhs_value = stack_data.allocate(lhs_size);
+    result.add_instruction(IROperation::MOV {
+        from: IROperand::Memory(IRMemOperand::IndirectAddress { pointer_location: Box::new(IRMemOperand::Stack { base: lhs_addr_ptr }) }),
+        to: IRMemOperand::Stack { base: lhs_value },
+        size: lhs_size,
+    });
+
+    result.add_comment("generating rhs of compound assignment");
+    let (rhs_asm, rhs_result) = rhs.generate_ir(asm_data, stack_data, global_asm_data);
+    result.merge(&rhs_asm);
+
+    //apply the operation, as if it were `lhs op rhs`
+    let (operation_asm, operation_result) = generate_binary_operation(operator, lhs_value, lhs_type.decay(), rhs_result.unwrap(), rhs_type.clone(), asm_data, stack_data);
+    result.merge(&operation_asm);
+
+    //convert the result back to lhs's type
+    let operation_type = calculate_binary_operation_type(operator, &lhs_type, &rhs_type);
+    let (cast_asm, casted_result) = promote(operation_result, operation_type, lhs_type.clone(), stack_data, asm_data);
+    result.add_instruction(cast_asm);
+
+    result.add_comment("saving result of compound assignment");
+    result.add_instruction(IROperation::MOV {
+        from: IROperand::Memory(IRMemOperand::Stack { base: casted_result }),
+        to: IRMemOperand::IndirectAddress { pointer_location: Box::new(IRMemOperand::Stack { base: lhs_addr_ptr }) },
+        size: lhs_size,
+    });
+
+    (result, Some(casted_result))
+}
+
+/// Generates `lhs_location operator rhs_location`, where the operands have already been calculated
+/// 
+/// Returns the assembly and the location of the result
+fn generate_binary_operation(operator: &BinaryExpressionOperator, lhs_location: StackItemKey, lhs_type: DataType, rhs_location: StackItemKey, rhs_type: DataType, asm_data: &AsmData, stack_data: &mut SimpleStackFrame) -> (IRCode, StackItemKey) {
+    let mut result = IRCode::make_empty();
+
+    //what type the result is
+    let resultant_type = calculate_binary_operation_type(operator, &lhs_type, &rhs_type);
+
+    //the type lhs and rhs have to be promoted to (sometimes rhs doesn't get promoted to this, as in bit shifts)
+    let promoted_type = match operator {
+        BinaryExpressionOperator::BitshiftLeft |
+        BinaryExpressionOperator::BitshiftRight => calculate_unary_type_arithmetic(&lhs_type),//bit shift type is related to the number being shifted
+        x if x.as_boolean_instr().is_some() => DataType::RAW(BaseType::Scalar(ScalarType::Integer(IntegerType::_BOOL))),//is a boolean operator, operands are booleans
+        _ => calculate_promoted_type_arithmetic(&lhs_type, &rhs_type)//else find a common meeting ground
+    };
+
+    //where the result of the computation goes
+    let resultant_location = stack_data.allocate(resultant_type.memory_size(asm_data));
+    //use these as lhs and rhs, as they have correct promotions (or correctly have no promotion)
+    let (lhs_promoted, rhs_promoted) = match operator {
+        BinaryExpressionOperator::Add |
+        BinaryExpressionOperator::Subtract |
+
+        BinaryExpressionOperator::Multiply |
+        BinaryExpressionOperator::Divide |
+        BinaryExpressionOperator::Mod |
+        BinaryExpressionOperator::CmpEqual |
+        BinaryExpressionOperator::CmpGreater |
+        BinaryExpressionOperator::CmpGreaterEqual |
+        BinaryExpressionOperator::CmpLess |
+        BinaryExpressionOperator::CmpLessEqual |
+        BinaryExpressionOperator::CmpNotEqual |
+        BinaryExpressionOperator::BooleanOr |
+        BinaryExpressionOperator::BooleanAnd |
+        BinaryExpressionOperator::BitwiseOr |
+        BinaryExpressionOperator::BitwiseAnd |
+        BinaryExpressionOperator::BitwiseXor => {
+            let (promote_lhs_op, lhs_promoted) = promote(lhs_location, lhs_type.clone(), promoted_type.clone(), stack_data, asm_data);
+            let (promote_rhs_op, rhs_promoted) = promote(rhs_location, rhs_type.clone(), promoted_type.clone(), stack_data, asm_data);
+
+            result.add_instruction(promote_lhs_op);
+            result.add_instruction(promote_rhs_op);
+
+            (lhs_promoted, rhs_promoted)
+        }
+
+        BinaryExpressionOperator::BitshiftLeft |
+        BinaryExpressionOperator::BitshiftRight => {
+            let (promote_lhs_op, lhs_promoted) = promote(lhs_location, lhs_type.clone(), promoted_type.clone(), stack_data, asm_data);
+            let (promote_rhs_op, rhs_promoted) = promote(rhs_location, rhs_type.clone(), DataType::RAW(BaseType::Scalar(ScalarType::Integer(IntegerType::U8))), stack_data, asm_data);//can only shift by u8 in assembly
+
+            result.add_instruction(promote_lhs_op);
+            result.add_instruction(promote_rhs_op);
+
+            (lhs_promoted, rhs_promoted)
+        }
+
+        BinaryExpressionOperator::Assign |
+        BinaryExpressionOperator::AdditionCombination |
+        BinaryExpressionOperator::SubtractionCombination |
+        BinaryExpressionOperator::MultiplicationCombination |
+        BinaryExpressionOperator::DivisionCombination |
+        BinaryExpressionOperator::ModCombination |
+        BinaryExpressionOperator::BitshiftLeftCombination |
+        BinaryExpressionOperator::BitshiftRightCombination |
+        BinaryExpressionOperator::BitwiseAndCombination |
+        BinaryExpressionOperator::BitwiseOrCombination |
+        BinaryExpressionOperator::BitwiseXorCombination => unreachable!("{:?} assigns, so is generated before getting here", operator)
+    };
+
+    //do different work based on the operator
+    match operator {
+        BinaryExpressionOperator::Add => {
+            result.add_comment(format!("adding {} numbers", promoted_type.memory_size(asm_data)));
+
+            let (ptr_scale_asm, lhs_scaled, rhs_scaled) = apply_pointer_scaling(lhs_promoted, &lhs_type, rhs_promoted, &rhs_type, &promoted_type, asm_data);
+            result.merge(&ptr_scale_asm);
+
+            result.add_instruction(IROperation::ADD {
+                lhs: Storage::Stack(lhs_scaled),
+                rhs: Storage::Stack(rhs_scaled),
+                to: Storage::Stack(resultant_location),
+                data_type: promoted_type.decay_to_primative(),
+            });
+            
+        },
+        BinaryExpressionOperator::Subtract => {
+            result.add_comment(format!("subtracting {} numbers", promoted_type.memory_size(asm_data)));
+
+            let (ptr_scale_asm, lhs_scaled, rhs_scaled) = apply_pointer_scaling(lhs_promoted, &lhs_type, rhs_promoted, &rhs_type, &promoted_type, asm_data);
+            result.merge(&ptr_scale_asm);
+
+            result.add_instruction(IROperation::SUB {
+                lhs: Storage::Stack(lhs_scaled),
+                rhs: Storage::Stack(rhs_scaled),
+                to: Storage::Stack(resultant_location),
+                data_type: promoted_type.decay_to_primative(),
+            });
+
+        }
+        BinaryExpressionOperator::Multiply => {
+            result.add_comment("mulitplying numbers");
+
+            unwrap_let!(DataType::RAW(BaseType::Scalar(promoted_underlying)) = promoted_type);
+            result.add_instruction(IROperation::MUL {
+                data_type: promoted_underlying,
+                lhs: Storage::Stack(lhs_promoted),
+                rhs: Storage::Stack(rhs_promoted),
+                to: Storage::Stack(resultant_location),
+            });
+
+        },
+        BinaryExpressionOperator::Divide => {
+            result.add_comment("dividing numbers");
+
+            unwrap_let!(DataType::RAW(BaseType::Scalar(promoted_base)) = promoted_type);
+            result.add_instruction(IROperation::DIV {
+                data_type: promoted_base,
+                lhs: Storage::Stack(lhs_promoted),
+                rhs: Storage::Stack(rhs_promoted),
+                to: Storage::Stack(resultant_location),
+            });
+        },
+
+        BinaryExpressionOperator::Mod => {
+            result.add_comment("calculating modulus");
+
+            unwrap_let!(DataType::RAW(BaseType::Scalar(ScalarType::Integer(promoted_base))) = promoted_type);
+            result.add_instruction(IROperation::MOD {
+                data_type: promoted_base,
+                lhs: Storage::Stack(lhs_promoted),
+                rhs: Storage::Stack(rhs_promoted),
+                to: Storage::Stack(resultant_location),
+            });
+        }
+
+        comparison if comparison.as_comparator_instr().is_some() => { // >, <, ==, >=, <=
+            result.add_comment("comparing numbers");
+
+            let promoted_base = promoted_type.decay_to_primative();
+            result.add_instruction(IROperation::CMP {
+                lhs: Storage::Stack(lhs_promoted),
+                rhs: Storage::Stack(rhs_promoted),
+                data_type: promoted_base.clone(),
+            });
+
+            let asm_comparison = comparison
+                .as_comparator_instr()
+                .unwrap()
+                .to_asm_comparison_for(&promoted_base);//take signedness (or float-ness) and convert comparison kind to an asm comparison
+
+            //create the correct setcc instruction
+            result.add_instruction(IROperation::SETCC {
+                comparison: asm_comparison,
+                to: Storage::Stack(resultant_location),
+                data_type: IntegerType::_BOOL//returns a bool, not taking into account the promoted type of lhs and rhs (obviously)
+            });
+
+        },
+
+        operator if operator.as_boolean_instr().is_some() => {
+            assert_eq!(promoted_type, DataType::RAW(BaseType::Scalar(ScalarType::Integer(IntegerType::_BOOL))));
+            result.add_comment("applying boolean operator");
+
+            let instruction = operator.as_boolean_instr().unwrap();
+            //operands should have been converted into booleans as this is a boolean instruction
+            result.add_instruction(IROperation::BitwiseOp {
+                operation: instruction,
+                lhs: Storage::Stack(lhs_promoted),
+                rhs: Storage::Stack(rhs_promoted),
+                to: Storage::Stack(resultant_location),
+                size: promoted_type.memory_size(asm_data),
+            });
+        },
+
+        operator if operator.as_bitwise_binary_instr().is_some() => {
+            result.add_comment("applying bitwise operator");
+
+            let instruction = operator.as_bitwise_binary_instr().unwrap();
+
+            result.add_instruction(IROperation::BitwiseOp {
+                operation: instruction,
+                lhs: Storage::Stack(lhs_promoted),
+                rhs: Storage::Stack(rhs_promoted),
+                to: Storage::Stack(resultant_location),
+                size: promoted_type.memory_size(asm_data),
+            });
+        },
+
+        //bit shifts left or right
+        BinaryExpressionOperator::BitshiftRight => {
+            result.add_comment("bitwise shift right");
+            
+            unwrap_let!(DataType::RAW(BaseType::Scalar(ScalarType::Integer(lhs_type))) = promoted_type);
+            result.add_instruction(IROperation::SHR {
+                from: Storage::Stack(lhs_promoted),
+                from_type: lhs_type,
+                amount: Storage::Stack(rhs_promoted),
+                to: Storage::Stack(resultant_location),
+            });
+        }
+        BinaryExpressionOperator::BitshiftLeft => {
+            result.add_comment("bitwise shift left");
+            
+            unwrap_let!(DataType::RAW(BaseType::Scalar(ScalarType::Integer(lhs_type))) = promoted_type);
+            result.add_instruction(IROperation::SHL {
+                from: Storage::Stack(lhs_promoted),
+                from_type: lhs_type,
+                amount: Storage::Stack(rhs_promoted),
+                to: Storage::Stack(resultant_location),
+            });
+        }
+
+        _ => panic!("assignment must be done beforehand")
+    }
+
+    (result, resultant_location)
+}
+
+
 fn apply_pointer_scaling(lhs_promoted: StackItemKey, raw_lhs_type: &DataType, rhs_promoted: StackItemKey, raw_rhs_type: &DataType, promoted_type: &DataType, asm_data: &AsmData) -> (IRCode, StackItemKey, StackItemKey) {
     let mut result = IRCode::make_empty();
 
@@ -1,11 +1,13 @@
 use stack_management::simple_stack_frame::SimpleStackFrame;
 
-use crate::{asm_gen_data::{AsmData, GlobalAsmData}, assembly::assembly::IRCode, ast_metadata::ASTMetadata, debugging::ASTDisplay, generate_ir_traits::GenerateIR, initialised_declaration::InitialisedDeclaration, lexer::{token_savepoint::TokenQueueSlice, token_walk::TokenQueue}, parse_data::ParseData, statement::Statement};
+use serde::Serialize;
+use crate::{asm_gen_data::{AsmData, GlobalAsmData}, assembly::assembly::IRCode, ast_metadata::ASTMetadata, debugging::ASTDisplay, diagnostics::semantic_checker::{CheckSemantics, SemanticChecker}, generate_ir_traits::GenerateIR, initialised_declaration::InitialisedDeclaration, lexer::{token_savepoint::TokenQueueSlice, token_walk::TokenQueue}, parse_data::ParseData, statement::Statement};
 
 /**
  * This represents either a statement or variable creation.
  * The sort of things found in functions
  */
+#[derive(Serialize)]
 pub enum StatementOrDeclaration {
     STATEMENT(Statement),
     DECLARATION(Vec<InitialisedDeclaration>),
@@ -21,9 +23,13 @@ impl StatementOrDeclaration {
         if previous_queue_idx.get_slice_size() == 0 {return None;}
         let curr_queue_idx = previous_queue_idx.clone();
 
+        let errors_before = scope_data.error_count();
         if let Some(ASTMetadata {remaining_slice, resultant_tree}) = Statement::try_consume(tokens_queue, &curr_queue_idx, scope_data) {
             return Some(ASTMetadata{remaining_slice, resultant_tree: Self::STATEMENT(resultant_tree)});
         }
+        if scope_data.error_count() > errors_before {
+            return None;//it was a statement, but it had errors
+        }
 
         if let Some(ASTMetadata {remaining_slice, resultant_tree}) = InitialisedDeclaration::try_consume(tokens_queue, &curr_queue_idx,  scope_data) {
             return Some(ASTMetadata{remaining_slice, resultant_tree: Self::DECLARATION(resultant_tree)});
@@ -31,6 +37,14 @@ impl StatementOrDeclaration {
 
         None
     }
+
+    /// see [Statement::falls_through]
+    pub fn falls_through(&self) -> bool {
+        match self {
+            Self::STATEMENT(statement) => statement.falls_through(),
+            Self::DECLARATION(_) => true,
+        }
+    }
 }
 
 impl GenerateIR for StatementOrDeclaration {
@@ -54,6 +68,19 @@ impl GenerateIR for StatementOrDeclaration {
     }
 }
 
+impl CheckSemantics for StatementOrDeclaration {
+    fn check_semantics(&self, checker: &mut SemanticChecker) {
+        match self {
+            Self::STATEMENT(statement) => statement.check_semantics(checker),
+            Self::DECLARATION(decls) => {
+                for decl in decls {
+                    decl.check_semantics(checker);
+                }
+            },
+        }
+    }
+}
+
 impl ASTDisplay for StatementOrDeclaration {
     fn display_ast(&self, f: &mut crate::debugging::TreeDisplayInfo) {
         match self {
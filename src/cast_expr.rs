@@ -1,17 +1,21 @@
-use crate::{data_type::recursive_data_type::DataType, debugging::ASTDisplay, expression::expression::Expression, expression_visitors::expr_visitor::ExprVisitor, generate_ir_traits::GetType};
+use serde::Serialize;
+use stack_management::{simple_stack_frame::SimpleStackFrame, stack_item::StackItemKey};
+use crate::{asm_gen_data::{AsmData, GlobalAsmData}, assembly::assembly::IRCode, data_type::{base_type::BaseType, recursive_data_type::DataType}, debugging::ASTDisplay, expression::expression::{promote, Expression}, expression_visitors::expr_visitor::ExprVisitor, generate_ir_traits::{GenerateIR, GetType}, lexer::source_span::SourceSpan};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct CastExpression {
     new_type: DataType,
-    expr: Box<Expression>
+    expr: Box<Expression>,
+    span: SourceSpan,
 }
 
 impl CastExpression {
 
-    pub fn new(new_type: DataType, expr: Expression) -> Self {
+    pub fn new(new_type: DataType, expr: Expression, span: SourceSpan) -> Self {
         Self {
-            new_type,
+            new_type: new_type.unqualified(),//the result is a temporary value, so casting to `const int` is the same as casting to `int`
             expr: Box::new(expr),
+            span,
         }
     }
 
@@ -25,6 +29,9 @@ impl CastExpression {
     pub fn get_uncasted_expr(&self) -> &Expression {
         &self.expr
     }
+    pub fn get_span(&self) -> &SourceSpan {
+        &self.span
+    }
 }
 
 impl GetType for CastExpression {
@@ -33,6 +40,26 @@ impl GetType for CastExpression {
     }
 }
 
+impl GenerateIR for CastExpression {
+    fn generate_ir(&self, asm_data: &AsmData, stack_data: &mut SimpleStackFrame, global_asm_data: &GlobalAsmData) -> (IRCode, Option<StackItemKey>) {
+        let mut result = IRCode::make_empty();
+
+        let (expr_asm, expr_location) = self.expr.generate_ir(asm_data, stack_data, global_asm_data);
+        result.merge(&expr_asm);
+
+        if self.new_type == DataType::RAW(BaseType::VOID) {
+            return (result, None);//the value is thrown away
+        }
+
+        //an explicit cast converts the value the same way as an implicit conversion
+        let expr_type = self.expr.get_type(asm_data).decay();//arrays and functions are already generated as pointers
+        let (cast_asm, cast_result) = promote(expr_location.unwrap(), expr_type, self.new_type.clone(), stack_data, asm_data);
+        result.add_instruction(cast_asm);
+
+        (result, Some(cast_result))
+    }
+}
+
 impl ASTDisplay for CastExpression {
     fn display_ast(&self, f: &mut crate::debugging::TreeDisplayInfo) {
         f.write(&format!("cast to {}", self.new_type));
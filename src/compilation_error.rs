@@ -2,6 +2,8 @@ use std::io;
 
 use thiserror::Error;
 
+use crate::diagnostics::{diagnostic::Diagnostic, render::render_all};
+
 #[derive(Debug, Error)]
 pub enum CompilationError {
     #[error("File IO error: {0}")]
@@ -10,12 +12,16 @@ pub enum CompilationError {
     #[error("Assembling/Linking error: {0}")]
     ASMLINK(String),
 
-    #[error("Parse error: {0}")]
-    PARSE(String),
+    /// Errors in the code being compiled, in the order they were found
+    #[error("{}", render_all(.0, 0))]
+    PARSE(Vec<Diagnostic>),
 
     #[error("Misc. error: {0}")]
     MISC(String),
+}
 
-    #[error("#error found in source code")]
-    HASHERR
+impl From<Diagnostic> for CompilationError {
+    fn from(value: Diagnostic) -> Self {
+        CompilationError::PARSE(vec![value])
+    }
 }
@@ -1,8 +1,11 @@
+use serde::Serialize;
 use crate::{function_definition::FunctionDefinition, parse_data::ParseData};
 
 /**
  * stores a list of all the functions that have been defined locally
  */
+#[derive(Serialize)]
+#[serde(transparent)]
 pub struct FunctionList {
     func_definitions: Vec<FunctionDefinition>,//all function definitions made in this translation unit
 }
@@ -1,71 +1,324 @@
-use std::{path::{Path, PathBuf}, process::Command};
+use std::{fs, path::{Path, PathBuf}, process::{self, Command}};
 
-use crate::{compilation_error::CompilationError, debugging::{ASTDisplay, IRDisplay, TreeDisplayInfo}, translation_unit::TranslationUnit};
+use crate::{assembly::assembly_file::AssemblyFile, compilation_error::CompilationError, debugging::{ASTDisplay, DebugInfo, DumpOptions, IRDisplay, TreeDisplayInfo}, diagnostics::{diagnostic::{Diagnostic, Severity}, render::render_all, warnings::WarningOptions}, preprocessor::{preprocess_options::PreprocessorOptions, preprocessor::{preprocess_c_file, preprocessed_text, PreprocessedFile}}, translation_unit::TranslationUnit};
 
+/// How far to take each input file, like gcc's `-E`, `-S` and `-c`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputKind {
+    /// `-E`: print the preprocessed C code
+    Preprocessed,
+    /// `--emit-ir`: stop once the textual IR is written
+    IR,
+    /// `-S`: stop once the NASM assembly is written
+    Assembly,
+    /// `-c`: stop once each file is assembled to an object file
+    Object,
+    /// assemble everything, then link all the objects into one binary
+    Executable,
+}
 
-pub fn compile(input_path: &Path, output_name: &Path, link_with: &[&Path], do_linking: bool, debug_json_output: Option<PathBuf>) -> Result<(),CompilationError> {
-    println!("compiling {:?}", input_path.to_str());
+/// Extra libraries to link with, set by the `-L` and `-l` options
+#[derive(Debug, Clone, Default)]
+pub struct LinkOptions {
+    /// `-L` folders, searched for libraries
+    pub library_folders: Vec<PathBuf>,
+    /// `-l` library names, like `m` for `libm`
+    pub libraries: Vec<String>,
+}
+
+/// What kind of file an input is, judging by its extension
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum InputKind {
+    C,
+    /// textual IR, written by `--emit-ir`
+    IR,
+    Assembly,
+    /// `.o` files, and anything else the linker might understand
+    Object,
+}
+
+impl InputKind {
+    fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|x| x.to_str()) {
+            Some("c") => InputKind::C,
+            Some("ir") => InputKind::IR,
+            Some("asm" | "s") => InputKind::Assembly,
+            _ => InputKind::Object,
+        }
+    }
+
+    /// Whether this kind of file still has to go through `output_kind`'s last step
+    fn needs(&self, output_kind: OutputKind) -> bool {
+        match (self, output_kind) {
+            (InputKind::C, _) => true,
+            (InputKind::IR, OutputKind::Assembly | OutputKind::Object | OutputKind::Executable) => true,
+            (InputKind::Assembly, OutputKind::Object | OutputKind::Executable) => true,
+            (InputKind::Object, OutputKind::Executable) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Compiles one C file, then links it into `output_name` if `do_linking` is set
+/// 
+/// The assembly and object files are written next to `output_name`
+pub fn compile(input_path: &Path, output_name: &Path, link_with: &[&Path], do_linking: bool, debug_json_output: Option<PathBuf>, warning_options: &WarningOptions) -> Result<(),CompilationError> {
     let assembly_filename = output_name.with_extension("asm");
     let object_filename = output_name.with_extension("o");
-    let binary_filename = output_name;
 
+    let debug_info = compile_to_assembly(input_path, &assembly_filename, &PreprocessorOptions::default(), &DumpOptions::default(), warning_options)?;
+    if let Some(debug_json_output) = debug_json_output {
+        write_debug_info(&debug_json_output, &[debug_info])?;
+    }
+    assemble(&assembly_filename, &object_filename)?;
+
+    if do_linking {
+        let objects: Vec<&Path> = link_with.iter().copied().chain(std::iter::once(object_filename.as_path())).collect();
+        link(&objects, output_name, &LinkOptions::default())?;
+    }
+
+    Ok(())
+}
+
+/// Takes every input as far as `output_kind` says, like running gcc with the same files
+/// 
+/// Each input is handled by its extension: `.c` files are compiled, `.ir` files are compiled from the IR onwards, `.asm` and `.s` files are assembled with NASM, and anything else is passed to the linker.
+/// When `output_name` is None, gcc's default names are used.
+/// 
+/// Every file is attempted, so that errors in all of them are found, and the errors from each failed file are returned
+pub fn compile_all(inputs: &[PathBuf], output_name: Option<&Path>, output_kind: OutputKind, debug_json_output: Option<&Path>, preprocessor_options: &PreprocessorOptions, dump_options: &DumpOptions, link_options: &LinkOptions, warning_options: &WarningOptions) -> Result<(), Vec<CompilationError>> {
+    dump_options.clear_files().map_err(|err| vec![err.into()])?;
+
+    let (used_inputs, unused_inputs): (Vec<_>, Vec<_>) = inputs.iter().partition(|x| InputKind::from_path(x).needs(output_kind));
+
+    for unused in unused_inputs {
+        eprint!("{}", render_all(&[Diagnostic::warning(format!("{}: linker input file unused because linking not done", unused.display()))], 0));
+    }
+
+    if used_inputs.len() > 1 && output_name.is_some() && output_kind != OutputKind::Executable {
+        return Err(vec![CompilationError::MISC("cannot specify -o with -c, -S, -E or --emit-ir with multiple files".to_string())]);
+    }
+
+    let mut errors = Vec::new();
+    let mut objects = Vec::new();//object files to link, and whether they should be deleted afterwards
+    let mut debug_infos = Vec::new();
+    let temporary_file = |index: usize, input: &Path, extension: &str| std::env::temp_dir().join(format!("stuc-{}-{}-{}", process::id(), index, input.file_stem().unwrap().to_string_lossy())).with_extension(extension);
 
-    let tu = TranslationUnit::new(input_path)?;
+    for (index, input) in used_inputs.into_iter().enumerate() {
+        //where this input's output goes, if it isn't being linked
+        let own_output = |extension: &str| output_name.map(Path::to_path_buf).unwrap_or_else(|| PathBuf::from(input.file_name().unwrap()).with_extension(extension));
 
-    let mut formatter = TreeDisplayInfo::default();
-    tu.display_ast(&mut formatter);
-    println!("{}", formatter.get_text());
-    
-    println!("{}", tu.display_ir());
+        let input_kind = InputKind::from_path(input);
+        //C and IR files take the same path once they are NASM assembly
+        let mut to_assembly = |assembly_filename: &Path| match input_kind {
+            InputKind::C => compile_to_assembly(input, assembly_filename, preprocessor_options, dump_options, warning_options)
+                .map(|debug_info| debug_infos.push(debug_info)),
+            InputKind::IR => ir_to_assembly(input, assembly_filename, dump_options),
+            _ => unreachable!("{:?} files are never compiled", input_kind),
+        };
 
-    tu.generate_assembly(&assembly_filename);
+        let result = match (input_kind, output_kind) {
+            (InputKind::C, OutputKind::Preprocessed) => preprocess(input, output_name, preprocessor_options, warning_options),
 
-    //assemble
+            (InputKind::C, OutputKind::IR) => compile_to_ir(input, preprocessor_options, dump_options, warning_options)
+                .and_then(|(assembly_file, debug_info)| {
+                    debug_infos.push(debug_info);
+                    fs::write(own_output("ir"), assembly_file.to_ir_text())?;
+                    Ok(())
+                }),
+
+            (InputKind::C | InputKind::IR, OutputKind::Assembly) => to_assembly(&own_output("s")),
+
+            (InputKind::C | InputKind::IR, OutputKind::Object) => {
+                let assembly_filename = temporary_file(index, input, "asm");
+                let result = to_assembly(&assembly_filename)
+                    .and_then(|_| assemble(&assembly_filename, &own_output("o")));
+                let _ = fs::remove_file(assembly_filename);
+                result
+            },
+
+            (InputKind::C | InputKind::IR, OutputKind::Executable) => {
+                let (assembly_filename, object_filename) = (temporary_file(index, input, "asm"), temporary_file(index, input, "o"));
+                let result = to_assembly(&assembly_filename)
+                    .and_then(|_| assemble(&assembly_filename, &object_filename));
+                let _ = fs::remove_file(assembly_filename);
+                objects.push((object_filename, true));
+                result
+            },
+
+            (InputKind::Assembly, OutputKind::Object) => assemble(input, &own_output("o")),
+
+            (InputKind::Assembly, OutputKind::Executable) => {
+                let object_filename = temporary_file(index, input, "o");
+                objects.push((object_filename.clone(), true));
+                assemble(input, &object_filename)
+            },
+
+            (InputKind::Object, _) => {
+                objects.push((input.to_path_buf(), false));
+                Ok(())
+            },
+
+            (kind, output_kind) => unreachable!("{:?} files are not used for {:?}", kind, output_kind),
+        };
+
+        if let Err(err) = result {
+            errors.push(err);
+        }
+    }
+
+    if let Some(debug_json_output) = debug_json_output {
+        if let Err(err) = write_debug_info(debug_json_output, &debug_infos) {
+            errors.push(err);
+        }
+    }
+
+    if errors.is_empty() && output_kind == OutputKind::Executable {
+        let object_paths: Vec<&Path> = objects.iter().map(|(path, _)| path.as_path()).collect();
+        if let Err(err) = link(&object_paths, output_name.unwrap_or(Path::new("a.out")), link_options) {
+            errors.push(err);
+        }
+    }
+
+    for (path, is_temporary) in objects {
+        if is_temporary {
+            let _ = fs::remove_file(path);
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Preprocesses a C file, and writes the result to `output_path`, or stdout if there is no path
+pub fn preprocess(input_path: &Path, output_path: Option<&Path>, preprocessor_options: &PreprocessorOptions, warning_options: &WarningOptions) -> Result<(), CompilationError> {
+    let PreprocessedFile { tokens, warnings, .. } = preprocess_c_file(input_path, preprocessor_options, warning_options)?;
+    if warnings.iter().any(|x| x.severity() == Severity::Error) {
+        return Err(CompilationError::PARSE(warnings));
+    }
+    eprint!("{}", render_all(&warnings, 0));
+    let text = preprocessed_text(&tokens);
+
+    match output_path {
+        Some(output_path) => fs::write(output_path, text)?,
+        None => print!("{}", text),
+    }
+
+    Ok(())
+}
+
+/// Compiles a C file to NASM assembly, saved at `assembly_filename`
+/// 
+/// Returns a description of the file's functions and variables, for `--debug-info`
+pub fn compile_to_assembly(input_path: &Path, assembly_filename: &Path, preprocessor_options: &PreprocessorOptions, dump_options: &DumpOptions, warning_options: &WarningOptions) -> Result<DebugInfo, CompilationError> {
+    let (assembly_file, debug_info) = compile_to_ir(input_path, preprocessor_options, dump_options, warning_options)?;
+    write_assembly(input_path, &assembly_file, assembly_filename, dump_options)?;
+
+    Ok(debug_info)
+}
+
+/// Reads a textual IR file, like one from `--emit-ir`, and saves it as NASM assembly at `assembly_filename`
+pub fn ir_to_assembly(input_path: &Path, assembly_filename: &Path, dump_options: &DumpOptions) -> Result<(), CompilationError> {
+    let text = fs::read_to_string(input_path)?;
+    let assembly_file = AssemblyFile::from_ir_text(&text, input_path)?;
+
+    write_assembly(input_path, &assembly_file, assembly_filename, dump_options)
+}
+
+/// Compiles a C file as far as the IR, along with a description of the file's functions and variables for `--debug-info`
+pub fn compile_to_ir(input_path: &Path, preprocessor_options: &PreprocessorOptions, dump_options: &DumpOptions, warning_options: &WarningOptions) -> Result<(AssemblyFile, DebugInfo), CompilationError> {
+    let PreprocessedFile { tokens, pragmas, warnings } = preprocess_c_file(input_path, preprocessor_options, warning_options)?;
+
+    if let Some(target) = &dump_options.tokens {
+        let text: Vec<String> = tokens.iter().map(|tok| format!("{}\t{}", tok.span, tok.data)).collect();
+        target.write(input_path, &text.join("\n"))?;
+    }
+
+    let tu = TranslationUnit::new(tokens, &pragmas)?;
+
+    if let Some(target) = &dump_options.ast {
+        let mut formatter = TreeDisplayInfo::default();
+        tu.display_ast(&mut formatter);
+        target.write(input_path, &formatter.get_text())?;
+    }
+
+    //find type errors before generating any IR, as IR generation assumes that the types are correct
+    let mut diagnostics: Vec<Diagnostic> = warnings.into_iter()
+        .chain(tu.check_semantics(warning_options))
+        .collect();
+    diagnostics.retain(|x| !pragmas.is_ignored(x));//turned off by `#pragma GCC diagnostic ignored`
+    if diagnostics.iter().any(|x| x.severity() == Severity::Error) {
+        return Err(CompilationError::PARSE(diagnostics));
+    }
+    eprint!("{}", render_all(&diagnostics, 0));
+
+    if let Some(target) = &dump_options.ir {
+        target.write(input_path, &tu.display_ir())?;
+    }
+
+    let assembly_file = tu.generate_assembly_file();
+    Ok((assembly_file, tu.into_debug_info(input_path)))
+}
+
+fn write_assembly(input_path: &Path, assembly_file: &AssemblyFile, assembly_filename: &Path, dump_options: &DumpOptions) -> Result<(), CompilationError> {
+    let assembly = assembly_file.to_nasm_file();
+    if let Some(target) = &dump_options.asm {
+        target.write(input_path, &assembly)?;
+    }
+    fs::write(assembly_filename, assembly)?;
+
+    Ok(())
+}
+
+/// Saves the debug info of every compiled file as a JSON array
+fn write_debug_info(path: &Path, debug_infos: &[DebugInfo]) -> Result<(), CompilationError> {
+    let json = serde_json::to_string_pretty(debug_infos).expect("debug info should always serialise");
+    fs::write(path, json)?;
+
+    Ok(())
+}
+
+/// Assembles a NASM file to an object file
+pub fn assemble(assembly_filename: &Path, object_filename: &Path) -> Result<(), CompilationError> {
     let nasm_status = Command::new("nasm")
         .arg("-f elf64")
         .arg("-O0").arg("-g")
         .arg("-o")
-        .arg(object_filename.clone())
+        .arg(object_filename)
         .arg(assembly_filename)
         .status();
 
     match nasm_status {
-        Ok(status) if status.success() => {},
-        _ => {
-            return Err(CompilationError::ASMLINK("NASM failed to assemble the file".to_string()));
-        }
+        Ok(status) if status.success() => Ok(()),
+        _ => Err(CompilationError::ASMLINK(format!("NASM failed to assemble {}", assembly_filename.display()))),
     }
+}
 
-    //link
-    let ld_status = if do_linking {
-            Some(
-            Command::new("ld")
-            .arg("-o")
-            .arg(binary_filename)//link to the binary file name using:
+/// Links object files with libc and any requested libraries, to make a binary
+pub fn link(objects: &[&Path], binary_filename: &Path, link_options: &LinkOptions) -> Result<(), CompilationError> {
+    let ld_status = Command::new("ld")
+        .arg("-o")
+        .arg(binary_filename)//link to the binary file name using:
 
-            .arg("/usr/lib/x86_64-linux-gnu/crt1.o")//link c runtime
-            .arg("/usr/lib/x86_64-linux-gnu/crti.o")//..
-            .args(link_with)//link with other requested binaries
+        .arg("/usr/lib/x86_64-linux-gnu/crt1.o")//link c runtime
+        .arg("/usr/lib/x86_64-linux-gnu/crti.o")//..
+        .args(link_options.library_folders.iter().map(|folder| format!("-L{}", folder.display())))
+        .args(objects)//link all the code together
+        .args(link_options.libraries.iter().map(|library| format!("-l{}", library)))//libraries go after the code that uses them
 
-            .arg(object_filename)//link my code with the library
-            .arg("-lc")//link with libc
-            .arg("/usr/lib/x86_64-linux-gnu/crtn.o")//c runtime termination
+        .arg("-lc")//link with libc
+        .arg("/usr/lib/x86_64-linux-gnu/crtn.o")//c runtime termination
 
-            .arg("--dynamic-linker")
-            .arg("/lib64/ld-linux-x86-64.so.2")//add a dynamic linker?
-            
-            .status()
-            )
-        } else {None};
+        .arg("--dynamic-linker")
+        .arg("/lib64/ld-linux-x86-64.so.2")//add a dynamic linker?
+        
+        .status();
 
     match ld_status {
-        Some(Ok(status)) if status.success() => {},
-        None => {},
-
-        _ => {
-            return Err(CompilationError::ASMLINK("Linker failed to link binary".to_string()));
-        }
+        Ok(status) if status.success() => Ok(()),
+        _ => Err(CompilationError::ASMLINK("Linker failed to link binary".to_string())),
     }
-
-    Ok(())
 }
\ No newline at end of file
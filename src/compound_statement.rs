@@ -1,13 +1,16 @@
 use stack_management::simple_stack_frame::SimpleStackFrame;
 
-use crate::{asm_gen_data::{AsmData, GlobalAsmData}, assembly::assembly::IRCode, ast_metadata::ASTMetadata, block_statement::StatementOrDeclaration, debugging::ASTDisplay, generate_ir_traits::GenerateIR, lexer::{punctuator::Punctuator, token::Token, token_savepoint::TokenQueueSlice, token_walk::TokenQueue}, parse_data::ParseData};
+use serde::Serialize;
+use crate::{asm_gen_data::{AsmData, GlobalAsmData}, assembly::assembly::IRCode, ast_metadata::ASTMetadata, block_statement::StatementOrDeclaration, goto_and_labels::SwitchCaseLabel, debugging::ASTDisplay, diagnostics::{semantic_checker::{CheckSemantics, SemanticChecker}, warnings::WarningKind}, generate_ir_traits::GenerateIR, lexer::{punctuator::Punctuator, source_span::SourceSpan, token::Token, token_savepoint::TokenQueueSlice, token_walk::TokenQueue}, parse_data::ParseData, statement::Statement};
 
 /**
  * this represents all the code inside a scope (i.e function definition)
  */
+#[derive(Serialize)]
 pub struct ScopeStatements {
     statements: Vec<StatementOrDeclaration>,
     local_scope_data: ParseData,//metadata to help with assembly generation
+    span: SourceSpan,
 }
 
 impl ScopeStatements {
@@ -27,23 +30,68 @@ impl ScopeStatements {
         }
 
         let squiggly_close_idx = tokens_queue.find_matching_close_bracket(curr_queue_idx.index-1);//-1 since it has already been consumed
+        let span = tokens_queue.get_span(&TokenQueueSlice { index: previous_queue_idx.index, max_index: squiggly_close_idx+1 });//including the { }
         
         //split to current tokens, and any after the slice
         let (mut curr_queue_idx, remaining_slice_after_scope) = tokens_queue.split_at(squiggly_close_idx, &curr_queue_idx);
 
-        //greedily consume as many statements as possible
-        while let Some(ASTMetadata{resultant_tree, remaining_slice}) = StatementOrDeclaration::try_consume(tokens_queue, &curr_queue_idx, &mut inner_scope_data) {
+        //consume every statement in the scope
+        while !tokens_queue.no_remaining_tokens(&curr_queue_idx) {
+            let errors_before = inner_scope_data.error_count();
 
-            statements.push(resultant_tree);
-            curr_queue_idx = remaining_slice;//jump to next one
+            match StatementOrDeclaration::try_consume(tokens_queue, &curr_queue_idx, &mut inner_scope_data) {
+                Some(ASTMetadata{resultant_tree, remaining_slice}) => {
+                    statements.push(resultant_tree);
+                    curr_queue_idx = remaining_slice;//jump to next one
+                }
+                None => {
+                    if inner_scope_data.error_count() == errors_before {
+                        //nothing more specific was reported
+                        inner_scope_data.report_error(tokens_queue.expected_error(&curr_queue_idx, "a statement or declaration"));
+                    }
+                    //skip the broken statement, so that errors in the rest of the scope can be found
+                    curr_queue_idx = tokens_queue.recovery_point(&curr_queue_idx);
+                }
+            }
         }
 
         //return the scope statements
         Some(ASTMetadata{
-            resultant_tree: ScopeStatements {statements, local_scope_data: inner_scope_data}, 
+            resultant_tree: ScopeStatements {statements, local_scope_data: inner_scope_data, span}, 
             remaining_slice: remaining_slice_after_scope,
         })
     }
+
+    pub fn get_span(&self) -> &SourceSpan {
+        &self.span
+    }
+
+    /// see [Statement::get_switch_cases](crate::statement::Statement::get_switch_cases)
+    pub fn get_switch_cases(&self) -> Vec<&SwitchCaseLabel> {
+        self.statements
+        .iter()
+        .flat_map(|x| match x {
+            StatementOrDeclaration::STATEMENT(statement) => statement.get_switch_cases(),
+            StatementOrDeclaration::DECLARATION(_) => Vec::new(),
+        })
+        .collect()
+    }
+
+    /// see [Statement::falls_through]
+    pub fn falls_through(&self) -> bool {
+        self.statements.iter().fold(true, |reachable, statement| match statement {
+            StatementOrDeclaration::STATEMENT(Statement::LABEL(_) | Statement::CASE(_)) => true,//labels can be jumped to, even if the code before them can't continue
+            statement => reachable && statement.falls_through(),
+        })
+    }
+
+    /// see [Statement::contains_break]
+    pub fn contains_break(&self) -> bool {
+        self.statements.iter().any(|x| match x {
+            StatementOrDeclaration::STATEMENT(statement) => statement.contains_break(),
+            StatementOrDeclaration::DECLARATION(_) => false,
+        })
+    }
 }
 
 impl GenerateIR for ScopeStatements {
@@ -61,6 +109,34 @@ impl GenerateIR for ScopeStatements {
     }
 }
 
+impl CheckSemantics for ScopeStatements {
+    fn check_semantics(&self, checker: &mut SemanticChecker) {
+        let outer_asm_data = checker.enter_scope(&self.local_scope_data);
+
+        let mut previous_jump: Option<&Statement> = None;//set if the previous statement jumps away, so the current one can't be reached
+        for statement in &self.statements {
+            match statement {
+                StatementOrDeclaration::STATEMENT(Statement::LABEL(_) | Statement::CASE(_)) => previous_jump = None,//can be jumped to
+                StatementOrDeclaration::STATEMENT(Statement::NOP(_)) => {},
+                StatementOrDeclaration::STATEMENT(unreachable) => if let Some(jump) = previous_jump.take() {
+                    checker.report(WarningKind::UnreachableCode, "unreachable statement", |warning| warning
+                        .with_primary(unreachable.get_span().clone(), "")
+                        .with_secondary(jump.get_span().clone(), "any code following this is unreachable"));
+                },
+                StatementOrDeclaration::DECLARATION(_) => {},
+            }
+
+            statement.check_semantics(checker);
+
+            if let StatementOrDeclaration::STATEMENT(jump @ (Statement::CONTROLFLOW(_) | Statement::GOTO(_))) = statement {
+                previous_jump = Some(jump);
+            }
+        }
+
+        checker.leave_scope(outer_asm_data);
+    }
+}
+
 impl ASTDisplay for ScopeStatements {
     fn display_ast(&self, f: &mut crate::debugging::TreeDisplayInfo) {
         f.write("new scope");
@@ -1,6 +1,7 @@
+use serde::Serialize;
 use crate::{assembly::comparison::ComparisonKind, binary_expression::BinaryExpression, data_type::base_type::IntegerType, debugging::IRDisplay, expression::{binary_expression_operator::BinaryExpressionOperator, expression::Expression, ternary::TernaryExpr, unary_prefix_expr::UnaryPrefixExpression, unary_prefix_operator::UnaryPrefixOperator}, number_literal::typed_value::NumberLiteral, string_literal::StringLiteral};
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub enum ConstexprValue {
     NUMBER(NumberLiteral),
     STRING(StringLiteral),
@@ -14,18 +15,19 @@ impl TryFrom<&Expression> for ConstexprValue {
     
     fn try_from(value: &Expression) -> Result<Self, Self::Error> {
         match value {
-            Expression::NUMBERLITERAL(number_literal) => Ok(ConstexprValue::NUMBER(number_literal.clone())),
+            Expression::NUMBERLITERAL(number_literal, _) => Ok(ConstexprValue::NUMBER(number_literal.clone())),
             Expression::VARIABLE(minimal_data_variable) => Err(format!("variable {} is not a compile-time constant", minimal_data_variable.name)),
             Expression::STRUCTMEMBERACCESS(struct_member_access) => todo!(),
-            Expression::STRINGLITERAL(string_literal) => Ok(ConstexprValue::STRING(string_literal.clone())),
+            Expression::STRINGLITERAL(string_literal, _) => Ok(ConstexprValue::STRING(string_literal.clone())),
             Expression::ARRAYLITERAL(array_initialisation) => todo!(),
-            Expression::FUNCCALL(function_call) => Err(format!("results of calling {} are not a compile time constant", function_call.get_callee_decl().function_name)),
+            Expression::FUNCCALL(_) => Err("results of function calls are not a compile time constant".to_owned()),
             Expression::UNARYPREFIX(unary_prefix_expression) => unary_prefix_expression.try_into(),
             Expression::UNARYSUFFIX(_) => Err("cannot fold unary postfix increment/decrement".to_owned()),
             Expression::BINARYEXPRESSION(binary_expression) => binary_expression.clone().try_into(),
             Expression::CAST(cast_expression) => todo!(),
-            Expression::SIZEOF(sizeof_expr) => Err(format!("no asm_data in constant folding, so cannot evaluate sizeof")),//sizeof
+            Expression::SIZEOF(sizeof_expr, _) => Err(format!("no asm_data in constant folding, so cannot evaluate sizeof")),//sizeof
             Expression::TERNARYEXPRESSION(ternary) => ternary.clone().try_into(),
+            Expression::VABUILTIN(..) => Err("variadic args are not a compile time constant".to_owned()),
         }
     }
 }
@@ -1,13 +1,17 @@
-use crate::{args_handling::location_allocation::{generate_only_return_location, ReturnLocation}, asm_gen_data::{AsmData, GlobalAsmData}, assembly::{assembly::IRCode, comparison::AsmComparison, operand::Storage, operation::{IROperation, CalleeReturnData}}, ast_metadata::ASTMetadata, data_type::{base_type::BaseType, recursive_data_type::DataType}, debugging::ASTDisplay, expression::expression::{self, promote, Expression}, generate_ir_traits::{GenerateIR, GetType}, lexer::{keywords::Keyword, punctuator::Punctuator, token::Token, token_savepoint::TokenQueueSlice, token_walk::{TokenQueue, TokenSearchType}}, parse_data::ParseData};
+use serde::Serialize;
+use crate::{args_handling::location_allocation::{generate_only_return_location, ReturnLocation}, asm_gen_data::{AsmData, GlobalAsmData}, assembly::{assembly::IRCode, comparison::AsmComparison, operand::Storage, operation::{IROperation, CalleeReturnData}}, ast_metadata::ASTMetadata, data_type::{base_type::BaseType, recursive_data_type::DataType}, debugging::ASTDisplay, diagnostics::{diagnostic::Diagnostic, semantic_checker::{CheckSemantics, SemanticChecker}}, expression::expression::{self, promote, Expression}, generate_ir_traits::{GenerateIR, GetType}, lexer::{keywords::Keyword, punctuator::Punctuator, source_span::SourceSpan, token::Token, token_savepoint::TokenQueueSlice, token_walk::{TokenQueue, TokenSearchType}}, parse_data::ParseData};
 use colored::Colorize;
 use stack_management::simple_stack_frame::SimpleStackFrame;
+use unwrap_let::unwrap_let;
 
 /**
  * this handles break, continue and return statements
  */
+#[derive(Serialize)]
 pub enum ControlFlowChange {
-    RETURN(Option<Expression>),
-    BREAK,
+    RETURN(Option<Expression>, SourceSpan),
+    BREAK(SourceSpan),
+    CONTINUE(SourceSpan),
 }
 
 impl ControlFlowChange {
@@ -29,19 +33,53 @@ impl ControlFlowChange {
 
                 let return_value = match return_value_slice.get_slice_size() {
                     0 => None,
-                    1.. => Some(expression::try_consume_whole_expr(tokens_queue, &return_value_slice, scope_data).unwrap())
+                    1.. => {
+                        let Some(value) = expression::try_consume_whole_expr(tokens_queue, &return_value_slice, scope_data) else {
+                            scope_data.report_error(Diagnostic::error("invalid return value").with_primary(tokens_queue.get_span(&return_value_slice), ""));
+                            return None;
+                        };
+                        Some(value)
+                    }
                 };
 
-                Some(ASTMetadata { resultant_tree: Self::RETURN(return_value), remaining_slice: TokenQueueSlice { index: semicolon_idx+1, max_index: curr_queue_idx.max_index } })
+                let span = tokens_queue.get_span(&TokenQueueSlice { index: previous_queue_idx.index, max_index: semicolon_idx+1 });
+
+                Some(ASTMetadata { resultant_tree: Self::RETURN(return_value, span), remaining_slice: TokenQueueSlice { index: semicolon_idx+1, max_index: curr_queue_idx.max_index } })
             }
             Keyword::BREAK => {
-                assert!(tokens_queue.consume(&mut curr_queue_idx, scope_data) == Some(Token::PUNCTUATOR(Punctuator::SEMICOLON)));
+                consume_semicolon(tokens_queue, &mut curr_queue_idx, scope_data, "`;` after `break`")?;
+                let span = tokens_queue.get_span(&TokenQueueSlice { index: previous_queue_idx.index, max_index: curr_queue_idx.index });
+
+                Some(ASTMetadata { remaining_slice: curr_queue_idx, resultant_tree: Self::BREAK(span)})
+            }
+            Keyword::CONTINUE => {
+                consume_semicolon(tokens_queue, &mut curr_queue_idx, scope_data, "`;` after `continue`")?;
+                let span = tokens_queue.get_span(&TokenQueueSlice { index: previous_queue_idx.index, max_index: curr_queue_idx.index });
 
-                Some(ASTMetadata { remaining_slice: curr_queue_idx, resultant_tree: Self::BREAK})
+                Some(ASTMetadata { remaining_slice: curr_queue_idx, resultant_tree: Self::CONTINUE(span)})
             }
             _ => None
         }
     }
+
+    pub fn get_span(&self) -> &SourceSpan {
+        match self {
+            ControlFlowChange::RETURN(_, span) |
+            ControlFlowChange::BREAK(span) |
+            ControlFlowChange::CONTINUE(span) => span,
+        }
+    }
+}
+
+/// Consumes the semicolon at the end of a statement, reporting an error if it is missing
+fn consume_semicolon(tokens_queue: &TokenQueue, curr_queue_idx: &mut TokenQueueSlice, scope_data: &ParseData, expected: &str) -> Option<()> {
+    if tokens_queue.peek(curr_queue_idx, scope_data) != Some(Token::PUNCTUATOR(Punctuator::SEMICOLON)) {
+        scope_data.report_error(tokens_queue.expected_error(curr_queue_idx, expected));
+        return None;
+    }
+    tokens_queue.consume(curr_queue_idx, scope_data);
+
+    Some(())
 }
 
 impl GenerateIR for ControlFlowChange {
@@ -49,7 +87,7 @@ impl GenerateIR for ControlFlowChange {
         let mut result = IRCode::make_empty();
 
         match self {
-            ControlFlowChange::RETURN(expression) => {
+            ControlFlowChange::RETURN(expression, _) => {
                 let return_data = 
                     expression
                     .as_ref()
@@ -57,8 +95,10 @@ impl GenerateIR for ControlFlowChange {
                         //find out what type to return and metadata about it
                         let return_type = asm_data.get_function_return_type();
                         let return_location = match generate_only_return_location(return_type, asm_data).unwrap() {
-                        ReturnLocation::InRegs(eight_byte_locations) => CalleeReturnData::InRegs{ regs_used: eight_byte_locations },
-                            ReturnLocation::HiddenPointer => todo!(),
+                            ReturnLocation::InRegs(eight_byte_locations) => CalleeReturnData::InRegs{ regs_used: eight_byte_locations },
+                            ReturnLocation::HiddenPointer => CalleeReturnData::InMemory {
+                                hidden_pointer_location: Storage::Stack(asm_data.get_hidden_pointer_location().expect("returning in memory, but the hidden pointer was not saved"))
+                            },
                         };
 
                         result.add_comment(format!("returning {} from function", return_type));
@@ -66,7 +106,8 @@ impl GenerateIR for ControlFlowChange {
                         let (expr_asm, expr_location) = expr.generate_ir(asm_data, stack_data, global_asm_data);
                         result.merge(&expr_asm);
                         //cast to resultant type
-                        let (cast_asm, cast_result) = promote(expr_location.unwrap(), expr.get_type(asm_data), return_type.clone(), stack_data, asm_data);
+                        let expr_type = expr.get_type(asm_data).decay();//arrays are already generated as pointers
+                        let (cast_asm, cast_result) = promote(expr_location.unwrap(), expr_type, return_type.clone(), stack_data, asm_data);
                         result.add_instruction(cast_asm);
                         //have the IR deal with where to put the result
                         (return_location, cast_result, return_type.memory_size(asm_data))
@@ -77,22 +118,49 @@ impl GenerateIR for ControlFlowChange {
                     return_data,
                 });
             },
-            ControlFlowChange::BREAK => {
-                let label = asm_data.get_break_label().expect("break statement outside of a loop");
+            ControlFlowChange::BREAK(_) => {
+                //check_semantics makes sure that this is in a loop or switch
+                unwrap_let!(Some(label) = asm_data.get_break_label());
                 //unconditionally jump to the label
                 //signedness does not matter as it unconditionally jumps
                 result.add_instruction(IROperation::JMPCC { label: label.clone(), comparison: AsmComparison::ALWAYS});
             },
+            ControlFlowChange::CONTINUE(_) => {
+                //check_semantics makes sure that this is in a loop
+                unwrap_let!(Some(label) = asm_data.get_continue_label());
+                //unconditionally jump to the next iteration of the loop
+                result.add_instruction(IROperation::JMPCC { label: label.clone(), comparison: AsmComparison::ALWAYS});
+            },
         }
 
         (result, None)
     }
 }
 
+impl CheckSemantics for ControlFlowChange {
+    fn check_semantics(&self, checker: &mut SemanticChecker) {
+        match self {
+            Self::RETURN(Some(value), _) => {
+                let return_type = checker.asm_data().get_function_return_type().clone();
+                if checker.check_expression(value) && checker.check_conversion(&return_type, value, "return") {
+                    checker.check_int_to_pointer(&return_type, value, "returning an integer");
+                }
+            },
+            Self::BREAK(span) if !checker.in_loop_or_switch() => {
+                checker.report_error(Diagnostic::error("`break` statement not within a loop or switch").with_primary(span.clone(), ""));
+            },
+            Self::CONTINUE(span) if !checker.in_loop() => {
+                checker.report_error(Diagnostic::error("`continue` statement not within a loop").with_primary(span.clone(), ""));
+            },
+            _ => {},
+        }
+    }
+}
+
 impl ASTDisplay for ControlFlowChange {
     fn display_ast(&self, f: &mut crate::debugging::TreeDisplayInfo) {
         match self {
-            ControlFlowChange::RETURN(expression) => {
+            ControlFlowChange::RETURN(expression, _) => {
                 f.write(&"return".yellow());
                 if let Some(expr) = expression {
                     f.indent();
@@ -100,7 +168,8 @@ impl ASTDisplay for ControlFlowChange {
                     f.dedent();
                 }
             }
-            ControlFlowChange::BREAK => f.write(&"break".yellow()),
+            ControlFlowChange::BREAK(_) => f.write(&"break".yellow()),
+            ControlFlowChange::CONTINUE(_) => f.write(&"continue".yellow()),
         }
     }
 }
\ No newline at end of file
@@ -1,15 +1,16 @@
 use std::fmt::Display;
 
+use serde::Serialize;
 use crate::{asm_gen_data::GetStructUnion, data_type::type_token::TypeInfo, struct_definition::StructIdentifier, union_definition::UnionIdentifier};
 use memory_size::MemorySize;
 
-#[derive(Debug, Clone, PartialEq, Eq, Copy)]
+#[derive(Debug, Clone, PartialEq, Eq, Copy, Serialize)]
 pub enum FloatType {
     F32,
     F64,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Copy)]
+#[derive(Debug, Clone, PartialEq, Eq, Copy, Serialize)]
 pub enum IntegerType {
     _BOOL,
     I8,
@@ -22,7 +23,7 @@ pub enum IntegerType {
     U64,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum ScalarType {
     Float(FloatType),
     Integer(IntegerType)
@@ -74,7 +75,7 @@ impl ScalarType {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum BaseType {
     VOID,
     VaArg,//varadic arg has a special type as it has no type?
@@ -1,17 +1,27 @@
 use std::fmt::{Debug, Display};
 
+use serde::Serialize;
 use crate::{asm_gen_data::GetStructUnion, data_type::base_type::{FloatType, IntegerType, ScalarType}, expression::expression::Expression};
 use memory_size::MemorySize;
 use unwrap_let::unwrap_let;
-use super::{base_type::BaseType, type_modifier::DeclModifier};
+use super::{base_type::BaseType, type_modifier::DeclModifier, type_qualifier::TypeQualifiers};
 
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub enum DataType {
     UNKNOWNSIZEARRAY{element: Box<DataType>},
     ARRAY{size: u64, element: Box<DataType>},
     POINTER(Box<DataType>),
-    RAW(BaseType)
+    /// A function, which can't be stored, but decays to a pointer when used as a value
+    FUNCTION{return_type: Box<DataType>, params: Vec<DataType>},
+    RAW(BaseType),
+    /// `inner` with `const` or `volatile` applied
+    /// 
+    /// Create with [DataType::add_qualifiers], which keeps these rules:
+    /// - qualifiers are never empty or nested
+    /// - arrays are never qualified, their elements are instead
+    /// - functions are never qualified
+    QUALIFIED{qualifiers: TypeQualifiers, inner: Box<DataType>},
 }
 
 impl DataType
@@ -27,27 +37,33 @@ impl DataType
             [DeclModifier::ARRAY(count), remaining @ ..] => DataType::ARRAY { size: *count, element: Box::new(Self::new_from_slice(base, remaining)) },
             //pointer to "remaining" tokens => pointer to process(remaining)
             [DeclModifier::POINTER, remaining @ ..] => DataType::POINTER(Box::new(Self::new_from_slice(base, remaining))),
-            [DeclModifier::UnknownSizeArray, remaining @ ..] => DataType::UNKNOWNSIZEARRAY { element: Box::new(Self::new_from_slice(base, remaining)) }
+            [DeclModifier::UnknownSizeArray, remaining @ ..] => DataType::UNKNOWNSIZEARRAY { element: Box::new(Self::new_from_slice(base, remaining)) },
+            //function returning "remaining" tokens => function returning process(remaining)
+            [DeclModifier::FUNCTION(params), remaining @ ..] => DataType::FUNCTION { return_type: Box::new(Self::new_from_slice(base, remaining)), params: params.clone() },
         }
     }
     
     pub fn decay(&self) -> Self {
         match self {
+            Self::QUALIFIED { inner, .. } => inner.decay(),//the value is a copy, so is not const or volatile
             Self::ARRAY { size:_, element } => DataType::POINTER(element.clone()),
             Self::UNKNOWNSIZEARRAY { element } => DataType::POINTER(element.clone()),
+            Self::FUNCTION { .. } => DataType::POINTER(Box::new(self.clone())),//function designators decay to function pointers
             _ => self.clone()
         }
     }
 
     /// converts arrays to u64 memory addresses
-    /// pointers to u64
+    /// pointers and functions to u64
     /// any raw type is unaffected
     pub fn decay_to_primative(&self) -> ScalarType {
         match self {
             DataType::UNKNOWNSIZEARRAY { .. } => ScalarType::Integer(IntegerType::U64),
             DataType::ARRAY { .. } => ScalarType::Integer(IntegerType::U64),
             DataType::POINTER(_) => ScalarType::Integer(IntegerType::U64),
+            DataType::FUNCTION { .. } => ScalarType::Integer(IntegerType::U64),
             DataType::RAW(BaseType::Scalar(s)) => s.clone(),
+            DataType::QUALIFIED { inner, .. } => inner.decay_to_primative(),
             DataType::RAW(bt) => panic!("{:?} base type can't be converted to a primative scalar", bt)
         }
     }
@@ -118,11 +134,11 @@ impl DataType
                 DataType::ARRAY { size: array_initialisation.calculate_element_count(), element }
             }
 
-            (DataType::UNKNOWNSIZEARRAY { element }, Some(Expression::STRINGLITERAL(string_initialisaiton))) => {
+            (DataType::UNKNOWNSIZEARRAY { element }, Some(Expression::STRINGLITERAL(string_initialisaiton, _))) => {
                 //char x[] = "hello world";
 
                 //ensure the element is char
-                assert_eq!(element.as_ref(), &DataType::RAW(BaseType::Scalar(ScalarType::Integer(IntegerType::I8))));
+                assert_eq!(&element.unqualified(), &DataType::RAW(BaseType::Scalar(ScalarType::Integer(IntegerType::I8))));
 
                 DataType::ARRAY { size: string_initialisaiton.get_num_chars() as u64, element }//size of array = number of chars in the string (including the zero byte)
             }
@@ -138,7 +154,9 @@ impl DataType
             Self::UNKNOWNSIZEARRAY { element } => *element.clone(),
             Self::ARRAY { size:_, element } => *element.clone(),
             Self::POINTER(element) => *element.clone(),
-            Self::RAW(_) => panic!("tried to remove outer modifier from raw type")
+            Self::FUNCTION { .. } => panic!("tried to remove outer modifier from function. perhaps it should be decayed to a pointer first?"),
+            Self::RAW(_) => panic!("tried to remove outer modifier from raw type"),
+            Self::QUALIFIED { inner, .. } => inner.remove_outer_modifier(),
         }
     }
     pub fn add_outer_modifier(&self, modifier: DeclModifier) -> Self {
@@ -146,6 +164,39 @@ impl DataType
             DeclModifier::POINTER => Self::POINTER(Box::new(self.clone())),
             DeclModifier::ARRAY(size) => Self::ARRAY { size, element: Box::new(self.clone()) },
             DeclModifier::UnknownSizeArray => Self::UNKNOWNSIZEARRAY { element: Box::new(self.clone()) },
+            DeclModifier::FUNCTION(params) => Self::FUNCTION { return_type: Box::new(self.unqualified()), params },//a returned value is a copy, so can't be const
+        }
+    }
+
+    /// Applies `qualifiers` to this type, like the `const` in `int *const x;`
+    pub fn add_qualifiers(&self, qualifiers: TypeQualifiers) -> Self {
+        if qualifiers.is_empty() {
+            return self.clone();
+        }
+
+        match self {
+            //a const array is an array of const elements
+            Self::UNKNOWNSIZEARRAY { element } => Self::UNKNOWNSIZEARRAY { element: Box::new(element.add_qualifiers(qualifiers)) },
+            Self::ARRAY { size, element } => Self::ARRAY { size: *size, element: Box::new(element.add_qualifiers(qualifiers)) },
+            Self::FUNCTION { .. } => self.clone(),
+            Self::QUALIFIED { qualifiers: existing, inner } => Self::QUALIFIED { qualifiers: existing.union(&qualifiers), inner: inner.clone() },
+            Self::POINTER(_) | Self::RAW(_) => Self::QUALIFIED { qualifiers, inner: Box::new(self.clone()) },
+        }
+    }
+
+    /// The qualifiers applied to this type itself, ignoring any on the type it points to
+    pub fn qualifiers(&self) -> TypeQualifiers {
+        match self {
+            Self::QUALIFIED { qualifiers, .. } => *qualifiers,
+            _ => TypeQualifiers::default(),
+        }
+    }
+
+    /// This type without its own qualifiers, so `const int*const` becomes `const int*`
+    pub fn unqualified(&self) -> Self {
+        match self {
+            Self::QUALIFIED { inner, .. } => *inner.clone(),
+            _ => self.clone(),
         }
     }
 
@@ -154,7 +205,9 @@ impl DataType
             DataType::UNKNOWNSIZEARRAY { .. } => panic!("cannot find size of unknow size array. perhaps this should return an Option???"),
             DataType::ARRAY { size, element } => MemorySize::from_bytes(size * &element.memory_size(struct_info).size_bytes()),
             DataType::POINTER(_) => MemorySize::from_bytes(8),
+            DataType::FUNCTION { .. } => panic!("functions have no size, only pointers to them do"),
             DataType::RAW(base) => base.memory_size(struct_info),
+            DataType::QUALIFIED { inner, .. } => inner.memory_size(struct_info),
         }
     }
 
@@ -197,7 +250,9 @@ impl Display for DataType {
             DataType::UNKNOWNSIZEARRAY { element } => format!("ARR[]({})", element),
             DataType::ARRAY { size, element } => format!("ARR[{}]({})", size, element),
             DataType::POINTER(data_type) => format!("PTR({})", data_type),
+            DataType::FUNCTION { return_type, params } => format!("FN({}) -> {}", params.iter().map(|x| x.to_string()).collect::<Vec<_>>().join(", "), return_type),
             DataType::RAW(base_type) => format!("{}",base_type),
+            DataType::QUALIFIED { qualifiers, inner } => format!("{} {}", qualifiers, inner),
         })
     }
 }
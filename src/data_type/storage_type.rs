@@ -1,8 +1,9 @@
 use std::fmt::Display;
+use serde::Serialize;
 
 
 /// represents how a variable is stored
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub enum StorageDuration {
     /// stored in the default location, either file scope(globally), or local scope. "register" defaults to this
     Default,
@@ -1,6 +1,9 @@
+use super::recursive_data_type::DataType;
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum DeclModifier {
     POINTER,//this declaration is for a pointer to something
     ARRAY(u64),//an array with usize elements
     UnknownSizeArray,//int x[] = {1,2,3};
+    FUNCTION(Vec<DataType>),//a function taking these params, like the (int, char*) in int (*fp)(int, char*);
 }
\ No newline at end of file
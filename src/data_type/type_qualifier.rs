@@ -1,4 +1,5 @@
 use std::fmt::Display;
+use serde::Serialize;
 
 
 
@@ -15,4 +16,46 @@ impl Display for TypeQualifier {
             TypeQualifier::Volatile => "volatile",
         })
     }
-}
\ No newline at end of file
+}
+
+/// The set of qualifiers applied to a type, like the `const volatile` in `const volatile int x;`
+/// 
+/// `volatile` is only recorded: nothing folds or combines memory accesses, so every `volatile` access already happens as written
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize)]
+pub struct TypeQualifiers {
+    pub is_const: bool,
+    pub is_volatile: bool,
+}
+
+impl TypeQualifiers {
+    pub fn add(&mut self, qualifier: TypeQualifier) {
+        match qualifier {
+            TypeQualifier::Const => self.is_const = true,
+            TypeQualifier::Volatile => self.is_volatile = true,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !self.is_const && !self.is_volatile
+    }
+
+    /// every qualifier found in either `self` or `other`
+    pub fn union(&self, other: &Self) -> Self {
+        Self {
+            is_const: self.is_const || other.is_const,
+            is_volatile: self.is_volatile || other.is_volatile,
+        }
+    }
+}
+
+impl Display for TypeQualifiers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names: Vec<&str> = [(self.is_const, "const"), (self.is_volatile, "volatile")]
+            .into_iter()
+            .filter(|(present, _)| *present)
+            .map(|(_, name)| name)
+            .collect();
+
+        write!(f, "{}", names.join(" "))
+    }
+}
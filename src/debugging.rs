@@ -1,4 +1,8 @@
-use std::fmt::Write;
+use std::{fmt::Write, fs::{self, OpenOptions}, io::{self, Write as IoWrite}, path::{Path, PathBuf}};
+
+use serde::Serialize;
+
+use crate::translation_unit::TranslationUnit;
 
 pub trait IRDisplay {
     fn display_ir(&self) -> String;
@@ -37,4 +41,87 @@ impl TreeDisplayInfo {
     pub fn get_text(self) -> String {
         self.text
     }
+}
+
+/// Where a `--dump-*` flag writes to
+#[derive(Debug, Clone, PartialEq)]
+pub enum DumpTarget {
+    Stderr,
+    File(PathBuf),
+}
+
+impl DumpTarget {
+    /// Parses the value of a `--dump-*=file` flag, where `-` means stderr
+    pub fn from_flag(value: &str) -> Self {
+        match value {
+            "-" => DumpTarget::Stderr,
+            path => DumpTarget::File(PathBuf::from(path)),
+        }
+    }
+
+    /// Writes `text`, which was made while compiling `input_path`
+    /// 
+    /// Files are appended to, so that dumps from several inputs can share one file
+    pub fn write(&self, input_path: &Path, text: &str) -> io::Result<()> {
+        let text = format!("==> {} <==\n{}\n", input_path.display(), text);
+
+        match self {
+            DumpTarget::Stderr => io::stderr().write_all(text.as_bytes()),
+            DumpTarget::File(path) => OpenOptions::new().create(true).append(true).open(path)?.write_all(text.as_bytes()),
+        }
+    }
+}
+
+/// Which of the compiler's internal representations to write out, set by the `--dump-tokens`, `--dump-ast`, `--dump-ir` and `--dump-asm` flags
+#[derive(Debug, Clone, Default)]
+pub struct DumpOptions {
+    pub tokens: Option<DumpTarget>,
+    pub ast: Option<DumpTarget>,
+    pub ir: Option<DumpTarget>,
+    pub asm: Option<DumpTarget>,
+}
+
+impl DumpOptions {
+    /// Empties any files that are dumped to, as each dump is appended to them
+    pub fn clear_files(&self) -> io::Result<()> {
+        for target in [&self.tokens, &self.ast, &self.ir, &self.asm] {
+            if let Some(DumpTarget::File(path)) = target {
+                fs::write(path, "")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The version of the `--debug-info` JSON, which goes up whenever fields are renamed or removed
+pub const DEBUG_INFO_FORMAT_VERSION: u32 = 1;
+
+/// Describes a compiled C file for debugging tools, which `--debug-info` saves as JSON
+#[derive(Serialize)]
+pub struct DebugInfo {
+    /// always [DEBUG_INFO_FORMAT_VERSION]
+    pub format_version: u32,
+    pub source_file: PathBuf,
+    pub functions: Vec<FunctionDebugInfo>,
+    pub global_variables: Vec<VariableDebugInfo>,
+    /// the whole syntax tree, with a span for each node and the types defined in each scope
+    pub ast: TranslationUnit,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FunctionDebugInfo {
+    /// the name, which is also the function's assembly label
+    pub name: String,
+    pub return_type: String,
+    pub params: Vec<VariableDebugInfo>,
+    pub start_line: u32,
+    pub end_line: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VariableDebugInfo {
+    pub name: String,
+    pub data_type: String,
+    pub line: u32,
 }
\ No newline at end of file
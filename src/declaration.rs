@@ -2,25 +2,31 @@ use std::fmt::Display;
 
 use colored::Colorize;
 
-use crate::{assembly::{assembly::IRCode, operand::{IRMemOperand, Storage, PTR_SIZE}, operation::IROperation}, data_type::recursive_data_type::DataType, expression_visitors::expr_visitor::ExprVisitor, generate_ir_traits::{GenerateIR, GetAddress, GetType}};
+use serde::Serialize;
+use crate::{assembly::{assembly::IRCode, operand::{IRMemOperand, Storage, PTR_SIZE}, operation::IROperation}, data_type::recursive_data_type::DataType, expression_visitors::expr_visitor::ExprVisitor, generate_ir_traits::{GenerateIR, GetAddress, GetType}, lexer::source_span::SourceSpan};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 /**
  * stores enough data to know about a variable, using available context during assembly generation
  */
 pub struct MinimalDataVariable {
-    pub(crate) name: String
+    pub(crate) name: String,
+    pub(crate) span: SourceSpan,
 }
 
 impl MinimalDataVariable {
     pub fn accept<V: ExprVisitor>(&self, visitor: &mut V) -> V::Output {
         visitor.visit_variable(self)
     }
+
+    pub fn get_span(&self) -> &SourceSpan {
+        &self.span
+    }
 }
 
 impl GetType for MinimalDataVariable {
     fn get_type(&self, asm_data: &crate::asm_gen_data::AsmData) -> DataType {
-        asm_data.get_variable(&self.name).data_type.clone()
+        asm_data.get_variable(&self.name).data_type.unqualified()//the value is a copy, so qualifiers only matter when assigning to the variable
     }
 }
 impl GetAddress for MinimalDataVariable {
@@ -30,8 +36,6 @@ impl GetAddress for MinimalDataVariable {
 
         let location = asm_data.get_variable(&self.name).location.clone();
 
-        println!("var {} at {:?}", self.name, location);
-
         result.add_instruction(IROperation::LEA {
             from: location.try_into().unwrap(),
             to: IRMemOperand::Stack { base: ptr },
@@ -42,16 +46,16 @@ impl GetAddress for MinimalDataVariable {
 }
 impl GenerateIR for MinimalDataVariable {
     fn generate_ir(&self, asm_data: &crate::asm_gen_data::AsmData, stack_data: &mut stack_management::simple_stack_frame::SimpleStackFrame, global_asm_data: &crate::asm_gen_data::GlobalAsmData) -> (IRCode, Option<stack_management::stack_item::StackItemKey>) {
-        let var_size = self.get_type(asm_data).memory_size(asm_data);
-        let var_result = stack_data.allocate(var_size);
-        let mut result = IRCode::make_empty();
-
         let var_data = &asm_data.get_variable(&self.name);
-        if matches!(var_data.data_type, DataType::ARRAY {..} | DataType::UNKNOWNSIZEARRAY {..}) {
+        if matches!(var_data.data_type, DataType::ARRAY {..} | DataType::UNKNOWNSIZEARRAY {..} | DataType::FUNCTION {..}) {
             let (ir, dest) = self.get_address(asm_data, stack_data, global_asm_data);
-            return (ir, Some(dest));//array decays to pointer
+            return (ir, Some(dest));//array or function decays to pointer
         }
 
+        let var_size = self.get_type(asm_data).memory_size(asm_data);
+        let var_result = stack_data.allocate(var_size);
+        let mut result = IRCode::make_empty();
+
         result.add_commented_instruction(IROperation::MOV {
             from: var_data.location.clone().into(),
             to: IRMemOperand::Stack { base: var_result },
@@ -66,7 +70,7 @@ impl GenerateIR for MinimalDataVariable {
  * stores enough data to declare a variable:
  * name and data type
  */
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct Declaration {
     pub(crate) data_type: DataType,
     pub(crate) name: String,
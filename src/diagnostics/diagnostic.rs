@@ -0,0 +1,138 @@
+use std::fmt::Display;
+
+use colored::{ColoredString, Colorize};
+
+use crate::{diagnostics::{render::render, warnings::WarningKind}, lexer::source_span::SourceSpan};
+
+/// How serious a diagnostic is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Note,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    /// Colours `text` to match this severity
+    pub fn colourise(&self, text: &str) -> ColoredString {
+        match self {
+            Severity::Note => text.cyan().bold(),
+            Severity::Warning => text.yellow().bold(),
+            Severity::Error => text.red().bold(),
+        }
+    }
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Note => write!(f, "note"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// Points at some source code, with an optional explanation of why
+#[derive(Debug, Clone)]
+pub struct DiagnosticLabel {
+    pub span: SourceSpan,
+    pub message: Option<String>,
+}
+
+/// An error or warning about the code being compiled, that can be shown to the user
+///
+/// Build one with [Diagnostic::error] or [Diagnostic::warning], then add spans, notes and help
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    severity: Severity,
+    message: String,
+    /// where the problem is
+    primary: Option<DiagnosticLabel>,
+    /// other places that are related to the problem
+    secondary: Vec<DiagnosticLabel>,
+    notes: Vec<String>,
+    help: Vec<String>,
+    /// the kind of warning that this is, so that it can be turned off by `#pragma GCC diagnostic`
+    warning_kind: Option<WarningKind>,
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, message: impl Into<String>) -> Diagnostic {
+        Diagnostic {
+            severity,
+            message: message.into(),
+            primary: None,
+            secondary: Vec::new(),
+            notes: Vec::new(),
+            help: Vec::new(),
+            warning_kind: None,
+        }
+    }
+    pub fn error(message: impl Into<String>) -> Diagnostic {
+        Self::new(Severity::Error, message)
+    }
+    pub fn warning(message: impl Into<String>) -> Diagnostic {
+        Self::new(Severity::Warning, message)
+    }
+
+    /// Sets where the problem is, and an explanation that is shown under it (empty for no explanation)
+    pub fn with_primary(mut self, span: SourceSpan, message: impl Into<String>) -> Diagnostic {
+        self.primary = Some(make_label(span, message.into()));
+        self
+    }
+    /// Adds another place that is related to the problem
+    pub fn with_secondary(mut self, span: SourceSpan, message: impl Into<String>) -> Diagnostic {
+        self.secondary.push(make_label(span, message.into()));
+        self
+    }
+    pub fn with_note(mut self, note: impl Into<String>) -> Diagnostic {
+        self.notes.push(note.into());
+        self
+    }
+    pub fn with_help(mut self, help: impl Into<String>) -> Diagnostic {
+        self.help.push(help.into());
+        self
+    }
+    pub fn with_warning_kind(mut self, kind: WarningKind) -> Diagnostic {
+        self.warning_kind = Some(kind);
+        self
+    }
+
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+    pub fn primary(&self) -> Option<&DiagnosticLabel> {
+        self.primary.as_ref()
+    }
+    pub fn secondary(&self) -> &[DiagnosticLabel] {
+        &self.secondary
+    }
+    pub fn notes(&self) -> &[String] {
+        &self.notes
+    }
+    pub fn help(&self) -> &[String] {
+        &self.help
+    }
+    pub fn warning_kind(&self) -> Option<WarningKind> {
+        self.warning_kind
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", render(self))
+    }
+}
+
+impl std::error::Error for Diagnostic {}
+
+fn make_label(span: SourceSpan, message: String) -> DiagnosticLabel {
+    DiagnosticLabel {
+        span,
+        message: if message.is_empty() {None} else {Some(message)},
+    }
+}
\ No newline at end of file
@@ -0,0 +1,5 @@
+pub mod diagnostic;
+pub mod render;
+pub mod warnings;
+pub mod semantic_checker;
+pub mod type_checker;
\ No newline at end of file
@@ -0,0 +1,152 @@
+use std::{fmt::Write, fs, path::Path};
+
+use colored::Colorize;
+
+use crate::{diagnostics::diagnostic::{Diagnostic, DiagnosticLabel, Severity}, lexer::source_span::SourceSpan};
+
+const TAB_WIDTH: usize = 4;
+
+/// Renders a diagnostic like rustc does, with underlined excerpts of the source code
+/// ```text
+/// error: message
+///  --> file.c:5:9
+///   |
+/// 5 |     int x = ;
+///   |             ^ label
+///   |
+///   = note: a note
+/// ```
+pub fn render(diagnostic: &Diagnostic) -> String {
+    let severity = diagnostic.severity();
+    let mut result = format!("{}{}\n", severity.colourise(&format!("{}: ", severity)), diagnostic.message().bold());
+
+    //(label, is primary)
+    let mut labels: Vec<(DiagnosticLabel, bool)> = Vec::new();
+    if let Some(primary) = diagnostic.primary() {
+        labels.extend(expand_macro_label(primary).into_iter().enumerate().map(|(i, label)| (label, i == 0)));
+    }
+    for secondary in diagnostic.secondary() {
+        labels.extend(expand_macro_label(secondary).into_iter().map(|label| (label, false)));
+    }
+
+    //the gutter must be wide enough for every line number shown
+    let gutter_width = labels.iter()
+        .map(|(label, _)| label.span.start.line.to_string().len())
+        .max()
+        .unwrap_or(1);
+    let gutter = " ".repeat(gutter_width);
+
+    for (i, (label, is_primary)) in labels.iter().enumerate() {
+        let arrow = if i == 0 {"-->"} else {":::"};
+        writeln!(result, "{}{} {}", gutter, arrow.blue().bold(), label.span).unwrap();
+        write_snippet(&mut result, label, *is_primary, diagnostic, gutter_width);
+    }
+
+    if !labels.is_empty() && (!diagnostic.notes().is_empty() || !diagnostic.help().is_empty()) {
+        writeln!(result, "{} {}", gutter, "|".blue().bold()).unwrap();
+    }
+    for note in diagnostic.notes() {
+        writeln!(result, "{} {} {}", gutter, "= note:".bold(), note).unwrap();
+    }
+    for help in diagnostic.help() {
+        writeln!(result, "{} {} {}", gutter, "= help:".bold(), help).unwrap();
+    }
+
+    result
+}
+
+/// Renders each diagnostic, followed by a count of the errors
+/// 
+/// At most `max_errors` errors are shown, or all of them if it is 0
+pub fn render_all(diagnostics: &[Diagnostic], max_errors: usize) -> String {
+    let mut result = String::new();
+    let mut error_count = 0;
+
+    for diagnostic in diagnostics {
+        if diagnostic.severity() == Severity::Error {
+            if max_errors != 0 && error_count == max_errors {
+                writeln!(result, "compilation terminated due to -fmax-errors={}.", max_errors).unwrap();
+                return result;
+            }
+            error_count += 1;
+        }
+        writeln!(result, "{}", render(diagnostic)).unwrap();
+    }
+
+    match error_count {
+        0 => {}
+        1 => writeln!(result, "{}", "error: aborting due to 1 previous error".red().bold()).unwrap(),
+        n => writeln!(result, "{}", format!("error: aborting due to {} previous errors", n).red().bold()).unwrap(),
+    }
+
+    result
+}
+
+/// Labels inside of macros are shown where the macro was used, with extra labels showing the macro definitions
+fn expand_macro_label(label: &DiagnosticLabel) -> Vec<DiagnosticLabel> {
+    let mut result = vec![DiagnosticLabel { span: label.span.expansion_root().clone(), message: label.message.clone() }];
+
+    let mut span = &label.span;
+    while let Some(expansion) = &span.expanded_from {
+        result.push(DiagnosticLabel {
+            span: SourceSpan { expanded_from: None, ..span.clone() },
+            message: Some(format!("in the definition of macro `{}`", expansion.macro_name)),
+        });
+        span = &expansion.call_site;
+    }
+
+    result
+}
+
+/// Writes the line of source that `label` points to, with the label underlined
+fn write_snippet(result: &mut String, label: &DiagnosticLabel, is_primary: bool, diagnostic: &Diagnostic, gutter_width: usize) {
+    let span = &label.span;
+    let Some(line) = read_line(&span.file, span.start.line) else {
+        return;//can't find the source code, so the location will have to do
+    };
+
+    //spans over multiple lines get underlined until the end of the first line
+    let line_length = line.chars().count() as u32 + 1;
+    let end_column = if span.end.line == span.start.line {span.end.column} else {line_length};
+    let end_column = end_column.clamp(span.start.column + 1, line_length.max(span.start.column + 1));
+
+    let mut printed_line = String::new();
+    let mut underline_offset = 0;
+    let mut underline_length = 0;
+    for (column, c) in (1..).zip(line.chars()) {
+        let width = if c == '\t' {TAB_WIDTH} else {1};
+        if column < span.start.column {
+            underline_offset += width;
+        } else if column < end_column {
+            underline_length += width;
+        }
+        if c == '\t' {
+            printed_line.push_str(&" ".repeat(TAB_WIDTH));
+        } else {
+            printed_line.push(c);
+        }
+    }
+    let underline_length = underline_length.max(1);
+
+    let gutter = " ".repeat(gutter_width);
+    let bar = "|".blue().bold();
+    let underline = if is_primary {
+        diagnostic.severity().colourise(&"^".repeat(underline_length))
+    } else {
+        "-".repeat(underline_length).blue().bold()
+    };
+    let message = label.message.as_deref().unwrap_or("");
+    let message = if is_primary {diagnostic.severity().colourise(message)} else {message.blue().bold()};
+
+    writeln!(result, "{} {}", gutter, bar).unwrap();
+    let line_number = format!("{:>width$}", span.start.line, width = gutter_width);
+    writeln!(result, "{} {} {}", line_number.blue().bold(), bar, printed_line).unwrap();
+    writeln!(result, "{} {} {}{} {}", gutter, bar, " ".repeat(underline_offset), underline, message).unwrap();
+}
+
+fn read_line(file: &Path, line_number: u32) -> Option<String> {
+    let text = fs::read_to_string(file).ok()?;
+    text.lines()
+        .nth(line_number.checked_sub(1)? as usize)
+        .map(|line| line.trim_end_matches('\r').to_string())
+}
\ No newline at end of file
@@ -0,0 +1,326 @@
+use stack_management::simple_stack_frame::SimpleStackFrame;
+
+use crate::{asm_gen_data::AsmData, binary_expression::BinaryExpression, cast_expr::CastExpression, data_type::{base_type::{BaseType, IntegerType, ScalarType}, recursive_data_type::{calculate_promoted_type, DataType}}, declaration::MinimalDataVariable, diagnostics::{diagnostic::Diagnostic, type_checker::TypeChecker, warnings::{WarningKind, WarningOptions}}, expression::{binary_expression_operator::BinaryExpressionOperator, expression::Expression, sizeof_expression::SizeofExpr, ternary::TernaryExpr, unary_postfix_expression::UnaryPostfixExpression, unary_prefix_expr::UnaryPrefixExpression, va_builtin::VaBuiltin}, expression_visitors::expr_visitor::ExprVisitor, function_call::FunctionCall, generate_ir_traits::GetType, lexer::source_span::SourceSpan, member_access::MemberAccess, number_literal::typed_value::NumberLiteral, parse_data::ParseData, statement::Statement, string_literal::StringLiteral};
+
+/// Checks that code makes sense before any IR is generated for it, finding type errors and code that is valid but probably a mistake
+pub trait CheckSemantics {
+    fn check_semantics(&self, checker: &mut SemanticChecker);
+}
+
+/// A variable that has been declared in one of the scopes being checked
+struct LocalVariable {
+    name: String,
+    span: SourceSpan,
+    is_param: bool,
+    used: bool,
+}
+
+/// Walks through a function, saving an error for each invalid piece of code and a warning for each suspicious one
+pub struct SemanticChecker<'a> {
+    options: &'a WarningOptions,
+    /// variable types for the current scope
+    asm_data: AsmData,
+    /// only used to create `asm_data` for each scope, as every variable needs a location
+    stack_data: SimpleStackFrame,
+    /// the variables declared in each scope, with the innermost scope last
+    scopes: Vec<Vec<LocalVariable>>,
+    /// how many loops the code being checked is in, as `continue` needs one
+    loop_depth: usize,
+    /// how many switch statements the code being checked is in, as `break` can leave a loop or a switch
+    switch_depth: usize,
+    /// whether the function takes `...`, as `va_start` needs unnamed args
+    is_variadic: bool,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> SemanticChecker<'a> {
+    /// Creates a checker for a function, where `asm_data` has the function's params and `is_variadic` is whether it takes `...`
+    pub fn new(options: &'a WarningOptions, asm_data: AsmData, stack_data: SimpleStackFrame, is_variadic: bool) -> Self {
+        Self {
+            options,
+            asm_data,
+            stack_data,
+            scopes: vec![Vec::new()],
+            loop_depth: 0,
+            switch_depth: 0,
+            is_variadic,
+            diagnostics: Vec::new(),
+        }
+    }
+
+    pub fn asm_data(&self) -> &AsmData {
+        &self.asm_data
+    }
+
+    /// Saves a warning, if that kind of warning is turned on
+    ///
+    /// `build` adds the spans, notes and help to the warning
+    pub fn report(&mut self, kind: WarningKind, message: impl Into<String>, build: impl FnOnce(Diagnostic) -> Diagnostic) {
+        if let Some(warning) = self.options.warning(kind, message) {
+            self.diagnostics.push(build(warning));
+        }
+    }
+    /// Saves an error, which stops the code from being compiled
+    pub fn report_error(&mut self, error: Diagnostic) {
+        self.diagnostics.push(error);
+    }
+    /// Saves a warning that points at `span`, if that kind of warning is turned on
+    pub fn warn(&mut self, kind: WarningKind, message: impl Into<String>, span: &SourceSpan) {
+        self.report(kind, message, |warning| warning.with_primary(span.clone(), ""));
+    }
+
+    /// Starts checking a new scope, returning the variables of the outer scope so that they can be restored by [SemanticChecker::leave_scope]
+    pub fn enter_scope(&mut self, parse_data: &ParseData) -> AsmData {
+        let inner_asm_data = self.asm_data.clone_for_new_scope(parse_data, &mut self.stack_data);
+        self.scopes.push(Vec::new());
+
+        std::mem::replace(&mut self.asm_data, inner_asm_data)
+    }
+    /// Finishes checking a scope, warning about any of its variables that were never used
+    pub fn leave_scope(&mut self, outer_asm_data: AsmData) {
+        self.asm_data = outer_asm_data;
+        self.report_unused_variables();
+    }
+
+    /// Finishes checking the function, returning every error and warning found
+    pub fn finish(mut self) -> Vec<Diagnostic> {
+        self.report_unused_variables();
+        assert!(self.scopes.is_empty(), "a scope was entered but never left");
+
+        self.diagnostics
+    }
+
+    /// Checks the body of a loop, where `break` and `continue` can be used
+    pub fn check_loop_body(&mut self, body: &Statement) {
+        self.loop_depth += 1;
+        body.check_semantics(self);
+        self.loop_depth -= 1;
+    }
+    /// Checks the body of a switch statement, where `break` can be used
+    pub fn check_switch_body(&mut self, body: &Statement) {
+        self.switch_depth += 1;
+        body.check_semantics(self);
+        self.switch_depth -= 1;
+    }
+    /// Whether `break` can be used here
+    pub fn in_loop_or_switch(&self) -> bool {
+        self.loop_depth > 0 || self.switch_depth > 0
+    }
+    /// Whether `continue` can be used here
+    pub fn in_loop(&self) -> bool {
+        self.loop_depth > 0
+    }
+
+    pub fn declare_parameter(&mut self, name: &str, function_span: &SourceSpan) {
+        self.scopes.last_mut().unwrap().push(LocalVariable { name: name.to_string(), span: function_span.clone(), is_param: true, used: false });
+    }
+    pub fn declare_variable(&mut self, name: &str, span: &SourceSpan) {
+        let shadowed = self.scopes.iter()
+            .rev()
+            .skip(1)//variables in the same scope would be a redefinition, not shadowing
+            .flatten()
+            .find(|var| var.name == name)
+            .map(|var| (var.span.clone(), var.is_param));
+
+        if let Some((shadowed_span, is_param)) = shadowed {
+            let label = if is_param {"the shadowed parameter belongs to this function"} else {"the shadowed variable is declared here"};
+            self.report(WarningKind::Shadow, format!("declaration of `{}` shadows a previous declaration", name), |warning| warning
+                .with_primary(span.clone(), "")
+                .with_secondary(shadowed_span, label));
+        }
+
+        self.scopes.last_mut().unwrap().push(LocalVariable { name: name.to_string(), span: span.clone(), is_param: false, used: false });
+    }
+
+    /// Checks the types in `expr`, then looks for warnings if it is valid
+    ///
+    /// Returns whether `expr` is valid, as the type of an invalid expression can't be calculated
+    pub fn check_expression(&mut self, expr: &Expression) -> bool {
+        let valid = self.type_check(|type_checker| type_checker.check_expression(expr));
+        if valid {
+            self.warn_expression(expr);
+        }
+        valid
+    }
+    /// Like [SemanticChecker::check_expression], but `condition` must also be something that can be true or false
+    pub fn check_condition(&mut self, condition: &Expression) {
+        if self.check_expression(condition) {
+            self.type_check(|type_checker| type_checker.check_condition(condition));
+        }
+    }
+    /// Checks `value`, which is the initial value of a variable of type `var_type`
+    pub fn check_initialiser(&mut self, var_type: &DataType, value: &Expression) {
+        if self.type_check(|type_checker| type_checker.check_initialiser(var_type, value)) {
+            self.check_int_to_pointer(var_type, value, "initialisation");
+            self.warn_expression(value);
+        }
+    }
+    /// see [TypeChecker::check_conversion]
+    pub fn check_conversion(&mut self, destination: &DataType, value: &Expression, context: &str) -> bool {
+        self.type_check(|type_checker| type_checker.check_conversion(destination, value, context))
+    }
+
+    /// Warns if `value` is an integer being implicitly converted to the pointer type `destination`
+    ///
+    /// `context` describes the conversion, like "assignment"
+    pub fn check_int_to_pointer(&mut self, destination: &DataType, value: &Expression, context: &str) {
+        if !matches!(destination.unqualified(), DataType::POINTER(_)) {
+            return;
+        }
+        if let Expression::NUMBERLITERAL(NumberLiteral::INTEGER { data: 0, .. }, _) = value {
+            return;//0 is a null pointer constant
+        }
+
+        if let DataType::RAW(BaseType::Scalar(ScalarType::Integer(_))) = value.get_type(&self.asm_data).decay() {
+            self.warn(WarningKind::IntConversion, format!("{} makes a pointer from an integer without a cast", context), value.get_span());
+        }
+    }
+
+    fn type_check(&mut self, check: impl FnOnce(&mut TypeChecker) -> bool) -> bool {
+        let mut type_checker = TypeChecker::new(&self.asm_data);
+        let valid = check(&mut type_checker);
+        self.diagnostics.extend(type_checker.finish());
+
+        valid
+    }
+
+    /// Looks for warnings in an expression that has already been type checked
+    ///
+    /// Also finds errors that depend on the function the expression is in, which the type checker doesn't know about
+    fn warn_expression(&mut self, expr: &Expression) {
+        match expr {
+            Expression::ARRAYLITERAL(array) => {
+                for element in array.get_elements() {
+                    self.warn_expression(element);
+                }
+            },
+            Expression::VABUILTIN(VaBuiltin::Start(va_list), span) if !self.is_variadic => {
+                self.report_error(Diagnostic::error("`va_start` used in a function with fixed arguments")
+                    .with_primary(span.clone(), "")
+                    .with_help("add `...` to the end of the function's parameters"));
+                self.warn_expression(va_list);
+            },
+            expr => expr.accept(self),
+        }
+    }
+
+    fn use_variable(&mut self, name: &str) {
+        //only the innermost variable with the name is being used
+        if let Some(var) = self.scopes.iter_mut().rev().flatten().find(|var| var.name == name) {
+            var.used = true;
+        }
+    }
+
+    fn report_unused_variables(&mut self) {
+        let scope = self.scopes.pop().expect("left more scopes than were entered");
+
+        for var in scope.iter().filter(|var| !var.used) {
+            if var.is_param {
+                self.report(WarningKind::UnusedParameter, format!("unused parameter `{}`", var.name), |warning| warning
+                    .with_primary(var.span.clone(), "in this function")
+                    .with_help(format!("if this is intentional, write `(void){};` in the function", var.name)));
+            } else {
+                self.warn(WarningKind::UnusedVariable, format!("unused variable `{}`", var.name), &var.span);
+            }
+        }
+    }
+
+    fn check_sign_compare(&mut self, expr: &BinaryExpression) {
+        let (lhs_type, rhs_type) = (expr.lhs.get_type(&self.asm_data).decay(), expr.rhs.get_type(&self.asm_data).decay());
+        let (DataType::RAW(BaseType::Scalar(ScalarType::Integer(lhs_int))), DataType::RAW(BaseType::Scalar(ScalarType::Integer(rhs_int)))) = (lhs_type, rhs_type) else {
+            return;//only integers can have different signedness
+        };
+
+        //if the comparison is done as unsigned, a negative operand would become a large positive number
+        let ScalarType::Integer(comparison_type) = calculate_promoted_type(&ScalarType::Integer(lhs_int), &ScalarType::Integer(rhs_int)) else {
+            return;
+        };
+        let could_be_negative = |operand: &Expression, operand_type: IntegerType| {
+            !operand_type.is_unsigned() && !matches!(operand, Expression::NUMBERLITERAL(NumberLiteral::INTEGER { data: 0.., .. }, _))
+        };
+
+        if comparison_type.is_unsigned() && (could_be_negative(&expr.lhs, lhs_int) || could_be_negative(&expr.rhs, rhs_int)) {
+            self.report(WarningKind::SignCompare, format!("comparison of integers of different signedness: `{}` and `{}`", lhs_int, rhs_int), |warning| warning
+                .with_primary(expr.get_span().clone(), "")
+                .with_note(format!("the signed operand is converted to `{}`, so negative values compare as very large numbers", comparison_type)));
+        }
+    }
+}
+
+impl<'a> ExprVisitor for SemanticChecker<'a> {
+    type Output = ();
+
+    fn visit_number_literal(&mut self, _: &NumberLiteral) {}
+
+    fn visit_variable(&mut self, var: &MinimalDataVariable) {
+        self.use_variable(&var.name);
+    }
+
+    fn visit_string_literal(&mut self, _: &StringLiteral) {}
+
+    fn visit_func_call(&mut self, func_call: &FunctionCall) {
+        self.warn_expression(func_call.get_callee());
+
+        let (_, param_types) = func_call.get_callee_signature(&self.asm_data);
+        for (i, (param_type, arg)) in param_types.iter().zip(func_call.get_args()).enumerate() {
+            self.check_int_to_pointer(param_type, arg, &format!("passing argument {}", i + 1));
+        }
+
+        for arg in func_call.get_args() {
+            self.warn_expression(arg);
+        }
+    }
+
+    fn visit_unary_prefix(&mut self, expr: &UnaryPrefixExpression) {
+        self.warn_expression(expr.get_operand());
+    }
+
+    fn visit_unary_postfix(&mut self, expr: &UnaryPostfixExpression) {
+        self.warn_expression(expr.get_operand());
+    }
+
+    fn visit_binary_expression(&mut self, expr: &BinaryExpression) {
+        if expr.operator.as_comparator_instr().is_some() {
+            self.check_sign_compare(expr);
+        }
+        if expr.operator == BinaryExpressionOperator::Assign {
+            let lhs_type = expr.lhs.get_type(&self.asm_data);
+            self.check_int_to_pointer(&lhs_type, &expr.rhs, "assignment");
+        }
+
+        self.warn_expression(&expr.lhs);
+        self.warn_expression(&expr.rhs);
+    }
+
+    fn visit_member_access(&mut self, expr: &MemberAccess) {
+        self.warn_expression(expr.get_base_tree());
+    }
+
+    fn visit_cast_expr(&mut self, expr: &CastExpression) {
+        self.warn_expression(expr.get_uncasted_expr());
+    }
+
+    fn visit_sizeof(&mut self, sizeof: &SizeofExpr) {
+        if let SizeofExpr::SizeofExpression(expr) = sizeof {
+            self.warn_expression(expr);
+        }
+    }
+
+    fn visit_ternary(&mut self, ternary: &TernaryExpr) {
+        self.warn_expression(ternary.condition());
+        self.warn_expression(ternary.true_branch());
+        self.warn_expression(ternary.false_branch());
+    }
+
+    fn visit_va_builtin(&mut self, builtin: &VaBuiltin) {
+        match builtin {
+            VaBuiltin::Start(va_list) |
+            VaBuiltin::End(va_list) |
+            VaBuiltin::Arg { va_list, .. } => self.warn_expression(va_list),
+            VaBuiltin::Copy { dest, src } => {
+                self.warn_expression(dest);
+                self.warn_expression(src);
+            },
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,416 @@
+use crate::{asm_gen_data::{AsmData, GetStructUnion}, binary_expression::BinaryExpression, cast_expr::CastExpression, data_type::{base_type::{BaseType, ScalarType}, recursive_data_type::DataType, type_qualifier::TypeQualifiers}, declaration::MinimalDataVariable, diagnostics::diagnostic::Diagnostic, expression::{binary_expression_operator::BinaryExpressionOperator, expression::Expression, sizeof_expression::SizeofExpr, ternary::TernaryExpr, unary_postfix_expression::UnaryPostfixExpression, unary_prefix_expr::UnaryPrefixExpression, unary_prefix_operator::UnaryPrefixOperator, va_builtin::VaBuiltin}, expression_visitors::expr_visitor::ExprVisitor, function_call::FunctionCall, generate_ir_traits::GetType, member_access::MemberAccess, number_literal::typed_value::NumberLiteral, string_literal::StringLiteral};
+
+/// Checks that the operands of each expression have suitable types, so that [GetType] and IR generation can't panic
+///
+/// Each visit returns whether the expression is valid.
+/// Once part of an expression is invalid its type is unknown, so nothing containing it is checked
+pub struct TypeChecker<'a> {
+    asm_data: &'a AsmData,
+    errors: Vec<Diagnostic>,
+}
+
+impl<'a> TypeChecker<'a> {
+    pub fn new(asm_data: &'a AsmData) -> Self {
+        Self { asm_data, errors: Vec::new() }
+    }
+
+    /// Returns every error found
+    pub fn finish(self) -> Vec<Diagnostic> {
+        self.errors
+    }
+
+    pub fn check_expression(&mut self, expr: &Expression) -> bool {
+        match expr {
+            Expression::ARRAYLITERAL(array) => {
+                self.error(Diagnostic::error("an initialiser list can only be used to initialise a variable")
+                    .with_primary(array.get_span().clone(), ""));
+                false
+            },
+            expr => expr.accept(self),
+        }
+    }
+
+    /// Checks `value`, which is the initial value of a variable of type `var_type`
+    pub fn check_initialiser(&mut self, var_type: &DataType, value: &Expression) -> bool {
+        match (var_type, value) {
+            (DataType::ARRAY { .. }, Expression::ARRAYLITERAL(array)) => self.check_array_elements(array.get_elements()),
+            (DataType::ARRAY { .. }, Expression::STRINGLITERAL(..)) => true,
+            (DataType::ARRAY { .. }, value) => {
+                self.error(Diagnostic::error("arrays must be initialised with an initialiser list or a string")
+                    .with_primary(value.get_span().clone(), ""));
+                false
+            },
+            (var_type, Expression::ARRAYLITERAL(array)) => {
+                self.error(Diagnostic::error(format!("cannot initialise `{}` with an initialiser list", var_type))
+                    .with_primary(array.get_span().clone(), ""));
+                false
+            },
+            (var_type, value) => self.check_expression(value) && self.check_conversion(var_type, value, "initialisation"),
+        }
+    }
+
+    /// Checks that `value` (which must already be valid) can be implicitly converted to `destination`
+    ///
+    /// `context` describes the conversion, like "assignment"
+    pub fn check_conversion(&mut self, destination: &DataType, value: &Expression, context: &str) -> bool {
+        let value_type = value.get_type(self.asm_data).decay();
+
+        let compatible = match (destination.decay(), &value_type) {
+            (_, DataType::RAW(BaseType::VOID)) => false,
+            (DataType::RAW(BaseType::Scalar(_)), DataType::RAW(BaseType::Scalar(_))) => true,
+            //integers and pointers can be converted to each other, but -Wint-conversion warns about it
+            (DataType::POINTER(_), DataType::POINTER(_) | DataType::RAW(BaseType::Scalar(ScalarType::Integer(_)))) => true,
+            (DataType::RAW(BaseType::Scalar(ScalarType::Integer(_))), DataType::POINTER(_)) => true,
+            //structs and unions can only be copied to the same type
+            (destination @ DataType::RAW(BaseType::Struct(_) | BaseType::Union(_)), value_type) => destination == *value_type,
+            _ => false,
+        };
+
+        if !compatible {
+            self.error(Diagnostic::error(format!("incompatible types in {}: expected `{}`, found `{}`", context, destination, value_type))
+                .with_primary(value.get_span().clone(), format!("this has type `{}`", value_type)));
+            return false;
+        }
+
+        //a const int* can't become an int*, as that would allow writing to the const int
+        if let (DataType::POINTER(destination_pointee), DataType::POINTER(value_pointee)) = (destination.decay(), &value_type) {
+            let discarded = TypeQualifiers {
+                is_const: value_pointee.qualifiers().is_const && !destination_pointee.qualifiers().is_const,
+                is_volatile: value_pointee.qualifiers().is_volatile && !destination_pointee.qualifiers().is_volatile,
+            };
+
+            if !discarded.is_empty() {
+                self.error(Diagnostic::error(format!("{} discards `{}` from the pointed-to type", context, discarded))
+                    .with_primary(value.get_span().clone(), format!("this has type `{}`", value_type))
+                    .with_note(format!("expected `{}`", destination)));
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Checks that `condition` (which must already be valid) can be tested for being true or false
+    pub fn check_condition(&mut self, condition: &Expression) -> bool {
+        let condition_type = condition.get_type(self.asm_data);
+        let valid = is_scalar(&condition_type);
+
+        if !valid {
+            self.error(Diagnostic::error(format!("`{}` used as a condition", condition_type))
+                .with_primary(condition.get_span().clone(), "conditions must be a number or pointer"));
+        }
+
+        valid
+    }
+
+    fn error(&mut self, error: Diagnostic) {
+        self.errors.push(error);
+    }
+
+    /// Checks every expression, even after finding an invalid one, so that all of their errors are reported
+    fn check_all<'b>(&mut self, exprs: impl IntoIterator<Item = &'b Expression>) -> bool {
+        exprs.into_iter().fold(true, |valid, expr| self.check_expression(expr) && valid)
+    }
+
+    fn check_array_elements(&mut self, elements: &[Expression]) -> bool {
+        elements.iter().fold(true, |valid, element| match element {
+            Expression::ARRAYLITERAL(nested) => self.check_array_elements(nested.get_elements()) && valid,
+            element => self.check_expression(element) && valid,
+        })
+    }
+
+    /// Checks that `expr` (which must already be valid) can be assigned to
+    fn check_assignable(&mut self, expr: &Expression) -> bool {
+        let problem = match expr.get_type(self.asm_data) {
+            DataType::ARRAY { .. } | DataType::UNKNOWNSIZEARRAY { .. } => Some("arrays can't be assigned to"),
+            DataType::FUNCTION { .. } => Some("functions can't be assigned to"),
+            _ if !is_lvalue(expr) => Some("this is a temporary value, not a variable or memory location"),
+            _ if expr.get_qualifiers(self.asm_data).is_const => Some("this is `const`, so can't be modified"),
+            _ => None,
+        };
+
+        if let Some(reason) = problem {
+            self.error(Diagnostic::error("expression is not assignable")
+                .with_primary(expr.get_span().clone(), reason));
+        }
+
+        problem.is_none()
+    }
+
+    /// Checks the operand of `++` or `--`, which must already be valid
+    fn check_increment(&mut self, operand: &Expression) -> bool {
+        if !self.check_assignable(operand) {
+            return false;
+        }
+
+        let operand_type = operand.get_type(self.asm_data);
+        let valid = is_scalar(&operand_type);
+        if !valid {
+            self.error(Diagnostic::error(format!("cannot increment or decrement `{}`", operand_type))
+                .with_primary(operand.get_span().clone(), "only numbers and pointers can be incremented"));
+        }
+
+        valid
+    }
+}
+
+impl<'a> ExprVisitor for TypeChecker<'a> {
+    type Output = bool;
+
+    fn visit_number_literal(&mut self, _: &NumberLiteral) -> bool {
+        true
+    }
+
+    fn visit_variable(&mut self, var: &MinimalDataVariable) -> bool {
+        let declared = self.asm_data.try_get_variable(&var.name).is_some();
+
+        if !declared {
+            self.error(Diagnostic::error(format!("use of undeclared identifier `{}`", var.name))
+                .with_primary(var.get_span().clone(), ""));
+        }
+
+        declared
+    }
+
+    fn visit_string_literal(&mut self, _: &StringLiteral) -> bool {
+        true
+    }
+
+    fn visit_func_call(&mut self, func_call: &FunctionCall) -> bool {
+        if !self.check_all(std::iter::once(func_call.get_callee()).chain(func_call.get_args())) {
+            return false;
+        }
+
+        let callee_type = func_call.get_callee().get_type(self.asm_data);
+        let params = match callee_type.decay() {
+            DataType::POINTER(pointed_at) => match *pointed_at {
+                DataType::FUNCTION { params, .. } => Some(params),
+                _ => None,
+            },
+            _ => None,
+        };
+        let Some(params) = params else {
+            self.error(Diagnostic::error(format!("called object of type `{}` is not a function or function pointer", callee_type))
+                .with_primary(func_call.get_callee().get_span().clone(), ""));
+            return false;
+        };
+
+        let is_variadic = params.last().is_some_and(|x| *x == DataType::new(BaseType::VaArg));
+        let named_params = if is_variadic {&params[..params.len() - 1]} else {&params[..]};
+        let args = func_call.get_args();
+
+        if args.len() < named_params.len() || (args.len() > named_params.len() && !is_variadic) {
+            let problem = if args.len() < named_params.len() {"too few"} else {"too many"};
+            let expected = if is_variadic {format!("at least {}", named_params.len())} else {named_params.len().to_string()};
+            self.error(Diagnostic::error(format!("{} arguments to function call, expected {}, have {}", problem, expected, args.len()))
+                .with_primary(func_call.get_span().clone(), ""));
+            return false;
+        }
+
+        named_params.iter()
+        .zip(args)
+        .enumerate()
+        .fold(true, |valid, (i, (param_type, arg))| self.check_conversion(param_type, arg, &format!("argument {}", i + 1)) && valid)
+    }
+
+    fn visit_unary_prefix(&mut self, expr: &UnaryPrefixExpression) -> bool {
+        let operand = expr.get_operand();
+        if !self.check_expression(operand) {
+            return false;
+        }
+
+        let operand_type = operand.get_type(self.asm_data);
+        let (valid, requirement) = match expr.get_operator() {
+            UnaryPrefixOperator::Reference => (is_lvalue(operand), "a variable or memory location"),
+            UnaryPrefixOperator::Dereference => (is_pointer(&operand_type), "a pointer"),
+            UnaryPrefixOperator::Negate |
+            UnaryPrefixOperator::UnaryPlus => (is_arithmetic(&operand_type), "a number"),
+            UnaryPrefixOperator::BitwiseNot => (is_integer(&operand_type), "an integer"),
+            UnaryPrefixOperator::BooleanNot => (is_scalar(&operand_type), "a number or pointer"),
+            UnaryPrefixOperator::Increment |
+            UnaryPrefixOperator::Decrement => return self.check_increment(operand),
+        };
+
+        if !valid {
+            let operator_name: &str = expr.get_operator().clone().into();
+            self.error(Diagnostic::error(format!("invalid operand to {}: expected {}", operator_name, requirement))
+                .with_primary(operand.get_span().clone(), format!("this has type `{}`", operand_type)));
+        }
+
+        valid
+    }
+
+    fn visit_unary_postfix(&mut self, expr: &UnaryPostfixExpression) -> bool {
+        self.check_expression(expr.get_operand()) && self.check_increment(expr.get_operand())
+    }
+
+    fn visit_binary_expression(&mut self, expr: &BinaryExpression) -> bool {
+        if !self.check_all([&*expr.lhs, &*expr.rhs]) {
+            return false;
+        }
+
+        if expr.operator == BinaryExpressionOperator::Assign {
+            return self.check_assignable(&expr.lhs) && self.check_conversion(&expr.lhs.get_type(self.asm_data), &expr.rhs, "assignment");
+        }
+
+        //compound assignments apply the operation, then assign the result
+        let operator = match expr.operator.as_compound_assignment_base() {
+            Some(_) if !self.check_assignable(&expr.lhs) => return false,
+            Some(base_operator) => base_operator,
+            None => expr.operator.clone(),
+        };
+
+        let (lhs_type, rhs_type) = (expr.lhs.get_type(self.asm_data).decay(), expr.rhs.get_type(self.asm_data).decay());
+        let both = |check: fn(&DataType) -> bool| check(&lhs_type) && check(&rhs_type);
+        let pointer_and_integer = (is_pointer(&lhs_type) && is_integer(&rhs_type)) || (is_integer(&lhs_type) && is_pointer(&rhs_type));
+
+        let valid = match operator {
+            BinaryExpressionOperator::Add => both(is_arithmetic) || pointer_and_integer,
+            BinaryExpressionOperator::Subtract => both(is_arithmetic) || both(is_pointer) || (is_pointer(&lhs_type) && is_integer(&rhs_type)),
+
+            BinaryExpressionOperator::Multiply |
+            BinaryExpressionOperator::Divide => both(is_arithmetic),
+
+            BinaryExpressionOperator::Mod |
+            BinaryExpressionOperator::BitwiseOr |
+            BinaryExpressionOperator::BitwiseAnd |
+            BinaryExpressionOperator::BitwiseXor |
+            BinaryExpressionOperator::BitshiftLeft |
+            BinaryExpressionOperator::BitshiftRight => both(is_integer),
+
+            BinaryExpressionOperator::BooleanOr |
+            BinaryExpressionOperator::BooleanAnd => both(is_scalar),
+
+            BinaryExpressionOperator::CmpLess |
+            BinaryExpressionOperator::CmpGreater |
+            BinaryExpressionOperator::CmpLessEqual |
+            BinaryExpressionOperator::CmpGreaterEqual |
+            BinaryExpressionOperator::CmpEqual |
+            BinaryExpressionOperator::CmpNotEqual => both(is_arithmetic) || both(is_pointer) || pointer_and_integer,
+
+            x => unreachable!("{:?} is an assignment, so should have been handled already", x)
+        };
+
+        if !valid {
+            self.error(Diagnostic::error(format!("invalid operands to binary `{}`: `{}` and `{}`", expr.operator.symbol(), lhs_type, rhs_type))
+                .with_primary(expr.get_span().clone(), ""));
+        }
+
+        valid
+    }
+
+    fn visit_member_access(&mut self, expr: &MemberAccess) -> bool {
+        if !self.check_expression(expr.get_base_tree()) {
+            return false;
+        }
+
+        let asm_data = self.asm_data;
+        let base_type = expr.get_base_tree().get_type(asm_data);
+        let member_names: Option<Vec<&str>> = match &base_type {
+            DataType::RAW(BaseType::Struct(name)) => asm_data.get_struct(name).get_all_members().as_ref()
+                .map(|members| members.iter().map(|(decl, _)| decl.name.as_str()).collect()),
+            DataType::RAW(BaseType::Union(name)) => asm_data.get_union(name).ordered_members.as_ref()
+                .map(|members| members.iter().map(|decl| decl.name.as_str()).collect()),
+            _ => {
+                self.error(Diagnostic::error(format!("`{}` is not a struct or union, so it has no members", base_type))
+                    .with_primary(expr.get_base_tree().get_span().clone(), ""));
+                return false;
+            }
+        };
+
+        match member_names {
+            None => {
+                self.error(Diagnostic::error(format!("`{}` is declared but never defined, so it has no members", base_type))
+                    .with_primary(expr.get_span().clone(), ""));
+                false
+            },
+            Some(names) if !names.contains(&expr.get_member_name()) => {
+                self.error(Diagnostic::error(format!("no member named `{}` in `{}`", expr.get_member_name(), base_type))
+                    .with_primary(expr.get_span().clone(), "")
+                    .with_note(format!("the members are: {}", names.join(", "))));
+                false
+            },
+            Some(_) => true,
+        }
+    }
+
+    fn visit_cast_expr(&mut self, expr: &CastExpression) -> bool {
+        if !self.check_expression(expr.get_uncasted_expr()) {
+            return false;
+        }
+
+        let (from, to) = (expr.get_uncasted_expr().get_type(self.asm_data).decay(), expr.get_new_type());
+        let valid = match (&from, to) {
+            (_, DataType::RAW(BaseType::VOID)) => true,//the value is being discarded
+            (DataType::POINTER(_), DataType::RAW(BaseType::Scalar(ScalarType::Float(_)))) |
+            (DataType::RAW(BaseType::Scalar(ScalarType::Float(_))), DataType::POINTER(_)) => false,
+            (from, to) => is_scalar(from) && is_scalar(to),
+        };
+
+        if !valid {
+            self.error(Diagnostic::error(format!("cannot cast `{}` to `{}`", from, to))
+                .with_primary(expr.get_span().clone(), ""));
+        }
+
+        valid
+    }
+
+    fn visit_sizeof(&mut self, sizeof: &SizeofExpr) -> bool {
+        match sizeof {
+            SizeofExpr::SizeofExpression(expr) => self.check_expression(expr),
+            SizeofExpr::SizeofType(_) => true,
+        }
+    }
+
+    fn visit_ternary(&mut self, ternary: &TernaryExpr) -> bool {
+        if !self.check_all([ternary.condition(), ternary.true_branch(), ternary.false_branch()]) || !self.check_condition(ternary.condition()) {
+            return false;
+        }
+
+        let (true_type, false_type) = (ternary.true_branch().get_type(self.asm_data).decay(), ternary.false_branch().get_type(self.asm_data).decay());
+        let compatible = (is_arithmetic(&true_type) && is_arithmetic(&false_type)) ||
+            (is_pointer(&true_type) && (is_pointer(&false_type) || is_integer(&false_type))) ||
+            (is_integer(&true_type) && is_pointer(&false_type));
+
+        if !compatible {
+            self.error(Diagnostic::error(format!("incompatible branches in conditional expression: `{}` and `{}`", true_type, false_type))
+                .with_primary(ternary.get_span().clone(), "")
+                .with_note("both branches must be numbers or pointers"));
+        }
+
+        compatible
+    }
+
+    fn visit_va_builtin(&mut self, builtin: &VaBuiltin) -> bool {
+        match builtin {
+            VaBuiltin::Start(va_list) |
+            VaBuiltin::End(va_list) |
+            VaBuiltin::Arg { va_list, .. } => self.check_expression(va_list),
+            VaBuiltin::Copy { dest, src } => self.check_all([&**dest, &**src]),
+        }
+    }
+}
+
+/// Whether `expr` refers to an object in memory, rather than a temporary value
+fn is_lvalue(expr: &Expression) -> bool {
+    match expr {
+        Expression::VARIABLE(_) |
+        Expression::STRINGLITERAL(..) => true,
+        Expression::UNARYPREFIX(expr) => *expr.get_operator() == UnaryPrefixOperator::Dereference,
+        Expression::STRUCTMEMBERACCESS(access) => is_lvalue(access.get_base_tree()),
+        _ => false,
+    }
+}
+
+fn is_integer(data_type: &DataType) -> bool {
+    matches!(data_type.decay(), DataType::RAW(BaseType::Scalar(ScalarType::Integer(_))))
+}
+fn is_arithmetic(data_type: &DataType) -> bool {
+    matches!(data_type.decay(), DataType::RAW(BaseType::Scalar(_)))
+}
+fn is_pointer(data_type: &DataType) -> bool {
+    matches!(data_type.decay(), DataType::POINTER(_))
+}
+fn is_scalar(data_type: &DataType) -> bool {
+    is_arithmetic(data_type) || is_pointer(data_type)
+}
\ No newline at end of file
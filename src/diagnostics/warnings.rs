@@ -0,0 +1,144 @@
+use std::collections::HashSet;
+
+use crate::diagnostics::diagnostic::{Diagnostic, Severity};
+
+/// Each kind of warning, named like the `-W` flag that controls it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WarningKind {
+    UnusedVariable,
+    UnusedParameter,
+    /// an integer was implicitly converted to a pointer
+    IntConversion,
+    /// signed and unsigned integers were compared, so the signed one is treated as unsigned
+    SignCompare,
+    /// a non-void function can finish without returning a value
+    ReturnType,
+    UnreachableCode,
+    /// a local variable hides another variable with the same name
+    Shadow,
+    /// an assignment is used as a condition, where `==` was probably meant
+    Parentheses,
+    /// a `#pragma` that the compiler does not understand, so it is ignored
+    UnknownPragmas,
+}
+
+impl WarningKind {
+    pub const ALL: [WarningKind; 9] = [
+        Self::UnusedVariable,
+        Self::UnusedParameter,
+        Self::IntConversion,
+        Self::SignCompare,
+        Self::ReturnType,
+        Self::UnreachableCode,
+        Self::Shadow,
+        Self::Parentheses,
+        Self::UnknownPragmas,
+    ];
+
+    /// The name used in `-W<name>` and `-Wno-<name>`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::UnusedVariable => "unused-variable",
+            Self::UnusedParameter => "unused-parameter",
+            Self::IntConversion => "int-conversion",
+            Self::SignCompare => "sign-compare",
+            Self::ReturnType => "return-type",
+            Self::UnreachableCode => "unreachable-code",
+            Self::Shadow => "shadow",
+            Self::Parentheses => "parentheses",
+            Self::UnknownPragmas => "unknown-pragmas",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.name() == name)
+    }
+
+    fn enabled_by_default(&self) -> bool {
+        matches!(self, Self::IntConversion | Self::UnknownPragmas)
+    }
+    /// whether `-Wall` turns this warning on
+    fn in_all(&self) -> bool {
+        matches!(self, Self::UnusedVariable | Self::ReturnType | Self::Parentheses | Self::UnknownPragmas)
+    }
+    /// whether `-Wextra` turns this warning on
+    fn in_extra(&self) -> bool {
+        matches!(self, Self::UnusedParameter | Self::SignCompare | Self::UnreachableCode)
+    }
+}
+
+/// Which warnings are reported, and whether they stop compilation
+#[derive(Debug, Clone)]
+pub struct WarningOptions {
+    enabled: HashSet<WarningKind>,
+    warnings_are_errors: bool,
+}
+
+impl Default for WarningOptions {
+    fn default() -> Self {
+        Self {
+            enabled: WarningKind::ALL.into_iter().filter(|kind| kind.enabled_by_default()).collect(),
+            warnings_are_errors: false,
+        }
+    }
+}
+
+impl WarningOptions {
+    /// Applies each `-W` flag in order, so later flags override earlier ones
+    ///
+    /// Flags are given without the `-W`, like `all`, `error` or `no-shadow`
+    pub fn from_flags<'a>(flags: impl IntoIterator<Item = &'a str>) -> Result<Self, String> {
+        let mut result = Self::default();
+
+        for flag in flags {
+            let (enable, name) = match flag.strip_prefix("no-") {
+                Some(name) => (false, name),
+                None => (true, flag),
+            };
+
+            let kinds: Vec<WarningKind> = match name {
+                "all" => WarningKind::ALL.into_iter().filter(|kind| kind.in_all()).collect(),
+                "extra" => WarningKind::ALL.into_iter().filter(|kind| kind.in_extra()).collect(),
+                "error" => {
+                    result.warnings_are_errors = enable;
+                    continue;
+                },
+                name => vec![WarningKind::from_name(name).ok_or_else(|| format!("unknown warning option -W{}", flag))?],
+            };
+
+            for kind in kinds {
+                if enable {
+                    result.enabled.insert(kind);
+                } else {
+                    result.enabled.remove(&kind);
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    pub fn is_enabled(&self, kind: WarningKind) -> bool {
+        self.enabled.contains(&kind)
+    }
+    pub fn warnings_are_errors(&self) -> bool {
+        self.warnings_are_errors
+    }
+
+    /// Creates a warning of the specified kind, or None if that kind of warning is turned off
+    ///
+    /// With `-Werror`, this is an error instead
+    pub fn warning(&self, kind: WarningKind, message: impl Into<String>) -> Option<Diagnostic> {
+        if !self.is_enabled(kind) {
+            return None;
+        }
+
+        let diagnostic = if self.warnings_are_errors {
+            Diagnostic::new(Severity::Error, message).with_note(format!("`-Werror` and `-W{}` are enabled", kind.name()))
+        } else {
+            Diagnostic::warning(message).with_note(format!("`-W{}` is enabled", kind.name()))
+        };
+
+        Some(diagnostic.with_warning_kind(kind))
+    }
+}
\ No newline at end of file
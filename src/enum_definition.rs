@@ -1,11 +1,13 @@
 use std::collections::HashMap;
 
+use serde::Serialize;
+
 use crate::{ast_metadata::ASTMetadata, data_type::base_type::IntegerType, lexer::{keywords::Keyword, punctuator::Punctuator, token::Token, token_savepoint::TokenQueueSlice, token_walk::TokenQueue}, number_literal::typed_value::NumberLiteral, parse_data::ParseData};
 
 /**
  * stores all the enums in a current scope
  */
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Serialize)]
 pub struct EnumList {
     all_variants: HashMap<String, NumberLiteral>,//converts enum variant name to number literal
 
@@ -1,10 +1,19 @@
+use serde::Serialize;
 use crate::{assembly::{comparison::ComparisonKind, operation::LogicalOperation}, lexer::punctuator::Punctuator};
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub enum BinaryExpressionOperator {
     Assign,
     AdditionCombination,
     SubtractionCombination,
+    MultiplicationCombination,
+    DivisionCombination,
+    ModCombination,
+    BitshiftLeftCombination,
+    BitshiftRightCombination,
+    BitwiseAndCombination,
+    BitwiseOrCombination,
+    BitwiseXorCombination,
 
     BooleanOr,
     BooleanAnd,
@@ -66,6 +75,58 @@ impl BinaryExpressionOperator {
         }
     }
 
+    /// if this is a compound assignment like `a += b`, returns the operation that is applied before assigning (`+`)
+    pub fn as_compound_assignment_base(&self) -> Option<BinaryExpressionOperator> {
+        match self {
+            Self::AdditionCombination => Some(Self::Add),
+            Self::SubtractionCombination => Some(Self::Subtract),
+            Self::MultiplicationCombination => Some(Self::Multiply),
+            Self::DivisionCombination => Some(Self::Divide),
+            Self::ModCombination => Some(Self::Mod),
+            Self::BitshiftLeftCombination => Some(Self::BitshiftLeft),
+            Self::BitshiftRightCombination => Some(Self::BitshiftRight),
+            Self::BitwiseAndCombination => Some(Self::BitwiseAnd),
+            Self::BitwiseOrCombination => Some(Self::BitwiseOr),
+            Self::BitwiseXorCombination => Some(Self::BitwiseXor),
+            _ => None
+        }
+    }
+
+    /// the operator as it is written in C, like `+=`
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Self::Assign => "=",
+            Self::AdditionCombination => "+=",
+            Self::SubtractionCombination => "-=",
+            Self::MultiplicationCombination => "*=",
+            Self::DivisionCombination => "/=",
+            Self::ModCombination => "%=",
+            Self::BitshiftLeftCombination => "<<=",
+            Self::BitshiftRightCombination => ">>=",
+            Self::BitwiseAndCombination => "&=",
+            Self::BitwiseOrCombination => "|=",
+            Self::BitwiseXorCombination => "^=",
+            Self::BooleanOr => "||",
+            Self::BooleanAnd => "&&",
+            Self::Add => "+",
+            Self::Subtract => "-",
+            Self::Multiply => "*",
+            Self::Divide => "/",
+            Self::Mod => "%",
+            Self::CmpLess => "<",
+            Self::CmpGreater => ">",
+            Self::CmpLessEqual => "<=",
+            Self::CmpGreaterEqual => ">=",
+            Self::CmpEqual => "==",
+            Self::CmpNotEqual => "!=",
+            Self::BitwiseOr => "|",
+            Self::BitwiseAnd => "&",
+            Self::BitwiseXor => "^",
+            Self::BitshiftRight => ">>",
+            Self::BitshiftLeft => "<<",
+        }
+    }
+
     pub fn from_punctuator(value: Punctuator) -> Option<Self> {
         match value {
             Punctuator::EQUALS => Some(Self::Assign),
@@ -89,6 +150,14 @@ impl BinaryExpressionOperator {
             Punctuator::LessLess => Some(Self::BitshiftLeft),
             Punctuator::AdditionCombination => Some(Self::AdditionCombination),
             Punctuator::SubtractionCombination => Some(Self::SubtractionCombination),
+            Punctuator::MultiplicationCombination => Some(Self::MultiplicationCombination),
+            Punctuator::DivisionCombination => Some(Self::DivisionCombination),
+            Punctuator::ModCombination => Some(Self::ModCombination),
+            Punctuator::BitshiftLeftCombination => Some(Self::BitshiftLeftCombination),
+            Punctuator::BitshiftRightCombination => Some(Self::BitshiftRightCombination),
+            Punctuator::BitwiseAndCombination => Some(Self::BitwiseAndCombination),
+            Punctuator::BitwiseOrCombination => Some(Self::BitwiseOrCombination),
+            Punctuator::BitwiseXorCombination => Some(Self::BitwiseXorCombination),
 
             _ => None,
         }
@@ -119,6 +188,14 @@ impl<'a> Into<&'a str> for BinaryExpressionOperator {
             BinaryExpressionOperator::BitshiftLeft => "shift left",
             Self::AdditionCombination => "increment by",
             Self::SubtractionCombination => "subtract by",
+            Self::MultiplicationCombination => "multiply by",
+            Self::DivisionCombination => "divide by",
+            Self::ModCombination => "mod by",
+            Self::BitshiftLeftCombination => "shift left by",
+            Self::BitshiftRightCombination => "shift right by",
+            Self::BitwiseAndCombination => "bitwise and by",
+            Self::BitwiseOrCombination => "bitwise or by",
+            Self::BitwiseXorCombination => "bitwise xor by",
         }
     }
 }
\ No newline at end of file
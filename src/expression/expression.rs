@@ -1,15 +1,16 @@
 use stack_management::{simple_stack_frame::SimpleStackFrame, stack_item::StackItemKey};
 use unwrap_let::unwrap_let;
-use crate::{ array_initialisation::ArrayInitialisation, asm_gen_data::{AsmData, GetStructUnion, GlobalAsmData}, assembly::{assembly::IRCode, operand::{immediate::ToImmediate, IRMemOperand, IROperand, Storage, PTR_SIZE}, operation::IROperation}, ast_metadata::ASTMetadata, binary_expression::BinaryExpression, cast_expr::CastExpression, data_type::{base_type::{BaseType, IntegerType, ScalarType}, recursive_data_type::DataType}, debugging::ASTDisplay, declaration::MinimalDataVariable, expression::{ternary::TernaryExpr, unary_prefix_expr::UnaryPrefixExpression}, expression_visitors::expr_visitor::ExprVisitor, function_call::FunctionCall, function_declaration::consume_fully_qualified_type, generate_ir_traits::{GenerateIR, GetAddress, GetType}, lexer::{keywords::Keyword, precedence, punctuator::Punctuator, token::Token, token_savepoint::TokenQueueSlice, token_walk::{TokenQueue, TokenSearchType}}, member_access::MemberAccess, number_literal::typed_value::NumberLiteral, parse_data::ParseData, string_literal::StringLiteral};
+use serde::Serialize;
+use crate::{ array_initialisation::ArrayInitialisation, asm_gen_data::{AsmData, GetStructUnion, GlobalAsmData}, assembly::{assembly::IRCode, operand::{immediate::ToImmediate, IRMemOperand, IROperand, Storage, PTR_SIZE}, operation::IROperation}, ast_metadata::ASTMetadata, binary_expression::BinaryExpression, cast_expr::CastExpression, data_type::{base_type::{BaseType, IntegerType, ScalarType}, recursive_data_type::DataType, type_qualifier::TypeQualifiers}, debugging::ASTDisplay, declaration::MinimalDataVariable, expression::{ternary::TernaryExpr, unary_prefix_expr::UnaryPrefixExpression}, expression_visitors::expr_visitor::ExprVisitor, function_call::FunctionCall, function_declaration::consume_fully_qualified_type, generate_ir_traits::{GenerateIR, GetAddress, GetType}, lexer::{keywords::Keyword, precedence, punctuator::Punctuator, source_span::SourceSpan, token::Token, token_savepoint::TokenQueueSlice, token_walk::{TokenQueue, TokenSearchType}}, member_access::MemberAccess, number_literal::typed_value::NumberLiteral, parse_data::ParseData, string_literal::StringLiteral};
 
-use super::{binary_expression_operator::BinaryExpressionOperator, sizeof_expression::SizeofExpr, unary_postfix_expression::UnaryPostfixExpression, unary_postfix_operator::UnaryPostfixOperator, unary_prefix_operator::UnaryPrefixOperator};
+use super::{binary_expression_operator::BinaryExpressionOperator, sizeof_expression::SizeofExpr, va_builtin::VaBuiltin, unary_postfix_expression::UnaryPostfixExpression, unary_postfix_operator::UnaryPostfixOperator, unary_prefix_operator::UnaryPrefixOperator};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub enum Expression {
-    NUMBERLITERAL(NumberLiteral),
+    NUMBERLITERAL(NumberLiteral, SourceSpan),
     VARIABLE(MinimalDataVariable),
     STRUCTMEMBERACCESS(MemberAccess),
-    STRINGLITERAL(StringLiteral),//TODO merge with array initialisation
+    STRINGLITERAL(StringLiteral, SourceSpan),//TODO merge with array initialisation
     ARRAYLITERAL(ArrayInitialisation),
     FUNCCALL(FunctionCall),
 
@@ -18,7 +19,8 @@ pub enum Expression {
     BINARYEXPRESSION(BinaryExpression),
     TERNARYEXPRESSION(TernaryExpr),
     CAST(CastExpression),
-    SIZEOF(SizeofExpr)
+    SIZEOF(SizeofExpr, SourceSpan),
+    VABUILTIN(VaBuiltin, SourceSpan),
 }
 
 impl Expression {
@@ -42,11 +44,30 @@ impl Expression {
         }
     }
 
+    /// Where this expression is in the source code
+    pub fn get_span(&self) -> &SourceSpan {
+        match self {
+            Expression::NUMBERLITERAL(_, span) |
+            Expression::STRINGLITERAL(_, span) |
+            Expression::SIZEOF(_, span) |
+            Expression::VABUILTIN(_, span) => span,
+            Expression::VARIABLE(x) => x.get_span(),
+            Expression::STRUCTMEMBERACCESS(x) => x.get_span(),
+            Expression::ARRAYLITERAL(x) => x.get_span(),
+            Expression::FUNCCALL(x) => x.get_span(),
+            Expression::UNARYPREFIX(x) => x.get_span(),
+            Expression::UNARYSUFFIX(x) => x.get_span(),
+            Expression::BINARYEXPRESSION(x) => x.get_span(),
+            Expression::TERNARYEXPRESSION(x) => x.get_span(),
+            Expression::CAST(x) => x.get_span(),
+        }
+    }
+
     pub fn accept<V: ExprVisitor>(&self, visitor: &mut V) -> V::Output {
         match self {
-            Expression::NUMBERLITERAL(x) => x.accept(visitor),
+            Expression::NUMBERLITERAL(x, _) => x.accept(visitor),
             Expression::VARIABLE(x) => x.accept(visitor),
-            Expression::STRINGLITERAL(x) => x.accept(visitor),
+            Expression::STRINGLITERAL(x, _) => x.accept(visitor),
             Expression::FUNCCALL(x) => x.accept(visitor),
             Expression::UNARYPREFIX(x) => x.accept(visitor),
             Expression::UNARYSUFFIX(x) => x.accept(visitor),
@@ -54,8 +75,26 @@ impl Expression {
             Expression::STRUCTMEMBERACCESS(x) => x.accept(visitor),
             Expression::CAST(cast_expression) => cast_expression.accept(visitor),
             Expression::ARRAYLITERAL(x) => panic!("cannot determine data type/assemebly for array literal, try looking for casts or array initialisation instead\nfor array {:?}", x),
-            Expression::SIZEOF(sizeof_expr) => sizeof_expr.accept(visitor),
+            Expression::SIZEOF(sizeof_expr, _) => sizeof_expr.accept(visitor),
             Expression::TERNARYEXPRESSION(x) => x.accept(visitor),
+            Expression::VABUILTIN(x, _) => x.accept(visitor),
+        }
+    }
+
+    /// Gets the qualifiers of the memory this expression refers to, like the `const` of a `const int` variable
+    /// 
+    /// [GetType::get_type] never includes these, since reading the value makes a copy
+    pub fn get_qualifiers(&self, asm_data: &AsmData) -> TypeQualifiers {
+        match self {
+            Expression::VARIABLE(var) => asm_data.get_variable(&var.name).data_type.qualifiers(),
+            Expression::STRUCTMEMBERACCESS(access) => access.get_qualified_type(asm_data).qualifiers(),
+            Expression::UNARYPREFIX(expr) if *expr.get_operator() == UnaryPrefixOperator::Dereference => {
+                match expr.get_operand().get_type(asm_data).decay() {
+                    DataType::POINTER(pointed_at) => pointed_at.qualifiers(),
+                    _ => TypeQualifiers::default(),
+                }
+            },
+            _ => TypeQualifiers::default(),//temporary values can't be modified anyway
         }
     }
 }
@@ -64,28 +103,29 @@ impl GenerateIR for Expression {
     fn generate_ir(&self, asm_data: &AsmData, stack_data: &mut SimpleStackFrame, global_asm_data: &GlobalAsmData) -> (IRCode, Option<StackItemKey>) {
 
         match self {
-            Expression::NUMBERLITERAL(number_literal) => number_literal.generate_ir(asm_data, stack_data, global_asm_data),
+            Expression::NUMBERLITERAL(number_literal, _) => number_literal.generate_ir(asm_data, stack_data, global_asm_data),
             Expression::VARIABLE(minimal_data_variable) => minimal_data_variable.generate_ir(asm_data, stack_data, global_asm_data),
-            Expression::STRUCTMEMBERACCESS(member_access) => todo!(),
-            Expression::STRINGLITERAL(string_literal) => todo!(),
+            Expression::STRUCTMEMBERACCESS(member_access) => member_access.generate_ir(asm_data, stack_data, global_asm_data),
+            Expression::STRINGLITERAL(string_literal, _) => string_literal.generate_ir(asm_data, stack_data, global_asm_data),
             Expression::ARRAYLITERAL(array_initialisation) => todo!(),
             Expression::FUNCCALL(function_call) => function_call.generate_ir(asm_data, stack_data, global_asm_data),
             Expression::UNARYPREFIX(unary_prefix_expression) => unary_prefix_expression.generate_ir(asm_data, stack_data, global_asm_data),
             Expression::UNARYSUFFIX(unary_postfix_expression) => unary_postfix_expression.generate_ir(asm_data, stack_data, global_asm_data),
             Expression::BINARYEXPRESSION(binary_expression) => binary_expression.generate_ir(asm_data, stack_data, global_asm_data),
             Expression::TERNARYEXPRESSION(ternary_expr) => todo!(),
-            Expression::CAST(cast_expression) => todo!(),
-            Expression::SIZEOF(sizeof_expr) => todo!(),
+            Expression::CAST(cast_expression) => cast_expression.generate_ir(asm_data, stack_data, global_asm_data),
+            Expression::SIZEOF(sizeof_expr, _) => sizeof_expr.generate_ir(asm_data, stack_data, global_asm_data),
+            Expression::VABUILTIN(va_builtin, _) => va_builtin.generate_ir(asm_data, stack_data, global_asm_data),
         }
     }
 }
 impl GetType for Expression {
     fn get_type(&self, asm_data: &AsmData) -> DataType {
         match self {
-            Expression::NUMBERLITERAL(number_literal) => DataType::RAW(BaseType::Scalar(number_literal.get_data_type())),
+            Expression::NUMBERLITERAL(number_literal, _) => DataType::RAW(BaseType::Scalar(number_literal.get_data_type())),
             Expression::VARIABLE(minimal_data_variable) => minimal_data_variable.get_type(asm_data),
             Expression::STRUCTMEMBERACCESS(member_access) => member_access.get_type(asm_data),
-            Expression::STRINGLITERAL(string_literal) => string_literal.get_type(asm_data),
+            Expression::STRINGLITERAL(string_literal, _) => string_literal.get_type(asm_data),
             Expression::ARRAYLITERAL(array_initialisation) => array_initialisation.get_type(asm_data),
             Expression::FUNCCALL(function_call) => function_call.get_type(asm_data),
             Expression::UNARYPREFIX(unary_prefix_expression) => unary_prefix_expression.get_type(asm_data),
@@ -93,25 +133,27 @@ impl GetType for Expression {
             Expression::BINARYEXPRESSION(binary_expression) => binary_expression.get_type(asm_data),
             Expression::TERNARYEXPRESSION(ternary_expr) => ternary_expr.get_type(asm_data),
             Expression::CAST(cast_expression) => cast_expression.get_type(asm_data),
-            Expression::SIZEOF(sizeof_expr) => sizeof_expr.get_type(asm_data),
+            Expression::SIZEOF(sizeof_expr, _) => sizeof_expr.get_type(asm_data),
+            Expression::VABUILTIN(va_builtin, _) => va_builtin.get_type(asm_data),
         }
     }
 }
 impl GetAddress for Expression {
     fn get_address(&self, asm_data: &AsmData, stack_data: &mut SimpleStackFrame, global_asm_data: &GlobalAsmData) -> (IRCode, StackItemKey) {
         match self {
-            Expression::NUMBERLITERAL(number_literal) => panic!("can't get address of number"),
+            Expression::NUMBERLITERAL(number_literal, _) => panic!("can't get address of number"),
             Expression::VARIABLE(minimal_data_variable) => minimal_data_variable.get_address(asm_data, stack_data, global_asm_data),
-            Expression::STRUCTMEMBERACCESS(member_access) => todo!(),
-            Expression::STRINGLITERAL(string_literal) => todo!(),
+            Expression::STRUCTMEMBERACCESS(member_access) => member_access.get_address(asm_data, stack_data, global_asm_data),
+            Expression::STRINGLITERAL(string_literal, _) => string_literal.get_address(asm_data, stack_data, global_asm_data),
             Expression::ARRAYLITERAL(array_initialisation) => panic!("can't get address of array literal"),
-            Expression::FUNCCALL(function_call) => todo!(),
+            Expression::FUNCCALL(function_call) => function_call.get_address(asm_data, stack_data, global_asm_data),
             Expression::UNARYPREFIX(unary_prefix_expression) => unary_prefix_expression.get_address(asm_data, stack_data, global_asm_data),
             Expression::UNARYSUFFIX(unary_postfix_expression) => todo!(),
             Expression::BINARYEXPRESSION(binary_expression) => panic!("can't get address of binary expression?"),
             Expression::TERNARYEXPRESSION(ternary_expr) => panic!("can't get address of ternary expression"),
             Expression::CAST(cast_expression) => panic!("can't get address of a cast"),
-            Expression::SIZEOF(sizeof_expr) => panic!("can't get address of sizeof expression"),
+            Expression::SIZEOF(sizeof_expr, _) => panic!("can't get address of sizeof expression"),
+            Expression::VABUILTIN(va_builtin, _) => panic!("can't get address of {:?}", va_builtin),
         }
     }
 }
@@ -144,17 +186,18 @@ pub fn try_consume_whole_expr(tokens_queue: &TokenQueue, previous_queue_idx: &To
         0 => None,//panic!("not expecting this, maybe it is not an expression"),
 
         1 => {
+            let span = tokens_queue.get_span(&curr_queue_idx);
             //1 token left, check if it is a number
             match tokens_queue.peek(& curr_queue_idx, &scope_data)? {
                 Token::NUMBER(num) => {
                     tokens_queue.consume(&mut curr_queue_idx, &scope_data);
-                    Some(Expression::NUMBERLITERAL(num))
+                    Some(Expression::NUMBERLITERAL(num.value, span))
                 },
                 Token::IDENTIFIER(var_name) => {
-                    Some(Expression::VARIABLE(MinimalDataVariable{name: var_name}))
+                    Some(Expression::VARIABLE(MinimalDataVariable{name: var_name, span}))
                 },
                 Token::STRING(string_lit) => {
-                    Some(Expression::STRINGLITERAL(string_lit))
+                    Some(Expression::STRINGLITERAL(string_lit, span))
                 }
                 _ => None
             }
@@ -178,6 +221,11 @@ pub fn try_consume_whole_expr(tokens_queue: &TokenQueue, previous_queue_idx: &To
                             return Some(Expression::UNARYPREFIX(index_expr));//since a[b] = *(a+b), indexing returns a unary prefix
                         }
 
+                        //builtins look like function calls, but can take a type as an arg
+                        if let Some(builtin) = VaBuiltin::try_consume_whole_expr(tokens_queue, &curr_queue_idx, scope_data) {
+                            return Some(Expression::VABUILTIN(builtin, tokens_queue.get_span(&curr_queue_idx)));
+                        }
+
                         if let Some(func) = FunctionCall::try_consume_whole_expr(tokens_queue, &curr_queue_idx, scope_data) {
                             return Some(Expression::FUNCCALL(func));
                         }
@@ -220,7 +268,7 @@ pub fn try_consume_whole_expr(tokens_queue: &TokenQueue, previous_queue_idx: &To
                             return Some(Expression::CAST(cast));
                         }
                         if let Some(sizeof_expr) = try_parse_sizeof(tokens_queue, &curr_queue_idx, scope_data) {
-                            return Some(Expression::SIZEOF(sizeof_expr));
+                            return Some(Expression::SIZEOF(sizeof_expr, tokens_queue.get_span(&curr_queue_idx)));
                         }
                     }
 
@@ -311,10 +359,10 @@ pub fn generate_assembly_for_assignment(lhs: &Expression, rhs: &Expression, asm_
 
     match (&promoted_type, rhs) {
         //initialising array to string literal
-        (DataType::ARRAY {..}, Expression::STRINGLITERAL(string_init)) => {
+        (DataType::ARRAY {..}, Expression::STRINGLITERAL(string_init, span)) => {
             result.merge(&assembly_for_array_assignment(
                 lhs,
-                string_init.zero_fill_and_flatten_to_iter(&promoted_type),
+                string_init.zero_fill_and_flatten_to_iter(&promoted_type, span),
                 &DataType::RAW(BaseType::Scalar(ScalarType::Integer(IntegerType::I8))),
                 asm_data, stack_data, global_asm_data
             ));
@@ -342,7 +390,8 @@ pub fn generate_assembly_for_assignment(lhs: &Expression, rhs: &Expression, asm_
             
             //put the value to assign in acc, and cast to correct type
             let (rhs_asm, rhs_value) = rhs.generate_ir(asm_data, stack_data, global_asm_data);
-            let (rhs_cast_asm, rhs_casted_value) = promote(rhs_value.unwrap(), rhs.get_type(asm_data), promoted_type.clone(), stack_data, asm_data);
+            let rhs_type = rhs.get_type(asm_data).decay();//arrays and functions are already generated as pointers
+            let (rhs_cast_asm, rhs_casted_value) = promote(rhs_value.unwrap(), rhs_type, promoted_type.clone(), stack_data, asm_data);
             result.merge(&rhs_asm);
             result.add_instruction(rhs_cast_asm);
 
@@ -424,7 +473,7 @@ fn try_parse_unary_prefix(tokens_queue: &TokenQueue, previous_queue_idx: &TokenQ
 
     let operand = try_consume_whole_expr(tokens_queue, &curr_queue_idx, scope_data)?;
 
-    Some(UnaryPrefixExpression::new(unary_op, operand))
+    Some(UnaryPrefixExpression::new(unary_op, operand, tokens_queue.get_span(previous_queue_idx)))
 }
 
 fn try_parse_unary_suffix(tokens_queue: &TokenQueue, previous_queue_idx: &TokenQueueSlice, scope_data: &mut ParseData) -> Option<UnaryPostfixExpression> {
@@ -438,7 +487,7 @@ fn try_parse_unary_suffix(tokens_queue: &TokenQueue, previous_queue_idx: &TokenQ
 
     let operand = try_consume_whole_expr(tokens_queue, &curr_queue_idx, scope_data)?;
 
-    Some(UnaryPostfixExpression::new(unary_op, operand))
+    Some(UnaryPostfixExpression::new(unary_op, operand, tokens_queue.get_span(previous_queue_idx)))
 }
 
 /**
@@ -458,7 +507,7 @@ fn try_parse_binary_expr(tokens_queue: &TokenQueue, curr_queue_idx: &TokenQueueS
     .and_then(|x| x.as_punctuator())//try to convert to punctuator
     .and_then(|x| BinaryExpressionOperator::from_punctuator(x))?;//try to convert to binary expression operator
 
-    Some(BinaryExpression::new(parsed_left, operator, parsed_right))
+    Some(BinaryExpression::new(parsed_left, operator, parsed_right, tokens_queue.get_span(curr_queue_idx)))
 }
 
 fn try_parse_array_index(tokens_queue: &TokenQueue, curr_queue_idx: &TokenQueueSlice, scope_data: &mut ParseData) -> Option<UnaryPrefixExpression> {
@@ -481,10 +530,13 @@ fn try_parse_array_index(tokens_queue: &TokenQueue, curr_queue_idx: &TokenQueueS
         let index_expr = try_consume_whole_expr(tokens_queue, &index_slice, scope_data)?;
         let array_expr = try_consume_whole_expr(tokens_queue, &array_slice, scope_data)?;
 
+        let span = tokens_queue.get_span(curr_queue_idx);
+
         //a[b] == *(a+b) in C
         return Some(
             UnaryPrefixExpression::new(UnaryPrefixOperator::Dereference, //dereference
-                Expression::BINARYEXPRESSION(BinaryExpression::new(array_expr, BinaryExpressionOperator::Add, index_expr))//pointer plus index
+                Expression::BINARYEXPRESSION(BinaryExpression::new(array_expr, BinaryExpressionOperator::Add, index_expr, span.clone())),//pointer plus index
+                span
             )
         );
     }
@@ -513,7 +565,7 @@ fn try_parse_member_access(tokens_queue: &TokenQueue, expr_slice: &TokenQueueSli
         //the first part must return a struct
         let struct_tree = try_consume_whole_expr(tokens_queue, &curr_queue_idx, scope_data)?;
 
-        return Some(MemberAccess::new(struct_tree, member_name));
+        return Some(MemberAccess::new(struct_tree, member_name, tokens_queue.get_span(expr_slice)));
     }
     
     None//failed to find correct identifiers
@@ -574,7 +626,7 @@ fn try_parse_cast(tokens_queue: &TokenQueue, expr_slice: &TokenQueueSlice, scope
 
     let base_expr = try_consume_whole_expr(tokens_queue, &remaining_expr_slice, scope_data)?;
 
-    Some(CastExpression::new(new_type, base_expr))
+    Some(CastExpression::new(new_type, base_expr, tokens_queue.get_span(expr_slice)))
 }
 
 fn try_parse_ternary(tokens_queue: &TokenQueue, expr_slice: &TokenQueueSlice, scope_data: &mut ParseData) -> Option<TernaryExpr> {
@@ -594,25 +646,27 @@ fn try_parse_ternary(tokens_queue: &TokenQueue, expr_slice: &TokenQueueSlice, sc
     Some(TernaryExpr::new(
         try_consume_whole_expr(tokens_queue, &condition, scope_data).unwrap(),
         try_consume_whole_expr(tokens_queue, &true_branch, scope_data).unwrap(),
-        try_consume_whole_expr(tokens_queue, &false_branch, scope_data).unwrap()
+        try_consume_whole_expr(tokens_queue, &false_branch, scope_data).unwrap(),
+        tokens_queue.get_span(expr_slice)
     ))
 }
 
 impl ASTDisplay for Expression {
     fn display_ast(&self, f: &mut crate::debugging::TreeDisplayInfo) {
         match self {
-            Expression::NUMBERLITERAL(number_literal) => f.write(&format!("{}", number_literal)),
+            Expression::NUMBERLITERAL(number_literal, _) => f.write(&format!("{}", number_literal)),
             Expression::VARIABLE(minimal_data_variable) => f.write(&format!("{}", minimal_data_variable)),
             Expression::STRUCTMEMBERACCESS(struct_member_access) => struct_member_access.display_ast(f),
-            Expression::STRINGLITERAL(string_literal) => f.write(&format!("{}", string_literal)),
+            Expression::STRINGLITERAL(string_literal, _) => f.write(&format!("{}", string_literal)),
             Expression::ARRAYLITERAL(array_initialisation) => array_initialisation.display_ast(f),
             Expression::FUNCCALL(function_call) => function_call.display_ast(f),
             Expression::UNARYPREFIX(unary_prefix_expression) => unary_prefix_expression.display_ast(f),
             Expression::UNARYSUFFIX(unary_suffix) => unary_suffix.display_ast(f),
             Expression::BINARYEXPRESSION(binary_expression) => binary_expression.display_ast(f),
             Expression::CAST(cast_expression) => cast_expression.display_ast(f),
-            Expression::SIZEOF(sizeof_expr) => sizeof_expr.display_ast(f),
+            Expression::SIZEOF(sizeof_expr, _) => sizeof_expr.display_ast(f),
             Expression::TERNARYEXPRESSION(ternary) => ternary.display_ast(f),
+            Expression::VABUILTIN(va_builtin, _) => va_builtin.display_ast(f),
         }
     }
 }
\ No newline at end of file
@@ -6,3 +6,4 @@ pub mod unary_postfix_expression;
 pub mod unary_postfix_operator;
 pub mod sizeof_expression;
 pub mod ternary;
+pub mod va_builtin;
@@ -1,30 +1,18 @@
 use colored::Colorize;
 use memory_size::MemorySize;
-use crate::{asm_gen_data::AsmData, data_type::{base_type::{BaseType, IntegerType, ScalarType}, recursive_data_type::DataType}, debugging::ASTDisplay, expression_visitors::{expr_visitor::ExprVisitor}, generate_ir_traits::GetType};
+use serde::Serialize;
+use stack_management::{simple_stack_frame::SimpleStackFrame, stack_item::StackItemKey};
+use crate::{asm_gen_data::{AsmData, GlobalAsmData}, assembly::assembly::IRCode, data_type::{base_type::{BaseType, IntegerType, ScalarType}, recursive_data_type::DataType}, debugging::ASTDisplay, expression_visitors::{expr_visitor::ExprVisitor}, generate_ir_traits::{GenerateIR, GetType}, number_literal::typed_value::NumberLiteral};
 
 use super::expression::Expression;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub enum SizeofExpr {
     SizeofExpression(Box<Expression>),
     SizeofType(DataType)
 }
 
 impl SizeofExpr {
-    // pub fn generate_assembly(&self, asm_data: &AsmData) -> Assembly {
-    //     let mut result = Assembly::make_empty();
-
-    //     let size = self.get_result(asm_data);
-
-    //     result.add_instruction(AsmOperation::MOV {
-    //         to: RegOrMem::GPReg(GPRegister::acc()),
-    //         from: Operand::Imm(size.as_imm()),
-    //         size: PTR_SIZE,//standard actually says it should be a size_t
-    //     });
-
-    //     result
-    // }
-
     /// evaluates the expression by calculating the size 
     pub fn get_result(&self, asm_data: &AsmData) -> MemorySize {
         match self {
@@ -38,6 +26,14 @@ impl SizeofExpr {
     }
 }
 
+impl GenerateIR for SizeofExpr {
+    fn generate_ir(&self, asm_data: &AsmData, stack_data: &mut SimpleStackFrame, global_asm_data: &GlobalAsmData) -> (IRCode, Option<StackItemKey>) {
+        //the size is known at compile time, so sizeof is just a size_t constant
+        let size = NumberLiteral::INTEGER { data: self.get_result(asm_data).size_bytes().into(), data_type: IntegerType::U64 };
+        size.generate_ir(asm_data, stack_data, global_asm_data)
+    }
+}
+
 impl GetType for SizeofExpr {
     fn get_type(&self, _: &AsmData) -> DataType {
         DataType::RAW(BaseType::Scalar(ScalarType::Integer(IntegerType::U64)))//sizeof is size_t-sized
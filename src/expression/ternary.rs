@@ -1,23 +1,26 @@
 use colored::Colorize;
 
-use crate::{data_type::recursive_data_type::{calculate_promoted_type_arithmetic, DataType}, debugging::ASTDisplay, expression::expression::Expression, expression_visitors::{expr_visitor::ExprVisitor}, generate_ir_traits::GetType};
+use serde::Serialize;
+use crate::{data_type::recursive_data_type::{calculate_promoted_type_arithmetic, DataType}, debugging::ASTDisplay, expression::expression::Expression, expression_visitors::{expr_visitor::ExprVisitor}, generate_ir_traits::GetType, lexer::source_span::SourceSpan};
 
 //TODO: a?b:c syntax parsing
 //then generate assembly for it
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct TernaryExpr {
     condition: Box<Expression>,
     true_branch: Box<Expression>,
     false_branch: Box<Expression>,
+    span: SourceSpan,
 }
 
 impl TernaryExpr {
-    pub fn new(condition: Expression, true_branch: Expression, false_branch: Expression) -> Self {
+    pub fn new(condition: Expression, true_branch: Expression, false_branch: Expression, span: SourceSpan) -> Self {
         Self {
             condition: Box::new(condition),
             true_branch: Box::new(true_branch),
-            false_branch: Box::new(false_branch)
+            false_branch: Box::new(false_branch),
+            span
         }
     }
     pub fn accept<V: ExprVisitor>(&self, visitor: &mut V) -> V::Output {
@@ -32,6 +35,9 @@ impl TernaryExpr {
     pub fn condition(&self) -> &Expression {
         &self.condition
     }
+    pub fn get_span(&self) -> &SourceSpan {
+        &self.span
+    }
 }
 
 impl GetType for TernaryExpr {
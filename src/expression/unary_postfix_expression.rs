@@ -1,13 +1,15 @@
 use colored::Colorize;
 use stack_management::simple_stack_frame::SimpleStackFrame;
-use crate::{asm_gen_data::{AsmData, GlobalAsmData}, assembly::assembly::IRCode, data_type::recursive_data_type::{calculate_unary_type_arithmetic, DataType}, debugging::ASTDisplay, expression_visitors::expr_visitor::ExprVisitor, generate_ir_traits::{GenerateIR, GetType}};
+use serde::Serialize;
+use crate::{asm_gen_data::{AsmData, GlobalAsmData}, assembly::assembly::IRCode, data_type::recursive_data_type::{calculate_unary_type_arithmetic, DataType}, debugging::ASTDisplay, expression_visitors::expr_visitor::ExprVisitor, generate_ir_traits::{GenerateIR, GetType}, lexer::source_span::SourceSpan};
 
 use super::{expression::Expression, unary_postfix_operator::UnaryPostfixOperator};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct UnaryPostfixExpression {
     operand: Box<Expression>,
     operator: UnaryPostfixOperator,
+    span: SourceSpan,
 }
 
 impl UnaryPostfixExpression {
@@ -15,8 +17,8 @@ impl UnaryPostfixExpression {
         visitor.visit_unary_postfix(self)
     }
 
-    pub fn new(operator: UnaryPostfixOperator, operand: Expression) -> Self{
-        Self { operand: Box::new(operand), operator }
+    pub fn new(operator: UnaryPostfixOperator, operand: Expression, span: SourceSpan) -> Self{
+        Self { operand: Box::new(operand), operator, span }
     }
 
     pub fn get_operator(&self) -> &UnaryPostfixOperator {
@@ -26,6 +28,10 @@ impl UnaryPostfixExpression {
     pub fn get_operand(&self) -> &Expression {
         &self.operand
     }
+
+    pub fn get_span(&self) -> &SourceSpan {
+        &self.span
+    }
 }
 
 impl GenerateIR for UnaryPostfixExpression {
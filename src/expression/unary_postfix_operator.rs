@@ -1,6 +1,7 @@
+use serde::Serialize;
 use crate::lexer::punctuator::Punctuator;
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub enum UnaryPostfixOperator {
     Decrement,
     Increment,
@@ -1,12 +1,14 @@
-use crate::{asm_gen_data::{AsmData, GlobalAsmData}, assembly::{assembly::IRCode, operand::{IRMemOperand, IROperand, Storage, PTR_SIZE}, operation::IROperation}, data_type::{base_type::{BaseType, IntegerType, ScalarType}, recursive_data_type::{calculate_unary_type_arithmetic, DataType}, type_modifier::DeclModifier}, debugging::ASTDisplay, expression::{expression::{promote, Expression}, unary_prefix_operator::UnaryPrefixOperator}, expression_visitors::expr_visitor::ExprVisitor, generate_ir_traits::{GenerateIR, GetAddress, GetType}};
+use serde::Serialize;
+use crate::{asm_gen_data::{AsmData, GlobalAsmData}, assembly::{assembly::IRCode, operand::{IRMemOperand, IROperand, Storage, PTR_SIZE}, operation::IROperation}, data_type::{base_type::{BaseType, IntegerType, ScalarType}, recursive_data_type::{calculate_unary_type_arithmetic, DataType}, type_modifier::DeclModifier}, debugging::ASTDisplay, expression::{expression::{promote, Expression}, unary_prefix_operator::UnaryPrefixOperator}, expression_visitors::expr_visitor::ExprVisitor, generate_ir_traits::{GenerateIR, GetAddress, GetType}, lexer::source_span::SourceSpan};
 use colored::Colorize;
 use stack_management::simple_stack_frame::SimpleStackFrame;
 use unwrap_let::unwrap_let;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct UnaryPrefixExpression {
     operand: Box<Expression>,
     operator: UnaryPrefixOperator,
+    span: SourceSpan,
 }
 
 impl UnaryPrefixExpression {
@@ -14,8 +16,8 @@ impl UnaryPrefixExpression {
         visitor.visit_unary_prefix(self)
     }
 
-    pub fn new(operator: UnaryPrefixOperator, operand: Expression) -> UnaryPrefixExpression {
-        UnaryPrefixExpression { operand: Box::new(operand), operator }
+    pub fn new(operator: UnaryPrefixOperator, operand: Expression, span: SourceSpan) -> UnaryPrefixExpression {
+        UnaryPrefixExpression { operand: Box::new(operand), operator, span }
     }
 
     pub fn get_operator(&self) -> &UnaryPrefixOperator {
@@ -25,12 +27,22 @@ impl UnaryPrefixExpression {
     pub fn get_operand(&self) -> &Expression {
         &self.operand
     }
+
+    pub fn get_span(&self) -> &SourceSpan {
+        &self.span
+    }
 }
 
 impl GenerateIR for UnaryPrefixExpression {
     fn generate_ir(&self, asm_data: &AsmData, stack_data: &mut SimpleStackFrame, global_asm_data: &GlobalAsmData) -> (IRCode, Option<stack_management::stack_item::StackItemKey>) {
         let mut result = IRCode::make_empty();
         let resultant_type = self.get_type(asm_data);
+
+        if let (UnaryPrefixOperator::Dereference, DataType::FUNCTION { .. }) = (&self.operator, &resultant_type) {
+            //*fp is a function designator, which decays straight back to the pointer
+            return self.operand.generate_ir(asm_data, stack_data, global_asm_data);
+        }
+
         let resultant_location = stack_data.allocate(resultant_type.memory_size(asm_data));
 
         match self.operator {
@@ -71,7 +83,16 @@ impl GenerateIR for UnaryPrefixExpression {
             UnaryPrefixOperator::Decrement => todo!(),
             UnaryPrefixOperator::Increment => todo!(),
             UnaryPrefixOperator::BooleanNot => todo!(),
-            UnaryPrefixOperator::BitwiseNot => todo!(),
+            UnaryPrefixOperator::BitwiseNot => {
+
+                let (operand_ir, operand_location) = self.operand.generate_ir(asm_data, stack_data, global_asm_data);
+                result.merge(&operand_ir);
+
+                let (promote_instruction, promoted_location) = promote(operand_location.unwrap(), self.operand.get_type(asm_data), resultant_type.clone(), stack_data, asm_data);
+                result.add_instruction(promote_instruction);
+
+                result.add_instruction(IROperation::BitwiseNot { from: Storage::Stack(promoted_location), to: Storage::Stack(resultant_location), size: resultant_type.memory_size(asm_data) });
+            },
         }
 
         (result, Some(resultant_location))
@@ -82,8 +103,8 @@ impl GetType for UnaryPrefixExpression {
     fn get_type(&self, asm_data: &AsmData) -> DataType {
         let operand_type = self.operand.get_type(asm_data);
         match self.operator {
-            UnaryPrefixOperator::Reference => operand_type.add_outer_modifier(DeclModifier::POINTER),//pointer to whatever rhs is
-            UnaryPrefixOperator::Dereference => operand_type.remove_outer_modifier(),
+            UnaryPrefixOperator::Reference => operand_type.add_qualifiers(self.operand.get_qualifiers(asm_data)).add_outer_modifier(DeclModifier::POINTER),//pointer to whatever rhs is, so &x is a const int* when x is const
+            UnaryPrefixOperator::Dereference => operand_type.decay().remove_outer_modifier().unqualified(),//decay, as *my_function is allowed
             UnaryPrefixOperator::UnaryPlus | UnaryPrefixOperator::Negate | UnaryPrefixOperator::Increment | UnaryPrefixOperator::Decrement | UnaryPrefixOperator::BitwiseNot => calculate_unary_type_arithmetic(&operand_type),//-x may promote x to a bigger type
             UnaryPrefixOperator::BooleanNot => DataType::RAW(BaseType::Scalar(ScalarType::Integer(IntegerType::_BOOL))),
         }
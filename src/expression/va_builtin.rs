@@ -0,0 +1,171 @@
+use colored::Colorize;
+use stack_management::{simple_stack_frame::SimpleStackFrame, stack_item::StackItemKey};
+
+use serde::Serialize;
+use crate::{args_handling::va_args::{generate_va_arg, generate_va_copy, generate_va_start}, asm_gen_data::{AsmData, GlobalAsmData}, assembly::assembly::IRCode, data_type::{base_type::BaseType, recursive_data_type::DataType}, debugging::ASTDisplay, diagnostics::diagnostic::Diagnostic, expression_visitors::expr_visitor::ExprVisitor, function_declaration::consume_fully_qualified_type, generate_ir_traits::{GenerateIR, GetAddress, GetType}, lexer::{punctuator::Punctuator, token::Token, token_savepoint::TokenQueueSlice, token_walk::{TokenQueue, TokenSearchType}}, parse_data::ParseData};
+
+use super::expression::{self, Expression};
+
+/// The builtins that stdarg.h's macros expand to
+#[derive(Clone, Debug, Serialize)]
+pub enum VaBuiltin {
+    /// `__builtin_va_start(ap, last_param)`
+    Start(Box<Expression>),
+    /// `__builtin_va_arg(ap, type)`
+    Arg {va_list: Box<Expression>, data_type: DataType},
+    /// `__builtin_va_end(ap)`
+    End(Box<Expression>),
+    /// `__builtin_va_copy(dest, src)`
+    Copy {dest: Box<Expression>, src: Box<Expression>},
+}
+
+impl VaBuiltin {
+    pub fn accept<V: ExprVisitor>(&self, visitor: &mut V) -> V::Output {
+        visitor.visit_va_builtin(self)
+    }
+
+    pub fn try_consume_whole_expr(tokens_queue: &TokenQueue, curr_queue_idx: &TokenQueueSlice, scope_data: &mut ParseData) -> Option<VaBuiltin> {
+        let span = tokens_queue.get_span(curr_queue_idx);
+        let mut curr_queue_idx = curr_queue_idx.clone();
+
+        let builtin_name = match tokens_queue.consume(&mut curr_queue_idx, scope_data)? {
+            Token::IDENTIFIER(name) if name.starts_with("__builtin_va_") => name,
+            _ => return None,
+        };
+
+        if !tokens_queue.slice_is_brackets(&curr_queue_idx, Punctuator::OPENCURLY) {
+            return None;//the rest must be the args in brackets
+        }
+
+        let all_args_slice = TokenQueueSlice {
+            index: curr_queue_idx.index+1,
+            max_index: curr_queue_idx.max_index-1
+        };
+        let args_slices = tokens_queue.split_outside_parentheses(&all_args_slice, |x| *x == Token::PUNCTUATOR(Punctuator::COMMA), &TokenSearchType::skip_all_brackets());
+
+        match (builtin_name.as_str(), args_slices.as_slice()) {
+            //the last named param is not needed, as the function definition knows where its unnamed args start
+            ("__builtin_va_start", [va_list]) |
+            ("__builtin_va_start", [va_list, _]) => Some(VaBuiltin::Start(consume_va_list(tokens_queue, va_list, scope_data))),
+
+            ("__builtin_va_arg", [va_list, data_type]) => {
+                let data_type = consume_fully_qualified_type(tokens_queue, data_type, scope_data)
+                    .map(|x| {
+                        assert!(x.remaining_slice.get_slice_size() == 0);
+                        x.resultant_tree.0
+                    })
+                    .expect("second arg of va_arg must be a type");
+
+                Some(VaBuiltin::Arg { va_list: consume_va_list(tokens_queue, va_list, scope_data), data_type })
+            },
+
+            ("__builtin_va_end", [va_list]) => Some(VaBuiltin::End(consume_va_list(tokens_queue, va_list, scope_data))),
+
+            ("__builtin_va_copy", [dest, src]) => Some(VaBuiltin::Copy {
+                dest: consume_va_list(tokens_queue, dest, scope_data),
+                src: consume_va_list(tokens_queue, src, scope_data)
+            }),
+
+            (name @ ("__builtin_va_start" | "__builtin_va_arg" | "__builtin_va_end" | "__builtin_va_copy"), args) => {
+                scope_data.report_error(Diagnostic::error(format!("wrong number of arguments to `{}`", name))
+                    .with_primary(span, format!("{} given", args.len())));
+                None
+            },
+
+            _ => None,//not a builtin, so it could be a normal function
+        }
+    }
+}
+
+impl GenerateIR for VaBuiltin {
+    fn generate_ir(&self, asm_data: &AsmData, stack_data: &mut SimpleStackFrame, global_asm_data: &GlobalAsmData) -> (IRCode, Option<StackItemKey>) {
+        let mut result = IRCode::make_empty();
+
+        match self {
+            VaBuiltin::Start(va_list) => {
+                let va_start_info = asm_data.get_va_start_info().expect("va_start in a function that is not variadic is reported by check_semantics");
+
+                let (va_list_asm, va_list_ptr) = generate_va_list_ptr(va_list, asm_data, stack_data, global_asm_data);
+                result.merge(&va_list_asm);
+                result.merge(&generate_va_start(va_list_ptr, va_start_info));
+
+                (result, None)
+            },
+            VaBuiltin::Arg { va_list, data_type } => {
+                let (va_list_asm, va_list_ptr) = generate_va_list_ptr(va_list, asm_data, stack_data, global_asm_data);
+                result.merge(&va_list_asm);
+
+                let (va_arg_asm, arg_value) = generate_va_arg(va_list_ptr, data_type, asm_data, stack_data);
+                result.merge(&va_arg_asm);
+
+                (result, Some(arg_value))
+            },
+            VaBuiltin::End(_) => (result, None),//nothing was allocated by va_start, so there is nothing to clean up
+            VaBuiltin::Copy { dest, src } => {
+                let (dest_asm, dest_ptr) = generate_va_list_ptr(dest, asm_data, stack_data, global_asm_data);
+                result.merge(&dest_asm);
+                let (src_asm, src_ptr) = generate_va_list_ptr(src, asm_data, stack_data, global_asm_data);
+                result.merge(&src_asm);
+
+                result.merge(&generate_va_copy(dest_ptr, src_ptr));
+
+                (result, None)
+            },
+        }
+    }
+}
+
+impl GetType for VaBuiltin {
+    fn get_type(&self, _: &AsmData) -> DataType {
+        match self {
+            VaBuiltin::Arg { data_type, .. } => data_type.clone(),
+            _ => DataType::RAW(BaseType::VOID)
+        }
+    }
+}
+
+impl ASTDisplay for VaBuiltin {
+    fn display_ast(&self, f: &mut crate::debugging::TreeDisplayInfo) {
+        match self {
+            VaBuiltin::Start(va_list) => {
+                f.write(&"va_start".yellow().to_string());
+                f.indent();
+                va_list.display_ast(f);
+            },
+            VaBuiltin::Arg { va_list, data_type } => {
+                f.write(&format!("{} as {}", "va_arg".yellow(), data_type));
+                f.indent();
+                va_list.display_ast(f);
+            },
+            VaBuiltin::End(va_list) => {
+                f.write(&"va_end".yellow().to_string());
+                f.indent();
+                va_list.display_ast(f);
+            },
+            VaBuiltin::Copy { dest, src } => {
+                f.write(&"va_copy".yellow().to_string());
+                f.indent();
+                dest.display_ast(f);
+                src.display_ast(f);
+            },
+        }
+        f.dedent();
+    }
+}
+
+fn consume_va_list(tokens_queue: &TokenQueue, va_list_slice: &TokenQueueSlice, scope_data: &mut ParseData) -> Box<Expression> {
+    Box::new(expression::try_consume_whole_expr(tokens_queue, va_list_slice, scope_data).expect("va_list arg must be an expression"))
+}
+
+/// Puts a pointer to the va_list on the stack
+///
+/// A local `va_list` is an array, so is pointed to directly, but a `va_list` param has already decayed to a pointer
+fn generate_va_list_ptr(va_list: &Expression, asm_data: &AsmData, stack_data: &mut SimpleStackFrame, global_asm_data: &GlobalAsmData) -> (IRCode, StackItemKey) {
+    match va_list.get_type(asm_data) {
+        DataType::ARRAY { .. } => va_list.get_address(asm_data, stack_data, global_asm_data),
+        _ => {
+            let (va_list_asm, va_list_ptr) = va_list.generate_ir(asm_data, stack_data, global_asm_data);
+            (va_list_asm, va_list_ptr.unwrap())
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{binary_expression::BinaryExpression, cast_expr::CastExpression, declaration::MinimalDataVariable, expression::{sizeof_expression::SizeofExpr, ternary::TernaryExpr, unary_postfix_expression::UnaryPostfixExpression, unary_prefix_expr::UnaryPrefixExpression}, function_call::FunctionCall, number_literal::typed_value::NumberLiteral, string_literal::StringLiteral, member_access::MemberAccess};
+use crate::{binary_expression::BinaryExpression, cast_expr::CastExpression, declaration::MinimalDataVariable, expression::{sizeof_expression::SizeofExpr, ternary::TernaryExpr, va_builtin::VaBuiltin, unary_postfix_expression::UnaryPostfixExpression, unary_prefix_expr::UnaryPrefixExpression}, function_call::FunctionCall, number_literal::typed_value::NumberLiteral, string_literal::StringLiteral, member_access::MemberAccess};
 
 
 //a test to see if a visitor pattern would be useful
@@ -16,4 +16,5 @@ pub trait ExprVisitor {
     fn visit_cast_expr(&mut self, expr: &CastExpression) -> Self::Output;
     fn visit_sizeof(&mut self, sizeof: &SizeofExpr) -> Self::Output;
     fn visit_ternary(&mut self, ternary: &TernaryExpr) -> Self::Output;
+    fn visit_va_builtin(&mut self, builtin: &VaBuiltin) -> Self::Output;
 }
\ No newline at end of file
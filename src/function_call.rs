@@ -1,13 +1,14 @@
-use crate::{args_handling::location_allocation::generate_param_and_return_locations, asm_gen_data::AsmData, assembly::{assembly::IRCode, operand::Storage, operation::{IROperation, CallerParamData, CallerReturnData}}, data_type::{base_type::{BaseType, FloatType, ScalarType}, recursive_data_type::{calculate_unary_type_arithmetic, DataType}}, debugging::ASTDisplay, expression::expression::{self, promote, Expression}, expression_visitors::expr_visitor::ExprVisitor, function_declaration::FunctionDeclaration, generate_ir_traits::{GenerateIR, GetType}, lexer::{punctuator::Punctuator, token::Token, token_savepoint::TokenQueueSlice, token_walk::{TokenQueue, TokenSearchType}}, parse_data::ParseData};
+use serde::Serialize;
+use crate::{args_handling::location_allocation::generate_param_and_return_locations, asm_gen_data::AsmData, assembly::{assembly::IRCode, operand::{IRMemOperand, Storage, PTR_SIZE}, operation::{CallTarget, CallerParamData, CallerReturnData, IROperation, Label}}, data_type::{base_type::{BaseType, FloatType, ScalarType}, recursive_data_type::{calculate_unary_type_arithmetic, DataType}}, debugging::ASTDisplay, expression::expression::{self, promote, Expression}, expression_visitors::expr_visitor::ExprVisitor, generate_ir_traits::{GenerateIR, GetAddress, GetType}, lexer::{punctuator::Punctuator, source_span::SourceSpan, token::Token, token_savepoint::TokenQueueSlice, token_walk::{TokenQueue, TokenSearchType}}, parse_data::ParseData};
 use memory_size::MemorySize;
 use stack_management::simple_stack_frame::SimpleStackFrame;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct FunctionCall {
-    func_name: String,//maybe an enum, for function pointers
+    callee: Box<Expression>,//function name, or any expression that gives a function pointer
     args: Vec<Expression>,
-
-    decl: FunctionDeclaration
+    never_returns: bool,
+    span: SourceSpan,
 }
 
 impl FunctionCall {
@@ -15,8 +16,30 @@ impl FunctionCall {
         visitor.visit_func_call(self)
     }
 
-    pub fn get_callee_decl(&self) -> &FunctionDeclaration {
-        &self.decl
+    pub fn get_span(&self) -> &SourceSpan {
+        &self.span
+    }
+    pub fn get_callee(&self) -> &Expression {
+        &self.callee
+    }
+    pub fn get_args(&self) -> &[Expression] {
+        &self.args
+    }
+
+    /// Whether this calls a function declared _Noreturn, like `exit`
+    pub fn never_returns(&self) -> bool {
+        self.never_returns
+    }
+
+    /// Finds the return type and param types of the function being called, whether it is called directly or through a pointer
+    pub fn get_callee_signature(&self, asm_data: &AsmData) -> (DataType, Vec<DataType>) {
+        match self.callee.get_type(asm_data).decay() {
+            DataType::POINTER(pointed_at) => match *pointed_at {
+                DataType::FUNCTION { return_type, params } => (*return_type, params),
+                x => panic!("tried to call a pointer to {}, which is not a function", x)
+            },
+            x => panic!("tried to call {}, which is not a function", x)
+        }
     }
     
     pub fn try_consume_whole_expr(tokens_queue: &TokenQueue, curr_queue_idx: &TokenQueueSlice, scope_data: &mut ParseData) -> Option<FunctionCall> {
@@ -49,19 +72,23 @@ impl FunctionCall {
             max_index: curly_open_idx
         };
 
-        if let Token::IDENTIFIER(func_name) = tokens_queue.peek(&func_slice, &scope_data)? {
-            //warning: label definition in a bad place will trip this up:
+        //warning: label definition in a bad place will trip this up:
 
-            //label: printf(""); will be interpreted at label()
-            let func_decl = scope_data.get_function_declaration(&func_name).expect(&format!("found function call but no corresponding function declaration: {}", func_name));
-            Some(FunctionCall {
-                func_name, 
-                args,
-                decl: func_decl.clone(),
-            })
-        } else {
-            None
-        }
+        //label: printf(""); will be interpreted at label()
+        let callee = expression::try_consume_whole_expr(tokens_queue, &func_slice, scope_data)?;
+
+        //only a direct call can be known not to return, as function pointers can't be _Noreturn
+        let never_returns = match &callee {
+            Expression::VARIABLE(func) => scope_data.get_function_declaration(&func.name).is_some_and(|decl| decl.noreturn),
+            _ => false,
+        };
+
+        Some(FunctionCall {
+            callee: Box::new(callee),
+            args,
+            never_returns,
+            span: tokens_queue.get_span(curr_queue_idx),
+        })
     }
 }
 
@@ -70,34 +97,46 @@ impl GenerateIR for FunctionCall {
         //system V ABI
         let mut result = IRCode::make_empty();
 
-        result.add_comment(format!("calling function: {}", self.func_name));
+        let (return_type, params) = self.get_callee_signature(asm_data);
+
+        let target = match (self.callee.as_ref(), self.callee.get_type(asm_data)) {
+            //calling a function by name can jump straight to its label
+            (Expression::VARIABLE(func), DataType::FUNCTION { .. }) => CallTarget::Direct(Label::Global(func.name.clone())),
+            //anything else is a function pointer, so generate it and call whatever it points to
+            _ => {
+                let (callee_asm, callee_location) = self.callee.generate_ir(asm_data, stack_data, global_asm_data);
+                result.merge(&callee_asm);
+                CallTarget::Indirect(Storage::Stack(callee_location.unwrap()))
+            }
+        };
+
+        result.add_comment(format!("calling function: {}", target));
 
-        let has_va_args = self.decl.params.last().is_some_and(|x| x.data_type == DataType::new(BaseType::VaArg));
+        let has_va_args = params.last().is_some_and(|x| *x == DataType::new(BaseType::VaArg));
 
         //attach type to each of the args
         let type_matched_args: Vec<_> = self.args.iter()
             .enumerate()
             .map(|(i, expr)|{
-                let last_param_or_after = i >= (self.decl.params.len()-1);
-                let param_type = if has_va_args && last_param_or_after{
+                let param_type = if has_va_args && i >= (params.len()-1) {//last param or after
                     //promotion of the arg is required, subject to some funny rules
                     match expr.get_type(asm_data).decay() {
                         DataType::RAW(BaseType::Scalar(ScalarType::Float(_))) => DataType::RAW(BaseType::Scalar(ScalarType::Float(FloatType::F64))),//for some reason, varadic args request promotion to f64
                         x => calculate_unary_type_arithmetic(&x)//promote the param via C99, §6.5.2.2/6
                     }
                 } else {
-                    self.decl.params[i].data_type.clone()//arg gets cast to param type
+                    params[i].clone()//arg gets cast to param type
                 };
 
                 (param_type, expr)
             })
             .collect();
 
-        let (return_location_type, params_locations) = generate_param_and_return_locations(type_matched_args.iter().map(|(t, _)| t), &self.decl.return_type, asm_data);
+        let (return_location_type, params_locations) = generate_param_and_return_locations(type_matched_args.iter().map(|(t, _)| t), &return_type, asm_data);
         
         let return_data = return_location_type
             .map(|return_location_info| {
-                let return_location_size = self.decl.return_type.memory_size(asm_data).align_up(&MemorySize::from_bytes(8));
+                let return_location_size = return_type.memory_size(asm_data).align_up(&MemorySize::from_bytes(8));
                 let return_location = stack_data.allocate(return_location_size);
                 CallerReturnData {return_location_info, return_location, return_location_size}
             });
@@ -108,7 +147,7 @@ impl GenerateIR for FunctionCall {
             type_matched_args.into_iter()
             .zip(params_locations.into_iter())
             .map(|((dtype, expr), location)| {
-                let expr_type = expr.get_type(asm_data);
+                let expr_type = expr.get_type(asm_data).decay();//arrays and functions are already generated as pointers
                 let casted_size = dtype.memory_size(asm_data);
 
                 //generate the arg value
@@ -127,25 +166,42 @@ impl GenerateIR for FunctionCall {
             .collect();
 
         result.add_instruction(IROperation::CALL {
-            label: self.func_name.clone(),
+            target,
             params: args_generated,
             return_data: return_data.clone(),
+            variadic: has_va_args,
         });
 
         (result, return_data.map(|x| x.return_location))
     }
 }
 
+impl GetAddress for FunctionCall {
+    fn get_address(&self, asm_data: &AsmData, stack_data: &mut SimpleStackFrame, global_asm_data: &crate::asm_gen_data::GlobalAsmData) -> (IRCode, stack_management::stack_item::StackItemKey) {
+        //a returned struct is stored in a temporary, so point to that
+        let (mut result, return_value) = self.generate_ir(asm_data, stack_data, global_asm_data);
+        let resultant_ptr = stack_data.allocate(PTR_SIZE);
+
+        result.add_instruction(IROperation::LEA {
+            from: IRMemOperand::Stack { base: return_value.expect("can't get address of a void return value") },
+            to: IRMemOperand::Stack { base: resultant_ptr },
+        });
+
+        (result, resultant_ptr)
+    }
+}
+
 impl GetType for FunctionCall {
     fn get_type(&self, asm_data: &AsmData) -> DataType {
-        self.get_callee_decl().return_type.clone()
+        self.get_callee_signature(asm_data).0
     }
 }
 
 impl ASTDisplay for FunctionCall {
     fn display_ast(&self, f: &mut crate::debugging::TreeDisplayInfo) {
-        f.write(&format!("call {}", self.func_name));
+        f.write("call");
         f.indent();
+        self.callee.display_ast(f);
         for i in &self.args {
             i.display_ast(f);
         }
@@ -1,13 +1,15 @@
 use std::fmt::Display;
 
-use crate::{ast_metadata::ASTMetadata, data_type::{base_type::BaseType, recursive_data_type::DataType, storage_type::StorageDuration, type_modifier::DeclModifier, type_token::TypeInfo}, declaration::Declaration, initialised_declaration::{consume_type_specifier, try_consume_declaration_modifiers}, lexer::{punctuator::Punctuator, token::Token, token_savepoint::TokenQueueSlice, token_walk::{TokenQueue, TokenSearchType}}, parse_data::ParseData};
+use serde::Serialize;
+use crate::{ast_metadata::ASTMetadata, data_type::{base_type::BaseType, recursive_data_type::DataType, storage_type::StorageDuration, type_modifier::DeclModifier, type_token::TypeInfo}, declaration::Declaration, initialised_declaration::{consume_pointer_qualifiers, consume_type_specifier, try_consume_declaration_modifiers}, lexer::{keywords::Keyword, punctuator::Punctuator, token::Token, token_savepoint::TokenQueueSlice, token_walk::{TokenQueue, TokenSearchType}}, parse_data::ParseData};
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct FunctionDeclaration {
     pub(crate) function_name: String,
     pub(crate) params: Vec<Declaration>,//should this be a data type?
     pub(crate) return_type: DataType,
     pub(crate) visibility: StorageDuration,//can be auto, static or extern, just like storage durations
+    pub(crate) noreturn: bool,//declared with _Noreturn, so calls to it never come back
 }
 
 impl FunctionDeclaration {
@@ -18,6 +20,34 @@ impl FunctionDeclaration {
         true//extern or not, this has external linkage
     }
 
+    /**
+     * detects whether the function takes a variable number of args, like printf
+     */
+    pub fn is_variadic(&self) -> bool {
+        self.params.last().is_some_and(|x| x.data_type == DataType::new(BaseType::VaArg))
+    }
+
+    /**
+     * gets the params that have a name and type, so excludes the trailing ... of a variadic function
+     */
+    pub fn named_params(&self) -> &[Declaration] {
+        if self.is_variadic() {
+            &self.params[..self.params.len()-1]
+        } else {
+            &self.params
+        }
+    }
+
+    /**
+     * gets the type of the function, for when it is used as a function designator
+     */
+    pub fn as_data_type(&self) -> DataType {
+        DataType::FUNCTION {
+            return_type: Box::new(self.return_type.clone()),
+            params: self.params.iter().map(|x| x.data_type.unqualified()).collect(),//f(const int x) can be called like f(int x), as x is a copy
+        }
+    }
+
     /**
      * consumes a function declaration only, and will return None if the function has a definition attached
      */
@@ -53,6 +83,8 @@ pub fn consume_decl_only(tokens_queue: &TokenQueue, previous_queue_idx: &TokenQu
     // the return value's storage duration (static, extern etc.) is the visibility of the function?
     let ASTMetadata { remaining_slice: mut curr_queue_idx, resultant_tree: (return_type, func_visibility) } = consume_fully_qualified_type(tokens_queue, previous_queue_idx, scope_data)?;
 
+    let noreturn = has_noreturn_specifier(tokens_queue, &TokenQueueSlice { index: previous_queue_idx.index, max_index: curr_queue_idx.index });
+
     //try to match an identifier, to find out the function name
 
     let function_name = 
@@ -76,17 +108,8 @@ pub fn consume_decl_only(tokens_queue: &TokenQueue, previous_queue_idx: &TokenQu
 
     tokens_queue.consume(&mut curr_queue_idx, &scope_data).unwrap();//consume the open bracket
 
-    let args_segments = tokens_queue.split_outside_parentheses(&args_location, |x| *x == Token::PUNCTUATOR(Punctuator::COMMA), &TokenSearchType::skip_all_brackets());
-
     //grab all the args
-    let mut params = Vec::new();
-    let param_list_is_empty = args_location.get_slice_size() == 0;
-    let param_list_is_void = args_location.get_slice_size() == 1 && tokens_queue.peek(&args_location, scope_data).unwrap() == Token::TYPESPECIFIER(TypeInfo::VOID);
-    if !(param_list_is_empty || param_list_is_void) {
-        for arg_segment in args_segments {
-            params.push(consume_fn_param(tokens_queue, &arg_segment, scope_data)?);
-        }
-    }
+    let params = consume_fn_params(tokens_queue, &args_location, scope_data)?;
 
     curr_queue_idx.index = args_location.max_index;//jump to end of args
 
@@ -102,12 +125,32 @@ pub fn consume_decl_only(tokens_queue: &TokenQueue, previous_queue_idx: &TokenQu
         FunctionDeclaration {
             function_name,
             params,
-            return_type,
-            visibility: func_visibility
+            return_type: return_type.unqualified(),//the returned value is a copy, so can't be const
+            visibility: func_visibility,
+            noreturn,
         },
         remaining_slice: curr_queue_idx});
 }
 
+/**
+ * parses the int x, char* y part of int f(int x, char* y)
+ * args_location is the slice inside the brackets
+ */
+pub fn consume_fn_params(tokens_queue: &TokenQueue, args_location: &TokenQueueSlice, scope_data: &mut ParseData) -> Option<Vec<Declaration>> {
+    let args_segments = tokens_queue.split_outside_parentheses(&args_location, |x| *x == Token::PUNCTUATOR(Punctuator::COMMA), &TokenSearchType::skip_all_brackets());
+
+    let mut params = Vec::new();
+    let param_list_is_empty = args_location.get_slice_size() == 0;
+    let param_list_is_void = args_location.get_slice_size() == 1 && tokens_queue.peek(&args_location, scope_data).unwrap() == Token::TYPESPECIFIER(TypeInfo::VOID);
+    if !(param_list_is_empty || param_list_is_void) {
+        for arg_segment in args_segments {
+            params.push(consume_fn_param(tokens_queue, &arg_segment, scope_data)?);
+        }
+    }
+
+    Some(params)
+}
+
 fn consume_fn_param(tokens_queue: &TokenQueue, arg_segment: &TokenQueueSlice, scope_data: &mut ParseData) -> Option<Declaration> {
     let mut curr_queue_idx = arg_segment.clone();
 
@@ -129,29 +172,34 @@ fn consume_fn_param(tokens_queue: &TokenQueue, arg_segment: &TokenQueueSlice, sc
     } = try_consume_declaration_modifiers(tokens_queue, &curr_queue_idx, &data_type_base, scope_data)?;
 
     Some(Declaration {
-        data_type: full_data_type.decay(),//.decay since arrays ALWAYS decay to pointers in function params
+        data_type: full_data_type.decay(),//.decay since arrays and functions ALWAYS decay to pointers in function params
         name: var_name
     })
 }
 
+/// detects whether the specifiers in `specifiers_slice` (the "_Noreturn static void" part of a declaration) include _Noreturn
+pub fn has_noreturn_specifier(tokens_queue: &TokenQueue, specifiers_slice: &TokenQueueSlice) -> bool {
+    tokens_queue.tokens[specifiers_slice.index..specifiers_slice.max_index]
+        .contains(&Token::KEYWORD(Keyword::NORETURN))
+}
+
 //TODO move to more appropriate file
 /// consumes a full type, like "long int *"
 /// 
 pub fn consume_fully_qualified_type(tokens_queue: &TokenQueue, previous_queue_idx: &TokenQueueSlice, scope_data: &mut ParseData) -> Option<ASTMetadata<(DataType, StorageDuration)>> {
-    let mut return_modifiers = Vec::new();
-
-    let ASTMetadata { remaining_slice, resultant_tree: (return_data_type, storage_duration) } = consume_type_specifier(tokens_queue, previous_queue_idx, scope_data)?;
+    let ASTMetadata { remaining_slice, resultant_tree: (mut return_data_type, storage_duration) } = consume_type_specifier(tokens_queue, previous_queue_idx, scope_data)?;
 
     let mut curr_queue_idx = remaining_slice.clone();
 
     while Some(Token::PUNCTUATOR(Punctuator::ASTERISK)) == tokens_queue.peek(&curr_queue_idx, &scope_data) {
-        return_modifiers.push(DeclModifier::POINTER);
         tokens_queue.consume(&mut curr_queue_idx, &scope_data);
+        let qualifiers = consume_pointer_qualifiers(tokens_queue, &mut curr_queue_idx, scope_data);
+        return_data_type = return_data_type.add_outer_modifier(DeclModifier::POINTER).add_qualifiers(qualifiers);
     }
 
     Some(ASTMetadata {
         remaining_slice: curr_queue_idx,
-        resultant_tree: (DataType::new_from_slice(return_data_type, &return_modifiers), storage_duration),
+        resultant_tree: (return_data_type, storage_duration),
     })
 }
 
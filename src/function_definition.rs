@@ -1,15 +1,18 @@
 use memory_size::MemorySize;
 use stack_management::simple_stack_frame::SimpleStackFrame;
-use crate::{args_handling::location_allocation::{generate_param_and_return_locations, AllocatedLocation, EightByteLocation, ReturnLocation}, asm_gen_data::{AsmData, GlobalAsmData}, assembly::{assembly::IRCode, operand::{ register::GPRegister, IRMemOperand, IROperand, Storage, STACK_ALIGN}, operation::{CalleeReturnData, IROperation, Label, ReadParamFromMem, ReadParamFromReg}}, ast_metadata::ASTMetadata, compound_statement::ScopeStatements, data_type::{base_type::{BaseType, IntegerType}, recursive_data_type::DataType}, debugging::ASTDisplay, function_declaration::{consume_decl_only, FunctionDeclaration}, generate_ir_traits::GenerateIR, lexer::{punctuator::Punctuator, token::Token, token_savepoint::TokenQueueSlice, token_walk::TokenQueue}, number_literal::typed_value::NumberLiteral, parse_data::ParseData};
+use serde::Serialize;
+use crate::{args_handling::{location_allocation::{generate_param_and_return_locations, gp_arg, AllocatedLocation, EightByteLocation, ReturnLocation}, va_args::{VaStartInfo, REG_SAVE_AREA_SIZE}}, asm_gen_data::{AsmData, GlobalAsmData}, assembly::{assembly::IRCode, operand::{ register::GPRegister, IRMemOperand, IROperand, Storage, PTR_SIZE, STACK_ALIGN}, operation::{CalleeReturnData, IROperation, Label, ReadParamFromMem, ReadParamFromReg}}, ast_metadata::ASTMetadata, compound_statement::ScopeStatements, data_type::{base_type::{BaseType, IntegerType}, recursive_data_type::DataType}, debugging::ASTDisplay, diagnostics::{diagnostic::Diagnostic, semantic_checker::{CheckSemantics, SemanticChecker}, warnings::{WarningKind, WarningOptions}}, function_declaration::{consume_decl_only, FunctionDeclaration}, generate_ir_traits::GenerateIR, lexer::{punctuator::Punctuator, source_span::{SourcePosition, SourceSpan}, token::Token, token_savepoint::TokenQueueSlice, token_walk::TokenQueue}, number_literal::typed_value::NumberLiteral, parse_data::ParseData};
 use unwrap_let::unwrap_let;
 
 /**
  * This is a definition of a function
  */
+#[derive(Serialize)]
 pub struct FunctionDefinition {
     code: ScopeStatements,//statement could be a scope if it wants. should this just be a Scope????
     decl: FunctionDeclaration,
-    local_scope_data: ParseData//metadata to help with assembly generation
+    local_scope_data: ParseData,//metadata to help with assembly generation
+    span: SourceSpan,
 }
 
 impl FunctionDefinition {
@@ -22,6 +25,9 @@ impl FunctionDefinition {
     pub fn as_decl(&self) -> FunctionDeclaration {
         self.decl.clone()
     }
+    pub fn get_span(&self) -> &SourceSpan {
+        &self.span
+    }
     /**
      * consumes tokens to try and make a function definition
      * returns some(function found, remaining tokens) if found, else None
@@ -36,52 +42,107 @@ impl FunctionDefinition {
         if tokens_queue.peek(&after_decl_slice, &scope_data)? == Token::PUNCTUATOR(Punctuator::SEMICOLON) {
             return None;//function declaration + semicolon means no definition for certain
         }
-        for i in func_decl.params.iter().rev() {
+        for i in func_decl.named_params().iter().rev() {
             scope_data.add_variable(&i.name, i.data_type.clone());
         }
 
         scope_data.add_declaration(func_decl.clone());//so that I can call recursively
 
         let ASTMetadata{resultant_tree, remaining_slice} = ScopeStatements::try_consume(tokens_queue, &after_decl_slice, &mut scope_data)?;
+
+        //any labels that no switch statement took are outside of every switch
+        for case in resultant_tree.get_switch_cases() {
+            let label = if case.get_value().is_some() {"case"} else {"default"};
+            scope_data.report_error(Diagnostic::error(format!("`{}` label not within a switch statement", label)).with_primary(case.get_span().clone(), ""));
+        }
+        //from the return type to the end of the body
+        let span = tokens_queue.get_span(&TokenQueueSlice { index: previous_queue_idx.index, max_index: remaining_slice.index });
         
         return Some(ASTMetadata{
             resultant_tree: FunctionDefinition {
                 code: resultant_tree,
                 decl: func_decl,
-                local_scope_data: scope_data
+                local_scope_data: scope_data,
+                span
             },
             remaining_slice});
     }
 
+    /// Type checks this function and looks for suspicious code, returning an error or warning for each problem found
+    pub fn check_semantics(&self, global_asm_data: &GlobalAsmData, options: &WarningOptions) -> Vec<Diagnostic> {
+        let mut stack_data = SimpleStackFrame::new(STACK_ALIGN);
+        let asm_data = AsmData::for_new_function(global_asm_data, &self.local_scope_data, self.get_return_type(), None, None, None, &mut stack_data);
+        let mut checker = SemanticChecker::new(options, asm_data, stack_data, self.decl.is_variadic());
+
+        for param in self.decl.named_params().iter().filter(|param| !param.name.is_empty()) {
+            checker.declare_parameter(&param.name, &self.span);
+        }
+
+        self.code.check_semantics(&mut checker);
+
+        //main automatically returns 0
+        let must_return_value = self.get_return_type() != DataType::RAW(BaseType::VOID) && self.get_name() != "main";
+        if must_return_value && self.code.falls_through() {
+            //point at the closing }
+            let body_span = self.code.get_span();
+            let closing_bracket = SourceSpan { start: SourcePosition { column: body_span.end.column.saturating_sub(1).max(1), ..body_span.end }, ..body_span.clone() };
+            checker.report(WarningKind::ReturnType, format!("control reaches the end of non-void function `{}`", self.get_name()), |warning| warning
+                .with_primary(closing_bracket, "")
+                .with_secondary(self.span.clone(), "this function should return a value"));
+        }
+
+        checker.finish()
+    }
+
     //cannot be GenerateIR as it creates some things like `stack_data`
     pub fn generate_assembly(&self, global_asm_data: &mut GlobalAsmData) -> (IRCode, SimpleStackFrame) {
         let mut result = IRCode::make_empty();
         //as per SYSV ABI, stack is aligned (once stack frame generated) to 16 bytes
         let mut stack_data = SimpleStackFrame::new(STACK_ALIGN);//stack starts as empty in a function
-        let (return_location, args_locations) = generate_param_and_return_locations(self.decl.params.iter().map(|decl| &decl.data_type), &self.get_return_type(), global_asm_data);
+        let named_params = self.decl.named_params();
+        let (return_location, args_locations) = generate_param_and_return_locations(named_params.iter().map(|decl| &decl.data_type), &self.get_return_type(), global_asm_data);
+
+        //big structs are returned by writing to a hidden pointer, which is passed like a pointer param before all the others
+        let hidden_pointer_param = AllocatedLocation::Regs(vec![EightByteLocation::GP(gp_arg(0))]);
+        let hidden_pointer_location = (return_location == Some(ReturnLocation::HiddenPointer)).then(|| stack_data.allocate(PTR_SIZE));
+
+        //variadic functions need somewhere to save the arg registers, so that va_arg can read unnamed args from them
+        let reg_save_area = self.decl.is_variadic().then(|| stack_data.allocate(REG_SAVE_AREA_SIZE));
+        let va_start_info = reg_save_area.map(|reg_save_area| {
+            let param_sizes = named_params.iter().map(|param| param.data_type.memory_size(global_asm_data));
+            let hidden_pointer = hidden_pointer_location.map(|_| (&hidden_pointer_param, PTR_SIZE));
+            VaStartInfo::new(hidden_pointer.into_iter().chain(args_locations.iter().zip(param_sizes)), reg_save_area)
+        });
 
         //clone myself, but add all my local variables, and add my return type
-        let asm_data = &AsmData::for_new_function(&global_asm_data, &self.local_scope_data, self.get_return_type(), return_location, &mut stack_data);
+        let asm_data = &AsmData::for_new_function(&global_asm_data, &self.local_scope_data, self.get_return_type(), return_location, va_start_info, hidden_pointer_location, &mut stack_data);
 
         //set label as same as function name
         result.add_instruction(IROperation::Label(Label::Global(self.decl.function_name.clone())));
         //create stack frame
         result.add_commented_instruction(IROperation::CreateStackFrame, "create stack frame");
 
+        if let Some(reg_save_area) = reg_save_area {
+            result.add_commented_instruction(IROperation::SaveVaArgRegs { reg_save_area }, "saving arg registers for va_arg");
+        }
+
         let (code_for_body, _) = self.code.generate_ir(asm_data, &mut stack_data, global_asm_data);//calculate stack needed for function, while generating asm
 
         result.add_comment("moving args to memory");
 
-        //varadic args not implemented yet
-        assert!(self.decl.params.last().is_none_or(|x| x.data_type != DataType::RAW(BaseType::VaArg)));
-
         //calculate where each arg is, and split into lists
         let mut reg_args = Vec::new();
         let mut mem_args = Vec::new();
 
+        if let Some(param_destination) = hidden_pointer_location {
+            //save the hidden pointer, so that return statements know where to write to
+            unwrap_let!(AllocatedLocation::Regs(eightbyte_locations) = hidden_pointer_param);
+            reg_args.push(ReadParamFromReg{ eightbyte_locations, param_size: PTR_SIZE, param_destination });
+        }
+
         // let mut memory_offset_tracker = MemorySize::new();
-        for param_idx in 0..self.decl.params.len() {
-            let param = &self.decl.params[param_idx];//get metadata about param
+        for param_idx in 0..named_params.len() {
+            let param = &named_params[param_idx];//get metadata about param
             let param_size = param.data_type.memory_size(asm_data);//get size of param
 
             let param_start_location = args_locations[param_idx].clone();
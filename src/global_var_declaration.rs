@@ -1,55 +1,60 @@
-use crate::{asm_gen_data::GetStructUnion, ast_metadata::ASTMetadata, constexpr_parsing::ConstexprValue, data_type::{base_type::{BaseType, IntegerType, ScalarType}, recursive_data_type::DataType, storage_type::StorageDuration}, debugging::IRDisplay, declaration::Declaration, expression::expression::try_consume_whole_expr, initialised_declaration::{ consume_type_specifier, try_consume_declaration_modifiers}, lexer::{punctuator::Punctuator, token::Token, token_savepoint::TokenQueueSlice, token_walk::{TokenQueue, TokenSearchType}}, number_literal::typed_value::NumberLiteral, parse_data::ParseData};
+use serde::Serialize;
+use crate::{asm_gen_data::GetStructUnion, assembly::global_data::{DataContents, GlobalData}, ast_metadata::ASTMetadata, constexpr_parsing::ConstexprValue, data_type::{base_type::{BaseType, IntegerType, ScalarType}, recursive_data_type::DataType, storage_type::StorageDuration}, debugging::IRDisplay, declaration::Declaration, expression::expression::try_consume_whole_expr, initialised_declaration::{ consume_type_specifier, try_consume_declaration_modifiers}, lexer::{punctuator::Punctuator, source_span::SourceSpan, token::Token, token_savepoint::TokenQueueSlice, token_walk::{TokenQueue, TokenSearchType}}, number_literal::typed_value::NumberLiteral, parse_data::ParseData};
 
 
+#[derive(Serialize)]
 pub struct GlobalVariable {
     decl: Declaration,
     default_value: ConstexprValue,//perhaps some more abstract data type when structs are implemented
-    storage_class: StorageDuration
+    storage_class: StorageDuration,
+    span: SourceSpan,
 }
 
 impl GlobalVariable {
-    pub fn generate_assembly(&self, struct_info: &dyn GetStructUnion) -> String {
-        match (&self.decl.data_type, &self.default_value) {
+    /// Describes what the variable's memory is set to when the program starts
+    pub fn generate_data(&self, struct_info: &dyn GetStructUnion) -> GlobalData {
+        let contents = match (&self.decl.data_type.unqualified(), &self.default_value) {
             //base type is set to a value
             (DataType::RAW(BaseType::Scalar(base_type)), ConstexprValue::NUMBER(number_literal)) => {
-                //cast the number to the variable's type, then generate a constant
-                number_literal.cast(&base_type).generate_data_definition_instruction(&self.decl.name)
+                //cast the number to the variable's type, then store its bytes
+                DataContents::Bytes(number_literal.cast(&base_type).data_bytes())
             },
 
             // array is being set to a string
             (DataType::ARRAY { size, element }, ConstexprValue::STRING(string_literal)) => {
-                assert_eq!(**element, DataType::RAW(BaseType::Scalar(ScalarType::Integer(IntegerType::I8))));
+                assert_eq!(element.unqualified(), DataType::RAW(BaseType::Scalar(ScalarType::Integer(IntegerType::I8))));
                 assert_eq!(*size as usize, string_literal.get_num_chars());
-                format!("{} db {}\n", self.decl.name, string_literal.get_comma_separated_bytes())
+                DataContents::Bytes(string_literal.get_bytes())
             }
 
             // unknown size array is being set to a string
             (DataType::UNKNOWNSIZEARRAY { element }, ConstexprValue::STRING(string_literal)) => {
-                assert_eq!(**element, DataType::RAW(BaseType::Scalar(ScalarType::Integer(IntegerType::I8))));
-                format!("{} db {}\n", self.decl.name, string_literal.get_comma_separated_bytes())
+                assert_eq!(element.unqualified(), DataType::RAW(BaseType::Scalar(ScalarType::Integer(IntegerType::I8))));
+                DataContents::Bytes(string_literal.get_bytes())
             }
 
             // pointer is being set to string
             // so make the pointer point at the string
             (DataType::POINTER(element), ConstexprValue::STRING(string_literal)) => {
-                assert_eq!(**element, DataType::RAW(BaseType::Scalar(ScalarType::Integer(IntegerType::I8))));
-                format!("{} dq {}\n", self.decl.name, string_literal.get_label())
+                assert_eq!(element.unqualified(), DataType::RAW(BaseType::Scalar(ScalarType::Integer(IntegerType::I8))));
+                DataContents::Pointer { label: string_literal.get_label().to_string(), offset: 0 }
             }
 
             //  pointer being set to a pointer...
             (DataType::POINTER(_), ConstexprValue::POINTER { label, offset }) => {
-                format!("{} dq {} + {}\n", self.decl.name, label,
-                match offset {
-                    NumberLiteral::INTEGER{data, ..} => data,
+                DataContents::Pointer { label: label.clone(), offset: match offset {
+                    NumberLiteral::INTEGER{data, ..} => *data,
                     NumberLiteral::FLOAT {..} => panic!("cannot offset a constant pointer by a float")
-                })
+                }}
             }
 
             // zeroing out the variable
-            (data_type, ConstexprValue::ZEROES) => format!("{} TIMES {} db 0", self.decl.name, data_type.memory_size(struct_info).size_bytes()),
+            (data_type, ConstexprValue::ZEROES) => DataContents::Zeroes(data_type.memory_size(struct_info)),
 
             (t, val) => panic!("cannot initialise {:?} to {:?}", t, val)
-        }
+        };
+
+        GlobalData { label: self.decl.name.clone(), contents }
     }
 
     pub fn try_consume(tokens_queue: &mut TokenQueue, previous_queue_idx: &TokenQueueSlice, scope_data: &mut ParseData) -> Option<ASTMetadata<Vec<GlobalVariable>>> {
@@ -83,12 +88,30 @@ impl GlobalVariable {
         })
     }
 
+    /// Whether this can never be modified, so can be stored in `.rodata`
+    /// 
+    /// This is true for `const` variables, and arrays of `const` elements
+    pub fn is_read_only(&self) -> bool {
+        let mut data_type = &self.decl.data_type;
+        while let DataType::ARRAY { element, .. } | DataType::UNKNOWNSIZEARRAY { element } = data_type {
+            data_type = element.as_ref();
+        }
+
+        data_type.qualifiers().is_const
+    }
+
+    pub fn data_type(&self) -> &DataType {
+        &self.decl.data_type
+    }
     pub fn storage_class(&self) -> &StorageDuration {
         &self.storage_class
     }
     pub fn var_name(&self) -> &str {
         &self.decl.name
     }
+    pub fn get_span(&self) -> &SourceSpan {
+        &self.span
+    }
 }
 
 impl IRDisplay for GlobalVariable {
@@ -122,7 +145,8 @@ fn try_consume_constexpr_declarator(tokens_queue: &mut TokenQueue, slice: &Token
         resultant_tree: GlobalVariable {
             decl,
             default_value,
-            storage_class
+            storage_class,
+            span: tokens_queue.get_span(slice),
         }, 
         remaining_slice: TokenQueueSlice::empty(),
     })
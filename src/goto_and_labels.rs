@@ -1,12 +1,15 @@
 use colored::Colorize;
 use unwrap_let::unwrap_let;
+use uuid::Uuid;
 
-use crate::{assembly::{assembly::IRCode, comparison::AsmComparison, operation::{IROperation, Label}}, ast_metadata::ASTMetadata, debugging::ASTDisplay, generate_ir_traits::GenerateIR, lexer::{keywords::Keyword, punctuator::Punctuator, token::Token, token_savepoint::TokenQueueSlice, token_walk::TokenQueue}, parse_data::ParseData};
+use serde::Serialize;
+use crate::{assembly::{assembly::IRCode, comparison::AsmComparison, operation::{IROperation, Label}}, ast_metadata::ASTMetadata, constexpr_parsing::ConstexprValue, debugging::ASTDisplay, diagnostics::diagnostic::Diagnostic, expression::expression, generate_ir_traits::GenerateIR, lexer::{keywords::Keyword, punctuator::Punctuator, source_span::SourceSpan, token::Token, token_savepoint::TokenQueueSlice, token_walk::TokenQueue}, number_literal::typed_value::NumberLiteral, parse_data::ParseData};
 
 /// A label in the style `label:`
 /// 
-/// This is not currently suited for switch cases `case 1+2:` `default:`
-pub struct CustomLabel(String);
+/// For switch cases `case 1+2:` `default:` see [SwitchCaseLabel]
+#[derive(Serialize)]
+pub struct CustomLabel(String, SourceSpan);
 
 impl CustomLabel {
     pub fn try_consume(tokens_queue: &mut TokenQueue, previous_queue_idx: &TokenQueueSlice, scope_data: &mut ParseData) -> Option<ASTMetadata<Self>> {
@@ -14,7 +17,8 @@ impl CustomLabel {
 
         if let Some(Token::IDENTIFIER(label)) = tokens_queue.consume(&mut curr_queue_idx, scope_data) {
             if tokens_queue.consume(&mut curr_queue_idx, scope_data) == Some(Token::PUNCTUATOR(Punctuator::COLON)) {
-                Some(ASTMetadata { remaining_slice: curr_queue_idx, resultant_tree: CustomLabel(label) })
+                let span = tokens_queue.get_span(&TokenQueueSlice { index: previous_queue_idx.index, max_index: curr_queue_idx.index });
+                Some(ASTMetadata { remaining_slice: curr_queue_idx, resultant_tree: CustomLabel(label, span) })
             } else {
                 None//label, but not label:
             }
@@ -22,6 +26,10 @@ impl CustomLabel {
             None //no label name
         }
     }
+
+    pub fn get_span(&self) -> &SourceSpan {
+        &self.1
+    }
 }
 impl GenerateIR for CustomLabel {
     fn generate_ir(&self, asm_data: &crate::asm_gen_data::AsmData, stack_data: &mut stack_management::simple_stack_frame::SimpleStackFrame, global_asm_data: &crate::asm_gen_data::GlobalAsmData) -> (IRCode, Option<stack_management::stack_item::StackItemKey>) {
@@ -33,7 +41,109 @@ impl GenerateIR for CustomLabel {
     }
 }
 
-pub struct Goto(String);
+/// A label inside a switch statement, in the style `case 1+2:` or `default:`
+#[derive(Clone, Serialize)]
+pub struct SwitchCaseLabel {
+    /// the folded case value, or None for `default:`
+    value: Option<NumberLiteral>,
+    /// unique label, so that the enclosing switch statement can jump here
+    #[serde(skip)]
+    label: Label,
+    span: SourceSpan,
+}
+
+impl SwitchCaseLabel {
+    pub fn get_value(&self) -> Option<&NumberLiteral> {
+        self.value.as_ref()
+    }
+    pub fn get_label(&self) -> &Label {
+        &self.label
+    }
+    pub fn get_span(&self) -> &SourceSpan {
+        &self.span
+    }
+
+    pub fn try_consume(tokens_queue: &mut TokenQueue, previous_queue_idx: &TokenQueueSlice, scope_data: &mut ParseData) -> Option<ASTMetadata<Self>> {
+        let mut curr_queue_idx = previous_queue_idx.clone();
+
+        let value = match tokens_queue.consume(&mut curr_queue_idx, scope_data)? {
+            Token::KEYWORD(Keyword::DEFAULT) => None,
+            Token::KEYWORD(Keyword::CASE) => {
+                //find the colon at the end of the case, skipping any ternary expressions like `case x ? 1 : 2:`
+                let mut colon_search = curr_queue_idx.clone();
+                let mut unmatched_ternaries = 0;
+                loop {
+                    let Some(tok) = tokens_queue.peek_raw(&colon_search) else {
+                        scope_data.report_error(tokens_queue.expected_error(&colon_search, "`:` after case label"));
+                        return None;
+                    };
+                    match tok {
+                        Token::PUNCTUATOR(Punctuator::QuestionMark) => unmatched_ternaries += 1,
+                        Token::PUNCTUATOR(Punctuator::COLON) if unmatched_ternaries == 0 => break,
+                        Token::PUNCTUATOR(Punctuator::COLON) => unmatched_ternaries -= 1,
+                        _ => {}
+                    }
+                    colon_search.next();
+                }
+
+                let value_slice = TokenQueueSlice {
+                    index: curr_queue_idx.index,
+                    max_index: colon_search.index
+                };
+                curr_queue_idx.index = colon_search.index;//skip to the colon
+
+                let value_span = tokens_queue.get_span(&value_slice);
+                let Some(value_expr) = expression::try_consume_whole_expr(tokens_queue, &value_slice, scope_data) else {
+                    scope_data.report_error(Diagnostic::error("invalid case label").with_primary(value_span, ""));
+                    return None;
+                };
+                let folded = match ConstexprValue::try_from(&value_expr) {
+                    Ok(x) => x,
+                    Err(reason) => {
+                        scope_data.report_error(Diagnostic::error("case label is not a compile-time constant").with_primary(value_span, "").with_note(reason));
+                        return None;
+                    }
+                };
+
+                match folded {
+                    ConstexprValue::NUMBER(x @ NumberLiteral::INTEGER { .. }) => Some(x),
+                    _ => {
+                        scope_data.report_error(Diagnostic::error("case label must be an integer constant").with_primary(value_span, ""));
+                        return None;
+                    }
+                }
+            }
+            _ => return None,
+        };
+
+        assert!(tokens_queue.consume(&mut curr_queue_idx, scope_data) == Some(Token::PUNCTUATOR(Punctuator::COLON)));
+
+        let label_name = match &value {
+            Some(_) => format!("{}_case", Uuid::new_v4().simple()),
+            None => format!("{}_default", Uuid::new_v4().simple()),
+        };
+
+        let span = tokens_queue.get_span(&TokenQueueSlice { index: previous_queue_idx.index, max_index: curr_queue_idx.index });
+
+        Some(ASTMetadata { remaining_slice: curr_queue_idx, resultant_tree: SwitchCaseLabel { value, label: Label::Local(label_name), span } })
+    }
+}
+impl GenerateIR for SwitchCaseLabel {
+    fn generate_ir(&self, asm_data: &crate::asm_gen_data::AsmData, stack_data: &mut stack_management::simple_stack_frame::SimpleStackFrame, global_asm_data: &crate::asm_gen_data::GlobalAsmData) -> (IRCode, Option<stack_management::stack_item::StackItemKey>) {
+        let mut result = IRCode::make_empty();
+
+        let comment = match &self.value {
+            Some(value) => format!("case {}", value),
+            None => "default".to_string(),
+        };
+        result.add_commented_instruction(IROperation::Label(self.label.clone()), comment);
+
+        (result, None)
+    }
+}
+
+#[derive(Serialize)]
+pub struct Goto(String, SourceSpan);
 
 impl Goto {
     pub fn try_consume(tokens_queue: &mut TokenQueue, previous_queue_idx: &TokenQueueSlice, scope_data: &mut ParseData) -> Option<ASTMetadata<Self>> {
@@ -43,7 +153,13 @@ impl Goto {
 
         unwrap_let!(Some(Token::IDENTIFIER(label_name)) = tokens_queue.consume(&mut curr_queue_idx, &scope_data));
 
-        Some(ASTMetadata { remaining_slice: curr_queue_idx, resultant_tree: Goto(label_name) })
+        let span = tokens_queue.get_span(&TokenQueueSlice { index: previous_queue_idx.index, max_index: curr_queue_idx.index });
+
+        Some(ASTMetadata { remaining_slice: curr_queue_idx, resultant_tree: Goto(label_name, span) })
+    }
+
+    pub fn get_span(&self) -> &SourceSpan {
+        &self.1
     }
 }
 impl GenerateIR for Goto {
@@ -65,6 +181,15 @@ impl ASTDisplay for CustomLabel {
     }
 }
 
+impl ASTDisplay for SwitchCaseLabel {
+    fn display_ast(&self, f: &mut crate::debugging::TreeDisplayInfo) {
+        match &self.value {
+            Some(value) => f.write(&format!("case {}", value.to_string().yellow())),
+            None => f.write(&"default".yellow().to_string()),
+        }
+    }
+}
+
 impl ASTDisplay for Goto {
     fn display_ast(&self, f: &mut crate::debugging::TreeDisplayInfo) {
         f.write(&format!("goto {}", self.0.yellow()))
@@ -1,12 +1,18 @@
-use crate::{asm_gen_data::{AsmData, GlobalAsmData}, assembly::assembly::IRCode, ast_metadata::ASTMetadata, binary_expression::BinaryExpression, constexpr_parsing::ConstexprValue, data_type::{base_type::{self, BaseType, ScalarType}, recursive_data_type::DataType, storage_type::StorageDuration, type_modifier::DeclModifier, type_qualifier::TypeQualifier, type_token::TypeInfo}, debugging::ASTDisplay, declaration::{Declaration, MinimalDataVariable}, enum_definition::try_consume_enum_as_type, expression::{binary_expression_operator::BinaryExpressionOperator, expression::{self, Expression}}, generate_ir_traits::GenerateIR, lexer::{keywords::Keyword, punctuator::Punctuator, token::Token, token_savepoint::TokenQueueSlice, token_walk::{TokenQueue, TokenSearchType}}, number_literal::typed_value::NumberLiteral, parse_data::ParseData, struct_definition::StructDefinition, union_definition::UnionDefinition};
+use serde::Serialize;
+use crate::{asm_gen_data::{AsmData, GlobalAsmData}, assembly::assembly::IRCode, ast_metadata::ASTMetadata, binary_expression::BinaryExpression, constexpr_parsing::ConstexprValue, data_type::{base_type::{self, BaseType, ScalarType}, recursive_data_type::DataType, storage_type::StorageDuration, type_modifier::DeclModifier, type_qualifier::{TypeQualifier, TypeQualifiers}, type_token::TypeInfo}, debugging::ASTDisplay, declaration::{Declaration, MinimalDataVariable}, diagnostics::{diagnostic::Diagnostic, semantic_checker::{CheckSemantics, SemanticChecker}}, enum_definition::try_consume_enum_as_type, expression::{binary_expression_operator::BinaryExpressionOperator, expression::{self, Expression}}, function_declaration::{consume_fn_params, has_noreturn_specifier, FunctionDeclaration}, generate_ir_traits::{GenerateIR, GetType}, lexer::{keywords::Keyword, punctuator::Punctuator, source_span::SourceSpan, token::Token, token_savepoint::TokenQueueSlice, token_walk::{TokenQueue, TokenSearchType}}, number_literal::typed_value::NumberLiteral, parse_data::ParseData, struct_definition::StructDefinition, union_definition::UnionDefinition};
 use stack_management::simple_stack_frame::SimpleStackFrame;
 use unwrap_let::unwrap_let;
 
 /**
  * stores a variable and assembly to construct it
  */
+#[derive(Serialize)]
 pub struct InitialisedDeclaration{
+    /// the variable being declared, or None if this declares a function
+    decl: Option<Declaration>,
     init_code: Option<Expression>,
+    /// the declarator, like the `x = 2` in `int x = 2, y;`
+    span: SourceSpan,
 }
 
 impl InitialisedDeclaration {
@@ -23,6 +29,8 @@ impl InitialisedDeclaration {
 
         let mut curr_queue_idx = remaining_slice.clone();
 
+        let noreturn = has_noreturn_specifier(tokens_queue, &TokenQueueSlice { index: previous_queue_idx.index, max_index: curr_queue_idx.index });
+
         //find semicolon
         let semicolon_idx = tokens_queue.find_closure_matches(&curr_queue_idx, false, |x| *x == Token::PUNCTUATOR(Punctuator::SEMICOLON), &TokenSearchType::skip_all_brackets())?;
         //find where all the declarators are (the x=2,y part in int x=2,y;)
@@ -32,7 +40,7 @@ impl InitialisedDeclaration {
 
         for declarator_segment in declarator_segments {
             //try and consume the declarator
-            if let Some(ASTMetadata { remaining_slice: _, resultant_tree}) = try_consume_declarator(tokens_queue, &declarator_segment, &data_type, scope_data) {
+            if let Some(ASTMetadata { remaining_slice: _, resultant_tree}) = try_consume_declarator(tokens_queue, &declarator_segment, &data_type, noreturn, scope_data) {
                 declarations.push(resultant_tree);//the declarator consumption actaully gives us a full declaration
             }
         }
@@ -44,6 +52,10 @@ impl InitialisedDeclaration {
             remaining_slice: curr_queue_idx,
         })
     }
+
+    pub fn get_span(&self) -> &SourceSpan {
+        &self.span
+    }
 }
 
 impl GenerateIR for InitialisedDeclaration {
@@ -59,6 +71,20 @@ impl GenerateIR for InitialisedDeclaration {
     }
 }
 
+impl CheckSemantics for InitialisedDeclaration {
+    fn check_semantics(&self, checker: &mut SemanticChecker) {
+        if let Some(decl) = &self.decl {
+            checker.declare_variable(&decl.name, &self.span);
+        }
+
+        if let Some(Expression::BINARYEXPRESSION(BinaryExpression { lhs, rhs, .. })) = &self.init_code {
+            //assigning the initial value doesn't count as using the variable, so only the value is checked
+            let var_type = lhs.get_type(checker.asm_data());
+            checker.check_initialiser(&var_type, rhs);
+        }
+    }
+}
+
 impl ASTDisplay for InitialisedDeclaration {
     fn display_ast(&self, f: &mut crate::debugging::TreeDisplayInfo) {
         if let Some(init) = &self.init_code {
@@ -69,18 +95,35 @@ impl ASTDisplay for InitialisedDeclaration {
 
 /**
  * claims to consume a declarator, but actaully takes in the data type too, and gives back a full declaration
+ * noreturn is whether the specifiers included _Noreturn, which only matters if this declares a function
  */
-pub fn try_consume_declarator(tokens_queue: &mut TokenQueue, slice: &TokenQueueSlice, base_type: &DataType, scope_data: &mut ParseData) -> Option<ASTMetadata<InitialisedDeclaration>> {
+pub fn try_consume_declarator(tokens_queue: &mut TokenQueue, slice: &TokenQueueSlice, base_type: &DataType, noreturn: bool, scope_data: &mut ParseData) -> Option<ASTMetadata<InitialisedDeclaration>> {
     if slice.get_slice_size() == 0 {
         return None;//obviously no declarations in ""
     }
     let mut curr_queue_idx = slice.clone();
+    let span = tokens_queue.get_span(slice);
 
     //by parsing the *x[2] part of int *x[2];, I can get the modifiers and the variable name
     let ASTMetadata{resultant_tree: Declaration { data_type: data_type_with_modifiers, name: var_name }, remaining_slice:remaining_tokens} = try_consume_declaration_modifiers(tokens_queue, &curr_queue_idx, base_type, scope_data)?;
 
-    assert!(tokens_queue.peek(&curr_queue_idx, scope_data) != Some(Token::PUNCTUATOR(Punctuator::OPENCURLY)), "found a function, and I can't handle that yet");
-
+    assert!(var_name.len() > 0, "found a declaration with no variable name");
+
+    if let DataType::FUNCTION { return_type, params } = data_type_with_modifiers {
+        //int f(int x); declares a function rather than a variable
+        scope_data.add_declaration(FunctionDeclaration {
+            function_name: var_name,
+            params: params.into_iter().map(|data_type| Declaration { data_type, name: String::new() }).collect(),
+            return_type: *return_type,
+            visibility: StorageDuration::Default,
+            noreturn,
+        });
+
+        return Some(ASTMetadata {
+            resultant_tree: InitialisedDeclaration {decl: None, init_code: None, span},
+            remaining_slice: TokenQueueSlice::empty(),
+        });
+    }
     
     curr_queue_idx = remaining_tokens;//tokens have been consumed
     
@@ -89,21 +132,22 @@ pub fn try_consume_declarator(tokens_queue: &mut TokenQueue, slice: &TokenQueueS
 
     //fix unknown size arrays int x[] = ... by inferring it from the initialisation
     let actual_data_type = data_type_with_modifiers.replace_unknown_array(&initialisation);
-    scope_data.add_variable(&var_name, actual_data_type);
+    scope_data.add_variable(&var_name, actual_data_type.clone());
 
-    //generate a tree that assigns the default value to the variable
+    //generate a tree that assigns the default value to the variable, which came from the whole declarator
     let initialisation = initialisation
         .map(|x|
             BinaryExpression::new(
-                Expression::VARIABLE(MinimalDataVariable{name: var_name.to_string()}),
+                Expression::VARIABLE(MinimalDataVariable{name: var_name.to_string(), span: span.clone()}),
                 BinaryExpressionOperator::Assign,
-                x
+                x,
+                span.clone()
             )
         )
         .map(|x| Expression::BINARYEXPRESSION(x));//wrap as binary expression
 
     Some(ASTMetadata {
-        resultant_tree: InitialisedDeclaration {init_code:initialisation}, 
+        resultant_tree: InitialisedDeclaration {decl: Some(Declaration { data_type: actual_data_type, name: var_name }), init_code:initialisation, span}, 
         remaining_slice: TokenQueueSlice::empty(),
     })
 }
@@ -111,48 +155,39 @@ pub fn try_consume_declarator(tokens_queue: &mut TokenQueue, slice: &TokenQueueS
 /**
  * takes the *x[3] part of int *x[3] = {1,2,3};
  * and parses the modifiers in order
- * also used in function params
- * function pointers not supported
+ * also used in function params and typedefs
+ * the name can be left out, like in the int (*)(char) of int f(int (*)(char));, in which case it is empty
  */
 pub fn try_consume_declaration_modifiers(tokens_queue: &TokenQueue, slice: &TokenQueueSlice, base_type: &DataType, scope_data: &mut ParseData) -> Option<ASTMetadata<Declaration>> {
     let mut curr_queue_idx = slice.clone();
 
     let mut pointer_modifiers = Vec::new();
-    let mut array_modifiers = Vec::new();
+    let mut suffix_modifiers = Vec::new();
 
-    loop {
-        if tokens_queue.peek(&curr_queue_idx, scope_data).unwrap() == Token::PUNCTUATOR(Punctuator::ASTERISK) {
-            tokens_queue.consume(&mut curr_queue_idx, &scope_data);//consume the token
-            pointer_modifiers.push(DeclModifier::POINTER);
-        } else {
-            break;//no more pointer info
-        }
+    while tokens_queue.peek(&curr_queue_idx, scope_data) == Some(Token::PUNCTUATOR(Punctuator::ASTERISK)) {
+        tokens_queue.consume(&mut curr_queue_idx, &scope_data);//consume the token
+        //the const in int *const x; applies to the pointer itself
+        let qualifiers = consume_pointer_qualifiers(tokens_queue, &mut curr_queue_idx, scope_data);
+        pointer_modifiers.push((DeclModifier::POINTER, qualifiers));
     }
 
-    //declarations are expected to go **(something)[][]
+    //declarations are expected to go **(something)[][] or **something(params)
     //so detect whether something is in brackets, or just an identifier
-    let inner_data = match tokens_queue.peek(&curr_queue_idx, &scope_data).unwrap() {
-        Token::PUNCTUATOR(Punctuator::OPENCURLY) => {
-            //find the corresponding close bracket, and deal with it
-            let in_brackets_tokens = tokens_queue.consume_inside_parenthesis(&mut curr_queue_idx);
-
-            let parsed_in_brackets = try_consume_declaration_modifiers(tokens_queue, &in_brackets_tokens, base_type, scope_data)?;
+    let mut in_brackets_tokens = None;
+    let mut name = String::new();
 
-            //curr queue idx is already advanced from consuming the parenthesis
-
-            parsed_in_brackets.resultant_tree//return the inside
-            
+    match tokens_queue.peek(&curr_queue_idx, &scope_data) {
+        Some(Token::PUNCTUATOR(Punctuator::OPENCURLY)) if brackets_contain_declarator(tokens_queue, &curr_queue_idx, scope_data) => {
+            //find the corresponding close bracket, and deal with it once the type outside the brackets is known
+            in_brackets_tokens = Some(tokens_queue.consume_inside_parenthesis(&mut curr_queue_idx));
         },
-        Token::IDENTIFIER(ident) => {
+        Some(Token::IDENTIFIER(ident)) => {
             tokens_queue.consume(&mut curr_queue_idx, &scope_data);//consume token
             //identifier name in the middle, grab it
-            Declaration {
-                data_type: base_type.clone(),
-                name: ident.to_string(),
-            }
-        }
-        x => panic!("unknown token in the middle of a declaration: {:?}", x)
-    };
+            name = ident.to_string();
+        },
+        _ => {}//no name, so this is an abstract declarator
+    }
 
     loop {
         match tokens_queue.peek(&curr_queue_idx, &scope_data) {
@@ -168,35 +203,48 @@ pub fn try_consume_declaration_modifiers(tokens_queue: &TokenQueue, slice: &Toke
                 curr_queue_idx.index = close_square_idx+1;//skip remaining slice to after the brackets
 
                 if in_square_brackets.get_slice_size() == 0 {
-                    array_modifiers.push(DeclModifier::UnknownSizeArray);
+                    suffix_modifiers.push(DeclModifier::UnknownSizeArray);
                 } else {
                     let array_size_expr = expression::try_consume_whole_expr(tokens_queue, &in_square_brackets, scope_data).expect("tried to parse constant expression for the size of an array, but failed to generate an expression");
                     let array_size_constexpr: ConstexprValue = (&array_size_expr).try_into().expect("array size is not a compile-time constant");
 
                     unwrap_let!(ConstexprValue::NUMBER(NumberLiteral::INTEGER{data: arr_len, ..}) = array_size_constexpr);
 
-                    array_modifiers.push(DeclModifier::ARRAY(arr_len.try_into().unwrap()));
+                    suffix_modifiers.push(DeclModifier::ARRAY(arr_len.try_into().unwrap()));
                 }
             },
+            Some(Token::PUNCTUATOR(Punctuator::OPENCURLY)) => {
+                //a param list, so this is a function
+                let in_brackets_params = tokens_queue.consume_inside_parenthesis(&mut curr_queue_idx);
+                let params = consume_fn_params(tokens_queue, &in_brackets_params, scope_data)?;
+
+                suffix_modifiers.push(DeclModifier::FUNCTION(params.into_iter().map(|x| x.data_type.unqualified()).collect()));
+            },
             _ => {break;}
         }
     }
 
-    //iterator item 0 is the outermost modifier. if it was pointer, it would be a pointer to whatever the rest was
-    let extra_modifiers = 
-    pointer_modifiers.iter()//all pointers take priority
-    .chain(array_modifiers.iter())//first on this iterator is the first [x] found after the variable name
-    .cloned();
-    
-    let result_type = Declaration {
-        data_type: 
-            extra_modifiers
-            .rev()//reverse, to put innermost first, then outer ones
-            .fold(
-                inner_data.data_type.clone(),//start with inner type
-                |curr_type, modifier| curr_type.add_outer_modifier(modifier)//add each modifier, innermost first
-            ),
-        name: inner_data.name.to_string(),
+    //pointers bind tightest to the base type, then suffixes are added with the first [x] or (params) found being the outermost
+    let outer_type = 
+        pointer_modifiers.into_iter()
+        .chain(suffix_modifiers.into_iter().rev().map(|modifier| (modifier, TypeQualifiers::default())))//reverse, to put innermost first, then outer ones
+        .fold(
+            base_type.clone(),
+            |curr_type, (modifier, qualifiers)| curr_type.add_outer_modifier(modifier).add_qualifiers(qualifiers)//add each modifier, innermost first
+        );
+
+    let result_type = match in_brackets_tokens {
+        //in int (*x)[3], the *x applies to the int[3] outside the brackets, so parse the inside using that as the base type
+        Some(in_brackets_tokens) => {
+            let parsed_in_brackets = try_consume_declaration_modifiers(tokens_queue, &in_brackets_tokens, &outer_type, scope_data)?;
+            assert!(parsed_in_brackets.remaining_slice.get_slice_size() == 0);//the brackets should only contain the declarator
+
+            parsed_in_brackets.resultant_tree
+        },
+        None => Declaration {
+            data_type: outer_type,
+            name,
+        }
     };
 
     Some(ASTMetadata {
@@ -205,6 +253,30 @@ pub fn try_consume_declaration_modifiers(tokens_queue: &TokenQueue, slice: &Toke
     })
 }
 
+/// consumes the qualifiers after a `*` in a declaration, like the `const` in `int *const x;`
+pub fn consume_pointer_qualifiers(tokens_queue: &TokenQueue, curr_queue_idx: &mut TokenQueueSlice, scope_data: &ParseData) -> TypeQualifiers {
+    let mut qualifiers = TypeQualifiers::default();
+
+    while let Some(Token::TYPEQUALIFIER(qualifier)) = tokens_queue.peek(curr_queue_idx, scope_data) {
+        tokens_queue.consume(curr_queue_idx, scope_data);
+        qualifiers.add(qualifier);
+    }
+
+    qualifiers
+}
+
+/**
+ * in a declaration, brackets either surround a declarator like the (*x) in int (*x)[3];
+ * or are the param list for a function, like the (int) in the abstract declarator int (int)
+ */
+fn brackets_contain_declarator(tokens_queue: &TokenQueue, open_bracket_slice: &TokenQueueSlice, scope_data: &ParseData) -> bool {
+    match tokens_queue.peek(&open_bracket_slice.next_clone(), scope_data) {
+        Some(Token::PUNCTUATOR(Punctuator::ASTERISK | Punctuator::OPENCURLY | Punctuator::OPENSQUARE)) => true,
+        Some(Token::IDENTIFIER(ident)) => scope_data.get_typedef(&ident).is_none(),//a typedef name would start a param's type instead
+        _ => false,//a type specifier, or an empty param list
+    }
+}
+
 /// this stores the partially calculated data type for consume_base_type
 enum DataTypeInfo {
     Partial(Vec<TypeInfo>),//when collecting "int" "unsigned" etc.
@@ -213,6 +285,7 @@ enum DataTypeInfo {
 
 pub struct ConsumedBaseType {
     data_type: DataTypeInfo,
+    qualifiers: TypeQualifiers,
     storage_duration: StorageDuration
 }
 
@@ -220,6 +293,7 @@ impl ConsumedBaseType {
     pub fn new(default_storage_duration: StorageDuration) -> Self {
         ConsumedBaseType {
             data_type: DataTypeInfo::Partial(Vec::new()),
+            qualifiers: TypeQualifiers::default(),
             storage_duration: default_storage_duration,
         }
     }
@@ -231,7 +305,12 @@ impl ConsumedBaseType {
             DataTypeInfo::Full(data_type) => data_type,
         };
 
-        Some((complete_data_type, self.storage_duration))
+        Some((complete_data_type.add_qualifiers(self.qualifiers), self.storage_duration))
+    }
+
+    ///detects whether no type information has been found yet
+    fn is_empty(&self) -> bool {
+        matches!(&self.data_type, DataTypeInfo::Partial(type_infos) if type_infos.len() == 0)
     }
 
     fn add_type_info(&mut self, extra: TypeInfo) {
@@ -249,7 +328,7 @@ impl ConsumedBaseType {
         self.data_type = DataTypeInfo::Full(new_type)
     }
     fn add_type_qualifier(&mut self, qual: TypeQualifier) {
-        //disregard them now
+        self.qualifiers.add(qual);
     }
 }
 
@@ -284,6 +363,11 @@ pub fn consume_type_specifier_recursive(tokens_queue: &TokenQueue, queue_idx: &T
             consume_type_specifier_recursive(tokens_queue, &queue_idx.next_clone(), scope_data, initial_type)
         }
 
+        Some(Token::KEYWORD(Keyword::NORETURN)) => {
+            //_Noreturn only matters for function declarations, which look for it themselves
+            consume_type_specifier_recursive(tokens_queue, &queue_idx.next_clone(), scope_data, initial_type)
+        }
+
         Some(Token::KEYWORD(Keyword::ENUM)) => {
             let ASTMetadata { remaining_slice, resultant_tree } = try_consume_enum_as_type(tokens_queue, &mut queue_idx.clone(), scope_data).unwrap();
 
@@ -307,11 +391,12 @@ pub fn consume_type_specifier_recursive(tokens_queue: &TokenQueue, queue_idx: &T
         }
         Some(Token::IDENTIFIER(name)) => {
             match scope_data.get_typedef(&name) {
-                Some(x) => {
+                //if a type has already been found, this must be a name that shadows the typedef
+                Some(x) if initial_type.is_empty() => {
                     initial_type.add_complete_type(x.clone());//get type of typedef and fill it in
                     consume_type_specifier_recursive(tokens_queue, &queue_idx.next_clone(), scope_data, initial_type)//consume other info
                 }
-                None => ASTMetadata { remaining_slice: queue_idx.clone(), resultant_tree: initial_type }//unknown identifier, probably a variable name
+                _ => ASTMetadata { remaining_slice: queue_idx.clone(), resultant_tree: initial_type }//unknown identifier, probably a variable name
             }
         },
 
@@ -333,5 +418,11 @@ fn consume_initialisation(tokens_queue: &mut TokenQueue, curr_queue_idx: &mut To
 
     tokens_queue.consume(curr_queue_idx, &scope_data).unwrap();//consume the equals sign
 
-    Some(expression::try_consume_whole_expr(tokens_queue, &curr_queue_idx, scope_data).unwrap())
+    let initial_value = expression::try_consume_whole_expr(tokens_queue, &curr_queue_idx, scope_data);
+    if initial_value.is_none() {
+        //carry on as if there was no initialisation, so that the rest of the code can be checked
+        scope_data.report_error(Diagnostic::error("invalid initial value").with_primary(tokens_queue.get_span(curr_queue_idx), ""));
+    }
+
+    initial_value
 }
\ No newline at end of file
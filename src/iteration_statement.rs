@@ -1,4 +1,5 @@
-use crate::{asm_gen_data::{AsmData, GlobalAsmData}, assembly::{assembly::IRCode, comparison::AsmComparison, operand::Storage, operation::{IROperation, Label}}, ast_metadata::ASTMetadata, block_statement::StatementOrDeclaration, data_type::{base_type::{BaseType, ScalarType}, recursive_data_type::DataType}, debugging::ASTDisplay, expression::expression::{self, Expression}, generate_ir_traits::{GenerateIR, GetType}, lexer::{keywords::Keyword, punctuator::Punctuator, token::Token, token_savepoint::TokenQueueSlice, token_walk::{TokenQueue, TokenSearchType}}, number_literal::typed_value::NumberLiteral, parse_data::ParseData, statement::Statement};
+use serde::Serialize;
+use crate::{asm_gen_data::{AsmData, GlobalAsmData}, assembly::{assembly::IRCode, comparison::{AsmComparison, ComparisonKind}, operand::Storage, operation::{IROperation, Label}}, ast_metadata::ASTMetadata, block_statement::StatementOrDeclaration, data_type::{base_type::{BaseType, ScalarType}, recursive_data_type::DataType}, debugging::ASTDisplay, diagnostics::{diagnostic::Diagnostic, semantic_checker::{CheckSemantics, SemanticChecker}}, expression::expression::{self, Expression}, generate_ir_traits::{GenerateIR, GetType}, goto_and_labels::SwitchCaseLabel, lexer::{keywords::Keyword, punctuator::Punctuator, source_span::SourceSpan, token::Token, token_savepoint::TokenQueueSlice, token_walk::{TokenQueue, TokenSearchType}}, number_literal::typed_value::NumberLiteral, parse_data::ParseData, statement::Statement};
 use colored::Colorize;
 use stack_management::simple_stack_frame::SimpleStackFrame;
 use unwrap_let::unwrap_let;
@@ -7,6 +8,7 @@ use uuid::Uuid;
 /**
  * this handles if statements and other conditionals
  */
+#[derive(Serialize)]
 pub enum IterationStatement{
     FOR{
         initialisation: Option<Box<StatementOrDeclaration>>,//can't be anything fancy like a scope or if statement, but expressions and declarations are OK
@@ -15,11 +17,18 @@ pub enum IterationStatement{
 
         local_scope_data: ParseData,//metadata to help with assembly generation
 
-        body: Box<Statement>
+        body: Box<Statement>,
+        span: SourceSpan,
     },
     WHILE {
         condition: Expression,
         body: Box<Statement>,
+        span: SourceSpan,
+    },
+    DOWHILE {
+        body: Box<Statement>,
+        condition: Expression,
+        span: SourceSpan,
     }
 }
 
@@ -37,8 +46,12 @@ impl IterationStatement {
                 //important: clone the local variables and enums, to prevent inner definitions from leaking out to outer scopes
                 let mut in_loop_data = outer_scope_data.clone_for_new_scope();
                 
+                if tokens_queue.peek(&curr_queue_idx, &in_loop_data) != Some(Token::PUNCTUATOR(Punctuator::OPENCURLY)) {
+                    in_loop_data.report_error(tokens_queue.expected_error(&curr_queue_idx, "`(` after `for`"));
+                    return None;
+                }
                 let closecurly_idx = tokens_queue.find_matching_close_bracket(curr_queue_idx.index);
-                assert!(Token::PUNCTUATOR(Punctuator::OPENCURLY) == tokens_queue.consume(&mut curr_queue_idx, &in_loop_data).unwrap());//ensure opening parenthesis
+                tokens_queue.consume(&mut curr_queue_idx, &in_loop_data);//consume the opening parenthesis
 
                 let items_slice = TokenQueueSlice{
                     index: curr_queue_idx.index,
@@ -46,7 +59,12 @@ impl IterationStatement {
                 };
 
                 let items = tokens_queue.split_outside_parentheses(&items_slice, |x| *x == Token::PUNCTUATOR(Punctuator::SEMICOLON), &TokenSearchType::skip_nothing());
-                assert!(items.len() == 3);
+                if items.len() != 3 {
+                    in_loop_data.report_error(Diagnostic::error(format!("expected 2 `;` in for loop header, found {}", items.len() - 1))
+                        .with_primary(tokens_queue.get_span(&TokenQueueSlice { index: previous_queue_idx.index, max_index: closecurly_idx+1 }), "")
+                        .with_help("the header should look like `for (initialisation; condition; increment)`"));
+                    return None;
+                }
                 let (init_slice, condition_slice, increment_slice) = (&items[0], &items[1], &items[2]);//get the slices that I need
 
                 let init_with_semicolon = TokenQueueSlice {
@@ -58,9 +76,9 @@ impl IterationStatement {
 
                 //get initialisation command, or None
                 let initialisation = StatementOrDeclaration::try_consume(tokens_queue, &init_with_semicolon, &mut in_loop_data).and_then(|ast_data| Some(Box::new(ast_data.resultant_tree)));
-                //get loop condition or if none, a constant "true" value
+                //get loop condition or if none, a constant "true" value that came from the "for (;;)" part
                 let condition = expression::try_consume_whole_expr(tokens_queue, &condition_slice, &mut in_loop_data)
-                    .unwrap_or(Expression::NUMBERLITERAL(NumberLiteral::from(1)));
+                    .unwrap_or_else(|| Expression::NUMBERLITERAL(NumberLiteral::from(1), tokens_queue.get_span(&TokenQueueSlice { index: previous_queue_idx.index, max_index: closecurly_idx+1 })));
                 //get increment or None
                 let increment = expression::try_consume_whole_expr(tokens_queue, &increment_slice, &mut in_loop_data);
 
@@ -71,25 +89,31 @@ impl IterationStatement {
                 };
 
                 //consume the body
-                let ASTMetadata{ remaining_slice, resultant_tree: loop_body } = Statement::try_consume(tokens_queue, &curr_queue_idx, &mut in_loop_data).unwrap();
+                let ASTMetadata{ remaining_slice, resultant_tree: loop_body } = Statement::try_consume_required(tokens_queue, &curr_queue_idx, &mut in_loop_data, "a statement after the for loop header")?;
                 curr_queue_idx = remaining_slice;
 
+                let span = tokens_queue.get_span(&TokenQueueSlice { index: previous_queue_idx.index, max_index: curr_queue_idx.index });
+
                 Some(ASTMetadata{
-                    resultant_tree: Self::FOR { initialisation, condition, increment, body: Box::new(loop_body), local_scope_data: in_loop_data }, 
+                    resultant_tree: Self::FOR { initialisation, condition, increment, body: Box::new(loop_body), local_scope_data: in_loop_data, span }, 
                     remaining_slice: curr_queue_idx
                 })
             },
             Keyword::WHILE => {
                 
+                if tokens_queue.peek(&curr_queue_idx, outer_scope_data) != Some(Token::PUNCTUATOR(Punctuator::OPENCURLY)) {
+                    outer_scope_data.report_error(tokens_queue.expected_error(&curr_queue_idx, "`(` after `while`"));
+                    return None;
+                }
                 let closecurly_idx = tokens_queue.find_matching_close_bracket(curr_queue_idx.index);
-                assert!(Token::PUNCTUATOR(Punctuator::OPENCURLY) == tokens_queue.consume(&mut curr_queue_idx, outer_scope_data).unwrap());//ensure opening parenthesis
+                tokens_queue.consume(&mut curr_queue_idx, outer_scope_data);//consume the opening parenthesis
 
                 let condition_slice = TokenQueueSlice{
                     index: curr_queue_idx.index,
                     max_index: closecurly_idx
                 };
 
-                let condition = expression::try_consume_whole_expr(tokens_queue, &condition_slice, outer_scope_data).unwrap();
+                let condition = consume_loop_condition(tokens_queue, &condition_slice, outer_scope_data)?;
 
                 //consume the "while ()" part
                 curr_queue_idx = TokenQueueSlice{
@@ -98,17 +122,95 @@ impl IterationStatement {
                 };
 
                 //consume the body
-                let ASTMetadata{ remaining_slice, resultant_tree: loop_body} = Statement::try_consume(tokens_queue, &curr_queue_idx, outer_scope_data).unwrap();
+                let ASTMetadata{ remaining_slice, resultant_tree: loop_body} = Statement::try_consume_required(tokens_queue, &curr_queue_idx, outer_scope_data, "a statement after the while loop condition")?;
                 curr_queue_idx = remaining_slice;
 
+                let span = tokens_queue.get_span(&TokenQueueSlice { index: previous_queue_idx.index, max_index: curr_queue_idx.index });
+
                 Some(ASTMetadata{
-                    resultant_tree: Self::WHILE { condition: condition, body: Box::new(loop_body)  }, 
+                    resultant_tree: Self::WHILE { condition: condition, body: Box::new(loop_body), span }, 
                     remaining_slice: curr_queue_idx, 
                 })
             }
+            Keyword::DO => {
+                //consume the body
+                let ASTMetadata{ remaining_slice, resultant_tree: loop_body} = Statement::try_consume_required(tokens_queue, &curr_queue_idx, outer_scope_data, "a statement after `do`")?;
+                curr_queue_idx = remaining_slice;
+
+                //ensure the while of do {} while()
+                if tokens_queue.peek(&curr_queue_idx, outer_scope_data) != Some(Token::KEYWORD(Keyword::WHILE)) {
+                    outer_scope_data.report_error(tokens_queue.expected_error(&curr_queue_idx, "`while` after the body of a do-while loop"));
+                    return None;
+                }
+                tokens_queue.consume(&mut curr_queue_idx, outer_scope_data);
+
+                if tokens_queue.peek(&curr_queue_idx, outer_scope_data) != Some(Token::PUNCTUATOR(Punctuator::OPENCURLY)) {
+                    outer_scope_data.report_error(tokens_queue.expected_error(&curr_queue_idx, "`(` after `while`"));
+                    return None;
+                }
+                let closecurly_idx = tokens_queue.find_matching_close_bracket(curr_queue_idx.index);
+                tokens_queue.consume(&mut curr_queue_idx, outer_scope_data);//consume the opening parenthesis
+
+                let condition_slice = TokenQueueSlice{
+                    index: curr_queue_idx.index,
+                    max_index: closecurly_idx
+                };
+
+                let condition = consume_loop_condition(tokens_queue, &condition_slice, outer_scope_data)?;
+
+                //consume the "while ()" part
+                curr_queue_idx = TokenQueueSlice{
+                    index: closecurly_idx + 1,
+                    max_index: curr_queue_idx.max_index
+                };
+
+                //do-while ends in a semicolon
+                if tokens_queue.peek(&curr_queue_idx, outer_scope_data) != Some(Token::PUNCTUATOR(Punctuator::SEMICOLON)) {
+                    outer_scope_data.report_error(tokens_queue.expected_error(&curr_queue_idx, "`;` after a do-while loop"));
+                    return None;
+                }
+                tokens_queue.consume(&mut curr_queue_idx, outer_scope_data);
+
+                let span = tokens_queue.get_span(&TokenQueueSlice { index: previous_queue_idx.index, max_index: curr_queue_idx.index });
+
+                Some(ASTMetadata{
+                    resultant_tree: Self::DOWHILE { body: Box::new(loop_body), condition, span },
+                    remaining_slice: curr_queue_idx,
+                })
+            }
             _ => None
         }
     }
+
+    pub fn get_span(&self) -> &SourceSpan {
+        match self {
+            Self::FOR { span, .. } |
+            Self::WHILE { span, .. } |
+            Self::DOWHILE { span, .. } => span,
+        }
+    }
+
+    /// see [Statement::get_switch_cases]
+    pub fn get_switch_cases(&self) -> Vec<&SwitchCaseLabel> {
+        match self {
+            Self::FOR { body, .. } |
+            Self::WHILE { body, .. } |
+            Self::DOWHILE { body, .. } => body.get_switch_cases(),
+        }
+    }
+
+    /// see [Statement::falls_through]
+    pub fn falls_through(&self) -> bool {
+        match self {
+            Self::FOR { condition, body, .. } |
+            Self::WHILE { condition, body, .. } |
+            Self::DOWHILE { body, condition, .. } => {
+                //loops like `while(1)` can only be left by breaking out of them
+                let always_loops = matches!(condition, Expression::NUMBERLITERAL(NumberLiteral::INTEGER { data, .. }, _) if *data != 0);
+                !always_loops || body.contains_break()
+            }
+        }
+    }
 }
 
 impl GenerateIR for IterationStatement {
@@ -119,11 +221,18 @@ impl GenerateIR for IterationStatement {
         let loop_start_label = Label::Local(format!("{}_loop_start", generic_label));
         let loop_end_label = Label::Local(format!("{}_loop_end", generic_label));
 
-        //overwrite asm_data, substituting the label to go to if "break;" is called
-        let asm_data = &asm_data.clone_for_new_loop(loop_end_label.clone());
+        //where "continue;" goes, which is just before the condition is checked (or for loops, just before the increment)
+        let loop_continue_label = match self {
+            Self::FOR {..} => Label::Local(format!("{}_loop_increment", generic_label)),
+            Self::WHILE {..} => loop_start_label.clone(),
+            Self::DOWHILE {..} => Label::Local(format!("{}_loop_condition", generic_label)),
+        };
+
+        //overwrite asm_data, substituting the labels to go to if "break;" or "continue;" is called
+        let asm_data = &asm_data.clone_for_new_loop(loop_end_label.clone(), loop_continue_label.clone());
 
         match self {
-            Self::FOR { initialisation, condition, increment, body, local_scope_data } => {
+            Self::FOR { initialisation, condition, increment, body, local_scope_data, .. } => {
                 //overwrite asm_data by creating new scope
                 let asm_data = asm_data.clone_for_new_scope(local_scope_data, stack_data);
                 
@@ -133,8 +242,6 @@ impl GenerateIR for IterationStatement {
                     ScalarType::Integer(integer_type) => NumberLiteral::INTEGER { data: 0, data_type: integer_type },
                 };
 
-                let loop_increment_label = Label::Local(format!("{}_loop_increment", generic_label));//extra label required for for loops
-
                 //write to stack data whilst generating assembly for initialising the loop body
                 let init_asm = match initialisation {
                     Some(x) => x.generate_ir(&asm_data, stack_data, global_asm_data).0,
@@ -154,20 +261,20 @@ impl GenerateIR for IterationStatement {
                 result.add_instruction(IROperation::CMP {
                     lhs: Storage::Stack(condition_value.unwrap()),
                     rhs: Storage::Constant(zero),
-                    data_type: condition_type
+                    data_type: condition_type.clone()
                 });
 
                 //if the result is 0, jump to the end of the loop
                 result.add_instruction(IROperation::JMPCC {
                     label: loop_end_label.clone(),
-                    comparison: AsmComparison::EQ,
+                    comparison: ComparisonKind::EQ.to_asm_comparison_for(&condition_type),
                 });
 
                 //overwrite stack data whilst generating assembly for the loop body
                 let (body_asm, _) = body.generate_ir(&asm_data, stack_data, global_asm_data);
                 result.merge(&body_asm);//generate the loop body
 
-                result.add_instruction(IROperation::Label(loop_increment_label));//add label to jump to incrementing the loop
+                result.add_instruction(IROperation::Label(loop_continue_label));//add label to jump to incrementing the loop
 
                 if let Some(inc) = increment {//if there is an increment
                     let (increment_asm, _) = inc.generate_ir(&asm_data, stack_data, global_asm_data);
@@ -183,7 +290,7 @@ impl GenerateIR for IterationStatement {
                 result.add_instruction(IROperation::Label(loop_end_label));
             },
 
-            Self::WHILE { condition, body } => {
+            Self::WHILE { condition, body, .. } => {
 
                 unwrap_let!(DataType::RAW(BaseType::Scalar(condition_type)) = condition.get_type(asm_data));
                 let zero = match condition_type {
@@ -200,13 +307,13 @@ impl GenerateIR for IterationStatement {
                 result.add_instruction(IROperation::CMP {
                     lhs: Storage::Stack(condition_value.unwrap()),
                     rhs: Storage::Constant(zero),
-                    data_type: condition_type,
+                    data_type: condition_type.clone(),
                 });
 
                 // if the result is 0, jump to the end of the loop
                 result.add_instruction(IROperation::JMPCC {
                     label: loop_end_label.clone(),
-                    comparison: AsmComparison::EQ,
+                    comparison: ComparisonKind::EQ.to_asm_comparison_for(&condition_type),
                 });
 
                 // generate the loop body
@@ -221,18 +328,79 @@ impl GenerateIR for IterationStatement {
 
                 result.add_instruction(IROperation::Label(loop_end_label));
             }
+
+            Self::DOWHILE { body, condition, .. } => {
+
+                unwrap_let!(DataType::RAW(BaseType::Scalar(condition_type)) = condition.get_type(asm_data));
+                let zero = match condition_type {
+                    ScalarType::Float(float_type) => NumberLiteral::FLOAT { data: 0f64, data_type: float_type },
+                    ScalarType::Integer(integer_type) => NumberLiteral::INTEGER { data: 0, data_type: integer_type },
+                };
+
+                result.add_instruction(IROperation::Label(loop_start_label.clone())); // label for loop's start
+
+                // generate the loop body, which always runs at least once
+                let (body_asm, _) = body.generate_ir(asm_data, stack_data, global_asm_data);
+                result.merge(&body_asm);
+
+                result.add_instruction(IROperation::Label(loop_continue_label)); // "continue;" still checks the condition
+
+                let (condition_asm, condition_value) = condition.generate_ir(&asm_data, stack_data, global_asm_data);
+                result.merge(&condition_asm); // generate the condition
+
+                // compare the result to 0
+                result.add_instruction(IROperation::CMP {
+                    lhs: Storage::Stack(condition_value.unwrap()),
+                    rhs: Storage::Constant(zero),
+                    data_type: condition_type.clone(),
+                });
+
+                // if the result is not 0, go to the top of the loop
+                result.add_instruction(IROperation::JMPCC {
+                    label: loop_start_label,
+                    comparison: ComparisonKind::NE.to_asm_comparison_for(&condition_type),
+                });
+
+                result.add_instruction(IROperation::Label(loop_end_label));
+            }
         }
         
         (result, None)
     }
 }
 
+impl CheckSemantics for IterationStatement {
+    fn check_semantics(&self, checker: &mut SemanticChecker) {
+        match self {
+            Self::FOR { initialisation, condition, increment, local_scope_data, body, .. } => {
+                let outer_asm_data = checker.enter_scope(local_scope_data);
+
+                if let Some(initialisation) = initialisation {
+                    initialisation.check_semantics(checker);
+                }
+                checker.check_condition(condition);
+                if let Some(increment) = increment {
+                    checker.check_expression(increment);
+                }
+                checker.check_loop_body(body);
+
+                checker.leave_scope(outer_asm_data);
+            },
+            Self::WHILE { condition, body, .. } |
+            Self::DOWHILE { body, condition, .. } => {
+                checker.check_condition(condition);
+                checker.check_loop_body(body);
+            },
+        }
+    }
+}
+
 impl ASTDisplay for IterationStatement {
     fn display_ast(&self, f: &mut crate::debugging::TreeDisplayInfo) {
         f.write(&"loop".red().to_string());
         f.indent();
         match self {
-            IterationStatement::FOR { initialisation, condition, increment, local_scope_data:_, body } => {
+            IterationStatement::FOR { initialisation, condition, increment, body, .. } => {
                 if let Some(init) = initialisation {
                     f.write(&"initialisation".red().to_string());
                     f.indent();
@@ -257,7 +425,7 @@ impl ASTDisplay for IterationStatement {
                 body.display_ast(f);
                 f.dedent();
             },
-            IterationStatement::WHILE { condition, body } => {
+            IterationStatement::WHILE { condition, body, .. } => {
                 f.write(&"condition".green().to_string());
                 f.indent();
                 condition.display_ast(f);
@@ -268,7 +436,29 @@ impl ASTDisplay for IterationStatement {
                 body.display_ast(f);
                 f.dedent();
             },
+            IterationStatement::DOWHILE { body, condition, .. } => {
+                f.write(&"body".red().to_string());
+                f.indent();
+                body.display_ast(f);
+                f.dedent();
+
+                f.write(&"condition".green().to_string());
+                f.indent();
+                condition.display_ast(f);
+                f.dedent();
+            },
         }
         f.dedent();
     }
+}
+
+/// Parses the condition of a while or do-while loop, reporting an error if it is invalid
+fn consume_loop_condition(tokens_queue: &TokenQueue, condition_slice: &TokenQueueSlice, scope_data: &mut ParseData) -> Option<Expression> {
+    let condition = expression::try_consume_whole_expr(tokens_queue, condition_slice, scope_data);
+
+    if condition.is_none() {
+        scope_data.report_error(Diagnostic::error("invalid loop condition").with_primary(tokens_queue.get_span(condition_slice), ""));
+    }
+
+    condition
 }
\ No newline at end of file
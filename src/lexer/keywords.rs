@@ -8,15 +8,20 @@ pub enum Keyword {
     UNION,
     IF,
     ELSE,
+    SWITCH,
+    CASE,
+    DEFAULT,
     FOR,
     WHILE,
+    DO,
     RETURN,
     BREAK,
     GOTO,
     CONTINUE,
     TYPEDEF,
     SIZEOF,
-    DEFINED
+    DEFINED,
+    NORETURN,
 }
 
 impl Display for Keyword {
@@ -28,8 +33,12 @@ impl Display for Keyword {
                 Keyword::UNION => "union",
                 Keyword::IF => "if",
                 Keyword::ELSE => "else",
+                Keyword::SWITCH => "switch",
+                Keyword::CASE => "case",
+                Keyword::DEFAULT => "default",
                 Keyword::FOR => "for",
                 Keyword::WHILE => "while",
+                Keyword::DO => "do",
                 Keyword::RETURN => "return",
                 Keyword::BREAK => "break",
                 Self::CONTINUE => "continue",
@@ -37,6 +46,7 @@ impl Display for Keyword {
                 Keyword::TYPEDEF => "typedef",
                 Keyword::SIZEOF => "sizeof",
                 Keyword::DEFINED => "defined",
+                Keyword::NORETURN => "_Noreturn",
             }
         )
     }
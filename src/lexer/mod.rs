@@ -3,4 +3,5 @@ pub mod token_walk;
 pub mod token_savepoint;
 pub mod punctuator;
 pub mod precedence;
-pub mod keywords;
\ No newline at end of file
+pub mod keywords;
+pub mod source_span;
\ No newline at end of file
@@ -47,6 +47,18 @@ pub enum Punctuator {
     /// +=
     AdditionCombination,
     SubtractionCombination,
+    /// *=
+    MultiplicationCombination,
+    DivisionCombination,
+    /// %=
+    ModCombination,
+    /// <<=
+    BitshiftLeftCombination,
+    BitshiftRightCombination,
+    /// &=
+    BitwiseAndCombination,
+    BitwiseOrCombination,
+    BitwiseXorCombination,
 
     FULLSTOP,
     ELIPSIS,
@@ -124,7 +136,15 @@ impl Punctuator {
 
             Self::EQUALS |
             Self::AdditionCombination |
-            Self::SubtractionCombination => Some(14),
+            Self::SubtractionCombination |
+            Self::MultiplicationCombination |
+            Self::DivisionCombination |
+            Self::ModCombination |
+            Self::BitshiftLeftCombination |
+            Self::BitshiftRightCombination |
+            Self::BitwiseAndCombination |
+            Self::BitwiseOrCombination |
+            Self::BitwiseXorCombination => Some(14),
             _ => None
         }
     }
@@ -195,6 +215,14 @@ impl Display for Punctuator {
             Punctuator::QuestionMark => "?",
             Punctuator::AdditionCombination => "+=",
             Punctuator::SubtractionCombination => "-=",
+            Punctuator::MultiplicationCombination => "*=",
+            Punctuator::DivisionCombination => "/=",
+            Punctuator::ModCombination => "%=",
+            Punctuator::BitshiftLeftCombination => "<<=",
+            Punctuator::BitshiftRightCombination => ">>=",
+            Punctuator::BitwiseAndCombination => "&=",
+            Punctuator::BitwiseOrCombination => "|=",
+            Punctuator::BitwiseXorCombination => "^=",
             Punctuator::FULLSTOP => ".",
             Punctuator::ELIPSIS => "...",
             Punctuator::Hash => "#",
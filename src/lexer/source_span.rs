@@ -0,0 +1,120 @@
+use std::{fmt::Display, ops::Range, path::PathBuf, rc::Rc};
+use serde::{ser::SerializeStruct, Serialize, Serializer};
+
+/// A line and column in a source file, both starting at 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+pub struct SourcePosition {
+    pub line: u32,
+    pub column: u32,
+}
+
+/// A macro that was used, and where it was used
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MacroExpansion {
+    pub macro_name: String,
+    pub call_site: SourceSpan,
+}
+
+/// Where something came from in the source code
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceSpan {
+    pub file: Rc<PathBuf>,
+    pub start: SourcePosition,
+    /// one past the last character
+    pub end: SourcePosition,
+    /// if this was produced by a macro, this is the macro and where it was used
+    pub expanded_from: Option<Rc<MacroExpansion>>,
+}
+
+impl SourceSpan {
+    /// Makes a copy of this span, that remembers it was produced by using `macro_name` at `call_site`
+    pub fn expanded_by(&self, macro_name: &str, call_site: &SourceSpan) -> SourceSpan {
+        SourceSpan {
+            expanded_from: Some(Rc::new(MacroExpansion { macro_name: macro_name.to_string(), call_site: call_site.clone() })),
+            ..self.clone()
+        }
+    }
+
+    /// Finds where this was written in the code that was being compiled, skipping through any macro definitions
+    pub fn expansion_root(&self) -> &SourceSpan {
+        match &self.expanded_from {
+            Some(expansion) => expansion.call_site.expansion_root(),
+            None => self,
+        }
+    }
+
+    /// Creates a span from the start of `self` to the end of `other`
+    ///
+    /// If they come from different macro expansions, the span covers where the macros were used instead
+    pub fn to(&self, other: &SourceSpan) -> SourceSpan {
+        if self.file == other.file && self.expanded_from == other.expanded_from {
+            SourceSpan { end: other.end, ..self.clone() }
+        } else {
+            let (start, end) = (self.expansion_root(), other.expansion_root());
+            if start.file == end.file {
+                SourceSpan { end: end.end, ..start.clone() }
+            } else {
+                start.clone()//somehow spread across files, so just point at the start
+            }
+        }
+    }
+}
+
+impl Display for SourceSpan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}:{}", self.file.display(), self.start.line, self.start.column)
+    }
+}
+
+//written by hand, as serde can't derive this for the Rc fields
+impl Serialize for SourceSpan {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut span = serializer.serialize_struct("SourceSpan", 4)?;
+        span.serialize_field("file", self.file.as_ref())?;
+        span.serialize_field("start", &self.start)?;
+        span.serialize_field("end", &self.end)?;
+        span.serialize_field("expanded_from", &self.expanded_from.as_deref())?;
+        span.end()
+    }
+}
+
+/// Something from the source code, along with where it came from
+#[derive(Debug, Clone, PartialEq)]
+pub struct Located<T> {
+    pub data: T,
+    pub span: SourceSpan,
+}
+
+/// Maps each byte of preprocessed source code back to where it was in the original file
+///
+/// This is the `extras` of the lexers, so that each token can find its span
+#[derive(Clone, Default)]
+pub struct SourceMap {
+    file: Rc<PathBuf>,
+    /// The position of each byte, with an extra one for the end of the text
+    positions: Rc<Vec<SourcePosition>>,
+}
+
+impl SourceMap {
+    /// `positions` must have one position per byte of the text, and one for the end of the text
+    pub fn new(file: PathBuf, positions: Vec<SourcePosition>) -> SourceMap {
+        SourceMap { file: Rc::new(file), positions: Rc::new(positions) }
+    }
+
+    /// Finds the span of `range`, which is relative to `source`
+    ///
+    /// `source` must be the end of the text that this was created for, as lexers only ever get a suffix of the text
+    pub fn span(&self, source: &str, range: Range<usize>) -> SourceSpan {
+        let source_start = self.positions.len() - 1 - source.len();
+        let start = self.positions[source_start + range.start];
+        //point one past the last character, so that the end does not wrap onto the next line
+        let last = self.positions[source_start + range.end.max(range.start + 1) - 1];
+
+        SourceSpan {
+            file: self.file.clone(),
+            start,
+            end: SourcePosition { line: last.line, column: last.column + 1 },
+            expanded_from: None,
+        }
+    }
+}
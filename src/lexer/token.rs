@@ -4,10 +4,11 @@ use logos::{Lexer, Logos};
 
 use crate::{data_type::{base_type::{IntegerType, ScalarType}, storage_type::StorageDuration, type_qualifier::TypeQualifier, type_token::TypeInfo}, number_literal::typed_value::NumberLiteral, string_literal::StringLiteral};
 
-use super::{keywords::Keyword, punctuator::Punctuator};
+use super::{keywords::Keyword, punctuator::Punctuator, source_span::{Located, SourceMap}};
 
 #[derive(Debug, Clone, PartialEq, Logos)]
 #[logos(skip " ")]
+#[logos(extras = SourceMap)]
 pub enum Token {
     #[regex(r#""((\\.)|[^"\\])*""#, |x| {//match a string including
         let slice = x.slice();
@@ -15,22 +16,32 @@ pub enum Token {
     })]
     STRING(StringLiteral),
 
-    //char literals get converted to numbers:
+    //char literals get converted to numbers, but remember how they were written:
     #[regex(r#"'((\\.)|[^\\'])+'"#, |x| {// similar to matching a string as some char literals '\n' can be multi-char
         let slice = x.slice();
         let as_string = StringLiteral::use_escape_sequences(&slice[1..slice.len()-1]);//remove the speech marks, then parse escape sequences to string literal
         assert!(as_string.len() == 2);//char and \0
         //TODO multibyte chars can still go in char literals
-        NumberLiteral::from(as_string[0].to_string()).cast(&ScalarType::Integer(IntegerType::I32))
-
+        NumberToken {
+            value: NumberLiteral::from(as_string[0].to_string()).cast(&ScalarType::Integer(IntegerType::I32)),
+            spelling: slice.to_string()
+        }
     })]
     //normal number literals here
-    #[regex(r"0x\.?([pP][+-]|[a-zA-Z0-9\.])*", |x| NumberLiteral::from(x.slice()))]//hex literal
-    #[regex(r"\.?[0-9]([eE][+-]|[a-zA-Z0-9\.])*", |x| NumberLiteral::from(x.slice()), priority=1000)]
-    NUMBER(NumberLiteral),
+    #[regex(r"0x\.?([pP][+-]|[a-zA-Z0-9\.])*", |x| NumberToken { value: NumberLiteral::from(x.slice()), spelling: x.slice().to_string() })]//hex literal
+    #[regex(r"\.?[0-9]([eE][+-]|[a-zA-Z0-9\.])*", |x| NumberToken { value: NumberLiteral::from(x.slice()), spelling: x.slice().to_string() }, priority=1000)]
+    NUMBER(NumberToken),
 
     #[token("+=", |_| Punctuator::AdditionCombination)]
     #[token("-=", |_| Punctuator::SubtractionCombination)]
+    #[token("*=", |_| Punctuator::MultiplicationCombination)]
+    #[token("/=", |_| Punctuator::DivisionCombination)]
+    #[token("%=", |_| Punctuator::ModCombination)]
+    #[token("<<=", |_| Punctuator::BitshiftLeftCombination)]
+    #[token(">>=", |_| Punctuator::BitshiftRightCombination)]
+    #[token("&=", |_| Punctuator::BitwiseAndCombination)]
+    #[token("|=", |_| Punctuator::BitwiseOrCombination)]
+    #[token("^=", |_| Punctuator::BitwiseXorCombination)]
     #[token(":", |_| Punctuator::COLON)]
     #[token("?", |_| Punctuator::QuestionMark)]
     #[token("#", |_| Punctuator::Hash)]
@@ -64,8 +75,12 @@ pub enum Token {
     #[token("union", |_| Keyword::UNION)]
     #[token("if", |_| Keyword::IF)]
     #[token("else", |_| Keyword::ELSE)]
+    #[token("switch", |_| Keyword::SWITCH)]
+    #[token("case", |_| Keyword::CASE)]
+    #[token("default", |_| Keyword::DEFAULT)]
     #[token("for", |_| Keyword::FOR)]
     #[token("while", |_| Keyword::WHILE)]
+    #[token("do", |_| Keyword::DO)]
     #[token("return", |_| Keyword::RETURN)]
     #[token("typedef", |_| Keyword::TYPEDEF)]
     #[token("break", |_| Keyword::BREAK)]
@@ -73,6 +88,7 @@ pub enum Token {
     #[token("sizeof", |_| Keyword::SIZEOF)]
     #[token("defined", |_| Keyword::DEFINED)]
     #[token("goto", |_| Keyword::GOTO)]
+    #[token("_Noreturn", |_| Keyword::NORETURN)]
     KEYWORD(Keyword),
 
     #[regex(r"[a-zA-Z_]\w*", |x| x.slice().to_string())]
@@ -85,8 +101,12 @@ pub enum Token {
 
 impl Token {
     /// Parses until a newline, but will still consume multiline strings if required
-    pub fn parse_logical_line<'a, L>(lex: &mut Lexer<'a, L>) -> Vec<Self>
-    where L: Clone, L: Logos<'a, Extras = (), Source = str, Error = ()>
+    /// 
+    /// Each token remembers where it was in the source code
+    /// 
+    /// Returns None if there is something that isn't a valid token
+    pub fn parse_logical_line<'a, L>(lex: &mut Lexer<'a, L>) -> Option<Vec<Located<Self>>>
+    where L: Clone, L: Logos<'a, Extras = SourceMap, Source = str, Error = ()>
     {
         let mut casted_lexer: Lexer<'_, Token> = lex.clone().morph::<Token>();
 
@@ -97,18 +117,21 @@ impl Token {
                 None |
                 Some(Ok(Token::NEWLINE)) => break 'outer,
 
-                Some(Ok(x)) => result.push(x),
+                Some(Ok(x)) => {
+                    let span = casted_lexer.extras.span(casted_lexer.source(), casted_lexer.span());
+                    result.push(Located { data: x, span })
+                },
                 Some(Err(())) => {
-                    let rem = casted_lexer.remainder();
-                    println!("result: {:?}", result);
-                    panic!("error when tokenizing. remainder: {}{}", casted_lexer.slice(), &rem[..usize::min(100, rem.len())]);
+                    //leave `lex` pointing at the invalid token, so that it can be reported
+                    *lex = casted_lexer.morph();
+                    return None;
                 }
             }
         }
 
         *lex = casted_lexer.morph();
 
-        result
+        Some(result)
     }
 
     pub fn as_punctuator(&self) -> Option<Punctuator> {
@@ -118,13 +141,21 @@ impl Token {
             None
         }
     }
+
+    /// How the token would be written in C, where numbers and chars keep their original text
+    pub fn spelling(&self) -> String {
+        match self {
+            Token::STRING(string) => string.spelling(),
+            token => token.to_string(),
+        }
+    }
 }
 
 impl Display for Token {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Token::STRING(x) => x.fmt(f),
-            Token::NUMBER(x) => x.fmt(f),
+            Token::NUMBER(x) => x.spelling.fmt(f),
             Token::PUNCTUATOR(x) => x.fmt(f),
             Token::TYPESPECIFIER(x) => x.fmt(f),
             Token::STORAGESPECIFIER(x) => x.fmt(f),
@@ -135,3 +166,21 @@ impl Display for Token {
         }
     }
 }
+
+
+/// A number or char literal, and how it was written in the source code
+/// 
+/// The spelling is kept so that `#`, `##` and `-E` write `0x10` or `'a'` rather than their value
+#[derive(Debug, Clone, PartialEq)]
+pub struct NumberToken {
+    pub value: NumberLiteral,
+    pub spelling: String,
+}
+
+impl NumberToken {
+    /// Creates a number that was not written in the source code, so is spelled as its value
+    pub fn from_value(value: NumberLiteral) -> Self {
+        let spelling = value.to_string();
+        Self { value, spelling }
+    }
+}
\ No newline at end of file
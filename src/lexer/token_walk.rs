@@ -1,12 +1,16 @@
-use crate::parse_data::ParseData;
+use crate::{diagnostics::diagnostic::Diagnostic, parse_data::ParseData, preprocessor::pragma::Packing};
 
-use super::{token::Token, token_savepoint::TokenQueueSlice, punctuator::Punctuator};
+use super::{punctuator::Punctuator, source_span::{Located, SourceSpan}, token::{NumberToken, Token}, token_savepoint::TokenQueueSlice};
 
 /**
  * this steps through each token
  */
 pub struct TokenQueue {
     pub(crate) tokens: Vec<Token>,
+    /// where each token in `tokens` came from
+    spans: Vec<SourceSpan>,
+    /// the alignment from `#pragma pack` at each token
+    packing: Packing,
 }
 
 /**
@@ -54,12 +58,49 @@ impl TokenSearchType {
 }
 
 impl TokenQueue {
-    pub fn new(token_list: Vec<Token>) -> TokenQueue{
+    pub fn new(token_list: Vec<Located<Token>>) -> TokenQueue{
+        let (tokens, spans) = token_list.into_iter()
+            .map(|tok| (tok.data, tok.span))
+            .unzip();
+
         TokenQueue {
-            tokens:token_list,
+            tokens,
+            spans,
+            packing: Packing::default(),
         }
     }
 
+    pub fn with_packing(self, packing: Packing) -> TokenQueue {
+        TokenQueue { packing, ..self }
+    }
+
+    /**
+     * returns the largest alignment that `#pragma pack` allows for struct members at the token at `idx`, or None for natural alignment
+     */
+    pub fn packing_at(&self, idx: usize) -> Option<u64> {
+        self.packing.at(idx)
+    }
+
+    /**
+     * returns where the token at `idx` came from
+     */
+    pub fn get_token_span(&self, idx: usize) -> &SourceSpan {
+        &self.spans[idx]
+    }
+
+    /**
+     * returns the span from the first to the last token in the slice
+     * if the slice is empty, it points to where the slice starts
+     */
+    pub fn get_span(&self, slice: &TokenQueueSlice) -> SourceSpan {
+        assert!(!self.spans.is_empty());
+        let last_idx = self.spans.len() - 1;
+        let start_idx = slice.index.min(last_idx);
+        let end_idx = slice.max_index.min(self.spans.len()).saturating_sub(1).clamp(start_idx, last_idx);
+
+        self.spans[start_idx].to(&self.spans[end_idx])
+    }
+
     /**
      * returns the next token that needs to be consumed
      */
@@ -119,6 +160,55 @@ impl TokenQueue {
         return next;
     }
 
+    /**
+     * makes an error saying that `expected` should be at `location`, pointing at what was found instead
+     */
+    pub fn expected_error(&self, location: &TokenQueueSlice, expected: &str) -> Diagnostic {
+        let span = self.get_span(&TokenQueueSlice { index: location.index, max_index: location.index + 1 });
+        let found = match self.peek_raw(location) {
+            Some(tok) => format!("found `{}`", tok),
+            None => "found nothing".to_string(),
+        };
+
+        Diagnostic::error(format!("expected {}", expected)).with_primary(span, found)
+    }
+
+    /**
+     * finds where parsing can carry on after an error at the start of `slice`
+     * this skips past the next `;`, or past the next `{ }` block (and a `;` after it), whichever comes first
+     * at least one token is always skipped
+     */
+    pub fn recovery_point(&self, slice: &TokenQueueSlice) -> TokenQueueSlice {
+        let max_index = self.tokens.len().min(slice.max_index);
+        let mut bracket_depth = 0;
+
+        for i in slice.index..max_index {
+            match &self.tokens[i] {
+                Token::PUNCTUATOR(Punctuator::OPENCURLY | Punctuator::OPENSQUARE | Punctuator::OPENSQUIGGLY) => bracket_depth += 1,
+                Token::PUNCTUATOR(Punctuator::CLOSECURLY | Punctuator::CLOSESQUARE) => bracket_depth -= 1,
+                Token::PUNCTUATOR(Punctuator::CLOSESQUIGGLY) => {
+                    bracket_depth -= 1;
+                    if bracket_depth <= 0 {
+                        //end of a block, so skip a trailing semicolon like the one in `struct x {...};`
+                        let after_block = if self.tokens.get(i+1) == Some(&Token::PUNCTUATOR(Punctuator::SEMICOLON)) && i+1 < max_index {i+2} else {i+1};
+                        return TokenQueueSlice { index: after_block, max_index: slice.max_index };
+                    }
+                },
+                Token::PUNCTUATOR(Punctuator::SEMICOLON) if bracket_depth <= 0 => {
+                    return TokenQueueSlice { index: i+1, max_index: slice.max_index };
+                },
+                _ => {}
+            }
+
+            if bracket_depth < 0 {
+                //stray close bracket, so skip just that
+                return TokenQueueSlice { index: i+1, max_index: slice.max_index };
+            }
+        }
+
+        TokenQueueSlice { index: max_index.max(slice.index), max_index: slice.max_index }
+    }
+
     pub fn display_slice(&self, slice: &TokenQueueSlice) -> String {
         let max_idx = slice.max_index.min(self.tokens.len());//whichever is smaller: list size, slice max index
 
@@ -363,7 +453,7 @@ fn substitute_token(original: Token, scope_data: &ParseData) -> Token {
     match &original {
         Token::IDENTIFIER(x) => {
             if let Some(enum_value) = scope_data.enums.try_get_variant(&x) {
-                Token::NUMBER(enum_value.clone())
+                Token::NUMBER(NumberToken::from_value(enum_value.clone()))
             } else {
                 original
             }
@@ -1,6 +1,9 @@
-use std::{env, path::{Path, PathBuf}};
+use std::{env, path::{Path, PathBuf}, process};
 
 use clap::{arg, command, Arg, ArgAction};
+use colored::Colorize;
+
+use crate::{compilation_error::CompilationError, compile::{LinkOptions, OutputKind}, debugging::{DumpOptions, DumpTarget}, preprocessor::preprocess_options::PreprocessorOptions, diagnostics::{diagnostic::Diagnostic, render::render_all, warnings::WarningOptions}};
 
 mod compound_statement;
 mod statement;
@@ -43,6 +46,7 @@ mod args_handling;
 pub mod goto_and_labels;
 pub mod union_definition;
 pub mod generate_ir_traits;
+pub mod diagnostics;
 
 fn main() {
 
@@ -50,32 +54,265 @@ fn main() {
         .arg(
             Arg::new("no libc")
             .long("no-libc")
+            .help("Don't link with libc, so stop once the object files are made")
             .action(ArgAction::SetTrue)
         )
         .arg(
             Arg::new("output file")
             .short('o')
             .long("output")
-            .default_value("a.out")
+            .help("Where to put the output, which is a.out for binaries, or named after the input otherwise")
+        )
+        .arg(
+            Arg::new("object only")
+            .short('c')
+            .help("Compile and assemble, but don't link")
+            .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("assembly only")
+            .short('S')
+            .help("Compile to NASM assembly, but don't assemble")
+            .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("emit ir")
+            .long("emit-ir")
+            .help("Compile to textual IR, which can be compiled later by passing the .ir file as an input")
+            .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("preprocess only")
+            .short('E')
+            .help("Print the preprocessed code, and don't compile it")
+            .action(ArgAction::SetTrue)
         )
         .arg(
             Arg::new("debug info")
             .short('d')
             .long("debug-info")
+            .value_name("file")
+            .help("Save the functions, global variables and syntax tree of each C file to a JSON file")
+        )
+        .arg(
+            Arg::new("dump tokens")
+            .long("dump-tokens")
+            .value_name("file")
+            .help("Write the preprocessed tokens of each C file to stderr, or to a file with --dump-tokens=file")
+            .num_args(0..=1)
+            .require_equals(true)
+            .default_missing_value("-")
+        )
+        .arg(
+            Arg::new("dump ast")
+            .long("dump-ast")
+            .value_name("file")
+            .help("Write the syntax tree of each C file to stderr, or to a file with --dump-ast=file")
+            .num_args(0..=1)
+            .require_equals(true)
+            .default_missing_value("-")
+        )
+        .arg(
+            Arg::new("dump ir")
+            .long("dump-ir")
+            .value_name("file")
+            .help("Write the intermediate representation of each C file to stderr, or to a file with --dump-ir=file")
+            .num_args(0..=1)
+            .require_equals(true)
+            .default_missing_value("-")
+        )
+        .arg(
+            Arg::new("dump asm")
+            .long("dump-asm")
+            .value_name("file")
+            .help("Write the generated assembly of each C file to stderr, or to a file with --dump-asm=file")
+            .num_args(0..=1)
+            .require_equals(true)
+            .default_missing_value("-")
+        )
+        .arg(
+            Arg::new("f options")
+            .short('f')
+            .value_name("option")
+            .help("Stop reporting errors after N of them with -fmax-errors=N, where 0 means no limit. -fPIC and -fPIE are rejected, and other -f options, like -fno-builtin, are ignored")
+            .action(ArgAction::Append)
+        )
+        .arg(
+            Arg::new("warnings")
+            .short('W')
+            .value_name("warning")
+            .help("Turn on warnings with -Wall, -Wextra or -W<name>, turn them off with -Wno-<name>, or make them errors with -Werror")
+            .action(ArgAction::Append)
+        )
+        .arg(
+            Arg::new("include folders")
+            .short('I')
+            .value_name("dir")
+            .help("Search for headers in this folder")
+            .action(ArgAction::Append)
+        )
+        .arg(
+            Arg::new("system include folders")
+            .long("isystem")
+            .value_name("dir")
+            .help("Search for headers in this folder, after any -I folders (written -isystem like gcc)")
+            .action(ArgAction::Append)
+        )
+        .arg(
+            Arg::new("forced includes")
+            .long("include")
+            .value_name("file")
+            .help("Include this file before the first line of each C file (written -include like gcc)")
+            .action(ArgAction::Append)
+        )
+        .arg(
+            Arg::new("defines")
+            .short('D')
+            .value_name("name[=value]")
+            .help("Define a macro, as 1 if there is no value")
+            .action(ArgAction::Append)
+        )
+        .arg(
+            Arg::new("undefines")
+            .short('U')
+            .value_name("name")
+            .help("Undefine a macro")
+            .action(ArgAction::Append)
+        )
+        .arg(
+            Arg::new("library folders")
+            .short('L')
+            .value_name("dir")
+            .help("Search for libraries in this folder")
+            .action(ArgAction::Append)
+        )
+        .arg(
+            Arg::new("libraries")
+            .short('l')
+            .value_name("library")
+            .help("Link with a library, like -lm for libm")
+            .action(ArgAction::Append)
         )
         .arg(
             Arg::new("inputs")
-            .help("C source files")
-            .default_value("test.c")
-            .num_args(1)
+            .help("C source files, textual IR .ir files, NASM .asm or .s files, and object files to link")
+            .required(true)
+            .num_args(1..)
+            .action(ArgAction::Append)
         )
-        .get_matches();
+        .get_matches_from(env::args().flat_map(gcc_style_long_option));
+
+    //like gcc, the option that stops earliest wins
+    let output_kind = if matches.get_flag("preprocess only") {
+        OutputKind::Preprocessed
+    } else if matches.get_flag("emit ir") {
+        OutputKind::IR
+    } else if matches.get_flag("assembly only") {
+        OutputKind::Assembly
+    } else if matches.get_flag("object only") || matches.get_flag("no libc") {
+        OutputKind::Object
+    } else {
+        OutputKind::Executable
+    };
+    let output_path = matches.get_one::<String>("output file").map(PathBuf::from);
+    let input_paths: Vec<PathBuf> = matches.get_many::<String>("inputs").unwrap().map(PathBuf::from).collect();
+    let debug_out_path = matches.get_one::<String>("debug info").map(PathBuf::from);
+    let dump_target = |name: &str| matches.get_one::<String>(name).map(|x| DumpTarget::from_flag(x));
+    let dump_options = DumpOptions {
+        tokens: dump_target("dump tokens"),
+        ast: dump_target("dump ast"),
+        ir: dump_target("dump ir"),
+        asm: dump_target("dump asm"),
+    };
+    let max_errors = parse_f_options(matches.get_many::<String>("f options").unwrap_or_default()).unwrap_or_else(|err| {
+        eprintln!("{}{}", "error: ".red().bold(), err);
+        process::exit(1);
+    });
+    let warning_flags = matches.get_many::<String>("warnings").unwrap_or_default().map(|x| x.as_str());
+    let warning_options = WarningOptions::from_flags(warning_flags).unwrap_or_else(|err| {
+        eprintln!("{}{}", "error: ".red().bold(), err);
+        process::exit(1);
+    });
+
+    let mut preprocessor_options = PreprocessorOptions::default();
+    for folder in matches.get_many::<String>("include folders").unwrap_or_default() {
+        preprocessor_options.add_include_folder(PathBuf::from(folder));
+    }
+    for folder in matches.get_many::<String>("system include folders").unwrap_or_default() {
+        preprocessor_options.add_system_include_folder(PathBuf::from(folder));
+    }
+    for file in matches.get_many::<String>("forced includes").unwrap_or_default() {
+        preprocessor_options.add_forced_include(PathBuf::from(file));
+    }
+    //-D and -U are applied in the order they were written, so later ones win
+    let mut macro_options: Vec<(usize, bool, &String)> = Vec::new();
+    for (name, is_define) in [("defines", true), ("undefines", false)] {
+        if let (Some(indices), Some(values)) = (matches.indices_of(name), matches.get_many::<String>(name)) {
+            macro_options.extend(indices.zip(values).map(|(index, value)| (index, is_define, value)));
+        }
+    }
+    macro_options.sort_by_key(|(index, _, _)| *index);
+    for (_, is_define, value) in macro_options {
+        if is_define {
+            preprocessor_options.define(value).unwrap_or_else(|err| {
+                eprintln!("{}{}", "error: ".red().bold(), err);
+                process::exit(1);
+            });
+        } else {
+            preprocessor_options.undefine(value);
+        }
+    }
+
+    let link_options = LinkOptions {
+        library_folders: matches.get_many::<String>("library folders").unwrap_or_default().map(PathBuf::from).collect(),
+        libraries: matches.get_many::<String>("libraries").unwrap_or_default().cloned().collect(),
+    };
 
-    let do_linking = !matches.get_flag("no libc");
-    let output_path = PathBuf::from(matches.get_one::<String>("output file").unwrap());
-    let input_path = PathBuf::from(matches.get_one::<String>("inputs").unwrap());
-    let debug_out_path = matches.get_one::<String>("debug info").map(|x| PathBuf::from(x));
 
+    //bugs in the compiler should not look like a rust crash to the user
+    std::panic::set_hook(Box::new(|info| {
+        eprintln!("{}{}", "error: internal compiler error: ".red().bold(), info);
+    }));
+
+    if let Err(errors) = compile::compile_all(&input_paths, output_path.as_deref(), output_kind, debug_out_path.as_deref(), &preprocessor_options, &dump_options, &link_options, &warning_options) {
+        for err in errors {
+            match err {
+                CompilationError::PARSE(diagnostics) => eprint!("{}", render_all(&diagnostics, max_errors)),
+                err => eprintln!("{}", err),
+            }
+        }
+        process::exit(1);
+    }
+}
+
+/// gcc writes some long options with one dash, like `-isystem dir` and `-include file`
+/// 
+/// This rewrites them with two dashes so that clap understands them, and splits `-isystemdir` into `--isystem dir`
+fn gcc_style_long_option(arg: String) -> Vec<String> {
+    for option in ["isystem", "include"] {
+        if let Some(value) = arg.strip_prefix(&format!("-{}", option)) {
+            let long_option = format!("--{}", option);
+            return if value.is_empty() {vec![long_option]} else {vec![long_option, value.to_string()]};
+        }
+    }
+
+    vec![arg]
+}
+
+/// Reads the `-f` options, returning N from the last `-fmax-errors=N`, or 0 if there isn't one
+/// 
+/// Position independent code can't be generated, so `-fPIC`, `-fpic`, `-fPIE` and `-fpie` are errors instead of failing later when linking
+///
+/// Other `-f` options, like `-fno-builtin`, don't change what this compiler does, so they are ignored with a warning
+fn parse_f_options<'a>(options: impl Iterator<Item = &'a String>) -> Result<usize, String> {
+    let mut max_errors = 0;
+    for option in options {
+        match option.strip_prefix("max-errors=") {
+            Some(count) => max_errors = count.parse().map_err(|_| format!("invalid error count {}", count))?,
+            None if ["PIC", "pic", "PIE", "pie"].contains(&option.as_str()) => return Err(format!("-f{} is not supported, as this compiler cannot generate position independent code", option)),
+            None => eprint!("{}", render_all(&[Diagnostic::warning(format!("ignoring unsupported option -f{}", option))], 0)),
+        }
+    }
 
-    compile::compile(&input_path, &output_path, &[], do_linking, debug_out_path).unwrap();
+    Ok(max_errors)
 }
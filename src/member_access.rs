@@ -1,16 +1,18 @@
-use crate::{asm_gen_data::{AsmData, GetStructUnion, GlobalAsmData}, assembly::{assembly::IRCode, operand::{immediate::ToImmediate, Storage, PTR_SIZE}, operation::IROperation}, data_type::{base_type::{BaseType, IntegerType, ScalarType}, recursive_data_type::DataType}, debugging::ASTDisplay, expression::expression::Expression, expression_visitors::expr_visitor::ExprVisitor, generate_ir_traits::{GetAddress, GetType}};
+use serde::Serialize;
+use crate::{asm_gen_data::{AsmData, GetStructUnion, GlobalAsmData}, assembly::{assembly::IRCode, operand::{immediate::ToImmediate, IRMemOperand, IROperand, Storage, PTR_SIZE}, operation::IROperation}, data_type::{base_type::{BaseType, IntegerType, ScalarType}, recursive_data_type::DataType}, debugging::ASTDisplay, expression::expression::Expression, expression_visitors::expr_visitor::ExprVisitor, generate_ir_traits::{GenerateIR, GetAddress, GetType}, lexer::source_span::SourceSpan};
 use memory_size::MemorySize;
 use stack_management::simple_stack_frame::SimpleStackFrame;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct MemberAccess {
     base_tree: Box<Expression>,//need a tree or something to represent what gives me back the struct/union
     member_name: String,
+    span: SourceSpan,
 }
 
 impl MemberAccess {
-    pub fn new(base_tree: Expression, member_name: String) -> MemberAccess {
-        MemberAccess { base_tree: Box::new(base_tree), member_name }
+    pub fn new(base_tree: Expression, member_name: String, span: SourceSpan) -> MemberAccess {
+        MemberAccess { base_tree: Box::new(base_tree), member_name, span }
     }
 
     pub fn accept<V: ExprVisitor>(&self, visitor: &mut V) -> V::Output {
@@ -23,6 +25,32 @@ impl MemberAccess {
     pub fn get_member_name(&self) -> &str {
         &self.member_name
     }
+    pub fn get_span(&self) -> &SourceSpan {
+        &self.span
+    }
+
+    /// Gets the type of the member including its qualifiers, which also has any qualifiers from the struct/union it is in
+    pub fn get_qualified_type(&self, asm_data: &AsmData) -> DataType {
+        let base_tree_type = self.base_tree.get_type(asm_data);//get type of the tree that returns the struct/union
+
+        let member_type = match base_tree_type {
+            DataType::RAW(BaseType::Struct(struct_name)) => {
+                let (member_decl, _) = asm_data.get_struct(&struct_name).get_member_data(&self.member_name);//get the type of the member
+
+                member_decl.data_type.clone()
+            }
+
+            DataType::RAW(BaseType::Union(union_name)) => {
+                asm_data.get_union(&union_name)
+                .get_member_data(&self.member_name)
+                .data_type.clone()
+            }
+            _ => panic!("this base type doesn't have members?")
+        };
+
+        //a member of a const struct is const too
+        member_type.add_qualifiers(self.base_tree.get_qualifiers(asm_data))
+    }
 }
 
 impl GetAddress for MemberAccess {
@@ -61,24 +89,30 @@ impl GetAddress for MemberAccess {
     }
 }
 
-impl GetType for MemberAccess {
-    fn get_type(&self, asm_data: &AsmData) -> DataType {
-        let base_tree_type = self.base_tree.get_type(asm_data);//get type of the tree that returns the struct/union
-
-        match base_tree_type {
-            DataType::RAW(BaseType::Struct(struct_name)) => {
-                let (member_decl, _) = asm_data.get_struct(&struct_name).get_member_data(&self.member_name);//get the type of the member
+impl GenerateIR for MemberAccess {
+    fn generate_ir(&self, asm_data: &AsmData, stack_data: &mut SimpleStackFrame, global_asm_data: &GlobalAsmData) -> (IRCode, Option<stack_management::stack_item::StackItemKey>) {
+        let mut result = IRCode::make_empty();
+        let member_size = self.get_type(asm_data).memory_size(asm_data);
+
+        //find where the member is
+        let (member_address_asm, member_ptr) = self.get_address(asm_data, stack_data, global_asm_data);
+        result.merge(&member_address_asm);
+
+        //copy the member out of the struct/union
+        let resultant_location = stack_data.allocate(member_size);
+        result.add_instruction(IROperation::MOV {
+            from: IROperand::Memory(IRMemOperand::IndirectAddress { pointer_location: Box::new(IRMemOperand::Stack { base: member_ptr }) }),
+            to: IRMemOperand::Stack { base: resultant_location },
+            size: member_size,
+        });
 
-                member_decl.data_type.clone()
-            }
+        (result, Some(resultant_location))
+    }
+}
 
-            DataType::RAW(BaseType::Union(union_name)) => {
-                asm_data.get_union(&union_name)
-                .get_member_data(&self.member_name)
-                .data_type.clone()
-            }
-            _ => panic!("this base type doesn't have members?")
-        }
+impl GetType for MemberAccess {
+    fn get_type(&self, asm_data: &AsmData) -> DataType {
+        self.get_qualified_type(asm_data).unqualified()
     }
 }
 
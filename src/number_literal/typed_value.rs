@@ -1,8 +1,9 @@
 use std::{cmp::Ordering, fmt::Display, i128, ops::{Add, BitAnd, BitOr, BitXor, Div, Mul, Neg, Rem, Shl, Shr, Sub}};
 use colored::Colorize;
+use serde::Serialize;
 use crate::{assembly::{assembly::IRCode, comparison::ComparisonKind, operand::{IRMemOperand, IROperand, Storage}, operation::IROperation}, data_type::{base_type::{FloatType, IntegerType, ScalarType}, recursive_data_type::{calculate_promoted_type, calculate_unary_type}}, expression_visitors::expr_visitor::ExprVisitor, generate_ir_traits::GenerateIR};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub enum NumberLiteral {
     INTEGER {data: i128, data_type: IntegerType},
     FLOAT {data: f64, data_type: FloatType},
@@ -36,25 +37,15 @@ impl NumberLiteral {
         )
     }
 
-    /// Generates the `x db 10` - type commands
-    pub fn generate_data_definition_instruction(&self, variable_name: &str) -> String {
+    /// The bytes that store this number in memory, in little endian order
+    pub fn data_bytes(&self) -> Vec<u8> {
         match self {
-            Self::INTEGER{data, data_type} => {
-                //store the integer as a list of bytes
-                format!("{} db {}",
-                    variable_name,
-
-                    data.to_le_bytes()[..data_type.memory_size().size_bytes() as usize].iter()
-                    .map(|x| x.to_string())
-                    .collect::<Vec<String>>()
-                    .join(",")
-                )
-            },
+            Self::INTEGER{data, data_type} => data.to_le_bytes()[..data_type.memory_size().size_bytes() as usize].to_vec(),
             
             Self::FLOAT{data_type, data} => {
                 match data_type {
-                    FloatType::F32 => format!("{} dd {}", variable_name, (*data as f32).to_bits()),
-                    FloatType::F64 => format!("{} dq {}", variable_name, data.to_bits()),
+                    FloatType::F32 => (*data as f32).to_le_bytes().to_vec(),
+                    FloatType::F64 => data.to_le_bytes().to_vec(),
                 }
             }
         }
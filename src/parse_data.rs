@@ -1,9 +1,10 @@
-use std::collections::HashMap;
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
 
 
+use serde::{ser::SerializeStruct, Serialize, Serializer};
 use uuid::Uuid;
 
-use crate::{data_type::recursive_data_type::DataType, enum_definition::EnumList, function_declaration::FunctionDeclaration, struct_definition::{StructIdentifier, UnpaddedStructDefinition}, union_definition::{UnionDefinition, UnionIdentifier}};
+use crate::{data_type::recursive_data_type::DataType, diagnostics::diagnostic::Diagnostic, enum_definition::EnumList, function_declaration::FunctionDeclaration, struct_definition::{StructIdentifier, UnpaddedStructDefinition}, union_definition::{UnionDefinition, UnionIdentifier}};
 
 #[derive(Debug)]
 pub struct ParseData {
@@ -14,6 +15,22 @@ pub struct ParseData {
     unions: Vec<(UnionIdentifier, UnionDefinition)>,
 
     local_symbol_table: Vec<(String, DataType)>,//this is filled slowly, so do not read from it
+
+    /// errors found while parsing, shared with every nested scope so that they can be reported at the end
+    diagnostics: Rc<RefCell<Vec<Diagnostic>>>,
+}
+
+//written by hand, as only the types are needed in the syntax tree, and the diagnostics can't be serialised
+//every type that can be used in the scope is included, even if an outer scope defined it
+impl Serialize for ParseData {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut scope = serializer.serialize_struct("ParseData", 4)?;
+        scope.serialize_field("structs", &self.structs)?;
+        scope.serialize_field("unions", &self.unions)?;
+        scope.serialize_field("enums", &self.enums)?;
+        scope.serialize_field("typedefs", &self.typedefs)?;
+        scope.end()
+    }
 }
 
 impl ParseData {
@@ -25,6 +42,7 @@ impl ParseData {
             structs: Vec::new(),
             unions: Vec::new(),
             local_symbol_table: Vec::new(),
+            diagnostics: Rc::new(RefCell::new(Vec::new())),
         }
     }
 
@@ -39,15 +57,30 @@ impl ParseData {
             structs: self.structs.clone(),
             unions: self.unions.clone(),
             local_symbol_table: Vec::new(),
+            diagnostics: self.diagnostics.clone(),
         }
     }
 
+    /// Saves an error, so that parsing can carry on and find more errors
+    pub fn report_error(&self, diagnostic: Diagnostic) {
+        self.diagnostics.borrow_mut().push(diagnostic);
+    }
+    /// How many errors have been reported so far, from any scope
+    pub fn error_count(&self) -> usize {
+        self.diagnostics.borrow().len()
+    }
+    /// Removes all the reported errors
+    pub fn take_diagnostics(&self) -> Vec<Diagnostic> {
+        self.diagnostics.take()
+    }
+
     pub fn func_declarations_as_vec(&self) -> Vec<FunctionDeclaration> {
         self.function_decls.clone()
     }
     
     pub fn add_declaration(&mut self, toadd: FunctionDeclaration) {
-        if self.get_function_declaration(&toadd.function_name).is_some() {
+        if let Some(existing) = self.function_decls.iter_mut().rev().find(|func| func.function_name == toadd.function_name) {
+            existing.noreturn |= toadd.noreturn;//_Noreturn on any declaration applies to the function
             return;//already declared, skip it
         }
 
@@ -0,0 +1,271 @@
+//! Expands macros as the tokens are read, following the C11 rules for rescanning replacements
+//!
+//! Each token carries a hide-set of the macros that it came from, which must not expand it again (it is "painted blue").
+//! Replacements are put back in front of the rest of the input, so a macro call can continue past the end of a replacement,
+//! or past a line or directive.
+
+use std::{collections::{BTreeSet, HashMap, VecDeque}, rc::Rc};
+
+use crate::{diagnostics::diagnostic::Diagnostic, lexer::{punctuator::Punctuator, source_span::{Located, SourceSpan}, token::Token}, preprocessor::{macro_operators::replace_params, preprocess_context::PreprocessContext, preprocess_token::MacroFunction}};
+
+/// The names of the macros that a token came from, which must not expand it again
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HideSet(Rc<BTreeSet<String>>);
+
+impl HideSet {
+    pub fn contains(&self, name: &str) -> bool {
+        self.0.contains(name)
+    }
+    /// Makes a copy of this hide-set, with `name` added
+    pub fn with(&self, name: &str) -> HideSet {
+        let mut names = (*self.0).clone();
+        names.insert(name.to_string());
+        HideSet(Rc::new(names))
+    }
+    pub fn union(&self, other: &HideSet) -> HideSet {
+        HideSet(Rc::new(self.0.union(&other.0).cloned().collect()))
+    }
+    pub fn intersection(&self, other: &HideSet) -> HideSet {
+        HideSet(Rc::new(self.0.intersection(&other.0).cloned().collect()))
+    }
+}
+
+/// A token that is being macro expanded
+#[derive(Debug, Clone)]
+pub struct HiddenToken {
+    pub token: Located<Token>,
+    pub hide_set: HideSet,
+}
+
+impl HiddenToken {
+    /// A token straight from the source code, which has not been expanded from anything
+    pub fn new(token: Located<Token>) -> Self {
+        HiddenToken { token, hide_set: HideSet::default() }
+    }
+}
+
+/// Where the expander reads tokens from, which also knows which macros are defined
+pub trait TokenSource {
+    /// Gets the next token that has not been macro expanded yet, or None at the end of the input
+    fn next_token(&mut self) -> Result<Option<Located<Token>>, Diagnostic>;
+    fn context(&self) -> &PreprocessContext;
+    /// Runs the text of a `_Pragma` operator, found at `span`
+    fn run_pragma(&mut self, text: &str, span: &SourceSpan) -> Result<(), Diagnostic>;
+}
+
+/// A source with no more tokens, for expanding tokens that are already known, like macro args
+struct NoMoreTokens<'a> {
+    ctx: &'a PreprocessContext,
+}
+
+impl TokenSource for NoMoreTokens<'_> {
+    fn next_token(&mut self) -> Result<Option<Located<Token>>, Diagnostic> {
+        Ok(None)
+    }
+    fn context(&self) -> &PreprocessContext {
+        self.ctx
+    }
+    fn run_pragma(&mut self, _: &str, _: &SourceSpan) -> Result<(), Diagnostic> {
+        unreachable!("tokens expanded by themselves never look for `_Pragma`")
+    }
+}
+
+/// Expands the macros in `tokens`, when they are all the tokens there are, like the condition of an `#if`
+pub fn expand_macros(tokens: Vec<Located<Token>>, ctx: &PreprocessContext) -> Result<Vec<Located<Token>>, Diagnostic> {
+    let expanded = expand_hidden(tokens.into_iter().map(HiddenToken::new).collect(), ctx)?;
+    Ok(expanded.into_iter().map(|tok| tok.token).collect())
+}
+
+/// Fully expands `tokens` by themselves, keeping their hide-sets
+fn expand_hidden(tokens: Vec<HiddenToken>, ctx: &PreprocessContext) -> Result<Vec<HiddenToken>, Diagnostic> {
+    let mut expander = MacroExpander { source: NoMoreTokens { ctx }, pending: tokens.into() };
+    let mut result = Vec::new();
+    while let Some(tok) = expander.next_hidden()? {
+        result.push(tok);
+    }
+
+    Ok(result)
+}
+
+pub struct MacroExpander<S: TokenSource> {
+    source: S,
+    /// Tokens to scan before reading any more from `source`, like the replacement of the macro that was just expanded
+    pending: VecDeque<HiddenToken>,
+}
+
+impl<S: TokenSource> MacroExpander<S> {
+    pub fn new(source: S) -> Self {
+        MacroExpander { source, pending: VecDeque::new() }
+    }
+
+    pub fn source_mut(&mut self) -> &mut S {
+        &mut self.source
+    }
+    pub fn into_source(self) -> S {
+        self.source
+    }
+
+    /// Gets the next token after expanding macros, or None at the end of the input
+    /// 
+    /// `_Pragma("text")` is run as it is found, as it may have come from a macro
+    pub fn next_token(&mut self) -> Result<Option<Located<Token>>, Diagnostic> {
+        loop {
+            let Some(tok) = self.next_hidden()? else {
+                return Ok(None);
+            };
+            if tok.token.data != Token::IDENTIFIER("_Pragma".to_string()) {
+                return Ok(Some(tok.token));
+            }
+
+            let invalid_pragma = || Diagnostic::error("`_Pragma` must be followed by a string in brackets")
+                .with_primary(tok.token.span.clone(), "")
+                .with_help("use `_Pragma(\"text\")`");
+            let open_bracket = self.next_hidden()?.ok_or_else(invalid_pragma)?;
+            let text = self.next_hidden()?.ok_or_else(invalid_pragma)?;
+            let close_bracket = self.next_hidden()?.ok_or_else(invalid_pragma)?;
+
+            match (open_bracket.token.data, text.token.data, close_bracket.token.data) {
+                (Token::PUNCTUATOR(Punctuator::OPENCURLY), Token::STRING(text), Token::PUNCTUATOR(Punctuator::CLOSECURLY)) => {
+                    self.source.run_pragma(&text.text(), &tok.token.span.to(&close_bracket.token.span))?;
+                },
+                _ => return Err(invalid_pragma()),
+            }
+        }
+    }
+
+    fn next_unexpanded(&mut self) -> Result<Option<HiddenToken>, Diagnostic> {
+        match self.pending.pop_front() {
+            Some(tok) => Ok(Some(tok)),
+            None => Ok(self.source.next_token()?.map(HiddenToken::new)),
+        }
+    }
+
+    fn next_hidden(&mut self) -> Result<Option<HiddenToken>, Diagnostic> {
+        loop {
+            let Some(tok) = self.next_unexpanded()? else {
+                return Ok(None);
+            };
+            let name = match &tok.token.data {
+                Token::IDENTIFIER(name) if !tok.hide_set.contains(name) => name.clone(),
+                _ => return Ok(Some(tok)),//not a macro, or painted blue so that it is never expanded
+            };
+
+            if let Some(definition) = self.source.context().get_definition(&name, &tok.token.span) {
+                //simple macro
+                let body = definition.into_iter()
+                    .map(|body_tok| HiddenToken::new(Located { span: body_tok.span.expanded_by(&name, &tok.token.span), ..body_tok }))//remember where the replacement came from
+                    .collect();
+                let replacement = replace_params(body, &HashMap::new(), false, None, &Ok::<Vec<HiddenToken>, Diagnostic>)?;//only applies ##, as there are no params
+                self.push_replacement(replacement, &tok.hide_set.with(&name));
+                continue;
+            }
+
+            let Some(function) = self.source.context().get_macro_func(&name) else {
+                return Ok(Some(tok));
+            };
+
+            match self.next_unexpanded()? {
+                Some(open_bracket) if open_bracket.token.data == Token::PUNCTUATOR(Punctuator::OPENCURLY) => {
+                    let (replacement, close_bracket) = self.expand_function_call(&name, function, &tok, &open_bracket)?;
+                    //only macros that both the name and the `)` came from are hidden, as the call was made from both
+                    let hide_set = tok.hide_set.intersection(&close_bracket.hide_set).with(&name);
+                    self.push_replacement(replacement, &hide_set);
+                },
+                other => {
+                    //without a bracket, the name is just an identifier
+                    if let Some(other) = other {
+                        self.pending.push_front(other);
+                    }
+                    return Ok(Some(tok));
+                },
+            }
+        }
+    }
+
+    /// Puts a macro's replacement back in front of the input, so that it is scanned again along with the rest of the input
+    fn push_replacement(&mut self, replacement: Vec<HiddenToken>, hide_set: &HideSet) {
+        for mut tok in replacement.into_iter().rev() {
+            tok.hide_set = tok.hide_set.union(hide_set);
+            self.pending.push_front(tok);
+        }
+    }
+
+    /// Reads the args of a call to a function-like macro, after its `(`, and replaces the params in its body
+    /// 
+    /// Returns the replacement and the `)`
+    fn expand_function_call(&mut self, name: &str, function: MacroFunction, name_tok: &HiddenToken, open_bracket: &HiddenToken) -> Result<(Vec<HiddenToken>, HiddenToken), Diagnostic> {
+        let mut args = vec![Vec::new()];
+        let mut commas = Vec::new();//the comma after each arg, except the last
+        let mut depth = 0;
+        let close_bracket = loop {
+            let Some(tok) = self.next_unexpanded()? else {
+                return Err(Diagnostic::error(format!("unterminated call to macro `{}`", name))
+                    .with_primary(name_tok.token.span.to(&open_bracket.token.span), "this `(` is never closed"));
+            };
+            match &tok.token.data {
+                Token::PUNCTUATOR(Punctuator::CLOSECURLY) if depth == 0 => break tok,
+                Token::PUNCTUATOR(Punctuator::COMMA) if depth == 0 => {
+                    commas.push(tok);
+                    args.push(Vec::new());
+                },
+                data => {
+                    match data {
+                        Token::PUNCTUATOR(Punctuator::OPENCURLY) => depth += 1,
+                        Token::PUNCTUATOR(Punctuator::CLOSECURLY) => depth -= 1,
+                        _ => {}
+                    }
+                    args.last_mut().unwrap().push(tok);
+                },
+            }
+        };
+        //the macro is used from its name to the close bracket
+        let call_site = name_tok.token.span.to(&close_bracket.token.span);
+
+        let MacroFunction { params, variadic, body } = function;
+
+        //`()` is one empty arg, which is also how a call with no args looks
+        let given = if args.len() == 1 && args[0].is_empty() {0} else {args.len()};
+        let correct_count = if variadic.is_some() {
+            args.len() >= params.len()
+        } else {
+            args.len() == params.len() || (params.is_empty() && given == 0)
+        };
+        if !correct_count {
+            //same wording as gcc
+            let plural = |count: usize| if count == 1 {""} else {"s"};
+            let message = if given < params.len() {
+                let at_least = if variadic.is_some() {"at least "} else {""};
+                format!("macro \"{}\" requires {}{} argument{}, but only {} given", name, at_least, params.len(), plural(params.len()), given)
+            } else {
+                format!("macro \"{}\" passed {} argument{}, but takes just {}", name, given, plural(given), params.len())
+            };
+            return Err(Diagnostic::error(message).with_primary(call_site, ""));
+        }
+
+        let body = body.into_iter()
+            .map(|tok| HiddenToken::new(Located { span: tok.span.expanded_by(name, &call_site), ..tok }))//remember where the body came from
+            .collect();
+
+        let mut param_substitutions = HashMap::new();
+        for (param, arg) in params.iter().zip(args.iter()) {
+            param_substitutions.insert(param.clone(), arg.clone());
+        }
+        if let Some(variadic) = &variadic {
+            //every arg after the named ones goes in the variadic param, including the commas between them
+            let mut extra_args = Vec::new();
+            for (i, arg) in args.iter().enumerate().skip(params.len()) {
+                if i > params.len() {
+                    extra_args.push(commas[i - 1].clone());
+                }
+                extra_args.extend(arg.iter().cloned());
+            }
+            param_substitutions.insert(variadic.clone(), extra_args);
+        }
+
+        //args are expanded by themselves, before going in the body
+        let ctx = self.source.context();
+        let replacement = replace_params(body, &param_substitutions, true, variadic.as_deref(), &|arg| expand_hidden(arg, ctx))?;
+
+        Ok((replacement, close_bracket))
+    }
+}
\ No newline at end of file
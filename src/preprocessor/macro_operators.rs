@@ -0,0 +1,183 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use logos::Logos;
+
+use crate::{diagnostics::diagnostic::Diagnostic, lexer::{punctuator::Punctuator, source_span::{Located, SourceMap, SourcePosition, SourceSpan}, token::Token}, preprocessor::macro_expander::HiddenToken, string_literal::StringLiteral};
+
+/// Part of a macro's replacement, before `##` is applied
+enum ReplacementPiece {
+    /// A token from the body, or everything an argument was replaced with, which may be nothing
+    Tokens(Vec<HiddenToken>),
+    /// A `##`, which pastes the pieces either side of it
+    Paste(SourceSpan),
+}
+
+/// Replaces each parameter in a macro's body with its argument, then applies the `#` and `##` operators
+/// 
+/// Arguments are macro expanded by `expand_arg` first, unless they are the operand of `#` or `##`.
+/// `#` is only an operator in function-like macros, which is when `is_function` is set.
+/// `variadic` is the name of the param for extra args, which enables `__VA_OPT__` and the GNU `, ## __VA_ARGS__`
+pub fn replace_params(body: Vec<HiddenToken>, args: &HashMap<String, Vec<HiddenToken>>, is_function: bool, variadic: Option<&str>, expand_arg: &dyn Fn(Vec<HiddenToken>) -> Result<Vec<HiddenToken>, Diagnostic>) -> Result<Vec<HiddenToken>, Diagnostic> {
+    let is_paste = |tok: Option<&HiddenToken>| tok.is_some_and(|tok| tok.token.data == Token::PUNCTUATOR(Punctuator::HashHash));
+    //the variadic param as a token, and the extra args that it was given
+    let variadic_param = variadic.map(|name| (Token::IDENTIFIER(name.to_string()), &args[name]));
+
+    let mut pieces = Vec::new();
+    let mut i = 0;
+    while i < body.len() {
+        let tok = &body[i];
+        match &tok.token.data {
+            Token::PUNCTUATOR(Punctuator::HashHash) => {
+                if i == 0 || i == body.len() - 1 {
+                    return Err(Diagnostic::error("`##` cannot be at the start or end of a macro")
+                        .with_primary(tok.token.span.clone(), "there is nothing to paste on this side"));
+                }
+                pieces.push(ReplacementPiece::Paste(tok.token.span.clone()));
+            },
+
+            Token::PUNCTUATOR(Punctuator::Hash) if is_function => {
+                let arg = match body.get(i + 1).map(|tok| &tok.token.data) {
+                    Some(Token::IDENTIFIER(name)) => args.get(name),
+                    _ => None,
+                };
+                let Some(arg) = arg else {
+                    return Err(Diagnostic::error("`#` is not followed by a macro parameter").with_primary(tok.token.span.clone(), ""));
+                };
+                let span = tok.token.span.to(&body[i + 1].token.span);
+                pieces.push(ReplacementPiece::Tokens(vec![HiddenToken::new(Located { data: Token::STRING(stringify(arg)), span })]));
+                i += 1;//skip the parameter
+            },
+
+            //GNU extension: `, ## __VA_ARGS__` removes the comma when there are no extra args
+            Token::PUNCTUATOR(Punctuator::COMMA) if is_paste(body.get(i + 1)) &&
+                variadic_param.as_ref().is_some_and(|(param, _)| body.get(i + 2).is_some_and(|tok| tok.token.data == *param)) => {
+                let (_, extra_args) = variadic_param.as_ref().unwrap();
+                if !extra_args.is_empty() {
+                    //nothing is pasted, and like other pasted args, they are not expanded
+                    pieces.push(ReplacementPiece::Tokens(vec![tok.clone()]));
+                    pieces.push(ReplacementPiece::Tokens(extra_args.to_vec()));
+                }
+                i += 2;//skip the ## and the variadic param
+            },
+
+            Token::IDENTIFIER(name) if name == "__VA_OPT__" && is_function => {
+                let Some((_, extra_args)) = &variadic_param else {
+                    return Err(Diagnostic::error("`__VA_OPT__` can only be used in a variadic macro").with_primary(tok.token.span.clone(), ""));
+                };
+                let open_bracket = body.get(i + 1).filter(|tok| tok.token.data == Token::PUNCTUATOR(Punctuator::OPENCURLY));
+                let close_bracket = open_bracket.and_then(|_| matching_close_bracket(&body[i + 2..]).map(|len| i + 2 + len));
+                let Some(close_bracket) = close_bracket else {
+                    return Err(Diagnostic::error("`__VA_OPT__` must be followed by its contents in brackets")
+                        .with_primary(tok.token.span.clone(), "")
+                        .with_help("use `__VA_OPT__(tokens)`"));
+                };
+
+                //the contents are only used if the extra args are not empty
+                let has_extra_args = !expand_arg(extra_args.to_vec())?.is_empty();
+                let contents = if has_extra_args {
+                    replace_params(body[i + 2..close_bracket].to_vec(), args, is_function, variadic, expand_arg)?
+                } else {
+                    Vec::new()
+                };
+                pieces.push(ReplacementPiece::Tokens(contents));
+                i = close_bracket;
+            },
+
+            Token::IDENTIFIER(name) if args.contains_key(name) => {
+                //arguments are expanded before replacing the parameter, except when they are pasted
+                let arg = args[name].clone();
+                let pasted = is_paste(i.checked_sub(1).and_then(|prev| body.get(prev))) || is_paste(body.get(i + 1));
+                pieces.push(ReplacementPiece::Tokens(if pasted {arg} else {expand_arg(arg)?}));
+            },
+
+            _ => pieces.push(ReplacementPiece::Tokens(vec![tok.clone()])),
+        }
+        i += 1;
+    }
+
+    let mut result: Vec<HiddenToken> = Vec::new();
+    let mut previous_piece_len = 0;//an empty argument pastes as nothing, so remember if there is anything to paste onto
+    let mut pieces = pieces.into_iter();
+    while let Some(piece) = pieces.next() {
+        match piece {
+            ReplacementPiece::Tokens(tokens) => {
+                previous_piece_len = tokens.len();
+                result.extend(tokens);
+            },
+            ReplacementPiece::Paste(paste_span) => {
+                let Some(ReplacementPiece::Tokens(mut rhs)) = pieces.next() else {
+                    return Err(Diagnostic::error("`##` cannot be followed by another `##`").with_primary(paste_span, ""));
+                };
+
+                if previous_piece_len == 0 {
+                    previous_piece_len = rhs.len();
+                    result.extend(rhs);
+                } else if !rhs.is_empty() {
+                    let rhs_rest = rhs.split_off(1);
+                    let lhs = result.pop().unwrap();
+                    result.push(paste(lhs, rhs.pop().unwrap(), &paste_span)?);
+                    previous_piece_len = 1 + rhs_rest.len();
+                    result.extend(rhs_rest);
+                }
+            },
+        }
+    }
+
+    Ok(result)
+}
+
+/// Finds the index of the `)` that closes a bracket, where `tokens` starts just after the `(`
+fn matching_close_bracket(tokens: &[HiddenToken]) -> Option<usize> {
+    let mut depth = 1;
+    for (i, tok) in tokens.iter().enumerate() {
+        match tok.token.data {
+            Token::PUNCTUATOR(Punctuator::OPENCURLY) => depth += 1,
+            Token::PUNCTUATOR(Punctuator::CLOSECURLY) => depth -= 1,
+            _ => {}
+        }
+        if depth == 0 {
+            return Some(i);
+        }
+    }
+
+    None
+}
+
+/// Turns the tokens of a macro argument into a string literal, for the `#` operator
+/// 
+/// Whitespace between tokens becomes a single space, and strings keep their speechmarks and escape sequences.
+/// Numbers and chars are written as they were in the source, so `0x10` stays as `0x10`
+fn stringify(arg: &[HiddenToken]) -> StringLiteral {
+    let mut text = String::new();
+    for (i, tok) in arg.iter().enumerate() {
+        if i > 0 && !are_touching(&arg[i - 1].token.span, &tok.token.span) {
+            text.push(' ');
+        }
+        text.push_str(&tok.token.data.spelling());
+    }
+
+    StringLiteral::new_from_raw(text.chars())
+}
+
+/// Joins two tokens into one with the `##` operator, by lexing their text again
+/// 
+/// The new token is hidden from macros that both sides were hidden from
+fn paste(lhs: HiddenToken, rhs: HiddenToken, paste_span: &SourceSpan) -> Result<HiddenToken, Diagnostic> {
+    let hide_set = lhs.hide_set.intersection(&rhs.hide_set);
+    let (lhs, rhs) = (lhs.token, rhs.token);
+    let text = format!("{}{}", lhs.data.spelling(), rhs.data.spelling());
+    //the pasted token gets the span of both sides, so the positions in this map are never used
+    let source_map = SourceMap::new(PathBuf::new(), vec![SourcePosition { line: 1, column: 1 }; text.len() + 1]);
+    let mut lexer = Token::lexer_with_extras(&text, source_map);
+
+    match (lexer.next(), lexer.next()) {
+        (Some(Ok(data)), None) if data != Token::NEWLINE => Ok(HiddenToken { token: Located { data, span: lhs.span.to(&rhs.span) }, hide_set }),
+        _ => Err(Diagnostic::error(format!("pasting `{}` and `{}` does not give a valid token", lhs.data, rhs.data))
+            .with_primary(paste_span.clone(), "")),
+    }
+}
+
+/// Whether there was no whitespace between two tokens
+fn are_touching(first: &SourceSpan, second: &SourceSpan) -> bool {
+    first.file == second.file && first.end == second.start
+}
\ No newline at end of file
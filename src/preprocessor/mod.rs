@@ -1,4 +1,8 @@
 pub mod preprocessor;
 mod preprocess_context;
 mod preprocess_token;
-mod preprocess_constant_fold;
\ No newline at end of file
+mod preprocess_constant_fold;
+mod macro_operators;
+mod macro_expander;
+pub mod pragma;
+pub mod preprocess_options;
\ No newline at end of file
@@ -0,0 +1,252 @@
+//! The `#pragma`s that the compiler understands
+//!
+//! Each pragma is recognised by its first words, using the handlers in [PRAGMAS]. Unknown pragmas are ignored, as the standard allows.
+//! `#pragma pack` and `#pragma GCC diagnostic` affect code after them, so their changes are saved in [PragmaEffects] by the index of the preprocessed token that they apply from.
+
+use std::{collections::{HashMap, HashSet}, path::PathBuf, rc::Rc};
+
+use logos::Logos;
+
+use crate::{diagnostics::{diagnostic::Diagnostic, warnings::WarningKind}, lexer::{punctuator::Punctuator, source_span::{Located, SourceMap, SourcePosition, SourceSpan}, token::{NumberToken, Token}}, number_literal::typed_value::NumberLiteral};
+
+/// A pragma that was recognised
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pragma {
+    /// `#pragma once`
+    Once,
+    Pack(PackPragma),
+    Diagnostic(DiagnosticPragma),
+}
+
+/// `#pragma pack`, which limits the alignment of struct members
+#[derive(Debug, Clone, PartialEq)]
+pub enum PackPragma {
+    /// `pack(n)`, or `pack()` for the natural alignment
+    Set(Option<u64>),
+    /// `pack(push)` saves the current packing, and `pack(push, n)` changes it afterwards
+    Push(Option<u64>),
+    /// `pack(pop)` goes back to the last packing that was pushed
+    Pop,
+}
+
+/// `#pragma GCC diagnostic`, which turns warnings off for the code after it
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiagnosticPragma {
+    /// saves which warnings are ignored
+    Push,
+    /// goes back to the warnings that were ignored at the last push
+    Pop,
+    /// `ignored "-Wname"`
+    Ignored(WarningKind),
+}
+
+/// Recognises a pragma from the tokens after its first words, or returns None if it is not supported
+type PragmaHandler = fn(&[Located<Token>], &SourceSpan) -> Result<Option<Pragma>, Diagnostic>;
+
+/// Every pragma that is understood, by the words that it starts with
+const PRAGMAS: &[(&[&str], PragmaHandler)] = &[
+    (&["once"], once_pragma),
+    (&["pack"], pack_pragma),
+    (&["GCC", "diagnostic"], diagnostic_pragma),
+];
+
+/// Recognises the text after `#pragma`, where `span` is the whole pragma
+/// 
+/// Returns None for pragmas that are not understood, which should be ignored
+pub fn parse_pragma(text: &str, span: &SourceSpan) -> Result<Option<Pragma>, Diagnostic> {
+    //the pragma is just text, so lex it, giving every token the span of the whole pragma
+    let source_map = SourceMap::new(PathBuf::new(), vec![SourcePosition { line: 1, column: 1 }; text.len() + 1]);
+    let mut tokens = Vec::new();
+    for token in Token::lexer_with_extras(text, source_map) {
+        match token {
+            Ok(Token::NEWLINE) => {},
+            Ok(data) => tokens.push(Located { data, span: span.clone() }),
+            Err(_) => return Ok(None),//not a pragma that can be understood
+        }
+    }
+
+    for (words, handler) in PRAGMAS {
+        let starts_with_words = tokens.len() >= words.len() &&
+            words.iter().zip(&tokens).all(|(word, tok)| tok.data == Token::IDENTIFIER(word.to_string()));
+        if starts_with_words {
+            return handler(&tokens[words.len()..], span);
+        }
+    }
+
+    Ok(None)
+}
+
+fn once_pragma(tokens: &[Located<Token>], span: &SourceSpan) -> Result<Option<Pragma>, Diagnostic> {
+    if !tokens.is_empty() {
+        return Err(Diagnostic::error("extra tokens after `#pragma once`").with_primary(span.clone(), ""));
+    }
+
+    Ok(Some(Pragma::Once))
+}
+
+fn pack_pragma(tokens: &[Located<Token>], span: &SourceSpan) -> Result<Option<Pragma>, Diagnostic> {
+    let invalid_pack = || Diagnostic::error("invalid #pragma pack")
+        .with_primary(span.clone(), "")
+        .with_help("use `#pragma pack(N)`, `#pragma pack()`, `#pragma pack(push, N)` or `#pragma pack(pop)`");
+    let alignment = |tok: &Located<Token>| match &tok.data {
+        Token::NUMBER(NumberToken { value: NumberLiteral::INTEGER { data, .. }, .. }) if [1, 2, 4, 8, 16].contains(data) => Ok(*data as u64),
+        _ => Err(invalid_pack().with_note("the alignment must be 1, 2, 4, 8 or 16")),
+    };
+
+    let contents = match tokens {
+        [open, contents @ .., close] if open.data == Token::PUNCTUATOR(Punctuator::OPENCURLY) && close.data == Token::PUNCTUATOR(Punctuator::CLOSECURLY) => contents,
+        _ => return Err(invalid_pack()),
+    };
+    let is_word = |tok: &Located<Token>, word: &str| tok.data == Token::IDENTIFIER(word.to_string());
+
+    let pack = match contents {
+        [] => PackPragma::Set(None),
+        [n] if !is_word(n, "push") && !is_word(n, "pop") => PackPragma::Set(Some(alignment(n)?)),
+        [push] if is_word(push, "push") => PackPragma::Push(None),
+        [push, comma, n] if is_word(push, "push") && comma.data == Token::PUNCTUATOR(Punctuator::COMMA) => PackPragma::Push(Some(alignment(n)?)),
+        [pop] if is_word(pop, "pop") => PackPragma::Pop,
+        _ => return Err(invalid_pack()),
+    };
+
+    Ok(Some(Pragma::Pack(pack)))
+}
+
+fn diagnostic_pragma(tokens: &[Located<Token>], span: &SourceSpan) -> Result<Option<Pragma>, Diagnostic> {
+    let kind = match tokens.first().map(|tok| &tok.data) {
+        Some(Token::IDENTIFIER(kind)) => kind.as_str(),
+        _ => return Err(Diagnostic::error("invalid #pragma GCC diagnostic")
+            .with_primary(span.clone(), "")
+            .with_help("use `#pragma GCC diagnostic push`, `#pragma GCC diagnostic pop` or `#pragma GCC diagnostic ignored \"-WNAME\"`")),
+    };
+
+    let pragma = match (kind, &tokens[1..]) {
+        ("push", []) => DiagnosticPragma::Push,
+        ("pop", []) => DiagnosticPragma::Pop,
+        ("ignored", [Located { data: Token::STRING(option), .. }]) => {
+            match option.text().strip_prefix("-W").and_then(WarningKind::from_name) {
+                Some(warning) => DiagnosticPragma::Ignored(warning),
+                None => return Ok(None),//a warning that this compiler doesn't have, so there is nothing to ignore
+            }
+        },
+        _ => return Ok(None),//like `warning` or `error`, which aren't supported
+    };
+
+    Ok(Some(Pragma::Diagnostic(pragma)))
+}
+
+/// The alignment from `#pragma pack` for each part of the file
+#[derive(Debug, Clone, Default)]
+pub struct Packing {
+    /// the index of the token that each packing applies from, in order, where None is the natural alignment
+    changes: Vec<(usize, Option<u64>)>,
+}
+
+impl Packing {
+    /// Gets the largest alignment that struct members can have at the token at `token_idx`, or None if they are naturally aligned
+    pub fn at(&self, token_idx: usize) -> Option<u64> {
+        self.changes.iter()
+            .rev()
+            .find(|(idx, _)| *idx <= token_idx)
+            .and_then(|(_, alignment)| *alignment)
+    }
+}
+
+/// What the pragmas in a file changed, for the stages after preprocessing
+#[derive(Debug, Clone, Default)]
+pub struct PragmaEffects {
+    pub packing: Packing,
+    /// the index of the token that each set of ignored warnings applies from, in order
+    ignored_warnings: Vec<(usize, HashSet<WarningKind>)>,
+    /// the index of the first token written at each place, so that warnings can be matched with the pragmas around them
+    token_positions: HashMap<(Rc<PathBuf>, SourcePosition), usize>,
+}
+
+impl PragmaEffects {
+    /// Remembers where each of the preprocessed tokens was written
+    pub fn locate_tokens(&mut self, tokens: &[Located<Token>]) {
+        if self.ignored_warnings.is_empty() {
+            return;//no warnings are ignored, so there is no need to know where any warning is
+        }
+        for (i, tok) in tokens.iter().enumerate() {
+            let origin = tok.span.expansion_root();
+            self.token_positions.entry((origin.file.clone(), origin.start)).or_insert(i);
+        }
+    }
+
+    /// Whether `diagnostic` is a warning that `#pragma GCC diagnostic ignored` turned off where it was found
+    pub fn is_ignored(&self, diagnostic: &Diagnostic) -> bool {
+        let (Some(kind), Some(primary)) = (diagnostic.warning_kind(), diagnostic.primary()) else {
+            return false;
+        };
+        let origin = primary.span.expansion_root();
+        let Some(token_idx) = self.token_positions.get(&(origin.file.clone(), origin.start)) else {
+            return false;
+        };
+
+        self.ignored_warnings.iter()
+            .rev()
+            .find(|(idx, _)| idx <= token_idx)
+            .is_some_and(|(_, ignored)| ignored.contains(&kind))
+    }
+}
+
+/// Keeps track of the pragmas while preprocessing, saving their changes in [PragmaEffects]
+#[derive(Debug, Default)]
+pub struct PragmaState {
+    pack: Option<u64>,
+    pack_stack: Vec<Option<u64>>,
+    ignored: HashSet<WarningKind>,
+    ignored_stack: Vec<HashSet<WarningKind>>,
+    effects: PragmaEffects,
+}
+
+impl PragmaState {
+    /// Applies a `#pragma pack` at `span`, which affects the code from the token at `token_idx`
+    pub fn apply_pack(&mut self, pack: PackPragma, token_idx: usize, span: &SourceSpan) -> Result<(), Diagnostic> {
+        match pack {
+            PackPragma::Set(alignment) => self.pack = alignment,
+            PackPragma::Push(alignment) => {
+                self.pack_stack.push(self.pack);
+                if alignment.is_some() {
+                    self.pack = alignment;
+                }
+            },
+            PackPragma::Pop => {
+                self.pack = self.pack_stack.pop()
+                    .ok_or_else(|| Diagnostic::error("`#pragma pack(pop)` without a matching `#pragma pack(push)`").with_primary(span.clone(), ""))?;
+            },
+        }
+        self.effects.packing.changes.push((token_idx, self.pack));
+
+        Ok(())
+    }
+
+    /// Applies a `#pragma GCC diagnostic` at `span`, which affects the code from the token at `token_idx`
+    pub fn apply_diagnostic(&mut self, diagnostic: DiagnosticPragma, token_idx: usize, span: &SourceSpan) -> Result<(), Diagnostic> {
+        match diagnostic {
+            DiagnosticPragma::Push => self.ignored_stack.push(self.ignored.clone()),
+            DiagnosticPragma::Pop => {
+                self.ignored = self.ignored_stack.pop()
+                    .ok_or_else(|| Diagnostic::error("`#pragma GCC diagnostic pop` without a matching `#pragma GCC diagnostic push`").with_primary(span.clone(), ""))?;
+            },
+            DiagnosticPragma::Ignored(kind) => {
+                self.ignored.insert(kind);
+            },
+        }
+        self.effects.ignored_warnings.push((token_idx, self.ignored.clone()));
+
+        Ok(())
+    }
+
+    /// Whether warnings of this kind are currently turned off
+    pub fn is_ignored(&self, kind: WarningKind) -> bool {
+        self.ignored.contains(&kind)
+    }
+
+    /// Gets what the pragmas changed, where `tokens` are all the preprocessed tokens
+    pub fn into_effects(self, tokens: &[Located<Token>]) -> PragmaEffects {
+        let mut effects = self.effects;
+        effects.locate_tokens(tokens);
+        effects
+    }
+}
\ No newline at end of file
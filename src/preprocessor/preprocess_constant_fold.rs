@@ -1,162 +1,149 @@
-use std::collections::{HashMap, VecDeque};
+use crate::{constexpr_parsing::ConstexprValue, data_type::base_type::IntegerType, diagnostics::diagnostic::Diagnostic, expression::expression::try_consume_whole_expr, lexer::{keywords::Keyword, punctuator::Punctuator, source_span::{Located, SourceSpan}, token::{NumberToken, Token}, token_savepoint::TokenQueueSlice, token_walk::TokenQueue}, number_literal::typed_value::NumberLiteral, parse_data::ParseData, preprocessor::{macro_expander::expand_macros, preprocess_context::PreprocessContext}};
 
-use unwrap_let::unwrap_let;
-
-use crate::{constexpr_parsing::ConstexprValue, data_type::base_type::IntegerType, expression::expression::try_consume_whole_expr, lexer::{keywords::Keyword, punctuator::Punctuator, token::Token, token_savepoint::TokenQueueSlice, token_walk::{TokenQueue, TokenSearchType}}, number_literal::typed_value::NumberLiteral, parse_data::ParseData, preprocessor::{preprocess_context::PreprocessContext, preprocess_token::MacroFunction}};
-
-/// Folds a constant for #if statements
-pub fn fold(tokens: Vec<Token>, ctx: &PreprocessContext) -> ConstexprValue {
+/// Folds a constant for #if statements, where `directive_span` is the whole #if line
+/// 
+/// `has_include` finds whether `__has_include` can find a header, given its name and whether it was in speechmarks
+pub fn fold(tokens: Vec<Located<Token>>, ctx: &PreprocessContext, has_include: &dyn Fn(&str, bool) -> bool, directive_span: &SourceSpan) -> Result<ConstexprValue, Diagnostic> {
     //replace defined(x) with 1 or 0
-    let tokens = fix_defined(tokens, ctx)
-        .into_iter()
-        .map(|tok| (tok, ctx.get_line_number()))
-        .collect();
+    let tokens = fix_defined(tokens, ctx)?;
+    //replace __has_include(x) with 1 or 0, after `defined(__has_include)` is replaced
+    let tokens = fix_has_include(tokens, has_include)?;
     //replace the remaining macros
-    let tokens = sub_definitions(tokens, ctx, &Vec::new(), &HashMap::new());
+    let tokens = expand_macros(tokens, ctx)?;
+
+    if tokens.is_empty() {
+        return Err(Diagnostic::error("expected an expression in preprocessor condition")
+            .with_primary(directive_span.clone(), "this condition is empty"));
+    }
+
     let tokens = TokenQueue::new(tokens);
     let slice = TokenQueueSlice::new();
+    let condition_span = tokens.get_span(&slice);
 
-    println!("{:?}", tokens.tokens);
-
-    let resultant_tree = try_consume_whole_expr(&tokens, &slice, &mut ParseData::make_empty()).unwrap();
+    let resultant_tree = try_consume_whole_expr(&tokens, &slice, &mut ParseData::make_empty())
+        .ok_or_else(|| Diagnostic::error("invalid expression in preprocessor condition")
+            .with_primary(condition_span.clone(), "could not parse this"))?;
 
-    (&resultant_tree).try_into().unwrap()
+    (&resultant_tree).try_into()
+        .map_err(|reason: String| Diagnostic::error("preprocessor condition is not a constant")
+            .with_primary(condition_span, "")
+            .with_note(reason))
 }
 
 /// Compares whether a #if would consider this constexpr value as true
-pub fn is_true(folded: ConstexprValue) -> bool {
+pub fn is_true(folded: ConstexprValue, directive_span: &SourceSpan) -> Result<bool, Diagnostic> {
     match folded{
         crate::constexpr_parsing::ConstexprValue::NUMBER(number_literal) => {
-            number_literal != NumberLiteral::INTEGER{data: 0, data_type: IntegerType::I32}
+            Ok(number_literal != NumberLiteral::INTEGER{data: 0, data_type: IntegerType::I32})
         }
-        crate::constexpr_parsing::ConstexprValue::STRING(string_literal) =>
-            panic!("found string when parsing constant: {:?}", string_literal),
-        crate::constexpr_parsing::ConstexprValue::POINTER { label, offset } =>
-            panic!("found a pointer when parsing constant: {:?} with offset {:?}", label, offset),
-        crate::constexpr_parsing::ConstexprValue::ZEROES => false,
+        crate::constexpr_parsing::ConstexprValue::STRING(_) =>
+            Err(Diagnostic::error("preprocessor condition must be an integer, found a string").with_primary(directive_span.clone(), "")),
+        crate::constexpr_parsing::ConstexprValue::POINTER { .. } =>
+            Err(Diagnostic::error("preprocessor condition must be an integer, found a pointer").with_primary(directive_span.clone(), "")),
+        crate::constexpr_parsing::ConstexprValue::ZEROES => Ok(false),
     }
 }
 
-fn fix_defined(mut tokens: Vec<Token>, ctx: &PreprocessContext) -> Vec<Token> {
-    let defined_idx = tokens.iter().position(|tok| *tok == Token::KEYWORD(Keyword::DEFINED));
+fn fix_defined(mut tokens: Vec<Located<Token>>, ctx: &PreprocessContext) -> Result<Vec<Located<Token>>, Diagnostic> {
+    let defined_idx = tokens.iter().position(|tok| tok.data == Token::KEYWORD(Keyword::DEFINED));
 
     if let Some(idx) = defined_idx {
-        tokens.remove(idx);//remove defined keyword
-        //match on (token, line number)
-        let macro_name = match tokens.remove(idx) {
-            Token::IDENTIFIER(x) => x,// defined x
-            Token::PUNCTUATOR(Punctuator::OPENCURLY) => {//defined(x)
-                unwrap_let!(Token::IDENTIFIER(name) = tokens.remove(idx));//grab the line number and token
-                assert_eq!(tokens.remove(idx), Token::PUNCTUATOR(Punctuator::CLOSECURLY));//remove the close bracket
+        let defined_span = tokens.remove(idx).span;//remove defined keyword
+        let invalid_defined = |found: Option<Located<Token>>| {
+            let span = found.map_or(defined_span.clone(), |tok| tok.span);
+            Diagnostic::error("invalid token after a defined keyword")
+                .with_primary(span, "expected a macro name")
+                .with_secondary(defined_span.clone(), "")
+                .with_help("use `defined NAME` or `defined(NAME)`")
+        };
+
+        let macro_name = match pop_token(&mut tokens, idx) {
+            Some(Located { data: Token::IDENTIFIER(x), .. }) => x,// defined x
+            Some(Located { data: Token::PUNCTUATOR(Punctuator::OPENCURLY), .. }) => {//defined(x)
+                let name = match pop_token(&mut tokens, idx) {
+                    Some(Located { data: Token::IDENTIFIER(name), .. }) => name,
+                    other => return Err(invalid_defined(other)),
+                };
+                match pop_token(&mut tokens, idx) {
+                    Some(Located { data: Token::PUNCTUATOR(Punctuator::CLOSECURLY), .. }) => {},//remove the close bracket
+                    other => return Err(invalid_defined(other).with_help("add a `)` after the macro name")),
+                }
 
                 name
             }
-            _ => panic!("invalid token after a defined keyword")
+            other => return Err(invalid_defined(other))
         };
 
-        tokens.insert(idx, Token::NUMBER(
-            NumberLiteral::INTEGER {
-                data: if ctx.has_definition(&macro_name) || ctx.get_macro_func(&macro_name).is_some() {1} else {0},
-                data_type: IntegerType::I32
-            }
-        ));
+        tokens.insert(idx, Located {
+            data: Token::NUMBER(NumberToken::from_value(
+                NumberLiteral::INTEGER {
+                    data: if ctx.has_definition(&macro_name) || ctx.get_macro_func(&macro_name).is_some() {1} else {0},
+                    data_type: IntegerType::I32
+                }
+            )),
+            span: defined_span
+        });
 
         fix_defined(tokens, ctx)//recursively handle any others
     } else {
-        tokens//nothing to replace
+        Ok(tokens)//nothing to replace
     }
 }
 
-/// Substitutes definitions for macros, except ones with the name `excluded_ident`
-/// 
-pub fn sub_definitions(tokens: Vec<(Token, i32)>, ctx: &PreprocessContext, excluded_ident: &Vec<String>, substitutions: &HashMap<String, Vec<Token>>) -> Vec<Token> {
-    let mut tokens = VecDeque::from(tokens);
-    let mut result = Vec::new();
-    while let Some((next_token, line_number)) = tokens.pop_front() {
-        match next_token {
-
-            Token::IDENTIFIER(macro_name) if ctx.has_definition(&macro_name) && !excluded_ident.contains(&macro_name) => {
-                //simple macro
-                let definition = ctx.get_definition(&macro_name, line_number).unwrap()//get replacement
-                    .into_iter()
-                    .map(|tok| (tok, line_number))//add a line number
-                    .collect();
-                let mut definition_exclusions =  excluded_ident.clone();
-                definition_exclusions.push(macro_name);
-                let definition = sub_definitions(definition, ctx, &definition_exclusions, substitutions);//recursively substitute the replacement
-                result.extend(definition);//add the replacement
-            }
-
-            Token::IDENTIFIER(macro_name) if ctx.get_macro_func(&macro_name).is_some() && !excluded_ident.contains(&macro_name) => {
-                //get the definition
-                let MacroFunction {body, params} = ctx.get_macro_func(&macro_name).unwrap();
-                let body = body
-                    .into_iter()
-                    .map(|tok| (tok, line_number))//add a line number
-                    .collect();
-                //consume the "("
-                assert_eq!(tokens.pop_front().unwrap().0, Token::PUNCTUATOR(Punctuator::OPENCURLY));
-                
-                //find the end of the param list
-                let close_bracket = simple_matching_closebracket(tokens.iter().map(|(tok, _)| tok)).unwrap();
-
-                //get the args
-                let args_vec: Vec<Token> = tokens.iter().take(close_bracket).map(|(tok, _)| tok.clone()).collect();
-                let slice = TokenQueueSlice{index: 0, max_index: args_vec.len()};
-                let queue = TokenQueue::new(args_vec);
-                let args = queue.split_outside_parentheses(&slice, |x| *x == Token::PUNCTUATOR(Punctuator::COMMA), &TokenSearchType::skip_all_brackets());
-                
-                let mut param_substitutions = substitutions.clone();//start with existing substitutions
-                for (param, arg) in params.into_iter().zip(args.into_iter()) {
-                    //get the param and match it to the arg
-                    println!("substituting {:?} for {:?}", param, &queue.tokens[arg.index..arg.max_index]);
-                    param_substitutions.insert(param, queue.tokens[arg.index..arg.max_index].to_vec());
+fn fix_has_include(mut tokens: Vec<Located<Token>>, has_include: &dyn Fn(&str, bool) -> bool) -> Result<Vec<Located<Token>>, Diagnostic> {
+    let has_include_idx = tokens.iter().position(|tok| tok.data == Token::IDENTIFIER("__has_include".to_string()));
+
+    let Some(idx) = has_include_idx else {
+        return Ok(tokens);//nothing to replace
+    };
+    let has_include_span = tokens.remove(idx).span;//remove __has_include
+    let invalid_has_include = |found: Option<Located<Token>>| {
+        let span = found.map_or(has_include_span.clone(), |tok| tok.span);
+        Diagnostic::error("invalid token in __has_include")
+            .with_primary(span, "expected a header name in brackets")
+            .with_secondary(has_include_span.clone(), "")
+            .with_help("use `__has_include(\"FILENAME\")` or `__has_include(<FILENAME>)`")
+    };
+
+    match pop_token(&mut tokens, idx) {
+        Some(Located { data: Token::PUNCTUATOR(Punctuator::OPENCURLY), .. }) => {},
+        other => return Err(invalid_has_include(other)),
+    }
+    let (filename, is_quoted) = match pop_token(&mut tokens, idx) {
+        Some(Located { data: Token::STRING(filename), .. }) => (filename.text(), true),
+        Some(Located { data: Token::PUNCTUATOR(Punctuator::Less), .. }) => {
+            //<filename> was lexed as normal tokens, so join them back together
+            let mut filename = String::new();
+            loop {
+                match pop_token(&mut tokens, idx) {
+                    Some(Located { data: Token::PUNCTUATOR(Punctuator::Greater), .. }) => break,
+                    Some(tok) => filename.push_str(&tok.data.to_string()),
+                    None => return Err(invalid_has_include(None).with_help("add a `>` after the header name")),
                 }
-
-                let mut definition_exclusions =  excluded_ident.clone();
-                definition_exclusions.push(macro_name);
-
-                let body = sub_definitions(body, ctx, &definition_exclusions, &param_substitutions);
-                result.extend(body);
-
-                //consume the close bracket
-                tokens.drain(..close_bracket);
-                assert_eq!(tokens.pop_front().unwrap().0, Token::PUNCTUATOR(Punctuator::CLOSECURLY));
             }
+            (filename, false)
+        }
+        other => return Err(invalid_has_include(other)),
+    };
+    match pop_token(&mut tokens, idx) {
+        Some(Located { data: Token::PUNCTUATOR(Punctuator::CLOSECURLY), .. }) => {},//remove the close bracket
+        other => return Err(invalid_has_include(other).with_help("add a `)` after the header name")),
+    }
 
-            Token::IDENTIFIER(sub_name) if substitutions.contains_key(&sub_name) => {
-                //TODO perhaps this has priority over macros?
-                //substitute the definition
-                result.extend(substitutions.get(&sub_name).unwrap().iter().cloned());
+    tokens.insert(idx, Located {
+        data: Token::NUMBER(NumberToken::from_value(
+            NumberLiteral::INTEGER {
+                data: if has_include(&filename, is_quoted) {1} else {0},
+                data_type: IntegerType::I32
             }
+        )),
+        span: has_include_span
+    });
 
-
-            x => {result.push(x);}//normal token
-        }
-    }
- 
-    result
+    fix_has_include(tokens, has_include)//recursively handle any others
 }
 
-/// Takes an iterator over tokens
-/// 
-/// assumes that the open "(" has been consumed already
-/// 
-/// TODO in the future, maybe generalize token queue to allow different types
-fn simple_matching_closebracket<'a>(slice: impl Iterator<Item = &'a Token>) -> Option<usize> {
-    let mut slice = slice.enumerate();
-    let mut depth = 1;
-    while let Some((i, tok)) = slice.next() {
-        print!("{:?} ", tok);
-        match tok {
-            Token::PUNCTUATOR(Punctuator::OPENCURLY) => {depth += 1;}
-            Token::PUNCTUATOR(Punctuator::CLOSECURLY) => {depth -= 1;}
-            _ => {}
-        }
-        if depth == 0 {
-            return Some(i);
-        }
-    }
-
-    return None;
+/// Removes the token at `idx` if there is one
+fn pop_token(tokens: &mut Vec<Located<Token>>, idx: usize) -> Option<Located<Token>> {
+    if idx < tokens.len() {Some(tokens.remove(idx))} else {None}
 }
\ No newline at end of file
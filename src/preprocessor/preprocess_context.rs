@@ -1,14 +1,13 @@
 use std::collections::HashMap;
 
-use crate::{data_type::base_type::IntegerType, lexer::token::Token, number_literal::typed_value::NumberLiteral, preprocessor::preprocess_token::MacroFunction, string_literal::StringLiteral};
+use crate::{data_type::base_type::IntegerType, lexer::{source_span::{Located, SourceSpan}, token::{NumberToken, Token}}, number_literal::typed_value::NumberLiteral, preprocessor::preprocess_token::MacroFunction, string_literal::StringLiteral};
 
 pub struct PreprocessContext {
-    defined: HashMap<String, Vec<Token>>,//for simple define
+    defined: HashMap<String, Vec<Located<Token>>>,//for simple define
     /// For macro function definitions
     defined_macro_functions: HashMap<String, MacroFunction>,
     selection_depth: i32,//how many if statements deep this is
     scan_type: ScanType,//am I skipping code inside a failed #if statement?
-    line_counter: i32,
     line_override: Option<i32>,
     /// Should be used only for the __FILE__ macro as it can be overwritten
     file_name: StringLiteral,
@@ -21,7 +20,6 @@ impl PreprocessContext {
             defined_macro_functions: HashMap::new(),
             selection_depth:0,
             scan_type: ScanType::NORMAL,
-            line_counter:1,
             line_override: None,
             file_name: StringLiteral::new_from_raw(filename.chars())
         }
@@ -35,7 +33,7 @@ impl PreprocessContext {
     }
 
     /// Defines a simple macro
-    pub fn define(&mut self, name: String, value: Vec<Token>) {
+    pub fn define(&mut self, name: String, value: Vec<Located<Token>>) {
         self.defined.insert(name, value);
     }
     pub fn undefine(&mut self, name: &str) {
@@ -48,20 +46,24 @@ impl PreprocessContext {
         self.defined_macro_functions.insert(name, func);
     }
 
-    pub fn get_definition(&self, name: &str, line_number: i32) -> Option<Vec<Token>> {
+    /// Gets the replacement for a simple macro, where builtin macros are treated as if they were defined at `call_site`
+    pub fn get_definition(&self, name: &str, call_site: &SourceSpan) -> Option<Vec<Located<Token>>> {
+        let builtin = |data| Some(vec![Located { data, span: call_site.clone() }]);
         match name {
             "__LINE__" => {
+                //macros that use __LINE__ get the line that they were used on
+                let line_number: i32 = call_site.expansion_root().start.line.try_into().unwrap();
                 let data: i128 = self.line_override.unwrap_or(line_number).into();
-                Some(vec![Token::NUMBER(NumberLiteral::INTEGER { data, data_type: IntegerType::I32 })])
+                builtin(Token::NUMBER(NumberToken::from_value(NumberLiteral::INTEGER { data, data_type: IntegerType::I32 })))
             }
-            "__FILE__" => Some(vec![Token::STRING(self.file_name.clone())]),
-            "__STDC_VERSION__" => Some(vec![Token::NUMBER(NumberLiteral::INTEGER { data: 201710, data_type: IntegerType::I64 })]),
+            "__FILE__" => builtin(Token::STRING(self.file_name.clone())),
+            "__STDC_VERSION__" => builtin(Token::NUMBER(NumberToken { value: NumberLiteral::INTEGER { data: 201710, data_type: IntegerType::I64 }, spelling: "201710L".to_string() })),
             _ => self.defined.get(name).cloned()
         }
     }
     pub fn has_definition(&self, name: &str) -> bool {
-        //substitutes a line number 0 as this is not required
-        self.get_definition(name, 0).is_some()
+        //__has_include only works in #if, but is defined so that code can check if it is supported
+        matches!(name, "__LINE__" | "__FILE__" | "__STDC_VERSION__" | "__has_include") || self.defined.contains_key(name)
     }
     pub fn get_macro_func(&self, name: &str) -> Option<MacroFunction> {
         self.defined_macro_functions.get(name).cloned()
@@ -82,13 +84,6 @@ impl PreprocessContext {
     pub fn override_line_number(&mut self, new_line: i32) {
         self.line_override = Some(new_line);
     }
-    pub fn set_line_number(&mut self, line: i32) {
-        self.line_counter = line;
-    }
-    /// Returns the line number, without being overriden
-    pub fn get_line_number(&self) -> i32 {
-        self.line_counter
-    }
 }
 
 #[derive(PartialEq, Clone, Copy)]
@@ -0,0 +1,111 @@
+use std::path::{Path, PathBuf};
+
+/// A `-D` or `-U` option, which are applied in the order they were given
+#[derive(Debug, Clone)]
+enum MacroCommand {
+    /// `-D name=value`, where `name` can also have params like `-D 'max(a,b)=...'`
+    Define { name: String, value: String },
+    Undefine(String),
+}
+
+/// Where to find headers, and what to do before preprocessing, set by the `-I`, `-isystem`, `-D`, `-U` and `-include` options
+#[derive(Debug, Clone, Default)]
+pub struct PreprocessorOptions {
+    /// `-I` folders, searched before any others
+    include_folders: Vec<PathBuf>,
+    /// `-isystem` folders, searched after `-I` folders but before the bundled `c_lib`
+    system_include_folders: Vec<PathBuf>,
+    macro_commands: Vec<MacroCommand>,
+    /// `-include` files, which are included before the first line of the file
+    forced_includes: Vec<PathBuf>,
+}
+
+impl PreprocessorOptions {
+    pub fn add_include_folder(&mut self, folder: PathBuf) {
+        self.include_folders.push(folder);
+    }
+    pub fn add_system_include_folder(&mut self, folder: PathBuf) {
+        self.system_include_folders.push(folder);
+    }
+    pub fn add_forced_include(&mut self, file: PathBuf) {
+        self.forced_includes.push(file);
+    }
+
+    /// Applies `-D name` or `-D name=value`, where just a name defines it as 1
+    /// 
+    /// The definition must fit on one `#define` line, so it can't contain a newline or end with a backslash
+    pub fn define(&mut self, definition: &str) -> Result<(), String> {
+        let (name, value) = definition.split_once('=').unwrap_or((definition, "1"));
+
+        if name.is_empty() {
+            return Err(format!("macro name missing in -D{}", definition));
+        }
+        if definition.contains(['\n', '\r']) {
+            return Err(format!("-D{} contains a newline", definition.escape_default()));
+        }
+        if value.ends_with('\\') {
+            return Err(format!("-D{} ends with a backslash, which would join it to the next line", definition));
+        }
+
+        self.macro_commands.push(MacroCommand::Define { name: name.to_string(), value: value.to_string() });
+        Ok(())
+    }
+    /// Applies `-U name`
+    pub fn undefine(&mut self, name: &str) {
+        self.macro_commands.push(MacroCommand::Undefine(name.to_string()));
+    }
+
+    pub fn forced_includes(&self) -> &[PathBuf] {
+        &self.forced_includes
+    }
+
+    /// The `-D` and `-U` options written as `#define` and `#undef` lines, so that they can be preprocessed before the file
+    pub fn command_line_source(&self) -> String {
+        self.macro_commands.iter()
+            .map(|command| match command {
+                MacroCommand::Define { name, value } => format!("#define {} {}\n", name, value),
+                MacroCommand::Undefine(name) => format!("#undef {}\n", name),
+            })
+            .collect()
+    }
+
+    /// Every folder that headers are searched for in, in order
+    /// 
+    /// `#include "file"` looks in the folder of the file that included it before these
+    pub fn search_path(&self) -> Vec<PathBuf> {
+        self.include_folders.iter()
+            .chain(self.system_include_folders.iter())
+            .cloned()
+            .chain(std::iter::once(bundled_include_folder()))
+            .collect()
+    }
+
+    /// Finds `filename` in the first folder of `search_path` that has it, starting from the folder at `first_folder`
+    /// 
+    /// Returns the index of the folder it was found in, and the path to the file
+    pub fn find_header(search_path: &[PathBuf], filename: &str, first_folder: usize) -> Option<(usize, PathBuf)> {
+        search_path.iter()
+            .enumerate()
+            .skip(first_folder)
+            .map(|(i, folder)| (i, folder.join(filename)))
+            .find(|(_, path)| path.is_file())
+    }
+}
+
+/// Where the `c_lib` headers can be, relative to the folder of the compiler's binary
+/// 
+/// They are either copied beside the binary, or installed in `share` like `/usr/local/share/StuCCompiler2/c_lib` for `/usr/local/bin/StuCCompiler2`
+const BUNDLED_INCLUDE_LOCATIONS: [&str; 2] = ["c_lib", "../share/StuCCompiler2/c_lib"];
+
+/// Finds the `c_lib` headers that come with the compiler, so that it works from any directory
+/// 
+/// Only the [BUNDLED_INCLUDE_LOCATIONS] are checked, so that an unrelated `c_lib` folder is never used.
+/// Otherwise, like when running from `target/debug`, the copy in the source code is used
+fn bundled_include_folder() -> PathBuf {
+    let exe_folder = std::env::current_exe().ok().and_then(|exe| exe.parent().map(Path::to_path_buf));
+
+    exe_folder.iter()
+        .flat_map(|folder| BUNDLED_INCLUDE_LOCATIONS.iter().map(move |location| folder.join(location)))
+        .find(|folder| folder.is_dir())
+        .unwrap_or_else(|| Path::new(env!("CARGO_MANIFEST_DIR")).join("c_lib"))
+}
\ No newline at end of file
@@ -1,28 +1,20 @@
-use std::{collections::VecDeque, fmt::Debug};
+use std::collections::VecDeque;
 
 use logos::{ Lexer, Logos};
 
-use crate::lexer::{punctuator::Punctuator, token::Token};
-
-pub struct LineNumbered {
-    pub line_num: i32,
-    pub data: PreprocessToken
-}
-
-impl Debug for LineNumbered {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "{}: {:?}", self.line_num, self.data)
-    }
-}
+use crate::{diagnostics::diagnostic::Diagnostic, lexer::{punctuator::Punctuator, source_span::{Located, SourceMap}, token::Token}};
 
 #[derive(Debug, Clone, Default)]
 pub struct MacroFunction {
     pub params: Vec<String>,
-    pub body: Vec<Token>
+    /// The param that takes any extra args, which is `__VA_ARGS__` for `...`, or `args` for the GNU `args...`
+    pub variadic: Option<String>,
+    pub body: Vec<Located<Token>>
 }
 
 impl MacroFunction {
-    pub fn new_from(lex: &mut Lexer<PreprocessToken>) -> (String, Self) {
+    /// Returns None if the line can't be tokenised or the params are invalid
+    pub fn new_from(lex: &mut Lexer<PreprocessToken>) -> Option<(String, Self)> {
         
         let macro_name = lex.slice()
             .split_once("define").expect("could not find 'define' in a #define macro")
@@ -31,44 +23,66 @@ impl MacroFunction {
             .trim()//get the x part of #define x(y) foo
             .to_string();
     
-        let mut tokens_after = VecDeque::from(Token::parse_logical_line(lex));
+        let mut tokens_after = VecDeque::from(Token::parse_logical_line(lex)?);
         let mut result = Self::default();
         
         'param_gather: loop {
-            let next = tokens_after.pop_front().unwrap();
-            match next {
+            let next = tokens_after.pop_front()?;
+            match next.data {
                 Token::PUNCTUATOR(Punctuator::CLOSECURLY) => break 'param_gather,
+                //named variadic param, like `args...`
+                Token::IDENTIFIER(param_name) if tokens_after.front().is_some_and(|tok| tok.data == Token::PUNCTUATOR(Punctuator::ELIPSIS)) => {
+                    tokens_after.pop_front();
+                    result.variadic = Some(param_name);
+                }
                 Token::IDENTIFIER(param_name) => result.params.push(param_name),
+                Token::PUNCTUATOR(Punctuator::ELIPSIS) => result.variadic = Some("__VA_ARGS__".to_string()),
                 Token::PUNCTUATOR(Punctuator::COMMA) => {}
-                _ => panic!("invalid token when parsing params of a macro function")
+                _ => return None//invalid token when parsing params of a macro function
+            }
+
+            if result.variadic.is_some() && !tokens_after.front().is_some_and(|tok| tok.data == Token::PUNCTUATOR(Punctuator::CLOSECURLY)) {
+                return None;//the variadic param must be last
             }
         }
 
         result.body = tokens_after.into();
 
-        (macro_name, result)
+        Some((macro_name, result))
+    }
+}
+
+/// The header that an `#include` or `#include_next` asks for
+#[derive(Debug, Clone)]
+pub struct IncludeTarget {
+    pub filename: String,
+    /// `#include "filename"`, which looks beside the current file first, rather than `#include <filename>`
+    pub is_quoted: bool,
+    /// `#include_next`, which carries on searching from after the folder that the current file was found in
+    pub is_next: bool,
+}
+
+impl IncludeTarget {
+    fn new_from(directive: &str, is_quoted: bool) -> Self {
+        let (open, close) = if is_quoted {('"', '"')} else {('<', '>')};
+        let start_idx = directive.find(open).unwrap() + 1;
+        let end_idx = directive.rfind(close).unwrap();
+        IncludeTarget {
+            filename: directive[start_idx..end_idx].to_string(),
+            is_quoted,
+            is_next: directive[..start_idx].contains("include_next"),
+        }
     }
 }
 
 #[derive(Clone, Logos, Debug)]
 #[logos(skip "[ \n]")]
+#[logos(extras = SourceMap)]
 pub enum PreprocessToken {
     
-    #[regex("#[ \n]*include *<[^>]+>\n", |x| {
-        let slice = x.slice();
-        let start_idx = slice.find("<").unwrap() + 1;
-        let end_idx = slice.rfind(">").unwrap();
-        slice[start_idx..end_idx].to_string()
-    })]
-    IncludeLib(String),
-
-    #[regex("#[ \n]*include *\"[^\"]+\".*\n", |x| {
-        let slice = x.slice();
-        let start_idx = slice.find("\"").unwrap() + 1;
-        let end_idx = slice.rfind("\"").unwrap();
-        slice[start_idx..end_idx].to_string()
-    })]
-    IncludeFile(String),
+    #[regex("#[ \n]*include(_next)? *<[^>]+>\n", |x| IncludeTarget::new_from(x.slice(), false))]
+    #[regex("#[ \n]*include(_next)? *\"[^\"]+\".*\n", |x| IncludeTarget::new_from(x.slice(), true))]
+    Include(IncludeTarget),
 
     #[regex("#[ \n]*ifdef +", |x| {
         let macro_name = x.remainder().split_once("\n").unwrap().0;
@@ -85,7 +99,7 @@ pub enum PreprocessToken {
     IfNDef(String),
 
     #[regex("#[ \n]*if", Token::parse_logical_line, priority=10)]
-    If(Vec<Token>),
+    If(Vec<Located<Token>>),
 
     #[regex("#[ \n]*pragma.+\n", |x| {
         x.slice()
@@ -103,7 +117,7 @@ pub enum PreprocessToken {
     Else,
 
     #[regex("#[ \n]*elif", Token::parse_logical_line)]
-    Elif(Vec<Token>),
+    Elif(Vec<Located<Token>>),
 
     #[regex("#[ \n]*define +\\w*", |lex| {
         let macro_name = lex.slice()
@@ -112,11 +126,11 @@ pub enum PreprocessToken {
             .trim()//get the x part of #define x foo
             .to_string();
         // Parse the definition after the macro name
-        let macro_definition = Token::parse_logical_line(lex);
+        let macro_definition = Token::parse_logical_line(lex)?;
 
-        (macro_name, macro_definition)
+        Some((macro_name, macro_definition))
     })]
-    DefineToken((String, Vec<Token>)),// #define x y
+    DefineToken((String, Vec<Located<Token>>)),// #define x y
 
     #[regex("#[ \n]*define +\\w+\\(", |lex| {
         MacroFunction::new_from(lex)
@@ -136,7 +150,7 @@ pub enum PreprocessToken {
     #[regex("#[ \n]*line", |lex| {
         Token::parse_logical_line(lex)
     })]
-    LineDirective(Vec<Token>),
+    LineDirective(Vec<Located<Token>>),
 
     #[regex("#[ ]*\n")]
     NullDirective,
@@ -145,41 +159,61 @@ pub enum PreprocessToken {
         let start_idx = lex.span().start;
         let text = &lex.source()[start_idx..];
         assert!(!text.starts_with("#"));
-        *lex = PreprocessToken::lexer(text);//take back the accidentally consumed character, then parse a line of code
+        *lex = PreprocessToken::lexer_with_extras(text, lex.extras.clone());//take back the accidentally consumed character, then parse a line of code
         Token::parse_logical_line(lex)
     }, priority = 1)]
-    LineOfCode(Vec<Token>),
+    LineOfCode(Vec<Located<Token>>),
 }
 
 impl PreprocessToken {
     /// Note: requires trailing newline
     /// 
     /// This still works if comments are present
-    pub fn parse(data: &str) -> Vec<LineNumbered> {
+    /// 
+    /// `source_map` must map each byte of `data` back to the original file
+    pub fn parse(data: &str, source_map: SourceMap) -> Result<Vec<Located<PreprocessToken>>, Diagnostic> {
         assert!(data.ends_with("\n"));
-        let total_line_count = line_count(data);
-        let mut iterator = Self::lexer(data);
+        let mut iterator = Self::lexer_with_extras(data, source_map.clone());
         let mut result = Vec::new();
 
-        while let Some(next) = iterator.next() {
+        loop {
+            //whitespace is skipped, so the directive or line of code starts at the next non-whitespace character
+            let remaining = iterator.remainder();
+            let start_idx = data.len() - remaining.trim_start_matches([' ', '\n']).len();
+
+            let Some(next) = iterator.next() else {break};
+
             match next {
-                Ok(x) => result.push(LineNumbered {
-                    line_num: total_line_count - line_count(iterator.remainder()),//total lines - lines remaining = line number
-                    data: x
-                }),
+                Ok(x) => {
+                    //don't include the newline that ends the line
+                    let end_idx = data[..data.len() - iterator.remainder().len()].trim_end_matches([' ', '\n']).len();
+                    result.push(Located {
+                        data: x,
+                        span: source_map.span(data, start_idx..end_idx.max(start_idx + 1))
+                    })
+                },
 
                 Err(_) => {
-                    let rem = iterator.remainder();
-                    panic!("error when collecting preprocessor lines. remainder: {:?}{:?}", iterator.slice(), &rem[..usize::min(100, rem.len())]);
+                    //the lexer may have been replaced while parsing a line, so find the error relative to its source
+                    let error_span = iterator.extras.span(iterator.source(), iterator.span());
+                    let error_start = data.len() - iterator.source().len() + iterator.span().start;
+                    let slice = iterator.slice();
+
+                    return Err(if slice.starts_with('#') && error_start == start_idx {
+                        Diagnostic::error("unknown preprocessing directive")
+                            .with_primary(error_span, "")
+                    } else if slice.trim().is_empty() {
+                        //the error was found at the end of a line, so the line itself is wrong
+                        Diagnostic::error("malformed preprocessing directive")
+                            .with_primary(source_map.span(data, start_idx..error_start.max(start_idx + 1)), "")
+                    } else {
+                        Diagnostic::error(format!("unrecognised token `{}`", slice))
+                            .with_primary(error_span, "")
+                    });
                 }
             }
         }
 
-        result
+        Ok(result)
     }
 }
-
-/// Note that this does not count the last empty line of a file for some reason
-fn line_count(data: &str) -> i32 {
-    data.lines().count().try_into().unwrap()
-}
\ No newline at end of file
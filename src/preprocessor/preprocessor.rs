@@ -1,95 +1,306 @@
-use std::{collections::{HashMap, VecDeque}, fs, path::{Path, PathBuf}};
+use std::{collections::{HashMap, HashSet, VecDeque}, fs, path::{Path, PathBuf}};
 
-use unwrap_let::unwrap_let;
+use crate::{diagnostics::{diagnostic::Diagnostic, warnings::{WarningKind, WarningOptions}}, lexer::{source_span::{Located, SourceMap, SourcePosition, SourceSpan}, token::{NumberToken, Token}}, number_literal::typed_value::NumberLiteral, preprocessor::{macro_expander::{expand_macros, MacroExpander, TokenSource}, preprocess_constant_fold::{fold, is_true}, pragma::{parse_pragma, Pragma, PragmaEffects, PragmaState}, preprocess_context::ScanType, preprocess_token::PreprocessToken}};
 
-use crate::{lexer::token::Token, number_literal::typed_value::NumberLiteral, preprocessor::{preprocess_constant_fold::{fold, is_true, sub_definitions}, preprocess_context::ScanType, preprocess_token::{LineNumbered, PreprocessToken}}};
+use super::{preprocess_context::PreprocessContext, preprocess_options::PreprocessorOptions};
 
-use super::preprocess_context::PreprocessContext;
+/// How deep files can be included inside each other, like gcc, so that a header that includes itself is caught
+const MAX_INCLUDE_DEPTH: usize = 200;
 
-const INCLUDE_FOLDERS: &[&str] = &["c_lib"];//local custom version of glibc 
+/// A C file after preprocessing
+pub struct PreprocessedFile {
+    pub tokens: Vec<Located<Token>>,
+    /// What the pragmas changed for the rest of the compilation
+    pub pragmas: PragmaEffects,
+    /// Warnings from preprocessing, like unknown pragmas
+    pub warnings: Vec<Diagnostic>,
+}
+
+pub fn preprocess_c_file(filename: &Path, options: &PreprocessorOptions, warning_options: &WarningOptions) -> Result<PreprocessedFile, Diagnostic> {
+    let mut runner = DirectiveRunner {
+        ctx: PreprocessContext::new(filename.file_name().unwrap().to_str().unwrap()),
+        options,
+        warning_options,
+        files: Vec::new(),
+        once_headers: HashSet::new(),
+        include_guards: HashMap::new(),
+        line: VecDeque::new(),
+        pragmas: PragmaState::default(),
+        warnings: Vec::new(),
+        token_count: 0,
+    };
+
+    //-D and -U are handled like #define and #undef before the first line, followed by any -include files
+    //the last file is read first, so they are added in reverse
+    let command_line_path = Path::new("<command line>");
+    let command_line = IncludedFile {
+        path: command_line_path.to_path_buf(),
+        search_index: None,
+        tokens: tokenise(&options.command_line_source(), command_line_path)?.into(),
+    };
+    runner.include(filename.to_path_buf(), None, None)?;
+    for forced_include in options.forced_includes().iter().rev() {
+        //like an #include "file" on the command line, so it is looked for in the working directory first
+        let (path, search_index) = find_include_file(forced_include.to_str().unwrap(), true, false, &command_line, options)
+            .ok_or_else(|| Diagnostic::error(format!("couldn't find file `{}` from -include", forced_include.display())))?;
+        runner.include(path, search_index, None)?;
+    }
+    runner.files.push(command_line);
+
+    //macros are expanded as the lines are read, so a macro call can carry on over lines and directives
+    let mut expander = MacroExpander::new(runner);
+    let mut tokens = Vec::new();
+    while let Some(tok) = expander.next_token()? {
+        tokens.push(tok);
+        expander.source_mut().token_count = tokens.len();
+    }
+
+    let runner = expander.into_source();
+    Ok(PreprocessedFile {
+        pragmas: runner.pragmas.into_effects(&tokens),
+        warnings: runner.warnings,
+        tokens,
+    })
+}
+
+/// Writes preprocessed tokens back out as C code, like `gcc -E` does
+/// 
+/// Each token stays on the line it was written on, and `# line "file"` markers show when the tokens came from somewhere else
+pub fn preprocessed_text(tokens: &[Located<Token>]) -> String {
+    let mut result = String::new();
+    let mut previous: Option<(&Path, u32)> = None;
+
+    for token in tokens {
+        let origin = token.span.expansion_root();//macros are shown where they were used
+        let (file, line) = (origin.file.as_path(), origin.start.line);
 
-pub fn preprocess_c_file(filename: &Path) -> Vec<Token> {
-    let initial_tokens = read_tokenise(filename);
+        match previous {
+            Some((previous_file, previous_line)) if previous_file == file && previous_line == line => result.push(' '),
+            //a few lines further on, so just skip to it
+            Some((previous_file, previous_line)) if previous_file == file && line > previous_line && line - previous_line <= 8 => {
+                result.push_str(&"\n".repeat((line - previous_line) as usize));
+            },
+            _ => {
+                if previous.is_some() {
+                    result.push('\n');
+                }
+                result.push_str(&format!("# {} \"{}\"\n", line, file.display()));
+            },
+        }
+
+        result.push_str(&token.data.spelling());
+        previous = Some((file, line));
+    }
+
+    result.push('\n');
+    result
+}
 
-    let include_handled = handle_includes(initial_tokens, 10);
+/// Reads and tokenises a file, where `included_from` is the #include that asked for the file
+fn read_tokenise(path: &Path, included_from: Option<&SourceSpan>) -> Result<Vec<Located<PreprocessToken>>, Diagnostic> {
+    let text = fs::read_to_string(path).map_err(|err| {
+        let diagnostic = Diagnostic::error(format!("couldn't read {}: {}", path.display(), err));
+        match included_from {
+            Some(span) => diagnostic.with_primary(span.clone(), "included here"),
+            None => diagnostic,
+        }
+    })?;
 
-    handle_preprocessor_commands(include_handled, filename.file_name().unwrap().to_str().unwrap())
+    tokenise(&text, path)
 }
 
-fn read_tokenise(path: &Path) -> Vec<LineNumbered> {
-    let text = format!("{}\n",
-        fs::read_to_string(path).expect(&format!("failed to open file {:?}", path))
-    )
-    .replace("\r\n", "\n")//fix weird newlines
-    .replace("\t", " ")//make all whitespace a space character or newline
-    .replace("\\\n", "");//remove \ newline, a feature in c
+/// Tokenises `text`, which came from the file at `path`
+fn tokenise(text: &str, path: &Path) -> Result<Vec<Located<PreprocessToken>>, Diagnostic> {
+    //remember where each character was, as the text gets changed before tokenising
+    let mut position = SourcePosition { line: 1, column: 1 };
+    let mut located_text = Vec::new();
+    for c in text.chars().chain(std::iter::once('\n')) {
+        located_text.push((c, position));
+        position = if c == '\n' {
+            SourcePosition { line: position.line + 1, column: 1 }
+        } else {
+            SourcePosition { line: position.line, column: position.column + 1 }
+        };
+    }
+
+    let mut cleaned_text: Vec<(char, SourcePosition)> = Vec::new();
+    for (c, position) in located_text {
+        if c == '\n' && cleaned_text.last().is_some_and(|(prev, _)| *prev == '\r') {
+            cleaned_text.pop();//fix weird newlines
+        }
+        if c == '\n' && cleaned_text.last().is_some_and(|(prev, _)| *prev == '\\') {
+            cleaned_text.pop();//remove \ newline, a feature in c
+            continue;
+        }
+        //make all whitespace a space character or newline
+        cleaned_text.push((if c == '\t' {' '} else {c}, position));
+    }
+
+    let cleaned_text = remove_comments(&cleaned_text);
 
-    let text = remove_comments(&text.chars().collect::<Vec<_>>()[..]);
+    //each byte of the text needs a position, as the lexers work in bytes
+    let mut text = String::new();
+    let mut positions = Vec::new();
+    for (c, position) in cleaned_text {
+        text.push(c);
+        positions.extend(std::iter::repeat(position).take(c.len_utf8()));
+    }
+    positions.push(position);//the end of the text
+
+    PreprocessToken::parse(&text, SourceMap::new(path.to_path_buf(), positions))
+}
 
-    PreprocessToken::parse(&text)
+/// A file that is being preprocessed
+struct IncludedFile {
+    path: PathBuf,
+    /// The folder of the search path that the file was found in, which `#include_next` carries on from
+    search_index: Option<usize>,
+    /// The rest of the file
+    tokens: VecDeque<Located<PreprocessToken>>,
 }
 
-fn handle_includes(tokens: Vec<LineNumbered>, include_limit: i32) -> Vec<LineNumbered> {
-    if include_limit == 0 {
-        return tokens;//ran out of recursive depth, don't bother including anything else
+/// Runs the preprocessor directives, and gives the lines of code that are not skipped to the macro expander
+struct DirectiveRunner<'a> {
+    ctx: PreprocessContext,
+    options: &'a PreprocessorOptions,
+    warning_options: &'a WarningOptions,
+    /// The files being read, where each one was included by the one before it
+    files: Vec<IncludedFile>,
+    /// Headers with `#pragma once`, which are never read again
+    once_headers: HashSet<PathBuf>,
+    /// The macro that each header with an include guard is skipped by
+    include_guards: HashMap<PathBuf, String>,
+    /// The rest of the line of code that is being read
+    line: VecDeque<Located<Token>>,
+    pragmas: PragmaState,
+    warnings: Vec<Diagnostic>,
+    /// How many tokens have been preprocessed so far, which is where a pragma starts to apply from
+    token_count: usize,
+}
+
+impl TokenSource for DirectiveRunner<'_> {
+    fn next_token(&mut self) -> Result<Option<Located<Token>>, Diagnostic> {
+        loop {
+            if let Some(tok) = self.line.pop_front() {
+                return Ok(Some(tok));
+            }
+            let Some(file) = self.files.last_mut() else {
+                return Ok(None);
+            };
+            match file.tokens.pop_front() {
+                Some(tok) => self.run(tok)?,
+                None => {
+                    self.files.pop();//carry on with the file that included this one
+                }
+            }
+        }
     }
 
-    tokens
-    .into_iter()
-    .flat_map(|tok| -> Box<dyn Iterator<Item = LineNumbered>> {
-        match tok.data {
-            PreprocessToken::IncludeFile(path) => {
-                let path = PathBuf::try_from(path).unwrap();
-                Box::new(handle_includes(read_tokenise(&path), include_limit-1).into_iter())
+    fn context(&self) -> &PreprocessContext {
+        &self.ctx
+    }
+
+    fn run_pragma(&mut self, text: &str, span: &SourceSpan) -> Result<(), Diagnostic> {
+        match parse_pragma(text, span)? {
+            Some(Pragma::Once) => {
+                self.once_headers.insert(file_identity(&self.files.last().unwrap().path));
             },
-            PreprocessToken::IncludeLib(include_filename) => {
-                let path = find_first_working_path(INCLUDE_FOLDERS, &include_filename).expect("couldn't find a folder that had that header");
-                Box::new(handle_includes(read_tokenise(&path), include_limit-1).into_iter())
+            Some(Pragma::Pack(pack)) => self.pragmas.apply_pack(pack, self.token_count, span)?,
+            Some(Pragma::Diagnostic(diagnostic)) => self.pragmas.apply_diagnostic(diagnostic, self.token_count, span)?,
+            None => {
+                //the standard allows pragmas that aren't recognised to be ignored
+                if !self.pragmas.is_ignored(WarningKind::UnknownPragmas) {
+                    let warning = self.warning_options.warning(WarningKind::UnknownPragmas, format!("ignoring unknown pragma `{}`", text))
+                        .map(|warning| warning.with_primary(span.clone(), ""));
+                    self.warnings.extend(warning);
+                }
             },
-            _ => Box::new(std::iter::once(tok)),
         }
-    })
-    .collect()
+
+        Ok(())
+    }
 }
 
-fn handle_preprocessor_commands(tokens: Vec<LineNumbered>, filename: &str) -> Vec<Token> {
-    let mut ctx = PreprocessContext::new(filename);
-    let mut result = Vec::new();
-    let mut result_buffer = Vec::new();//while parsing sequential lines (not separated by preprocess directives) store them here before they get flushed
-    let mut tokens: VecDeque<_> = tokens.into();
+impl DirectiveRunner<'_> {
+    /// Starts reading the file at `path`, unless `#pragma once` or its include guard means that it would be skipped
+    fn include(&mut self, path: PathBuf, search_index: Option<usize>, included_from: Option<&SourceSpan>) -> Result<(), Diagnostic> {
+        let key = file_identity(&path);
+        if self.once_headers.contains(&key) {
+            return Ok(());
+        }
+        if let Some(guard) = self.include_guards.get(&key) {
+            if self.ctx.has_definition(guard) || self.ctx.get_macro_func(guard).is_some() {
+                return Ok(());//the whole file would be skipped, so don't bother reading it again
+            }
+        }
 
-    while let Some(tok) = tokens.pop_front() {
-        let next_tok = tokens.get(0);
+        if self.files.len() >= MAX_INCLUDE_DEPTH {
+            let diagnostic = Diagnostic::error("#include nested too deeply")
+                .with_note(format!("files can only be included {} deep", MAX_INCLUDE_DEPTH))
+                .with_help("add an include guard or `#pragma once` to headers that are included in a loop");
+            return Err(match included_from {
+                Some(span) => diagnostic.with_primary(span.clone(), ""),
+                None => diagnostic,
+            });
+        }
 
-        ctx.set_line_number(tok.line_num);
+        let tokens = read_tokenise(&path, included_from)?;
+        if let Some(guard) = find_include_guard(&tokens) {
+            self.include_guards.insert(key, guard);
+        }
+        self.files.push(IncludedFile { path, search_index, tokens: tokens.into() });
+
+        Ok(())
+    }
+
+    /// Runs a directive, or queues up a line of code if it is not being skipped
+    fn run(&mut self, tok: Located<PreprocessToken>) -> Result<(), Diagnostic> {
+        let ctx = &mut self.ctx;
+        //__has_include looks for headers like an #include in the current file would
+        let has_include = |filename: &str, is_quoted: bool| find_include_file(filename, is_quoted, false, self.files.last().unwrap(), self.options).is_some();
         match tok.data {
             PreprocessToken::NullDirective => {},//this does nothing
-            PreprocessToken::IncludeLib(_) |
-            PreprocessToken::IncludeFile(_) => panic!("you need to substitute includes before handling preprocessor commands"),
+            PreprocessToken::Include(target) => {
+                if ctx.get_scan_type() == ScanType::NORMAL {
+                    let (path, search_index) = find_include_file(&target.filename, target.is_quoted, target.is_next, self.files.last().unwrap(), self.options)
+                        .ok_or_else(|| Diagnostic::error(format!("couldn't find header `{}`", target.filename))
+                            .with_primary(tok.span.clone(), "included here")
+                            .with_help(format!("searched in: {}", self.options.search_path().iter().map(|x| x.display().to_string()).collect::<Vec<_>>().join(", "))))?;
+                    self.include(path, search_index, Some(&tok.span))?;
+                }
+            },
 
             PreprocessToken::LineDirective(text) => {
-                let text = text.into_iter().map(|x| (x, tok.line_num)).collect();
                 //sub macros
-                let text = sub_definitions(text, &ctx, &Vec::new(), &HashMap::new());
-
-                assert!(matches!(text.len(), 1..=2));
-                unwrap_let!(Token::NUMBER(NumberLiteral::INTEGER { data:new_line,.. }) = text[0]);
-                ctx.override_line_number(new_line.try_into().unwrap());
-
-                if let Some(Token::STRING(new_filename)) = text.get(1) {
-                    ctx.override_filename(new_filename.clone());
+                let text = expand_macros(text, ctx)?;
+
+                let invalid_line = || Diagnostic::error("invalid #line directive")
+                    .with_primary(tok.span.clone(), "")
+                    .with_help("use `#line NUMBER` or `#line NUMBER \"FILENAME\"`");
+
+                let new_line = match text.first() {
+                    Some(Located { data: Token::NUMBER(NumberToken { value: NumberLiteral::INTEGER { data, .. }, .. }), .. }) if text.len() <= 2 => *data,
+                    _ => return Err(invalid_line()),
+                };
+                ctx.override_line_number(new_line.try_into().map_err(|_| invalid_line().with_note("the line number is too large"))?);
+
+                match text.get(1) {
+                    Some(Located { data: Token::STRING(new_filename), .. }) => ctx.override_filename(new_filename.clone()),
+                    Some(_) => return Err(invalid_line()),
+                    None => {}
                 }
             }
 
             PreprocessToken::Error(err) => {
                 if ctx.get_scan_type() == ScanType::NORMAL {
-                    panic!("encountered {}", err);
+                    let message = err.split_once("error").unwrap().1.trim();
+                    return Err(Diagnostic::error(format!("#error {}", message)).with_primary(tok.span, ""));
                 }
             }
 
             PreprocessToken::IfDef(x) => {
                 let defined = 
-                    ctx.get_definition(&x, ctx.get_line_number()).is_some() ||
+                    ctx.has_definition(&x) ||
                     ctx.get_macro_func(&x).is_some();
                 ctx.inc_selection_depth();
                 if !defined && ctx.get_scan_type() == ScanType::NORMAL {
@@ -99,7 +310,7 @@ fn handle_preprocessor_commands(tokens: Vec<LineNumbered>, filename: &str) -> Ve
             },
             PreprocessToken::IfNDef(x) => {
                 let defined = 
-                    ctx.get_definition(&x, ctx.get_line_number()).is_some() ||
+                    ctx.has_definition(&x) ||
                     ctx.get_macro_func(&x).is_some();
                 ctx.inc_selection_depth();
                 if defined && ctx.get_scan_type() == ScanType::NORMAL {
@@ -109,14 +320,18 @@ fn handle_preprocessor_commands(tokens: Vec<LineNumbered>, filename: &str) -> Ve
             },
 
             PreprocessToken::If(condition_tokens) => {
-                let condition: bool = is_true(fold(condition_tokens, &ctx));
+                let condition: bool = is_true(fold(condition_tokens, ctx, &has_include, &tok.span)?, &tok.span)?;
                 ctx.inc_selection_depth();
                 if !condition && ctx.get_scan_type() == ScanType::NORMAL {
                     // Was previously scanning, but this conditional failed
                     ctx.set_scan_type(ScanType::FINDINGTRUEBRANCH(ctx.selection_depth()));
                 }
             },
-            PreprocessToken::Pragma(_) => todo!(),
+            PreprocessToken::Pragma(pragma) => {
+                if ctx.get_scan_type() == ScanType::NORMAL {
+                    self.run_pragma(&pragma, &tok.span)?;
+                }
+            },
             PreprocessToken::Endif => {
                 ctx.dec_selection_depth();
 
@@ -141,7 +356,7 @@ fn handle_preprocessor_commands(tokens: Vec<LineNumbered>, filename: &str) -> Ve
                 })
             },
             PreprocessToken::Elif(condition_tokens) => {
-                let condition: bool = is_true(fold(condition_tokens, &ctx));
+                let condition: bool = is_true(fold(condition_tokens, ctx, &has_include, &tok.span)?, &tok.span)?;
                 match ctx.get_scan_type() {
                     ScanType::NORMAL => {
                         //was previously on taken branch, now skip all branches at this depth
@@ -174,41 +389,72 @@ fn handle_preprocessor_commands(tokens: Vec<LineNumbered>, filename: &str) -> Ve
             
             PreprocessToken::LineOfCode(line) => {
                 if ctx.get_scan_type() == ScanType::NORMAL {
-                    // TODO some macros are called over multiple lines, which means I need a buffer of lines until there is a #xyz then flush the buffer
-                    result_buffer.extend(
-                        line.into_iter()
-                        .map(|tok| (tok, ctx.get_line_number()))//add line counters
-                    );
-
-                    if let Some(LineNumbered {data: PreprocessToken::LineOfCode(_), ..}) = &next_tok {
-                        //next line of code is a valid line of code, don't flush buffer yet
-                    } else {
-                        //preprocess directive or EOF next, flush buffer
-                        result.extend(sub_definitions(result_buffer, &ctx, &Vec::new(), &HashMap::new()));//apply preprocessor, save to result
-                        result_buffer = Vec::new();//empty the buffer
-                    }
+                    self.line.extend(line);//each token already knows where it is
                 }
             },
         }
-    }
 
-    result
+        Ok(())
+    }
 }
 
 
-fn find_first_working_path(folders: &[&str], filename: &str) -> Option<PathBuf> {
-    for folder in folders {
-        let path = Path::new(folder).join(filename);
+/// Finds the file for an #include in `includer`, returning its path and the index of the search path folder it was found in
+/// 
+/// `#include "filename"` looks in the folder of `includer` first, and `#include_next` looks after the folder that `includer` was found in
+fn find_include_file(filename: &str, is_quoted: bool, is_next: bool, includer: &IncludedFile, options: &PreprocessorOptions) -> Option<(PathBuf, Option<usize>)> {
+    let search_path = options.search_path();
+    let find_header = |first_folder| PreprocessorOptions::find_header(&search_path, filename, first_folder).map(|(i, path)| (path, Some(i)));
+
+    match (is_next, includer.search_index) {
+        (true, Some(search_index)) => find_header(search_index + 1),
+        //otherwise #include_next is just like #include, as there is no folder to carry on from
+        _ => {
+            let beside_includer = includer.path.parent().unwrap_or(Path::new("")).join(filename);
+            if is_quoted && beside_includer.is_file() {
+                Some((beside_includer, None))
+            } else {
+                find_header(0)
+            }
+        }
+    }
+}
 
-        if path.exists() {
-            return Some(path);
+/// Finds the macro that guards a whole file, like `#ifndef NAME` ... `#endif`, so the file can be skipped without reading it when NAME is defined
+fn find_include_guard(tokens: &[Located<PreprocessToken>]) -> Option<String> {
+    let Some(PreprocessToken::IfNDef(guard)) = tokens.first().map(|tok| &tok.data) else {
+        return None;
+    };
+
+    //the #endif for the #ifndef must be the end of the file, without an #else or #elif
+    let mut depth = 0;
+    for (i, tok) in tokens.iter().enumerate() {
+        match tok.data {
+            PreprocessToken::If(_) |
+            PreprocessToken::IfDef(_) |
+            PreprocessToken::IfNDef(_) => depth += 1,
+            PreprocessToken::Else |
+            PreprocessToken::Elif(_) if depth == 1 => return None,
+            PreprocessToken::Endif => {
+                depth -= 1;
+                if depth == 0 {
+                    return (i == tokens.len() - 1).then(|| guard.clone());
+                }
+            },
+            _ => {}
         }
     }
 
     None
 }
 
-fn remove_comments(data: &[char]) -> String {
+/// The same file always has the same identity, however its path was written
+fn file_identity(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Removes comments, keeping track of where each remaining character came from
+fn remove_comments(data: &[(char, SourcePosition)]) -> Vec<(char, SourcePosition)> {
     enum State {
         Normal,
         CharLit,
@@ -217,11 +463,11 @@ fn remove_comments(data: &[char]) -> String {
         MultilineComment,
     }
     let mut state = State::Normal;
-    let mut result = String::new();
+    let mut result = Vec::new();
 
     for i in 0..data.len() {
-        let curr = data[i];
-        let prev = if let Some(prev_idx) = i.checked_sub(1) {Some(data[prev_idx])} else {None};
+        let (curr, curr_position) = data[i];
+        let prev = if let Some(prev_idx) = i.checked_sub(1) {Some(data[prev_idx].0)} else {None};
 
         match (&state, prev, curr) {
             (State::Normal, _, '"') => {
@@ -232,12 +478,12 @@ fn remove_comments(data: &[char]) -> String {
             }
             (State::Normal, Some('/'), '/') => {
                 state = State::LineComment;// start of single line comment
-                assert_eq!(result.pop(), Some('/'));//remove the first / that was accidentally added
+                assert_eq!(result.pop().map(|(c, _)| c), Some('/'));//remove the first / that was accidentally added
                 continue;
             }
             (State::Normal, Some('/'), '*') => {
                 state = State::MultilineComment;//start of multiline comment
-                assert_eq!(result.pop(), Some('/'));//remove the first / that was accidentally added
+                assert_eq!(result.pop().map(|(c, _)| c), Some('/'));//remove the first / that was accidentally added
                 continue;
             }
             (State::Normal, _, _) => {}//normal character
@@ -249,7 +495,7 @@ fn remove_comments(data: &[char]) -> String {
 
             (State::MultilineComment, Some('*'), '/') => {
                 state = State::Normal;//end of multiline comment
-                result.push(' ');//push whitespace to ensure that multiline comment becomes a whitespace character of some sort
+                result.push((' ', curr_position));//push whitespace to ensure that multiline comment becomes a whitespace character of some sort
                 continue;//don't push the '/'
             }
             (State::MultilineComment, _, '\n') => {}//newlines in multiline comments are preserved - note that this causes problems
@@ -269,7 +515,7 @@ fn remove_comments(data: &[char]) -> String {
         }
 
         //match done, push the character
-        result.push(curr);
+        result.push((curr, curr_position));
     }
 
     result
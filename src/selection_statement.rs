@@ -1,17 +1,33 @@
-use crate::{asm_gen_data::{AsmData, GlobalAsmData}, assembly::{assembly::IRCode, comparison::AsmComparison, operand::Storage, operation::{IROperation, Label}}, ast_metadata::ASTMetadata, data_type::{base_type::{BaseType, ScalarType}, recursive_data_type::DataType}, debugging::ASTDisplay, expression::expression::{self, Expression}, generate_ir_traits::{GenerateIR, GetType}, lexer::{keywords::Keyword, punctuator::Punctuator, token::Token, token_savepoint::TokenQueueSlice, token_walk::TokenQueue}, number_literal::typed_value::NumberLiteral, parse_data::ParseData, statement::Statement};
+use serde::Serialize;
+use crate::{asm_gen_data::{AsmData, GlobalAsmData}, assembly::{assembly::IRCode, comparison::{AsmComparison, ComparisonKind}, jump_table::JumpTable, operand::Storage, operation::{IROperation, Label}}, ast_metadata::ASTMetadata, data_type::{base_type::{BaseType, IntegerType, ScalarType}, recursive_data_type::{calculate_unary_type_arithmetic, DataType}}, debugging::ASTDisplay, diagnostics::{diagnostic::Diagnostic, semantic_checker::{CheckSemantics, SemanticChecker}, warnings::WarningKind}, expression::{binary_expression_operator::BinaryExpressionOperator, expression::{self, promote, Expression}}, generate_ir_traits::{GenerateIR, GetType}, goto_and_labels::SwitchCaseLabel, lexer::{keywords::Keyword, punctuator::Punctuator, source_span::SourceSpan, token::Token, token_savepoint::TokenQueueSlice, token_walk::TokenQueue}, number_literal::typed_value::NumberLiteral, parse_data::ParseData, statement::Statement};
 use colored::Colorize;
 use stack_management::{simple_stack_frame::SimpleStackFrame, stack_item::StackItemKey};
 use unwrap_let::unwrap_let;
 use uuid::Uuid;
 
+/// switch statements with at least this many cases can use a jump table
+const JUMP_TABLE_MIN_CASES: usize = 4;
+/// switch statements use a jump table if there is at most this many table entries per case (so unused entries are limited)
+const JUMP_TABLE_MAX_ENTRIES_PER_CASE: i128 = 3;
+
 /**
  * this handles if statements and other conditionals
  */
+#[derive(Serialize)]
 pub enum SelectionStatement{
     IF{
         condition: Expression,
         if_body: Box<Statement>,
-        else_body: Option<Box<Statement>>
+        else_body: Option<Box<Statement>>,
+        /// whether the condition has an extra set of brackets, like `if ((x = 1))`
+        parenthesised_condition: bool,
+        span: SourceSpan,
+    },
+    SWITCH{
+        condition: Expression,
+        body: Box<Statement>,
+        cases: Vec<SwitchCaseLabel>,//all the `case x:` and `default:` labels found in the body
+        span: SourceSpan,
     }
 }
 
@@ -26,15 +42,23 @@ impl SelectionStatement {
         match kw {
             Keyword::IF => {
                 
+                if tokens_queue.peek(&curr_queue_idx, &scope_data) != Some(Token::PUNCTUATOR(Punctuator::OPENCURLY)) {
+                    scope_data.report_error(tokens_queue.expected_error(&curr_queue_idx, "`(` after `if`"));
+                    return None;
+                }
                 let closecurly_idx = tokens_queue.find_matching_close_bracket(curr_queue_idx.index);
-                assert!(Token::PUNCTUATOR(Punctuator::OPENCURLY) == tokens_queue.consume(&mut curr_queue_idx, &scope_data).unwrap());//ensure opening parenthesis
+                tokens_queue.consume(&mut curr_queue_idx, &scope_data);//consume the opening parenthesis
 
                 let condition_slice = TokenQueueSlice{
                     index: curr_queue_idx.index,
                     max_index: closecurly_idx
                 };
 
-                let condition = expression::try_consume_whole_expr(tokens_queue, &condition_slice, scope_data).expect(&tokens_queue.display_slice(&condition_slice));
+                let Some(condition) = expression::try_consume_whole_expr(tokens_queue, &condition_slice, scope_data) else {
+                    scope_data.report_error(Diagnostic::error("invalid condition in if statement").with_primary(tokens_queue.get_span(&condition_slice), ""));
+                    return None;
+                };
+                let parenthesised_condition = tokens_queue.slice_is_brackets(&condition_slice, Punctuator::OPENCURLY);
 
                 //consume the condition
                 curr_queue_idx = TokenQueueSlice{
@@ -43,7 +67,7 @@ impl SelectionStatement {
                 };
 
                 //consume the function body
-                let ASTMetadata{ remaining_slice, resultant_tree: taken_body } = Statement::try_consume(tokens_queue, &curr_queue_idx, scope_data).unwrap();
+                let ASTMetadata{ remaining_slice, resultant_tree: taken_body } = Statement::try_consume_required(tokens_queue, &curr_queue_idx, scope_data, "a statement after the if condition")?;
                 curr_queue_idx = remaining_slice;
 
                 let has_else_branch = tokens_queue.peek(&curr_queue_idx, &scope_data).is_some_and(|x| x == Token::KEYWORD(Keyword::ELSE));
@@ -51,7 +75,7 @@ impl SelectionStatement {
                 //try and consume the else branch
                 let not_taken_body: Option<Box<Statement>> = if has_else_branch {
                     tokens_queue.consume(&mut curr_queue_idx, &scope_data);//consume the else keyword
-                    let ASTMetadata{ remaining_slice, resultant_tree: else_body} = Statement::try_consume(tokens_queue, &curr_queue_idx, scope_data).unwrap();
+                    let ASTMetadata{ remaining_slice, resultant_tree: else_body} = Statement::try_consume_required(tokens_queue, &curr_queue_idx, scope_data, "a statement after `else`")?;
                     curr_queue_idx = remaining_slice;//consume the else
 
                     Some(Box::new(else_body))
@@ -59,14 +83,104 @@ impl SelectionStatement {
                     None//no else branch
                 };
 
+                let span = tokens_queue.get_span(&TokenQueueSlice { index: previous_queue_idx.index, max_index: curr_queue_idx.index });
+
                 Some(ASTMetadata{
-                    resultant_tree: Self::IF{condition, if_body: Box::new(taken_body), else_body: not_taken_body}, 
+                    resultant_tree: Self::IF{condition, if_body: Box::new(taken_body), else_body: not_taken_body, parenthesised_condition, span}, 
                     remaining_slice: curr_queue_idx, 
                 })
             }
+            Keyword::SWITCH => {
+
+                if tokens_queue.peek(&curr_queue_idx, &scope_data) != Some(Token::PUNCTUATOR(Punctuator::OPENCURLY)) {
+                    scope_data.report_error(tokens_queue.expected_error(&curr_queue_idx, "`(` after `switch`"));
+                    return None;
+                }
+                let closecurly_idx = tokens_queue.find_matching_close_bracket(curr_queue_idx.index);
+                tokens_queue.consume(&mut curr_queue_idx, &scope_data);//consume the opening parenthesis
+
+                let condition_slice = TokenQueueSlice{
+                    index: curr_queue_idx.index,
+                    max_index: closecurly_idx
+                };
+
+                let Some(condition) = expression::try_consume_whole_expr(tokens_queue, &condition_slice, scope_data) else {
+                    scope_data.report_error(Diagnostic::error("invalid controlling expression in switch statement").with_primary(tokens_queue.get_span(&condition_slice), ""));
+                    return None;
+                };
+
+                //consume the condition
+                curr_queue_idx = TokenQueueSlice{
+                    index: closecurly_idx + 1,
+                    max_index: curr_queue_idx.max_index
+                };
+
+                //consume the body
+                let ASTMetadata{ remaining_slice, resultant_tree: body } = Statement::try_consume_required(tokens_queue, &curr_queue_idx, scope_data, "a statement after the switch condition")?;
+                curr_queue_idx = remaining_slice;
+
+                let cases: Vec<_> = body.get_switch_cases().into_iter().cloned().collect();
+
+                let defaults: Vec<_> = cases.iter().filter(|x| x.get_value().is_none()).collect();
+                if let [first, duplicates @ ..] = defaults.as_slice() {
+                    for duplicate in duplicates {
+                        scope_data.report_error(Diagnostic::error("multiple default labels in one switch statement")
+                            .with_primary(duplicate.get_span().clone(), "")
+                            .with_secondary(first.get_span().clone(), "previous default label here"));
+                    }
+                }
+
+                let span = tokens_queue.get_span(&TokenQueueSlice { index: previous_queue_idx.index, max_index: curr_queue_idx.index });
+
+                Some(ASTMetadata{
+                    resultant_tree: Self::SWITCH { condition, body: Box::new(body), cases, span },
+                    remaining_slice: curr_queue_idx,
+                })
+            }
             _ => None
         }
     }
+
+    pub fn get_span(&self) -> &SourceSpan {
+        match self {
+            Self::IF { span, .. } |
+            Self::SWITCH { span, .. } => span,
+        }
+    }
+
+    /// see [Statement::get_switch_cases]
+    pub fn get_switch_cases(&self) -> Vec<&SwitchCaseLabel> {
+        match self {
+            Self::IF { if_body, else_body, .. } => {
+                let mut result = if_body.get_switch_cases();
+                if let Some(else_body) = else_body {
+                    result.extend(else_body.get_switch_cases());
+                }
+                result
+            },
+            Self::SWITCH { .. } => Vec::new(),//cases in a nested switch belong to that switch
+        }
+    }
+
+    /// see [Statement::falls_through]
+    pub fn falls_through(&self) -> bool {
+        match self {
+            Self::IF { if_body, else_body: Some(else_body), .. } => if_body.falls_through() || else_body.falls_through(),
+            Self::IF { else_body: None, .. } => true,//the condition could be false
+            Self::SWITCH { body, cases, .. } => {
+                let has_default = cases.iter().any(|x| x.get_value().is_none());
+                !has_default || body.falls_through() || body.contains_break()
+            },
+        }
+    }
+
+    /// see [Statement::contains_break]
+    pub fn contains_break(&self) -> bool {
+        match self {
+            Self::IF { if_body, else_body, .. } => if_body.contains_break() || else_body.as_ref().is_some_and(|x| x.contains_break()),
+            Self::SWITCH { .. } => false,//breaks in a nested switch leave that switch
+        }
+    }
 }
 
 impl GenerateIR for SelectionStatement {
@@ -74,7 +188,7 @@ impl GenerateIR for SelectionStatement {
         let mut result = IRCode::make_empty();
 
         match self {
-            Self::IF { condition, if_body, else_body } => {
+            Self::IF { condition, if_body, else_body, .. } => {
                 let generic_label = Uuid::new_v4().simple().to_string();
                 let else_label = Label::Local(format!("{}_else", generic_label));//jump for the else branch
                 let if_end_label = Label::Local(format!("{}_end", generic_label));//rendevous point for the if and else branches
@@ -94,13 +208,13 @@ impl GenerateIR for SelectionStatement {
                 result.add_instruction(IROperation::CMP {
                     lhs: Storage::Stack(condition_value.unwrap()),
                     rhs: Storage::Constant(zero),
-                    data_type: condition_type
+                    data_type: condition_type.clone()
                 });
 
                 //if the result is 0, jump to the else block or the end of the if statement
                 result.add_instruction(IROperation::JMPCC {
                     label: cond_false_label.clone(),
-                    comparison: AsmComparison::EQ,
+                    comparison: ComparisonKind::EQ.to_asm_comparison_for(&condition_type),
                 });
 
                 //generate the body of the if statement
@@ -127,16 +241,150 @@ impl GenerateIR for SelectionStatement {
                 result.add_instruction(IROperation::Label(if_end_label));
 
             }
+            Self::SWITCH { condition, body, cases, .. } => {
+                let generic_label = Uuid::new_v4().simple().to_string();
+                let switch_end_label = Label::Local(format!("{}_switch_end", generic_label));//"break;" jumps here
+
+                //the controlling expression is promoted (C99 6.8.4.2/5)
+                let condition_type = condition.get_type(asm_data);
+                let promoted_type = calculate_unary_type_arithmetic(&condition_type);
+                let switch_type = match &promoted_type {
+                    DataType::RAW(BaseType::Scalar(ScalarType::Integer(x))) => *x,
+                    x => unreachable!("non-integer switch conditions are reported by check_semantics, found {}", x)
+                };
+
+                let (condition_asm, condition_value) = condition.generate_ir(asm_data, stack_data, global_asm_data);
+                result.merge(&condition_asm);
+                let (promote_asm, switch_value) = promote(condition_value.unwrap(), condition_type, promoted_type, stack_data, asm_data);
+                result.add_instruction(promote_asm);
+
+                //case values are converted to the promoted type, and must be unique once converted
+                let mut case_values: Vec<(i128, &Label)> = Vec::new();
+                let mut default_label = None;
+                for case in cases {
+                    match case.get_value() {
+                        Some(value) => {
+                            unwrap_let!(NumberLiteral::INTEGER { data, .. } = value.cast(&ScalarType::Integer(switch_type)));
+                            assert!(case_values.iter().all(|(existing, _)| *existing != data), "duplicate case values are reported by check_semantics");
+                            case_values.push((data, case.get_label()));
+                        },
+                        None => default_label = Some(case.get_label()),
+                    }
+                }
+                //if no case matches, go to default, or skip the switch entirely
+                let no_match_label = default_label.unwrap_or(&switch_end_label).clone();
+
+                let lowest_value = case_values.iter().map(|(x, _)| *x).min();
+                let highest_value = case_values.iter().map(|(x, _)| *x).max();
+
+                let jump_table = match (lowest_value, highest_value) {
+                    (Some(lowest), Some(highest)) if case_values.len() >= JUMP_TABLE_MIN_CASES && highest - lowest + 1 <= JUMP_TABLE_MAX_ENTRIES_PER_CASE * case_values.len() as i128 => {
+                        //cases are dense, so generate a jump table
+                        let targets = (lowest..=highest)
+                            .map(|x| {
+                                case_values.iter()
+                                .find(|(value, _)| *value == x)
+                                .map(|(_, label)| (*label).clone())
+                                .unwrap_or(no_match_label.clone())//gaps in the cases act like no match
+                            })
+                            .collect();
+                        JumpTable::new(Label::Local(format!("{}_switch_table", generic_label)), lowest, targets, no_match_label.clone())
+                    }
+                    _ => None
+                };
+
+                match jump_table {
+                    Some(table) => {
+                        result.add_commented_instruction(IROperation::JMPTABLE { value: Storage::Stack(switch_value), data_type: switch_type, table }, "jump to the correct case");
+                    }
+                    None => {
+                        //compare against each case in turn
+                        for (value, label) in &case_values {
+                            result.add_instruction(IROperation::CMP {
+                                lhs: Storage::Stack(switch_value),
+                                rhs: Storage::Constant(NumberLiteral::INTEGER { data: *value, data_type: switch_type }),
+                                data_type: ScalarType::Integer(switch_type)
+                            });
+                            result.add_instruction(IROperation::JMPCC {
+                                label: (*label).clone(),
+                                comparison: AsmComparison::EQ,
+                            });
+                        }
+                        result.add_instruction(IROperation::JMPCC {
+                            label: no_match_label,
+                            comparison: AsmComparison::ALWAYS,
+                        });
+                    }
+                }
+
+                //generate the body, where cases fall through to each other unless "break;" is used
+                let asm_data = asm_data.clone_for_new_switch(switch_end_label.clone());
+                let (body_asm, _) = body.generate_ir(&asm_data, stack_data, global_asm_data);
+                result.merge(&body_asm);
+
+                result.add_instruction(IROperation::Label(switch_end_label));
+            }
         }
 
         (result, None)
     }
 }
 
+impl CheckSemantics for SelectionStatement {
+    fn check_semantics(&self, checker: &mut SemanticChecker) {
+        match self {
+            Self::IF { condition, if_body, else_body, parenthesised_condition, .. } => {
+                if let Expression::BINARYEXPRESSION(assignment) = condition {
+                    if assignment.operator == BinaryExpressionOperator::Assign && !parenthesised_condition {
+                        checker.report(WarningKind::Parentheses, "assignment used as a condition", |warning| warning
+                            .with_primary(condition.get_span().clone(), "")
+                            .with_help("use `==` to compare, or put the assignment in parentheses if it is intentional"));
+                    }
+                }
+
+                checker.check_condition(condition);
+                if_body.check_semantics(checker);
+                if let Some(else_body) = else_body {
+                    else_body.check_semantics(checker);
+                }
+            },
+            Self::SWITCH { condition, body, cases, .. } => {
+                if checker.check_expression(condition) {
+                    let promoted_type = calculate_unary_type_arithmetic(&condition.get_type(checker.asm_data()));
+                    match promoted_type {
+                        DataType::RAW(BaseType::Scalar(ScalarType::Integer(switch_type))) => check_duplicate_cases(cases, switch_type, checker),
+                        x => checker.report_error(Diagnostic::error(format!("switch statement requires an integer controlling expression, found `{}`", x))
+                            .with_primary(condition.get_span().clone(), "")),
+                    }
+                }
+                checker.check_switch_body(body);
+            },
+        }
+    }
+}
+
+/// Reports each case label that has the same value as an earlier one, once the values are converted to the type of the switch
+fn check_duplicate_cases(cases: &[SwitchCaseLabel], switch_type: IntegerType, checker: &mut SemanticChecker) {
+    let mut case_values: Vec<(i128, &SwitchCaseLabel)> = Vec::new();
+    for case in cases {
+        let Some(value) = case.get_value() else {
+            continue;//duplicate defaults are found while parsing
+        };
+        unwrap_let!(NumberLiteral::INTEGER { data, .. } = value.cast(&ScalarType::Integer(switch_type)));
+
+        match case_values.iter().find(|(existing, _)| *existing == data) {
+            Some((_, first)) => checker.report_error(Diagnostic::error(format!("duplicate case value {}", data))
+                .with_primary(case.get_span().clone(), "")
+                .with_secondary(first.get_span().clone(), "previous case label here")),
+            None => case_values.push((data, case)),
+        }
+    }
+}
+
 impl ASTDisplay for SelectionStatement {
     fn display_ast(&self, f: &mut crate::debugging::TreeDisplayInfo) {
         match self {
-            SelectionStatement::IF { condition, if_body, else_body } => {
+            SelectionStatement::IF { condition, if_body, else_body, .. } => {
                 f.write(&"if statement".red().to_string());
                 f.indent();
 
@@ -157,6 +405,22 @@ impl ASTDisplay for SelectionStatement {
                     f.dedent();
                 }
 
+                f.dedent();
+            }
+            SelectionStatement::SWITCH { condition, body, .. } => {
+                f.write(&"switch statement".red().to_string());
+                f.indent();
+
+                f.write(&"condition".green().to_string());
+                f.indent();
+                condition.display_ast(f);
+                f.dedent();
+
+                f.write(&"body".red().to_string());
+                f.indent();
+                body.display_ast(f);
+                f.dedent();
+
                 f.dedent();
             }
         }
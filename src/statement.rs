@@ -1,7 +1,9 @@
 use stack_management::simple_stack_frame::SimpleStackFrame;
 
-use crate::{asm_gen_data::{AsmData, GlobalAsmData}, assembly::assembly::IRCode, ast_metadata::ASTMetadata, compound_statement::ScopeStatements, control_flow_statement::ControlFlowChange, debugging::ASTDisplay, expression::expression::Expression, generate_ir_traits::GenerateIR, goto_and_labels::{CustomLabel, Goto}, iteration_statement::IterationStatement, lexer::{punctuator::Punctuator, token::Token, token_savepoint::TokenQueueSlice, token_walk::TokenQueue}, parse_data::ParseData, selection_statement::SelectionStatement};
+use serde::Serialize;
+use crate::{asm_gen_data::{AsmData, GlobalAsmData}, assembly::assembly::IRCode, ast_metadata::ASTMetadata, compound_statement::ScopeStatements, control_flow_statement::ControlFlowChange, debugging::ASTDisplay, diagnostics::semantic_checker::{CheckSemantics, SemanticChecker}, expression::expression::Expression, generate_ir_traits::GenerateIR, goto_and_labels::{CustomLabel, Goto, SwitchCaseLabel}, iteration_statement::IterationStatement, lexer::{punctuator::Punctuator, source_span::SourceSpan, token::Token, token_savepoint::TokenQueueSlice, token_walk::TokenQueue}, parse_data::ParseData, selection_statement::SelectionStatement};
 
+#[derive(Serialize)]
 pub enum Statement {
     EXPRESSION(Expression),
     COMPOUND(ScopeStatements),//this is a scope (not nescessarily for a function)
@@ -10,7 +12,8 @@ pub enum Statement {
     CONTROLFLOW(ControlFlowChange),
     GOTO(Goto),
     LABEL(CustomLabel),//this is technically not a statement, as it should be attached to a statement, not be the statement itself
-    NOP,//for example, the line of code ";;;;;;;;"
+    CASE(SwitchCaseLabel),//same as above, but for `case x:` and `default:`
+    NOP(SourceSpan),//for example, the line of code ";;;;;;;;"
 }
 
 impl Statement {
@@ -20,12 +23,17 @@ impl Statement {
      */
     pub fn try_consume(tokens_queue: &mut TokenQueue, previous_queue_idx: &TokenQueueSlice, scope_data: &mut ParseData) -> Option<ASTMetadata<Statement>> {
         let curr_queue_idx = previous_queue_idx.clone();
+        let errors_before = scope_data.error_count();
 
         //this should be first, because label: could be counted as an expression which would break everything
         if let Some(ASTMetadata { remaining_slice, resultant_tree }) = CustomLabel::try_consume(tokens_queue, previous_queue_idx, scope_data) {
             return Some(ASTMetadata { remaining_slice, resultant_tree: Self::LABEL(resultant_tree) })
         }
 
+        if let Some(ASTMetadata { remaining_slice, resultant_tree }) = SwitchCaseLabel::try_consume(tokens_queue, previous_queue_idx, scope_data) {
+            return Some(ASTMetadata { remaining_slice, resultant_tree: Self::CASE(resultant_tree) })
+        }
+
         if let Some(ASTMetadata{resultant_tree, remaining_slice}) = ScopeStatements::try_consume(tokens_queue, &curr_queue_idx, &scope_data){
             return Some(ASTMetadata{resultant_tree: Self::COMPOUND(resultant_tree), remaining_slice});
         }
@@ -42,6 +50,10 @@ impl Statement {
             return Some(ASTMetadata{resultant_tree: Self::CONTROLFLOW(resultant_tree), remaining_slice});
         }
 
+        if scope_data.error_count() > errors_before {
+            return None;//a statement starting with a keyword was found, but it had errors
+        }
+
         if let Some(ASTMetadata{resultant_tree, remaining_slice}) = Expression::try_consume(tokens_queue, &curr_queue_idx, scope_data){
             return Some(ASTMetadata{resultant_tree: Self::EXPRESSION(resultant_tree), remaining_slice});
         }
@@ -52,11 +64,96 @@ impl Statement {
 
         if tokens_queue.peek(&curr_queue_idx, scope_data) == Some(Token::PUNCTUATOR(Punctuator::SEMICOLON)) {
             //just a ; so is a nop
-            return Some(ASTMetadata { remaining_slice: curr_queue_idx.next_clone(), resultant_tree: Self::NOP });
+            let span = tokens_queue.get_token_span(curr_queue_idx.index).clone();
+            return Some(ASTMetadata { remaining_slice: curr_queue_idx.next_clone(), resultant_tree: Self::NOP(span) });
         }
 
         None
     }
+
+    /// Parses a statement that has to be there, like the body of a loop, reporting an error if it can't be found
+    /// 
+    /// `expected` describes what should have been there
+    pub fn try_consume_required(tokens_queue: &mut TokenQueue, previous_queue_idx: &TokenQueueSlice, scope_data: &mut ParseData, expected: &str) -> Option<ASTMetadata<Statement>> {
+        if tokens_queue.no_remaining_tokens(previous_queue_idx) {
+            scope_data.report_error(tokens_queue.expected_error(previous_queue_idx, expected));
+            return None;
+        }
+
+        let errors_before = scope_data.error_count();
+        let result = Self::try_consume(tokens_queue, previous_queue_idx, scope_data);
+
+        if result.is_none() && scope_data.error_count() == errors_before {
+            //nothing more specific was reported
+            scope_data.report_error(tokens_queue.expected_error(previous_queue_idx, expected));
+        }
+
+        result
+    }
+
+    /// Where this statement is in the source code
+    pub fn get_span(&self) -> &SourceSpan {
+        match self {
+            Self::EXPRESSION(expr) => expr.get_span(),
+            Self::COMPOUND(scope) => scope.get_span(),
+            Self::SELECTION(selection) => selection.get_span(),
+            Self::ITERATION(it) => it.get_span(),
+            Self::CONTROLFLOW(command) => command.get_span(),
+            Self::GOTO(goto) => goto.get_span(),
+            Self::LABEL(label) => label.get_span(),
+            Self::CASE(case) => case.get_span(),
+            Self::NOP(span) => span,
+        }
+    }
+
+    /// Finds all the `case x:` and `default:` labels that belong to a switch statement with this as its body
+    /// 
+    /// This does not look inside nested switch statements, as they own their own cases
+    pub fn get_switch_cases(&self) -> Vec<&SwitchCaseLabel> {
+        match self {
+            Self::CASE(case) => vec![case],
+            Self::COMPOUND(scope) => scope.get_switch_cases(),
+            Self::SELECTION(selection) => selection.get_switch_cases(),
+            Self::ITERATION(it) => it.get_switch_cases(),
+            Self::EXPRESSION(_) |
+            Self::CONTROLFLOW(_) |
+            Self::GOTO(_) |
+            Self::LABEL(_) |
+            Self::NOP(_) => Vec::new(),
+        }
+    }
+
+    /// Whether the code after this statement can run, i.e this statement does not always jump away
+    pub fn falls_through(&self) -> bool {
+        match self {
+            Self::EXPRESSION(Expression::FUNCCALL(call)) => !call.never_returns(),
+            Self::COMPOUND(scope) => scope.falls_through(),
+            Self::SELECTION(selection) => selection.falls_through(),
+            Self::ITERATION(it) => it.falls_through(),
+            Self::CONTROLFLOW(_) |
+            Self::GOTO(_) => false,
+            Self::EXPRESSION(_) |
+            Self::LABEL(_) |
+            Self::CASE(_) |
+            Self::NOP(_) => true,
+        }
+    }
+
+    /// Whether this contains a `break;` that leaves the loop or switch statement that this is in
+    pub fn contains_break(&self) -> bool {
+        match self {
+            Self::CONTROLFLOW(ControlFlowChange::BREAK(_)) => true,
+            Self::COMPOUND(scope) => scope.contains_break(),
+            Self::SELECTION(selection) => selection.contains_break(),
+            Self::ITERATION(_) => false,//breaks in a nested loop leave that loop
+            Self::EXPRESSION(_) |
+            Self::CONTROLFLOW(_) |
+            Self::GOTO(_) |
+            Self::LABEL(_) |
+            Self::CASE(_) |
+            Self::NOP(_) => false,
+        }
+    }
 }
 
 impl GenerateIR for Statement {
@@ -80,7 +177,27 @@ impl GenerateIR for Statement {
 
             Self::LABEL(label) => label.generate_ir(asm_data, stack_data, global_asm_data),
 
-            Self::NOP => (IRCode::make_empty(), None),
+            Self::CASE(case) => case.generate_ir(asm_data, stack_data, global_asm_data),
+
+            Self::NOP(_) => (IRCode::make_empty(), None),
+        }
+    }
+}
+
+impl CheckSemantics for Statement {
+    fn check_semantics(&self, checker: &mut SemanticChecker) {
+        match self {
+            Self::EXPRESSION(expr) => {
+                checker.check_expression(expr);
+            },
+            Self::COMPOUND(scope) => scope.check_semantics(checker),
+            Self::SELECTION(selection) => selection.check_semantics(checker),
+            Self::ITERATION(it) => it.check_semantics(checker),
+            Self::CONTROLFLOW(command) => command.check_semantics(checker),
+            Self::GOTO(_) |
+            Self::LABEL(_) |
+            Self::CASE(_) |
+            Self::NOP(_) => {},
         }
     }
 }
@@ -95,7 +212,8 @@ impl ASTDisplay for Statement {
             Statement::CONTROLFLOW(control_flow_change) => control_flow_change.display_ast(f),
             Statement::GOTO(goto) => goto.display_ast(f),
             Statement::LABEL(label) => label.display_ast(f),
-            Statement::NOP => f.write("NOP"),
+            Statement::CASE(case) => case.display_ast(f),
+            Statement::NOP(_) => f.write("NOP"),
         }
     }
 }
\ No newline at end of file
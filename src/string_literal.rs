@@ -3,17 +3,26 @@ use std::hash::Hash;
 use unwrap_let::unwrap_let;
 use uuid::Uuid;
 
+use serde::Serialize;
+use stack_management::simple_stack_frame::SimpleStackFrame;
+use stack_management::stack_item::StackItemKey;
+use crate::asm_gen_data::{AsmData, GlobalAsmData};
+use crate::assembly::assembly::IRCode;
+use crate::assembly::operand::{IRMemOperand, PTR_SIZE};
+use crate::assembly::operation::{IROperation, Label};
 use crate::data_type::base_type::{BaseType, IntegerType, ScalarType};
 use crate::data_type::recursive_data_type::DataType;
 use crate::data_type::type_modifier::DeclModifier;
 use crate::expression::expression::Expression;
 use crate::expression_visitors::expr_visitor::ExprVisitor;
-use crate::generate_ir_traits::GetType;
+use crate::generate_ir_traits::{GenerateIR, GetAddress, GetType};
+use crate::lexer::source_span::SourceSpan;
 use crate::number_literal::typed_value::NumberLiteral;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct StringLiteral {
     text: Vec<i8>,//text plus zero terminator
+    #[serde(skip)]
     label: String
 }
 
@@ -33,11 +42,11 @@ impl StringLiteral {
     pub fn get_label(&self) -> &str {
         &self.label
     }
-    pub fn get_comma_separated_bytes(&self) -> String {
+    /// Includes the zero terminator
+    pub fn get_bytes(&self) -> Vec<u8> {
         self.text.iter()
-        .map(|x| x.to_string())
-        .collect::<Vec<_>>()
-        .join(",")
+        .map(|x| *x as u8)
+        .collect()
     }
     pub fn try_new(inside_speechmarks: &str) -> Option<StringLiteral> {
         assert!(inside_speechmarks.is_ascii());
@@ -58,6 +67,31 @@ impl StringLiteral {
         }
     }
 
+    /// The characters of the string, without the zero terminator
+    pub fn text(&self) -> String {
+        let chars = self.text.strip_suffix(&[0]).unwrap_or(&self.text);
+        chars.iter().map(|x| *x as u8 as char).collect()
+    }
+
+    /// Writes the string as it would appear in C code, with speechmarks and escape sequences
+    pub fn spelling(&self) -> String {
+        let chars = self.text.strip_suffix(&[0]).unwrap_or(&self.text);//the zero terminator is not written
+        let mut result = String::from("\"");
+        for c in chars.iter().map(|x| *x as u8 as char) {
+            match c {
+                '"' => result.push_str("\\\""),
+                '\\' => result.push_str("\\\\"),
+                '\n' => result.push_str("\\n"),
+                '\t' => result.push_str("\\t"),
+                '\r' => result.push_str("\\r"),
+                '\0' => result.push_str("\\0"),
+                c => result.push(c),
+            }
+        }
+        result.push('"');
+        result
+    }
+
     pub fn use_escape_sequences(text: &str) -> Vec<i8> {
         text
         .replace("\\0", "\0")//replace end of string
@@ -79,14 +113,15 @@ impl StringLiteral {
         .collect()
     }
 
-    pub fn zero_fill_and_flatten_to_iter(&self, array_data_type: &DataType) -> Vec<Expression> {
+    /// Each char gets the span of the string literal, as it is where they all came from
+    pub fn zero_fill_and_flatten_to_iter(&self, array_data_type: &DataType, span: &SourceSpan) -> Vec<Expression> {
         unwrap_let!(DataType::ARRAY{size, ..} = array_data_type);
 
         let extra_zeroes = size.checked_sub(self.text.len() as u64).unwrap();
 
         self.text.iter()
         .chain(std::iter::repeat_n(&0i8, extra_zeroes as usize))
-        .map(|num| Expression::NUMBERLITERAL(NumberLiteral::INTEGER { data: (*num).into(), data_type: IntegerType::I8 }))
+        .map(|num| Expression::NUMBERLITERAL(NumberLiteral::INTEGER { data: (*num).into(), data_type: IntegerType::I8 }, span.clone()))
         .collect()
     }
 }
@@ -98,6 +133,27 @@ impl GetType for StringLiteral {
     }
 }
 
+impl GetAddress for StringLiteral {
+    fn get_address(&self, _: &AsmData, stack_data: &mut SimpleStackFrame, _: &GlobalAsmData) -> (IRCode, StackItemKey) {
+        let ptr = stack_data.allocate(PTR_SIZE);
+        let mut result = IRCode::make_empty();
+
+        //the chars are saved in .rodata by the translation unit
+        result.add_instruction(IROperation::LEA {
+            from: IRMemOperand::Label { label: Label::Global(self.label.clone()) },
+            to: IRMemOperand::Stack { base: ptr },
+        });
+
+        (result, ptr)
+    }
+}
+impl GenerateIR for StringLiteral {
+    fn generate_ir(&self, asm_data: &AsmData, stack_data: &mut SimpleStackFrame, global_asm_data: &GlobalAsmData) -> (IRCode, Option<StackItemKey>) {
+        let (result, ptr) = self.get_address(asm_data, stack_data, global_asm_data);
+        (result, Some(ptr))//like any other array, the string decays to a pointer
+    }
+}
+
 impl Hash for StringLiteral {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
         self.label.hash(state);
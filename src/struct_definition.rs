@@ -1,12 +1,14 @@
 use std::fmt::Display;
 
+use serde::{Serialize, Serializer};
 use crate::{asm_gen_data::GetStructUnion, ast_metadata::ASTMetadata, data_type::{recursive_data_type::DataType, storage_type::StorageDuration}, declaration::Declaration, initialised_declaration::{consume_type_specifier, try_consume_declaration_modifiers}, lexer::{keywords::Keyword, punctuator::Punctuator, token::Token, token_savepoint::TokenQueueSlice, token_walk::{TokenQueue, TokenSearchType}}, parse_data::ParseData};
 use memory_size::MemorySize;
 use uuid::Uuid;
 
-#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize)]
 pub struct StructIdentifier {
     pub(crate) name: Option<String>,
+    /// random on each compile, but tells apart structs with the same name in different scopes
     pub(crate) id: Uuid
 }
 impl Display for StructIdentifier {
@@ -19,9 +21,12 @@ impl Display for StructIdentifier {
 /**
  * before assembly generation, structs have not had padding calculated
  */
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub struct UnpaddedStructDefinition {
-    pub(crate) ordered_members: Option<Vec<Declaration>>
+    pub(crate) ordered_members: Option<Vec<Declaration>>,
+    /// the largest alignment that a member can have, from `#pragma pack`
+    #[serde(serialize_with = "serialize_max_alignment")]
+    pub(crate) max_alignment: Option<MemorySize>,
 }
 
 impl UnpaddedStructDefinition {
@@ -34,7 +39,7 @@ impl UnpaddedStructDefinition {
         let mut result = Vec::new();
         if let Some(some_ordered_members) = self.ordered_members.as_ref() {
             for m in some_ordered_members {
-                let alignment_bytes = calculate_alignment(&m.data_type, struct_info).size_bytes();
+                let alignment_bytes = member_alignment(&m.data_type, self.max_alignment, struct_info).size_bytes();
     
                 let bytes_past_last_boundary = current_offset.size_bytes() % alignment_bytes;
                 let extra_padding = (alignment_bytes - bytes_past_last_boundary) % alignment_bytes;
@@ -46,7 +51,7 @@ impl UnpaddedStructDefinition {
     
             //lastly, align to largest member's alignment, so that if this struct is in an array, subsequent structs are aligned
             let largest_member_alignment = self.ordered_members.as_ref().unwrap().iter()
-                .map(|x| calculate_alignment(&x.data_type, struct_info))
+                .map(|x| member_alignment(&x.data_type, self.max_alignment, struct_info))
                 .fold(MemorySize::new(), |acc, x| acc.max(x))
                 .size_bytes();
             let bytes_past_last_boundary = current_offset.size_bytes() % largest_member_alignment;
@@ -60,6 +65,11 @@ impl UnpaddedStructDefinition {
     }
 }
 
+/// Writes the `#pragma pack` alignment as a number of bytes
+pub(crate) fn serialize_max_alignment<S: Serializer>(max_alignment: &Option<MemorySize>, serializer: S) -> Result<S::Ok, S::Error> {
+    max_alignment.map(|x| x.size_bytes()).serialize(serializer)
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct StructDefinition {
     ordered_members: Option<Vec<(Declaration, MemorySize)>>,//decl and offset from start that this member is located
@@ -112,7 +122,8 @@ impl StructDefinition {
 
                 assert!(inside_variants.get_slice_size() == 0);//must consume all tokens in variants
 
-                let struct_definition = UnpaddedStructDefinition { ordered_members: Some(members),  };
+                let max_alignment = tokens_queue.packing_at(previous_slice.index).map(MemorySize::from_bytes);//the packing at the `struct` keyword
+                let struct_definition = UnpaddedStructDefinition { ordered_members: Some(members), max_alignment };
                 let struct_identifier = scope_data.add_struct(&struct_name, &struct_definition);
 
                 Some(ASTMetadata {
@@ -124,7 +135,7 @@ impl StructDefinition {
             _ => Some(ASTMetadata { 
                 remaining_slice: curr_queue_idx,
                 //add declaration and return identifier of it
-                resultant_tree: scope_data.add_struct(&struct_name, &UnpaddedStructDefinition { ordered_members: None })
+                resultant_tree: scope_data.add_struct(&struct_name, &UnpaddedStructDefinition { ordered_members: None, max_alignment: None })
             })
         }
     }
@@ -160,6 +171,15 @@ pub fn try_consume_member(tokens_queue: &TokenQueue, curr_queue_idx: &mut TokenQ
 
 }
 
+/// the alignment of a struct or union member, which `#pragma pack` may have lowered to `max_alignment`
+pub fn member_alignment(data_type: &DataType, max_alignment: Option<MemorySize>, struct_info: &dyn GetStructUnion) -> MemorySize {
+    let natural_alignment = calculate_alignment(data_type, struct_info);
+    match max_alignment {
+        Some(max_alignment) => natural_alignment.min(max_alignment),
+        None => natural_alignment,
+    }
+}
+
 fn calculate_alignment(data_type: &DataType, struct_info: &dyn GetStructUnion) -> MemorySize {
     if let DataType::ARRAY {..} = data_type {
         calculate_alignment(&data_type.remove_outer_modifier(), struct_info) //array of x should align to a boundary of sizeof x, but call myself recursively to handle 2d arrays
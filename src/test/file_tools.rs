@@ -1,4 +1,4 @@
-use std::{fs, path::{Path, PathBuf}};
+use std::{fs, panic::{self, AssertUnwindSafe}, path::{Path, PathBuf}};
 
 
 pub fn find_folders<P: AsRef<Path>>(dir: P) -> Vec<PathBuf> {
@@ -29,4 +29,20 @@ pub fn find_c_files<P: AsRef<Path>>(dir: P) -> Vec<std::path::PathBuf> {
     }
  
     c_files
+}
+
+/// Runs `test` on every case, carrying on after a case fails so that one unsupported file doesn't hide the results of the rest
+/// 
+/// Panics at the end if any case failed, listing the names of the failures
+pub fn run_each_case<T>(cases: Vec<T>, case_name: impl Fn(&T) -> String, test: impl Fn(T)) {
+    let mut failures = Vec::new();
+
+    for case in cases {
+        let name = case_name(&case);
+        if panic::catch_unwind(AssertUnwindSafe(|| test(case))).is_err() {
+            failures.push(name);//the panic message has already been printed
+        }
+    }
+
+    assert!(failures.is_empty(), "{} test cases failed: {}", failures.len(), failures.join(", "));
 }
\ No newline at end of file
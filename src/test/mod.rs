@@ -5,4 +5,9 @@ pub mod test_gcc_linked;
 pub mod test_custom;
 pub mod test_suite;
 pub mod test_fujitsu;
+pub mod test_compile_fail;
+pub mod test_ir;
+pub mod test_output_kinds;
+pub mod test_debug_info;
+pub mod test_preprocessor_options;
 mod file_tools;
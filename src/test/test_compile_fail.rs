@@ -0,0 +1,60 @@
+/**
+ * checks that invalid code is rejected with a helpful diagnostic, instead of crashing
+ */
+
+use std::{fs, path::PathBuf, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{compilation_error::CompilationError, compile, diagnostics::warnings::WarningOptions};
+
+use super::file_tools::run_each_case;
+
+#[derive(Serialize, Deserialize, Debug)]
+struct TestFile {
+    filename: String,
+    /// part of the error message that is expected
+    error: String,
+    /// the line that the error should point to
+    line: Option<u32>,
+    /// how many errors should be found
+    error_count: Option<usize>,
+    /// warning flags to compile with, without the -W, like "all"
+    warnings: Option<Vec<String>>,
+}
+
+#[test]
+fn test_all() {
+    let test_folder = PathBuf::from_str("tests/compile_fail").unwrap();
+
+    let contents_filename = test_folder.join("test_contents.json");
+    let json_data = fs::read_to_string(contents_filename).expect("Unable to read file");
+
+    let test_cases: Vec<TestFile> = serde_json::from_str(&json_data).expect("Unable to parse JSON");
+
+    run_each_case(test_cases, |testfile| testfile.filename.clone(), |testfile| {
+        println!("testing errors for {}", testfile.filename);
+
+        let filename = test_folder.join(testfile.filename.clone());
+        let output_filename = test_folder.join("test_output.out");
+        let warning_options = WarningOptions::from_flags(testfile.warnings.iter().flatten().map(|x| x.as_str())).unwrap();
+        let result = compile::compile(&filename, &output_filename, &[], true, None, &warning_options);
+
+        let Err(CompilationError::PARSE(diagnostics)) = result else {
+            panic!("expected {} to fail with a diagnostic, got {:?}", testfile.filename, result);
+        };
+
+        let diagnostic = diagnostics.iter()
+            .find(|diagnostic| diagnostic.message().contains(&testfile.error))
+            .unwrap_or_else(|| panic!("expected error {:?}, found {:?}", testfile.error, diagnostics.iter().map(|x| x.message()).collect::<Vec<_>>()));
+
+        if let Some(error_count) = testfile.error_count {
+            assert_eq!(diagnostics.len(), error_count);
+        }
+
+        if let Some(line) = testfile.line {
+            let primary = diagnostic.primary().expect("diagnostic should point at the code");
+            assert_eq!(primary.span.expansion_root().start.line, line);
+        }
+    });
+}
\ No newline at end of file
@@ -6,7 +6,9 @@ use std::{fs, io::Write, path::PathBuf, process::{Command, Stdio}, str::FromStr}
 
 use serde::{Deserialize, Serialize};
 
-use crate::compile;
+use crate::{compile, diagnostics::warnings::WarningOptions};
+
+use super::file_tools::run_each_case;
 
 #[derive(Serialize, Deserialize, Debug)]
 struct TestFile {
@@ -26,10 +28,10 @@ fn test_all() {
 
     let test_cases: Vec<TestFile> = serde_json::from_str(&json_data).expect("Unable to parse JSON");
 
-    for testfile in test_cases {
+    run_each_case(test_cases, |testfile| testfile.filename.clone(), |testfile| {
         let filename = test_folder.join(testfile.filename.clone());
         let output_filename = test_folder.join("test_output.out");
-        compile::compile(&filename, &output_filename, &[], true, None).unwrap();
+        compile::compile(&filename, &output_filename, &[], true, None, &WarningOptions::default()).unwrap();
 
         let fixed_args  = testfile.args.or(Some(Vec::new())).unwrap();
 
@@ -61,5 +63,5 @@ fn test_all() {
         if let Some(text_output) = testfile.stdout {
             assert_eq!(String::from_utf8_lossy(&binary_command.stdout), text_output);
         }
-    }
+    });
 }
@@ -0,0 +1,61 @@
+/**
+ * checks the shape of the --debug-info JSON, which debugging tools rely on
+ */
+
+use std::{fs, path::PathBuf, str::FromStr};
+
+use serde_json::Value;
+
+use crate::{compile::{compile_all, LinkOptions, OutputKind}, debugging::{DumpOptions, DEBUG_INFO_FORMAT_VERSION}, diagnostics::warnings::WarningOptions, preprocessor::preprocess_options::PreprocessorOptions};
+
+/// Finds the definition called `name` in a list of `[identifier, definition]` pairs
+fn find_definition<'a>(definitions: &'a Value, name: &str) -> &'a Value {
+    definitions.as_array().unwrap().iter()
+        .find(|pair| pair[0]["name"] == name)
+        .unwrap_or_else(|| panic!("{} is not in {}", name, definitions))
+}
+
+#[test]
+fn test_debug_info_shape() {
+    let test_folder = PathBuf::from_str("tests/debug_info").unwrap();
+    let json_filename = test_folder.join("test_output.json");
+
+    compile_all(&[test_folder.join("types.c")], Some(&test_folder.join("test_output.s")), OutputKind::Assembly, Some(&json_filename), &PreprocessorOptions::default(), &DumpOptions::default(), &LinkOptions::default(), &WarningOptions::default()).unwrap();
+
+    let json: Value = serde_json::from_str(&fs::read_to_string(&json_filename).unwrap()).unwrap();
+    let files = json.as_array().unwrap();
+    assert_eq!(files.len(), 1);
+    let file = &files[0];
+
+    assert_eq!(file["format_version"], DEBUG_INFO_FORMAT_VERSION);
+    assert_eq!(file["source_file"], "tests/debug_info/types.c");
+
+    let area = &file["functions"][0];
+    assert_eq!(area["name"], "area");
+    assert_eq!(area["params"].as_array().unwrap().len(), 2);
+    assert_eq!(area["start_line"], 14);
+    assert_eq!(area["end_line"], 23);
+    assert_eq!(file["functions"][1]["name"], "main");
+    assert_eq!(file["global_variables"][0]["name"], "origin");
+
+    //types defined at file scope
+    let global_scope = &file["ast"]["global_scope_data"];
+    let point = find_definition(&global_scope["structs"], "point");
+    assert!(point[0]["id"].is_string());
+    assert_eq!(point[1]["ordered_members"].as_array().unwrap().len(), 2);
+    let number = find_definition(&global_scope["unions"], "number");
+    assert!(number[0]["id"].is_string());
+    assert_eq!(number[1]["ordered_members"].as_array().unwrap().len(), 2);
+    assert!(global_scope["enums"]["all_enum_names"]["colour"].is_string());
+    assert_eq!(global_scope["enums"]["all_variants"]["GREEN"]["INTEGER"]["data"], 5);
+    assert!(global_scope["typedefs"]["size"].is_object());
+
+    //the global variable's type refers to the struct by its id
+    let origin_type = &file["ast"]["global_variables"][0]["decl"]["data_type"];
+    assert_eq!(origin_type["RAW"]["Struct"]["id"], point[0]["id"]);
+
+    //types defined in a function are in the scope of its body, and not the file's
+    let area_scope = &file["ast"]["functions"][0]["code"]["local_scope_data"];
+    find_definition(&area_scope["structs"], "local_pair");
+    assert!(global_scope["structs"].as_array().unwrap().iter().all(|pair| pair[0]["name"] != "local_pair"));
+}
\ No newline at end of file
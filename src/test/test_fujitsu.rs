@@ -6,7 +6,7 @@ use std::{fs::{self, OpenOptions}, io::Write, path::PathBuf, process::{Command,
 
 use unwrap_let::unwrap_let;
 
-use crate::compile;
+use crate::{compile, diagnostics::warnings::WarningOptions};
 
 use super::file_tools::{find_c_files, find_folders};
 
@@ -57,7 +57,7 @@ fn test_all() {
                 Err(_) => None
             };
 
-            compile::compile(&c_file_path, &output_filename, &[], true, None).unwrap();
+            compile::compile(&c_file_path, &output_filename, &[], true, None, &WarningOptions::default()).unwrap();
     
             let binary_process = Command::new(&output_filename)
                 .stdout(Stdio::piped())
@@ -5,7 +5,9 @@ use std::{fs, io::Write, path::PathBuf, process::{Command, Stdio}, str::FromStr}
 
 use serde::{Deserialize, Serialize};
 
-use crate::compile;
+use crate::{compile, diagnostics::warnings::WarningOptions};
+
+use super::file_tools::run_each_case;
 
 #[derive(Serialize, Deserialize, Debug)]
 struct GccLinkedTest {
@@ -25,7 +27,7 @@ fn test_linked_with_gcc() {
 
     let test_cases: Vec<GccLinkedTest> = serde_json::from_str(&json_data).expect("Unable to parse JSON");
 
-    for test_case in test_cases {
+    run_each_case(test_cases, |test_case| test_case.foldername.clone(), |test_case| {
         let test_folder = all_tests_folder.join(test_case.foldername.clone());//go in the folder that holds the test case
         //compile gcc's part of the test
         let gcc_code = test_folder.join("gcc_code.c");
@@ -42,7 +44,7 @@ fn test_linked_with_gcc() {
         //compile my part of the test
         let filename = test_folder.join("main.c");
         let output_filename = test_folder.join("test_output.out");
-        compile::compile(&filename, &output_filename, &[&gcc_object], true, None).unwrap();//compile, and link with gcc's code
+        compile::compile(&filename, &output_filename, &[&gcc_object], true, None, &WarningOptions::default()).unwrap();//compile, and link with gcc's code
 
         //test the code
 
@@ -75,5 +77,5 @@ fn test_linked_with_gcc() {
         if let Some(text_output) = test_case.stdout {
             assert_eq!(String::from_utf8_lossy(&binary_command.stdout), text_output);
         }
-    }
+    });
 }
\ No newline at end of file
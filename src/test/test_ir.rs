@@ -0,0 +1,74 @@
+/**
+ * compiles each standalone test to textual IR, checks that it reads back the same, then compiles the IR and runs it
+ */
+
+use std::{env, fs, io::Write, path::PathBuf, process::{Command, Stdio}, str::FromStr};
+
+use serde::Deserialize;
+
+use crate::{assembly::assembly_file::AssemblyFile, compile::{compile_all, LinkOptions, OutputKind}, debugging::DumpOptions, diagnostics::warnings::WarningOptions, preprocessor::preprocess_options::PreprocessorOptions};
+
+use super::file_tools::run_each_case;
+
+#[derive(Deserialize, Debug)]
+struct TestFile {
+    filename: String,
+    args: Option<Vec<String>>,
+    stdin:Option<String>,
+    stdout: Option<String>,
+    return_code: Option<i32>,
+}
+
+#[test]
+fn test_ir_round_trip() {
+    let test_folder = PathBuf::from_str("tests/standalone").unwrap();
+
+    let contents_filename = test_folder.join("test_contents.json");
+    let json_data = fs::read_to_string(contents_filename).expect("Unable to read file");
+
+    let test_cases: Vec<TestFile> = serde_json::from_str(&json_data).expect("Unable to parse JSON");
+
+    run_each_case(test_cases, |testfile| testfile.filename.clone(), |testfile| {
+        println!("testing IR for {}", testfile.filename);
+
+        let filename = test_folder.join(testfile.filename.clone());
+        //keep the outputs out of the source tree
+        let ir_filename = env::temp_dir().join("StuCCompiler2_test_ir_output.ir");
+        let output_filename = env::temp_dir().join("StuCCompiler2_test_ir_output.out");
+
+        compile_all(&[filename], Some(&ir_filename), OutputKind::IR, None, &PreprocessorOptions::default(), &DumpOptions::default(), &LinkOptions::default(), &WarningOptions::default()).unwrap();
+
+        //reading the IR then writing it again should give exactly the same text
+        let ir_text = fs::read_to_string(&ir_filename).unwrap();
+        let parsed = AssemblyFile::from_ir_text(&ir_text, &ir_filename).unwrap();
+        assert_eq!(parsed.to_ir_text(), ir_text);
+
+        compile_all(&[ir_filename], Some(&output_filename), OutputKind::Executable, None, &PreprocessorOptions::default(), &DumpOptions::default(), &LinkOptions::default(), &WarningOptions::default()).unwrap();
+
+        let binary_process = Command::new(&output_filename)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .args(testfile.args.unwrap_or_default())
+            .spawn()
+            .expect("Failed to run the compiled binary");
+
+        if let Some(text_input) = testfile.stdin {
+            binary_process.stdin
+            .as_ref()
+            .unwrap()
+            .write_all(text_input.as_bytes())
+            .unwrap();
+        }
+
+        let binary_command = binary_process
+            .wait_with_output()
+            .expect("Failed to run test case");
+
+        if let Some(ret_code) = testfile.return_code {
+            assert_eq!(binary_command.status.code().expect("binary was terminated by OS signal?"), ret_code);
+        }
+        if let Some(text_output) = testfile.stdout {
+            assert_eq!(String::from_utf8_lossy(&binary_command.stdout), text_output);
+        }
+    });
+}
\ No newline at end of file
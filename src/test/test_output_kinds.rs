@@ -0,0 +1,62 @@
+/**
+ * checks what -E, -S and -c write, and that several inputs are linked into one binary
+ */
+
+use std::{fs, path::{Path, PathBuf}, process::Command, str::FromStr};
+
+use crate::{compile::{compile_all, LinkOptions, OutputKind}, debugging::DumpOptions, diagnostics::warnings::WarningOptions, preprocessor::preprocess_options::PreprocessorOptions};
+
+fn test_folder() -> PathBuf {
+    PathBuf::from_str("tests/output_kinds").unwrap()
+}
+
+fn compile_to(inputs: &[PathBuf], output: &Path, output_kind: OutputKind) {
+    compile_all(inputs, Some(output), output_kind, None, &PreprocessorOptions::default(), &DumpOptions::default(), &LinkOptions::default(), &WarningOptions::default()).unwrap();
+}
+
+#[test]
+fn test_preprocess_only() {
+    let output = test_folder().join("test_output.i");
+    compile_to(&[test_folder().join("spellings.c")], &output, OutputKind::Preprocessed);
+
+    let text = fs::read_to_string(&output).unwrap();
+    //literals are written as they were in the source, not by value
+    for spelling in ["'\\n'", "1.5f", "2.5e3", "0x10UL", "\"say \\\"hi\\\"\\n\""] {
+        assert!(text.contains(spelling), "{} is missing from the preprocessed code:\n{}", spelling, text);
+    }
+    assert!(!text.contains("#define"));
+    //the code starts on the line that `main` was written on
+    assert!(text.starts_with("# 4 "), "{}", text);
+}
+
+#[test]
+fn test_assembly_only() {
+    let output = test_folder().join("test_output.s");
+    compile_to(&[test_folder().join("add.c")], &output, OutputKind::Assembly);
+
+    let text = fs::read_to_string(&output).unwrap();
+    assert!(text.contains("global add"), "{}", text);
+    assert!(text.contains("add:"), "{}", text);
+}
+
+#[test]
+fn test_object_only() {
+    let output = test_folder().join("test_output.o");
+    compile_to(&[test_folder().join("times_two.c")], &output, OutputKind::Object);
+
+    let object = fs::read(&output).unwrap();
+    assert!(object.starts_with(b"\x7fELF"));
+}
+
+#[test]
+fn test_link_several_inputs() {
+    //one input is already an object file, to check that those are linked too
+    let object = test_folder().join("test_output_times_two.o");
+    compile_to(&[test_folder().join("times_two.c")], &object, OutputKind::Object);
+
+    let output = test_folder().join("test_output_linked.out");
+    compile_to(&[test_folder().join("main.c"), test_folder().join("add.c"), object], &output, OutputKind::Executable);
+
+    let status = Command::new(&output).status().expect("Failed to run the compiled binary");
+    assert_eq!(status.code(), Some(14));
+}
\ No newline at end of file
@@ -0,0 +1,74 @@
+/**
+ * checks that the -I, -D, -U and -include options change how files are preprocessed
+ */
+
+use std::{path::PathBuf, process::Command, str::FromStr};
+
+use crate::{compile::{compile_all, LinkOptions, OutputKind}, debugging::DumpOptions, diagnostics::warnings::WarningOptions, preprocessor::preprocess_options::PreprocessorOptions};
+
+/// Compiles and runs a file from tests/preprocessor_options, returning its exit code
+fn run_with_options(filename: &str, preprocessor_options: &PreprocessorOptions) -> i32 {
+    let test_folder = PathBuf::from_str("tests/preprocessor_options").unwrap();
+    let output_filename = test_folder.join(filename).with_extension("out");
+
+    compile_all(&[test_folder.join(filename)], Some(&output_filename), OutputKind::Executable, None, preprocessor_options, &DumpOptions::default(), &LinkOptions::default(), &WarningOptions::default()).unwrap();
+
+    Command::new(&output_filename)
+        .status()
+        .expect("Failed to run the compiled binary")
+        .code()
+        .expect("binary was terminated by OS signal?")
+}
+
+#[test]
+fn test_include_folder() {
+    let mut options = PreprocessorOptions::default();
+    options.add_include_folder(PathBuf::from("tests/preprocessor_options/headers"));
+
+    assert_eq!(run_with_options("include_folder.c", &options), 42);
+}
+
+#[test]
+fn test_missing_include_folder() {
+    //without -I, the header can't be found
+    let test_folder = PathBuf::from_str("tests/preprocessor_options").unwrap();
+    let result = compile_all(&[test_folder.join("include_folder.c")], Some(&test_folder.join("include_folder.s")), OutputKind::Assembly, None, &PreprocessorOptions::default(), &DumpOptions::default(), &LinkOptions::default(), &WarningOptions::default());
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_define_and_undefine() {
+    let mut options = PreprocessorOptions::default();
+    options.define("FLAG").unwrap();
+    options.define("VALUE=10").unwrap();
+    options.define("TWICE(x)=((x) * 2)").unwrap();
+    options.define("REMOVED").unwrap();
+    options.undefine("REMOVED");//later options win
+    options.undefine("NEVER_DEFINED");
+
+    assert_eq!(run_with_options("defines.c", &options), 20);
+}
+
+#[test]
+fn test_define_without_name() {
+    let mut options = PreprocessorOptions::default();
+    assert!(options.define("=1").is_err());
+}
+
+#[test]
+fn test_define_must_be_one_line() {
+    let mut options = PreprocessorOptions::default();
+    assert!(options.define("VALUE=1\nint x;").is_err());
+    assert!(options.define("VALUE=1\\").is_err());
+    //a backslash that doesn't end the value is kept
+    assert!(options.define("PATH=\"a\\\\b\"").is_ok());
+}
+
+#[test]
+fn test_forced_include() {
+    let mut options = PreprocessorOptions::default();
+    options.add_forced_include(PathBuf::from("tests/preprocessor_options/forced.h"));
+
+    assert_eq!(run_with_options("forced_include.c", &options), 14);
+}
\ No newline at end of file
@@ -4,22 +4,22 @@
 
 use std::{fs, path::PathBuf, process::{Command, Stdio}, str::FromStr};
 
-use crate::compile;
+use crate::{compile, diagnostics::warnings::WarningOptions};
 
-use super::file_tools::find_c_files;
+use super::file_tools::{find_c_files, run_each_case};
 
 #[test]
 #[ignore = "not yet implemented"]
 fn test_all() {
     let test_folder = PathBuf::from_str("tests/c_testsuite").unwrap();
 
-    for c_file_path in find_c_files(&test_folder) {
+    run_each_case(find_c_files(&test_folder), |c_file_path| c_file_path.display().to_string(), |c_file_path| {
 
         let expected_output_path = c_file_path.with_extension("c.expected");
         let expected_stdout = fs::read_to_string(expected_output_path).unwrap();
         let output_filename = test_folder.join("test_output.out");
 
-        compile::compile(&c_file_path, &output_filename, &[], true, None).unwrap();
+        compile::compile(&c_file_path, &output_filename, &[], true, None, &WarningOptions::default()).unwrap();
 
         let binary_process = Command::new(output_filename)
             .stdout(Stdio::piped())
@@ -38,5 +38,5 @@ fn test_all() {
 
         assert_eq!(String::from_utf8_lossy(&binary_command.stdout), expected_stdout);
 
-    }
+    });
 }
\ No newline at end of file
@@ -1,29 +1,28 @@
 use colored::Colorize;
 use stack_management::simple_stack_frame::SimpleStackFrame;
 
-use crate::{asm_gen_data::GlobalAsmData, assembly::{assembly::IRCode, assembly_file::AssemblyFile}, ast_metadata::ASTMetadata, compilation_error::CompilationError, compilation_state::{functions::FunctionList}, data_type::storage_type::StorageDuration, debugging::{ASTDisplay, IRDisplay}, function_declaration::FunctionDeclaration, function_definition::FunctionDefinition, global_var_declaration::GlobalVariable, lexer::{ token::Token, token_savepoint::TokenQueueSlice, token_walk::TokenQueue}, parse_data::ParseData, preprocessor::preprocessor::preprocess_c_file, string_literal::StringLiteral, typedef::Typedef};
-use std::{collections::HashSet, fs::File, io::Write, path::Path};
+use serde::Serialize;
+use crate::{asm_gen_data::GlobalAsmData, assembly::{assembly::IRCode, assembly_file::AssemblyFile, global_data::{DataContents, GlobalData}}, ast_metadata::ASTMetadata, compilation_error::CompilationError, compilation_state::{functions::FunctionList}, data_type::storage_type::StorageDuration, debugging::{ASTDisplay, DebugInfo, FunctionDebugInfo, DEBUG_INFO_FORMAT_VERSION, IRDisplay, VariableDebugInfo}, diagnostics::{diagnostic::Diagnostic, warnings::WarningOptions}, function_declaration::FunctionDeclaration, function_definition::FunctionDefinition, global_var_declaration::GlobalVariable, lexer::{source_span::Located, token::Token, token_savepoint::TokenQueueSlice, token_walk::TokenQueue}, parse_data::ParseData, preprocessor::pragma::PragmaEffects, string_literal::StringLiteral, typedef::Typedef};
+use std::{collections::HashSet, path::Path};
 
+#[derive(Serialize)]
 pub struct TranslationUnit {
     functions: FunctionList,
     global_scope_data: ParseData,
+    #[serde(skip)]//these are already in the tree, where they are used
     string_literals: HashSet<StringLiteral>,
     global_variables: Vec<GlobalVariable>
 }
 
 impl TranslationUnit {
-    pub fn new(filename: &Path) -> Result<TranslationUnit, CompilationError> {
-
-        let tokens = preprocess_c_file(filename);
-
-        println!("{:?}", tokens);
-
+    /// Parses the preprocessed tokens of a C file, using what the file's pragmas changed
+    pub fn new(tokens: Vec<Located<Token>>, pragmas: &PragmaEffects) -> Result<TranslationUnit, CompilationError> {
         let string_literals: HashSet<StringLiteral> = tokens.iter()
-            .filter_map(|tok| if let Token::STRING(str_lit) = tok {Some(str_lit)} else {None})//get all strings from the token list
+            .filter_map(|tok| if let Token::STRING(str_lit) = &tok.data {Some(str_lit)} else {None})//get all strings from the token list
             .cloned()
             .collect();
 
-        let mut token_queue = TokenQueue::new(tokens);
+        let mut token_queue = TokenQueue::new(tokens).with_packing(pragmas.packing.clone());
         let mut token_idx = TokenQueueSlice::new();
 
         let mut functions = FunctionList::new();
@@ -31,6 +30,7 @@ impl TranslationUnit {
         let mut scope_data = ParseData::make_empty();
 
         while !token_queue.no_remaining_tokens(&token_idx) {
+            let errors_before = scope_data.error_count();
 
             if let Some(ASTMetadata{resultant_tree, remaining_slice }) = FunctionDefinition::try_consume(&mut token_queue, &token_idx, &scope_data){
                 functions.add_function(&mut scope_data, resultant_tree);
@@ -48,10 +48,22 @@ impl TranslationUnit {
                 scope_data.add_typedef(name, new_def);
                 token_idx = remaining_slice;
             } else {
-                return Err(CompilationError::PARSE(format!("unknown remaining data in translation unit: tokens {} and onwards:\n{}", token_idx.index, token_queue.display_slice(&token_idx))));
+                if scope_data.error_count() == errors_before {
+                    //nothing more specific was reported
+                    let span = token_queue.get_token_span(token_idx.index).clone();
+                    scope_data.report_error(Diagnostic::error("expected a declaration or function definition")
+                        .with_primary(span, "could not parse the code starting here"));
+                }
+                //skip to the next declaration, so that errors in the rest of the file can be found
+                token_idx = token_queue.recovery_point(&token_idx);
             }
         }
 
+        let errors = scope_data.take_diagnostics();
+        if !errors.is_empty() {
+            return Err(CompilationError::PARSE(errors));
+        }
+
         Ok(TranslationUnit {
             functions,
             global_scope_data: scope_data,
@@ -60,8 +72,17 @@ impl TranslationUnit {
         })
     }
 
-    pub fn generate_assembly(&self, output_filename: &Path) {
-        let mut output_file = File::create(output_filename).unwrap();
+    /// Type checks every function and looks for suspicious code, returning an error or warning for each problem found
+    pub fn check_semantics(&self, options: &WarningOptions) -> Vec<Diagnostic> {
+        let global_asm_data = GlobalAsmData::new(&self.global_scope_data);
+
+        self.functions.func_definitions_as_slice().iter()
+        .flat_map(|x| x.check_semantics(&global_asm_data, options))
+        .collect()
+    }
+
+    /// Generates the IR for the whole file, ready to be written as NASM or textual IR
+    pub fn generate_assembly_file(&self) -> AssemblyFile {
         let mut global_asm_data = GlobalAsmData::new(&self.global_scope_data);
 
         //get the names of global and extern functions
@@ -89,31 +110,57 @@ impl TranslationUnit {
             .collect();
 
         let string_literals = self.string_literals.iter()
-            .map(|x| format!("{} db {}\n", x.get_label(), x.get_comma_separated_bytes()))
+            .map(|x| GlobalData { label: x.get_label().to_string(), contents: DataContents::Bytes(x.get_bytes()) })
             .collect::<Vec<_>>();
 
-        let global_vars_init = self.global_variables.iter()
+        //const variables go in .rodata, so writing to them crashes instead of silently changing them
+        let (read_only_vars, writable_vars): (Vec<_>, Vec<_>) = self.global_variables.iter()
             .filter(|x| *x.storage_class() != StorageDuration::Extern)//extern variables must not be defined
-            .map(|x| x.generate_assembly(&global_asm_data))
+            .partition(|x| x.is_read_only());
+        let global_vars_data = writable_vars.iter()
+            .map(|x| x.generate_data(&global_asm_data))
+            .collect::<Vec<_>>();
+        let read_only_vars_data = read_only_vars.iter()
+            .map(|x| x.generate_data(&global_asm_data))
             .collect::<Vec<_>>();
 
         let instructions = self.generate_fn_asm(&mut global_asm_data);
 
-        let assembly_file = AssemblyFile::builder()
+        AssemblyFile::builder()
         .global_label_lines(global_labels)
         .extern_label_lines(extern_labels)
-        .string_literal_lines(string_literals)
-        .global_variable_init(global_vars_init)
+        .string_literals(string_literals)
+        .global_variables(global_vars_data)
+        .read_only_variables(read_only_vars_data)
         .functions(instructions)
-        .build();
+        .build()
+    }
 
-        let assembly_code = assembly_file.to_nasm_file();
+    /// Describes the functions, global variables and syntax tree, for `--debug-info`
+    pub fn into_debug_info(self, source_file: &Path) -> DebugInfo {
+        let functions = self.functions.func_definitions_as_slice().iter()
+            .map(|func| FunctionDebugInfo {
+                name: func.get_name().to_string(),
+                return_type: func.get_return_type().to_string(),
+                params: func.as_decl().params.iter()
+                    .map(|param| VariableDebugInfo { name: param.name.clone(), data_type: param.data_type.to_string(), line: func.get_span().start.line })
+                    .collect(),
+                start_line: func.get_span().start.line,
+                end_line: func.get_span().end.line,
+            })
+            .collect();
 
-        let banned_registers = ["rbx", "r12", "r13", "r14", "r15"];//these ones are callee saved and could cause problems
-        assert!(!banned_registers.iter()
-            .any(|reg| assembly_code.contains(reg)));//ensure my code does not contain the bad registers
+        let global_variables = self.global_variables.iter()
+            .map(|var| VariableDebugInfo { name: var.var_name().to_string(), data_type: var.data_type().to_string(), line: var.get_span().start.line })
+            .collect();
 
-        output_file.write(&assembly_code.into_bytes()).unwrap();
+        DebugInfo {
+            format_version: DEBUG_INFO_FORMAT_VERSION,
+            source_file: source_file.to_path_buf(),
+            functions,
+            global_variables,
+            ast: self,
+        }
     }
 
     fn generate_fn_asm(&self, global_asm_data: &mut GlobalAsmData) -> Vec<(IRCode, SimpleStackFrame)> {
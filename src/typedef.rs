@@ -1,5 +1,4 @@
-use crate::{ast_metadata::ASTMetadata, data_type::{recursive_data_type::DataType, storage_type::StorageDuration}, function_declaration::consume_fully_qualified_type, lexer::{keywords::Keyword, punctuator::Punctuator, token::Token, token_savepoint::TokenQueueSlice, token_walk::{TokenQueue, TokenSearchType}}, parse_data::ParseData};
-use unwrap_let::unwrap_let;
+use crate::{ast_metadata::ASTMetadata, data_type::{recursive_data_type::DataType, storage_type::StorageDuration}, declaration::Declaration, initialised_declaration::{consume_type_specifier, try_consume_declaration_modifiers}, lexer::{keywords::Keyword, punctuator::Punctuator, token::Token, token_savepoint::TokenQueueSlice, token_walk::{TokenQueue, TokenSearchType}}, parse_data::ParseData};
 pub struct Typedef;
 
 impl Typedef {
@@ -15,14 +14,9 @@ impl Typedef {
 
         let semicolon_idx = tokens_queue.find_closure_matches(&curr_queue_idx, false, |x| *x == Token::PUNCTUATOR(Punctuator::SEMICOLON), &TokenSearchType::skip_all_brackets()).unwrap();
 
-        //slice in which the data type is specified
+        //slice in which the data type and the name being associated with it are specified
         let type_slice = TokenQueueSlice {
             index: curr_queue_idx.index,
-            max_index: semicolon_idx-1,//-1 to exclude the name being typedef'd to
-        };
-        //slice in which the name being associated with the type is located
-        let name_slice = TokenQueueSlice {
-            index: semicolon_idx-1,
             max_index: semicolon_idx,
         };
         let remaining = TokenQueueSlice {
@@ -30,13 +24,13 @@ impl Typedef {
             max_index: curr_queue_idx.max_index,
         };
         assert!(type_slice.get_slice_size() > 0);
-        assert!(name_slice.get_slice_size() == 1);
 
-        //get name
-        unwrap_let!(Token::IDENTIFIER(name) = tokens_queue.peek(&name_slice, scope_data).unwrap());
-        //get type, discarding storage duration
-        let ASTMetadata { remaining_slice: type_remaining, resultant_tree: (type_represented, storage_duration) } = consume_fully_qualified_type(tokens_queue, &type_slice, scope_data).unwrap();
+        //get the base type, like the int in typedef int (*cmp)(int, int);
+        let ASTMetadata { remaining_slice: declarator_slice, resultant_tree: (base_type, storage_duration) } = consume_type_specifier(tokens_queue, &type_slice, scope_data).unwrap();
+        //the declarator holds the name, and any pointers, arrays or params that modify the base type
+        let ASTMetadata { remaining_slice: type_remaining, resultant_tree: Declaration { data_type: type_represented, name } } = try_consume_declaration_modifiers(tokens_queue, &declarator_slice, &base_type, scope_data).unwrap();
         assert!(type_remaining.get_slice_size() == 0);//must consume all of previous
+        assert!(name.len() > 0, "typedef has no name");
 
         Some(ASTMetadata {
             remaining_slice: remaining,
@@ -3,11 +3,13 @@ use std::fmt::Display;
 use memory_size::MemorySize;
 use uuid::Uuid;
 
-use crate::{asm_gen_data::GetStructUnion, ast_metadata::ASTMetadata, declaration::Declaration, lexer::{keywords::Keyword, punctuator::Punctuator, token::Token, token_savepoint::TokenQueueSlice, token_walk::TokenQueue}, parse_data::ParseData, struct_definition::try_consume_member};
+use serde::Serialize;
+use crate::{asm_gen_data::GetStructUnion, ast_metadata::ASTMetadata, declaration::Declaration, lexer::{keywords::Keyword, punctuator::Punctuator, token::Token, token_savepoint::TokenQueueSlice, token_walk::TokenQueue}, parse_data::ParseData, struct_definition::{member_alignment, serialize_max_alignment, try_consume_member}};
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct UnionIdentifier {
     pub name: Option<String>,
+    /// random on each compile, but tells apart unions with the same name in different scopes
     pub id: Uuid
 }
 impl Display for UnionIdentifier {
@@ -16,20 +18,33 @@ impl Display for UnionIdentifier {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct UnionDefinition {
     pub ordered_members: Option<Vec<Declaration>>,
+    /// the largest alignment that a member can have, from `#pragma pack`
+    #[serde(serialize_with = "serialize_max_alignment")]
+    pub max_alignment: Option<MemorySize>,
 }
 
 impl UnionDefinition {
     pub fn calculate_size(&self, struct_info: &dyn GetStructUnion) -> Option<MemorySize> {
-        self.ordered_members
-        .as_ref()
-        .and_then(|members|
-            members.iter()
+        let members = self.ordered_members.as_ref()?;
+
+        let largest_member = members.iter()
             .map(|x| x.data_type.memory_size(struct_info))
-            .max()
-        )
+            .max()?;
+
+        //pad to the largest member's alignment, so that if this union is in an array, subsequent unions are aligned
+        let largest_member_alignment = members.iter()
+            .map(|x| member_alignment(&x.data_type, self.max_alignment, struct_info))
+            .fold(MemorySize::new(), |acc, x| acc.max(x))
+            .size_bytes();
+        let bytes_past_last_boundary = largest_member.size_bytes() % largest_member_alignment;
+        let extra_padding = (largest_member_alignment - bytes_past_last_boundary) % largest_member_alignment;
+
+        let mut size = largest_member;
+        size += MemorySize::from_bytes(extra_padding);
+        Some(size)
     }
 
     pub fn get_member_data(&self, member_name: &str) -> Declaration {
@@ -68,7 +83,8 @@ impl UnionDefinition {
 
                 assert!(inside_variants.get_slice_size() == 0);//must consume all tokens in variants
 
-                let union_definition = UnionDefinition { ordered_members: Some(members),  };
+                let max_alignment = tokens_queue.packing_at(previous_slice.index).map(MemorySize::from_bytes);//the packing at the `union` keyword
+                let union_definition = UnionDefinition { ordered_members: Some(members), max_alignment };
                 let union_identifier = scope_data.add_union(&union_name, &union_definition);
 
                 Some(ASTMetadata {
@@ -80,7 +96,7 @@ impl UnionDefinition {
             _ => Some(ASTMetadata { 
                 remaining_slice: curr_queue_idx,
                 //add declaration and return identifier of it
-                resultant_tree: scope_data.add_union(&union_name, &UnionDefinition { ordered_members: None })
+                resultant_tree: scope_data.add_union(&union_name, &UnionDefinition { ordered_members: None, max_alignment: None })
             })
         }
 
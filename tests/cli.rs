@@ -0,0 +1,139 @@
+/**
+ * runs the compiler's binary, to check what it prints and writes for each command line option
+ */
+
+use std::{env, fs, path::PathBuf, process::{Command, Output}};
+
+const COMPILER: &str = env!("CARGO_BIN_EXE_StuCCompiler2");
+const PROGRAM: &str = "tests/cli/program.c";
+
+/// A path in the temporary folder, which is unique to this test
+fn temporary_path(test_name: &str, filename: &str) -> PathBuf {
+    let folder = env::temp_dir().join(format!("stucc-cli-{}-{}", std::process::id(), test_name));
+    fs::create_dir_all(&folder).unwrap();
+    folder.join(filename)
+}
+
+fn run_compiler(args: &[&str]) -> Output {
+    let output = Command::new(COMPILER)
+        .args(args)
+        .output()
+        .expect("Failed to run the compiler");
+
+    assert!(output.status.success(), "compiler failed: {}", String::from_utf8_lossy(&output.stderr));
+    output
+}
+
+#[test]
+fn test_silent_by_default() {
+    let binary = temporary_path("silent", "program.out");
+    let output = run_compiler(&[PROGRAM, "-o", binary.to_str().unwrap()]);
+
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "");
+    assert_eq!(String::from_utf8_lossy(&output.stderr), "");
+
+    let status = Command::new(&binary).status().expect("Failed to run the compiled binary");
+    assert_eq!(status.code(), Some(0));
+}
+
+#[test]
+fn test_dump_to_files() {
+    let binary = temporary_path("dump_files", "program.out");
+    //each flag, the file it writes to, and something that it should contain
+    let dumps = [
+        ("--dump-tokens", temporary_path("dump_files", "tokens.txt"), "increment"),
+        ("--dump-ast", temporary_path("dump_files", "ast.txt"), "increment"),
+        ("--dump-ir", temporary_path("dump_files", "ir.txt"), "increment"),
+        ("--dump-asm", temporary_path("dump_files", "asm.txt"), "global main"),
+    ];
+
+    let mut args = vec![PROGRAM.to_string(), "-o".to_string(), binary.to_str().unwrap().to_string()];
+    args.extend(dumps.iter().map(|(flag, path, _)| format!("{}={}", flag, path.display())));
+    let output = run_compiler(&args.iter().map(String::as_str).collect::<Vec<_>>());
+
+    //dumping to a file doesn't print anything
+    assert_eq!(String::from_utf8_lossy(&output.stderr), "");
+
+    for (flag, path, expected) in dumps {
+        let text = fs::read_to_string(&path).unwrap_or_else(|_| panic!("{} did not write {}", flag, path.display()));
+        assert!(text.starts_with(&format!("==> {} <==\n", PROGRAM)), "{} wrote:\n{}", flag, text);
+        assert!(text.contains(expected), "{} wrote:\n{}", flag, text);
+    }
+}
+
+#[test]
+fn test_dump_to_stderr() {
+    for (flag, expected) in [("--dump-tokens", "increment"), ("--dump-ast", "increment"), ("--dump-ir", "increment"), ("--dump-asm", "global main")] {
+        let assembly = temporary_path("dump_stderr", "program.s");
+        let output = run_compiler(&[PROGRAM, "-S", "-o", assembly.to_str().unwrap(), flag]);
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.starts_with(&format!("==> {} <==\n", PROGRAM)), "{} wrote:\n{}", flag, stderr);
+        assert!(stderr.contains(expected), "{} wrote:\n{}", flag, stderr);
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "");
+    }
+}
+
+#[test]
+fn test_debug_info_file() {
+    let assembly = temporary_path("debug_info", "program.s");
+    let json_path = temporary_path("debug_info", "info.json");
+    let output = run_compiler(&[PROGRAM, "-S", "-o", assembly.to_str().unwrap(), "--debug-info", json_path.to_str().unwrap()]);
+
+    assert_eq!(String::from_utf8_lossy(&output.stderr), "");
+    let json = fs::read_to_string(&json_path).unwrap();
+    assert!(json.contains("\"format_version\""));
+    assert!(json.contains("\"increment\""));
+}
+
+#[test]
+fn test_f_options() {
+    //-f can be repeated, and ones that don't apply to this compiler are ignored
+    let binary = temporary_path("f_options", "program.out");
+    let output = run_compiler(&[PROGRAM, "-o", binary.to_str().unwrap(), "-fmax-errors=5", "-fno-builtin", "-fmax-errors=3"]);
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("ignoring unsupported option -fno-builtin"), "{}", stderr);
+    assert!(binary.is_file());
+}
+
+#[test]
+fn test_position_independent_code_rejected() {
+    //the generated code is not position independent, so asking for it must fail instead of breaking when linking
+    for option in ["-fPIC", "-fpic", "-fPIE", "-fpie"] {
+        let binary = temporary_path("pic", "program.out");
+        let output = Command::new(COMPILER)
+            .args([PROGRAM, "-o", binary.to_str().unwrap(), option])
+            .output()
+            .expect("Failed to run the compiler");
+
+        assert!(!output.status.success(), "{} was accepted", option);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains(&format!("{} is not supported", option)), "{}", stderr);
+    }
+}
+
+#[test]
+fn test_max_errors() {
+    let binary = temporary_path("max_errors", "multipleerrors.out");
+    let output = Command::new(COMPILER)
+        .args(["tests/compile_fail/multipleerrors.c", "-o", binary.to_str().unwrap(), "-fno-builtin", "-fmax-errors=1"])
+        .output()
+        .expect("Failed to run the compiler");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("compilation terminated due to -fmax-errors=1."), "{}", stderr);
+}
+
+#[test]
+fn test_no_inputs() {
+    //like gcc, there is no default input file
+    let output = Command::new(COMPILER)
+        .output()
+        .expect("Failed to run the compiler");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("required"), "{}", stderr);
+}
@@ -0,0 +1,9 @@
+int counter = 3;
+
+int increment(int x) {
+    return x + 1;
+}
+
+int main() {
+    return increment(counter) - 4;
+}
\ No newline at end of file
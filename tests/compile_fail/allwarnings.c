@@ -0,0 +1,21 @@
+int *make_pointer(int unused_param) {
+    return 1;
+}
+
+int missing_return(int x) {
+    if (x = 2) {
+        return x;
+    }
+}
+
+int main() {
+    int unused;
+    int count = 0;
+    unsigned int limit = 10;
+    if (count < limit) {
+        int count = 1;
+        return count;
+    }
+    return 0;
+    count = 2;
+}
\ No newline at end of file
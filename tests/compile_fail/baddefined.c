@@ -0,0 +1,7 @@
+#if defined(1)
+int x;
+#endif
+
+int main() {
+    return 0;
+}
\ No newline at end of file
@@ -0,0 +1,5 @@
+#define PASTE(a, b) a ## b
+
+int main() {
+    return 1 PASTE(+, -) 1;
+}
\ No newline at end of file
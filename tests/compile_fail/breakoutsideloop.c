@@ -0,0 +1,7 @@
+int main() {
+    int x = 1;
+    if (x) {
+        break;
+    }
+    return 0;
+}
\ No newline at end of file
@@ -0,0 +1,8 @@
+int main() {
+    int x = 1;
+    if (x) {
+        case 1:
+            return 1;
+    }
+    return 0;
+}
\ No newline at end of file
@@ -0,0 +1,18 @@
+void takes_mutable(char *text) {
+    text[0] = 'a';
+}
+
+int main() {
+    const int limit = 10;
+    int value = 0;
+    const int *readonly = &value;
+    int *const fixed = &value;
+    limit = 5;
+    *readonly = 1;
+    takes_mutable("ok");
+    const char *message = "hello";
+    takes_mutable(message);
+    limit++;
+    *fixed = 2;
+    return value;
+}
\ No newline at end of file
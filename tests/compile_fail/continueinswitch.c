@@ -0,0 +1,8 @@
+int main() {
+    int x = 2;
+    switch (x) {
+        case 2:
+            continue;
+    }
+    return 0;
+}
\ No newline at end of file
@@ -0,0 +1,8 @@
+int main() {
+    int x = 1;
+    while (x) {
+        x = 0;
+    }
+    continue;
+    return 0;
+}
\ No newline at end of file
@@ -0,0 +1,8 @@
+int main() {
+    int x = 1;
+    while (x) {
+        default:
+            x = 0;
+    }
+    return 0;
+}
\ No newline at end of file
@@ -0,0 +1,12 @@
+int main() {
+    int x = 2;
+    switch (x) {
+        case 1:
+            return 1;
+        case 2:
+            return 2;
+        case 3 - 2:
+            return 3;
+    }
+    return 0;
+}
\ No newline at end of file
@@ -0,0 +1,9 @@
+#define BROKEN 1
+
+#if BROKEN
+#error this configuration is not supported
+#endif
+
+int main() {
+    return 0;
+}
\ No newline at end of file
@@ -0,0 +1,5 @@
+#define ZERO() 0
+
+int main() {
+    return ZERO(1);
+}
\ No newline at end of file
@@ -0,0 +1,5 @@
+#include <nonexistent_header.h>
+
+int main() {
+    return 0;
+}
\ No newline at end of file
@@ -0,0 +1,18 @@
+int first() {
+    if 1) {
+        return 1;
+    }
+    return 0;
+}
+
+int second() {
+    while 1) {
+    }
+    return 0;
+}
+
+}
+
+int main() {
+    return first() + second();
+}
\ No newline at end of file
@@ -0,0 +1,8 @@
+int main() {
+    float x = 1.5;
+    switch (x) {
+        case 1:
+            return 1;
+    }
+    return 0;
+}
\ No newline at end of file
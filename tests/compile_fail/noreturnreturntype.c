@@ -0,0 +1,20 @@
+_Noreturn void stop(int code);
+void carry_on(int code);
+
+int checked(int x) {
+    if (x) {
+        return x;
+    }
+    stop(1);
+}
+
+int unchecked(int x) {
+    if (x) {
+        return x;
+    }
+    carry_on(1);
+}
+
+int main() {
+    return checked(1) + unchecked(1);
+}
\ No newline at end of file
@@ -0,0 +1,10 @@
+#pragma not_a_real_pragma
+
+int main() {
+#pragma GCC diagnostic push
+#pragma GCC diagnostic ignored "-Wunused-variable"
+    int ignored;
+#pragma GCC diagnostic pop
+    int reported;
+    return 0;
+}
\ No newline at end of file
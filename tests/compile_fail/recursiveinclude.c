@@ -0,0 +1,5 @@
+#include "recursiveinclude.c"
+
+int main() {
+    return 0;
+}
\ No newline at end of file
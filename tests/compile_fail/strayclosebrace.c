@@ -0,0 +1,5 @@
+int main() {
+    return 0;
+}
+
+}
\ No newline at end of file
@@ -0,0 +1,5 @@
+#define ADD(a, b) ((a) + (b))
+
+int main() {
+    return ADD(1);
+}
\ No newline at end of file
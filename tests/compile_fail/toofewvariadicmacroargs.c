@@ -0,0 +1,5 @@
+#define LOG(level, format, ...) level
+
+int main() {
+    return LOG(0);
+}
\ No newline at end of file
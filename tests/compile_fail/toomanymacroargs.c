@@ -0,0 +1,6 @@
+#define SQUARE(x) ((x) * (x))
+
+int main() {
+    int y = 2;
+    return SQUARE(y, 3);
+}
\ No newline at end of file
@@ -0,0 +1,22 @@
+struct point {
+    int x;
+    int y;
+};
+
+int add(int a, int b) {
+    return a + b;
+}
+
+int main() {
+    struct point p;
+    int arr[4];
+    int value = 0;
+    5 = value;
+    value = add(1);
+    value = p.z;
+    value = p + 1;
+    arr = 0;
+    value = undeclared;
+    if (p) {}
+    return value;
+}
\ No newline at end of file
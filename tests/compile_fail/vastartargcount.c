@@ -0,0 +1,12 @@
+#include <stdarg.h>
+
+int sum(int count, ...) {
+    va_list args;
+    __builtin_va_start(args, count, 1);
+    va_end(args);
+    return count;
+}
+
+int main() {
+    return sum(1, 2);
+}
\ No newline at end of file
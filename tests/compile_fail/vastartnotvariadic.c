@@ -0,0 +1,12 @@
+#include <stdarg.h>
+
+int first(int count) {
+    va_list args;
+    va_start(args, count);
+    va_end(args);
+    return count;
+}
+
+int main() {
+    return first(1);
+}
\ No newline at end of file
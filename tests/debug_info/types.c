@@ -0,0 +1,30 @@
+struct point {
+    int x;
+    int y;
+};
+union number {
+    int whole;
+    double fraction;
+};
+enum colour {RED, GREEN = 5};
+typedef unsigned long size;
+
+struct point origin;
+
+int area(int width, int height) {
+    struct local_pair {
+        int first;
+        int second;
+    };
+    struct local_pair pair;
+    pair.first = width;
+    pair.second = height;
+    return pair.first * pair.second;
+}
+
+int main() {
+    union number n;
+    n.whole = GREEN;
+    size s = 3;
+    return area(n.whole, s) - 15;
+}
\ No newline at end of file
@@ -0,0 +1,76 @@
+#include <stdarg.h>
+
+struct mixed { int a; float b; };/* one INTEGER eightbyte */
+struct pair { double x; double y; };/* two SSE eightbytes */
+struct tagged { double value; long tag; };/* SSE then INTEGER */
+struct floats { float a; float b; float c; };/* both SSE, the second half full */
+
+double scale(double x, float factor) {
+    return x * factor;
+}
+
+/* more float args than there are vector registers */
+double many_floats(double a, float b, double c, float d, double e, float f, double g, float h, double i, int j) {
+    return a + b + c + d + e + f + g + h + i + j;
+}
+
+double sum_doubles(int count, ...) {
+    va_list args;
+    va_start(args, count);
+    double total = 0;
+    for (int n = 0; n < count; n++) {
+        total += va_arg(args, double);
+    }
+    va_end(args);
+    return total;
+}
+
+float read_mixed(struct mixed m) {
+    return m.a + m.b;
+}
+
+struct pair make_pair(double x, double y) {
+    struct pair result = { x, y };
+    return result;
+}
+
+struct tagged make_tagged(double value, long tag) {
+    struct tagged result = { value, tag };
+    return result;
+}
+
+/* these are compiled by the other compiler */
+extern double halve(double x);
+extern float add_floats(float a, int b, float c);
+extern struct pair swap_pair(struct pair p);
+extern struct tagged retag(struct tagged t, long tag);
+extern struct mixed make_mixed(int a, float b);
+extern float sum_floats(struct floats f);
+
+int call_back(void) {
+    if (halve(5.0) != 2.5) {
+        return 1;
+    }
+    if (add_floats(1.5f, 2, 0.25f) != 3.75f) {
+        return 2;
+    }
+    struct pair p = { 1.0, 2.0 };
+    struct pair swapped = swap_pair(p);
+    if (swapped.x != 2.0 || swapped.y != 1.0) {
+        return 3;
+    }
+    struct tagged t = { 0.5, 1 };
+    struct tagged retagged = retag(t, 9);
+    if (retagged.value != 0.5 || retagged.tag != 9) {
+        return 4;
+    }
+    struct mixed m = make_mixed(3, 0.5f);
+    if (m.a != 3 || m.b != 0.5f) {
+        return 5;
+    }
+    struct floats f = { 1.0f, 2.0f, 4.0f };
+    if (sum_floats(f) != 7.0f) {
+        return 6;
+    }
+    return 0;
+}
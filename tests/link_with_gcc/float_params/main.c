@@ -0,0 +1,85 @@
+struct mixed { int a; float b; };
+struct pair { double x; double y; };
+struct tagged { double value; long tag; };
+struct floats { float a; float b; float c; };
+
+extern double scale(double x, float factor);
+extern double many_floats(double a, float b, double c, float d, double e, float f, double g, float h, double i, int j);
+extern double sum_doubles(int count, ...);
+extern float read_mixed(struct mixed m);
+extern struct pair make_pair(double x, double y);
+extern struct tagged make_tagged(double value, long tag);
+extern int call_back(void);
+
+double halve(double x) {
+    return x / 2;
+}
+
+float add_floats(float a, int b, float c) {
+    return a + b + c;
+}
+
+struct pair swap_pair(struct pair p) {
+    double temp = p.x;
+    p.x = p.y;
+    p.y = temp;
+    return p;
+}
+
+struct tagged retag(struct tagged t, long tag) {
+    t.tag = tag;
+    return t;
+}
+
+struct mixed make_mixed(int a, float b) {
+    struct mixed result;
+    result.a = a;
+    result.b = b;
+    return result;
+}
+
+float sum_floats(struct floats f) {
+    return f.a + f.b + f.c;
+}
+
+int main() {
+    if (scale(1.5, 4.0f) != 6.0) {
+        return 1;
+    }
+    if (many_floats(1.0, 2.0f, 3.0, 4.0f, 5.0, 6.0f, 7.0, 8.0f, 9.0, 10) != 55.0) {
+        return 2;
+    }
+    float quarter = 0.25f;
+    if (sum_doubles(3, 1.5, quarter, 2.0) != 3.75) {
+        return 3;
+    }
+
+    struct mixed m;
+    m.a = 2;
+    m.b = 0.5f;
+    if (read_mixed(m) != 2.5f) {
+        return 4;
+    }
+
+    struct pair p;
+    p = make_pair(1.25, -3.0);
+    if (p.x != 1.25 || p.y != -3.0) {
+        return 5;
+    }
+    struct tagged t;
+    t = make_tagged(6.5, 42);
+    if (t.value != 6.5 || t.tag != 42) {
+        return 6;
+    }
+
+    /* calls between functions compiled here */
+    if (halve(scale(2.0, 3.0f)) != 3.0) {
+        return 7;
+    }
+
+    if (call_back() != 0) {
+        return 8;
+    }
+
+    return 0;
+}
@@ -0,0 +1,37 @@
+struct big { long a; int b; long c; };
+
+long sum_big(struct big s, int scale) {
+    return (s.a + s.b + s.c) * scale;
+}
+
+long after_registers(int p1, int p2, int p3, int p4, int p5, int p6, int p7, struct big s, int p8) {
+    if (p1 != 1 || p2 != 2 || p3 != 3 || p4 != 4 || p5 != 5 || p6 != 6 || p7 != 7 || p8 != 8) {
+        return -1;
+    }
+    return s.a * 100 + s.b * 10 + s.c;
+}
+
+struct big make_big(long a, int b, long c) {
+    struct big result = { a, b, c };
+    return result;
+}
+
+/* these are compiled by the other compiler */
+extern struct big scale_big(struct big s, int scale);
+extern long read_big(int p1, int p2, int p3, int p4, int p5, int p6, struct big s, int p7, struct big t);
+
+int call_back(void) {
+    struct big s = { 1, 2, 3 };
+    struct big scaled = scale_big(s, 3);
+    if (scaled.a != 3 || scaled.b != 6 || scaled.c != 9) {
+        return 1;
+    }
+    if (s.a != 1 || s.b != 2 || s.c != 3) {
+        return 2;/* passed by value, so the original is unchanged */
+    }
+    struct big t = { 4, 5, 6 };
+    if (read_big(1, 2, 3, 4, 5, 6, s, 7, t) != 123456) {
+        return 3;
+    }
+    return 0;
+}
@@ -0,0 +1,59 @@
+struct big { long a; int b; long c; };
+
+extern long sum_big(struct big s, int scale);
+extern long after_registers(int p1, int p2, int p3, int p4, int p5, int p6, int p7, struct big s, int p8);
+extern struct big make_big(long a, int b, long c);
+extern int call_back(void);
+
+struct big scale_big(struct big s, int scale) {
+    s.a = s.a * scale;
+    s.b = s.b * scale;
+    s.c = s.c * scale;
+    return s;
+}
+
+long read_big(int p1, int p2, int p3, int p4, int p5, int p6, struct big s, int p7, struct big t) {
+    if (p1 + p2 + p3 + p4 + p5 + p6 != 21) {
+        return -1;
+    }
+    if (p7 != 7) {
+        return -2;
+    }
+    return s.a * 100000 + s.b * 10000 + s.c * 1000 + t.a * 100 + t.b * 10 + t.c;
+}
+
+int main() {
+    struct big s;
+    s.a = 1;
+    s.b = 2;
+    s.c = 3;
+
+    if (sum_big(s, 2) != 12) {
+        return 1;
+    }
+    if (after_registers(1, 2, 3, 4, 5, 6, 7, s, 8) != 123) {
+        return 2;
+    }
+
+    struct big made;
+    made = make_big(7, 8, 9);
+    if (made.a != 7 || made.b != 8 || made.c != 9) {
+        return 3;
+    }
+    if (make_big(4, 5, 6).c != 6) {
+        return 4;
+    }
+
+    /* returning a big struct from code compiled here */
+    struct big scaled;
+    scaled = scale_big(s, 5);
+    if (scaled.a != 5 || scaled.b != 10 || scaled.c != 15) {
+        return 5;
+    }
+
+    if (call_back() != 0) {
+        return 6;
+    }
+
+    return 0;
+}
@@ -0,0 +1,6 @@
+int add(int a, int b);
+int times_two(int x);
+
+int main() {
+    return times_two(add(3, 4));
+}
\ No newline at end of file
@@ -0,0 +1,11 @@
+#define GREETING "say \"hi\"\n"
+#define MASK 0x10UL
+
+int main() {
+    char c = '\n';
+    float f = 1.5f;
+    double d = 2.5e3;
+    unsigned long mask = MASK;
+    char* greeting = GREETING;
+    return 0;
+}
\ No newline at end of file
@@ -0,0 +1,3 @@
+int times_two(int x) {
+    return x * 2;
+}
\ No newline at end of file
@@ -0,0 +1,15 @@
+int main() {
+#ifndef FLAG
+    return 1;
+#endif
+#if FLAG != 1
+    return 2;//-D without a value defines it as 1
+#endif
+#ifdef REMOVED
+    return 3;
+#endif
+#ifdef NEVER_DEFINED
+    return 4;
+#endif
+    return VALUE + TWICE(5);
+}
\ No newline at end of file
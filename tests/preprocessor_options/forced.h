@@ -0,0 +1,4 @@
+#define FORCED 7
+int forced_function() {
+    return FORCED;
+}
\ No newline at end of file
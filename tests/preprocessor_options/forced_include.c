@@ -0,0 +1,3 @@
+int main() {
+    return FORCED + forced_function();
+}
\ No newline at end of file
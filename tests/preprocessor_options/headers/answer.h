@@ -0,0 +1 @@
+#define ANSWER 42
\ No newline at end of file
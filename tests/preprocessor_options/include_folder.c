@@ -0,0 +1,5 @@
+#include "answer.h"
+
+int main() {
+    return ANSWER;
+}
\ No newline at end of file
@@ -0,0 +1,63 @@
+
+/* FNV-1a hash, with the upper bits folded in */
+unsigned int hash(char* s) {
+    unsigned int h = 2166136261U;
+    while (*s) {
+        h = h ^ *s;
+        h = h * 16777619U;
+        s = s + 1;
+    }
+    return h ^ (h >> 16);
+}
+
+int main() {
+    /* right shifts depend on the signedness of the shifted value */
+    unsigned int u = 2147483648U;
+    if ((u >> 31) != 1) return 1;
+    int i = -2147483647 - 1;
+    if ((i >> 31) != -1) return 2;
+    long long big = -1;
+    if ((big >> 40) != -1) return 3;
+    unsigned long ubig = 1;
+    ubig = ubig << 63;
+    if ((ubig >> 63) != 1) return 4;
+
+    /* left shifts truncate to the promoted type */
+    unsigned int overflow = 3;
+    if ((overflow << 31) != 2147483648U) return 5;
+    unsigned char small = 255;
+    if ((small << 4) != 4080) return 6;
+
+    /* shift count comes from a variable */
+    int amount = 5;
+    long long wide = 1;
+    if ((wide << (amount * 8)) != 1099511627776) return 7;
+
+    /* modulus takes the sign of the dividend */
+    int a = -7;
+    int b = 3;
+    if (a % b != -1) return 8;
+    if (7 % -3 != 1) return 9;
+    unsigned int ua = 4294967295U;
+    if (ua % 10 != 5) return 10;
+    long long la = 1000000000000;
+    if (la % 7 != 1) return 11;
+
+    /* bitwise operators on every width */
+    char c = 12;
+    short s = -4;
+    if ((c & s) != 12) return 12;
+    if ((c | 3) != 15) return 13;
+    if ((c ^ c) != 0) return 14;
+    unsigned long mask = 18446744073709551615UL;
+    if ((mask ^ 1) != 18446744073709551614UL) return 15;
+    if (~mask != 0) return 16;
+    unsigned char uc = 0;
+    if (~uc != -1) return 17;
+
+    if (hash("abc") != 440922956U) return 18;
+    char* text = "abc";
+    if (hash(text) != 440922956U) return 19;
+
+    return 0;
+}
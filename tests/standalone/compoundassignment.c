@@ -0,0 +1,73 @@
+
+/* increments the counter, so the number of times the lvalue is calculated can be checked */
+int bump(int* counter) {
+    *counter = *counter + 1;
+    return *counter;
+}
+
+int main() {
+    int x = 7;
+    x *= 6;
+    if (x != 42) return 1;
+    x /= 5;
+    if (x != 8) return 2;
+    x %= 5;
+    if (x != 3) return 3;
+    x <<= 4;
+    if (x != 48) return 4;
+    x >>= 2;
+    if (x != 12) return 5;
+    x &= 10;
+    if (x != 8) return 6;
+    x |= 3;
+    if (x != 11) return 7;
+    x ^= 15;
+    if (x != 4) return 8;
+    x -= 10;
+    if (x != -6) return 9;
+    x += 1;
+    if (x != -5) return 10;
+
+    /* the result is converted back to the type of lhs */
+    char c = 100;
+    c *= 3;
+    if (c != 44) return 11;
+    unsigned char uc = 1;
+    uc <<= 9;
+    if (uc != 0) return 12;
+    int halved = 7;
+    halved *= 0.5;
+    if (halved != 3) return 13;
+    double d = 3.0;
+    d /= 2;
+    if (d != 1.5) return 14;
+
+    /* signedness of the lhs decides the kind of shift */
+    int negative = -64;
+    negative >>= 3;
+    if (negative != -8) return 15;
+    unsigned int positive = 4294967232U;
+    positive >>= 28;
+    if (positive != 15) return 16;
+
+    /* pointers are scaled by the size of what they point to */
+    long values[4] = {10, 20, 30, 40};
+    long* p = values;
+    p += 3;
+    if (*p != 40) return 17;
+    p -= 2;
+    if (*p != 20) return 18;
+
+    /* the lvalue is only calculated once */
+    int arr[3] = {1, 1, 1};
+    int calls = 0;
+    arr[bump(&calls)] |= 4;
+    if (calls != 1 || arr[1] != 5 || arr[0] != 1 || arr[2] != 1) return 19;
+
+    /* the value of the expression is the new value of lhs */
+    int y = 5;
+    int z = (y *= 2) + 1;
+    if (y != 10 || z != 11) return 20;
+
+    return 0;
+}
@@ -0,0 +1,97 @@
+
+/* do-while always runs the body at least once */
+int do_while_once(void) {
+    int count = 0;
+    do {
+        count = count + 1;
+    } while (0);
+    return count;
+}
+
+int do_while_sum(void) {
+    int i = 0;
+    int sum = 0;
+    do sum = sum + i; while ((i = i + 1) < 5);
+    return sum;
+}
+
+/* continue in a for loop still runs the increment */
+int for_continue(void) {
+    int sum = 0;
+    int i;
+    for (i = 0; i < 10; i = i + 1) {
+        if (i == 3 || i == 7) {
+            continue;
+        }
+        sum = sum + i;
+    }
+    return sum;
+}
+
+/* continue in a while loop re-checks the condition */
+int while_continue(void) {
+    int i = 0;
+    int sum = 0;
+    while (i < 10) {
+        i = i + 1;
+        if (i > 3) {
+            continue;
+        }
+        sum = sum + i;
+    }
+    return sum;
+}
+
+/* continue in a do-while loop goes to the condition, not the top of the body */
+int do_while_continue(void) {
+    int i = 0;
+    int runs = 0;
+    do {
+        runs = runs + 1;
+        i = i + 1;
+        continue;
+    } while (i < 3);
+    return runs;
+}
+
+/* continue inside a switch refers to the enclosing loop, and break in a do-while leaves it */
+int continue_in_switch(void) {
+    int i = 0;
+    int sum = 0;
+    do {
+        i = i + 1;
+        switch (i) {
+            case 2:
+                continue;
+            case 5:
+                break;
+        }
+        if (i == 6) {
+            break;
+        }
+        sum = sum + i;
+    } while (1);
+    return sum;
+}
+
+int main() {
+    if (do_while_once() != 1) {
+        return 1;
+    }
+    if (do_while_sum() != 10) {
+        return 2;
+    }
+    if (for_continue() != 35) {
+        return 3;
+    }
+    if (while_continue() != 6) {
+        return 4;
+    }
+    if (do_while_continue() != 3) {
+        return 5;
+    }
+    if (continue_in_switch() != 13) {
+        return 6;
+    }
+    return 0;
+}
@@ -0,0 +1,72 @@
+
+int main() {
+    double zero = 0.0;
+    double nan = zero / zero;
+    float nanf = (float)nan;
+
+    /* NaN is unordered, so only != is true */
+    if (nan == nan || nan < 1.0 || nan <= 1.0 || nan > 1.0 || nan >= 1.0) {
+        return 1;
+    }
+    if ((nan != nan) == 0 || (nanf != nanf) == 0) {
+        return 2;
+    }
+    if (nanf == nanf || nanf < 0.0f || nanf <= 0.0f) {
+        return 3;
+    }
+
+    /* branches on a float condition */
+    int taken = 0;
+    if (nan) {
+        taken = 1;
+    }
+    if (taken == 0) {
+        return 4;
+    }
+    _Bool truthy = nan;
+    _Bool falsy = zero;
+    if (truthy == 0 || falsy) {
+        return 5;
+    }
+
+    /* arithmetic and negation */
+    float f = 1.5f;
+    double d = -f * 4.0 - 1.0;
+    if (d != -7.0) {
+        return 6;
+    }
+    if ((int)(d / 2.0) != -3) {
+        return 7;
+    }
+
+    /* unsigned 64 bit integers with the top bit set */
+    unsigned long max = 0;
+    max = max - 1;
+    double max_as_double = max;
+    if (max_as_double != 18446744073709551616.0) {
+        return 8;
+    }
+    float max_as_float = max;
+    if (max_as_float != 18446744073709551616.0f) {
+        return 9;
+    }
+
+    double big = 10000000000000000000.0;
+    unsigned long big_int = big;
+    if (big_int / 1000000000000000000 != 10) {
+        return 10;
+    }
+    unsigned long small_int = 3.99f;
+    if (small_int != 3) {
+        return 11;
+    }
+
+    /* float to smaller integers */
+    char c = -2.5;
+    unsigned short s = 65000.0f;
+    if (c != -2 || s != 65000) {
+        return 12;
+    }
+
+    return 0;
+}
@@ -0,0 +1,59 @@
+
+typedef int (*binop)(int, int);
+typedef int unary_fn(int);
+
+int add(int a, int b) { return a + b; }
+int sub(int a, int b) { return a - b; }
+int mul(int a, int b) { return a * b; }
+
+int square(int x) { return x * x; }
+int negate(int x) { return -x; }
+
+/* callbacks can be declared with a full declarator or a typedef */
+int apply(int (*f)(int), int x) {
+    return f(x);
+}
+int apply_twice(unary_fn f, int x) {
+    return f(f(x));
+}
+int fold(binop op, int start, int a, int b) {
+    return op(op(start, a), b);
+}
+
+/* chooses an operation at runtime */
+binop pick(int which) {
+    switch (which) {
+        case 0: return add;
+        case 1: return sub;
+    }
+    return mul;
+}
+
+int main() {
+    int (*fp)(int) = square;
+    if (fp(7) != 49) return 1;
+    if ((*fp)(3) != 9) return 2;
+
+    /* &function and *pointer both give the same function */
+    fp = &negate;
+    if (fp(5) != -5) return 3;
+    if ((**fp)(4) != -4) return 4;
+
+    /* function designators decay to pointers when passed */
+    if (apply(square, 6) != 36) return 5;
+    if (apply(fp, 6) != -6) return 6;
+    if (apply_twice(square, 3) != 81) return 7;
+
+    binop op = pick(1);
+    if (op(10, 4) != 6) return 8;
+    if (op != sub) return 9;
+    if (pick(2)(3, 4) != 12) return 10;
+    if (fold(add, 1, 2, 3) != 6) return 11;
+    if (fold(pick(2), 1, 2, 3) != 6) return 12;
+
+    /* a pointer to a function that returns a function pointer */
+    binop (*picker)(int) = pick;
+    if (picker(0)(2, 3) != 5) return 13;
+
+    return 0;
+}
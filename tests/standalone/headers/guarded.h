@@ -0,0 +1,11 @@
+#ifndef GUARDED_H
+#define GUARDED_H
+
+//found beside this header, not beside the file that included it
+#include "sibling.h"
+
+int guarded_value() {
+    return SIBLING_VALUE;
+}
+
+#endif
\ No newline at end of file
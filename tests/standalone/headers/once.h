@@ -0,0 +1,6 @@
+#pragma once
+
+#ifdef ONCE_VALUE
+#error once.h was included twice
+#endif
+#define ONCE_VALUE 4
\ No newline at end of file
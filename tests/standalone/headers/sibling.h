@@ -0,0 +1 @@
+#define SIBLING_VALUE 3
\ No newline at end of file
@@ -0,0 +1,4 @@
+//wraps the real stdio.h, which #include_next finds in the search path
+#include_next <stdio.h>
+
+#define WRAPPED_STDIO 1
\ No newline at end of file
@@ -0,0 +1,25 @@
+//quoted includes are found beside this file, wherever the compiler is run from
+#include "headers/guarded.h"
+#include "headers/guarded.h"
+#include "headers/once.h"
+#include "headers/once.h"
+#include "headers/stdio.h"
+
+#ifndef WRAPPED_STDIO
+#error headers/stdio.h was not included
+#endif
+
+#if !__has_include("headers/once.h")
+#error __has_include could not find a header beside this file
+#endif
+#if __has_include(<nonexistent_header.h>)
+#error __has_include found a header that does not exist
+#endif
+#if !defined(__has_include) || !__has_include(<stdio.h>)
+#error __has_include could not find stdio.h
+#endif
+
+int main() {
+    printf("%d %d\n", guarded_value(), ONCE_VALUE);
+    return 0;
+}
\ No newline at end of file
@@ -0,0 +1,11 @@
+#define HERE __LINE__
+#define LINE_OF(x) HERE
+
+int main() {
+    if (HERE != 5) {return 1;}
+    /* a comment
+       spanning lines */ if (LINE_OF(0) != 7) {return 2;}
+    int here = HERE;
+
+    return here - 8;
+}
\ No newline at end of file
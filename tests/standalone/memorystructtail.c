@@ -0,0 +1,49 @@
+//structs bigger than 16 bytes are passed on the stack, including the bytes after their last whole eightbyte
+struct nineteen {
+    char bytes[19];
+};
+struct twentytwo {
+    short halves[11];
+};
+
+int check_nineteen(struct nineteen s) {
+    unsigned char* bytes = (unsigned char*)&s;
+    for (int i = 0; i < 19; i = i + 1) {
+        if (bytes[i] != i + 1) {
+            return i + 1;
+        }
+    }
+    return 0;
+}
+
+int check_twentytwo(int before, struct twentytwo s, int after) {
+    if (before != 7 || after != 9) {
+        return 50;
+    }
+    unsigned char* bytes = (unsigned char*)&s;
+    for (int i = 0; i < 22; i = i + 1) {
+        if (bytes[i] != 100 + i) {
+            return 20 + i;
+        }
+    }
+    return 0;
+}
+
+int main() {
+    struct nineteen small;
+    unsigned char* small_bytes = (unsigned char*)&small;
+    for (int i = 0; i < 19; i = i + 1) {
+        small_bytes[i] = i + 1;
+    }
+    int result = check_nineteen(small);
+    if (result != 0) {
+        return result;
+    }
+
+    struct twentytwo big;
+    unsigned char* big_bytes = (unsigned char*)&big;
+    for (int i = 0; i < 22; i = i + 1) {
+        big_bytes[i] = 100 + i;
+    }
+    return check_twentytwo(7, big, 9);
+}
\ No newline at end of file
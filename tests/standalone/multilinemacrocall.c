@@ -0,0 +1,43 @@
+#define ADD(a, b) ((a) + (b))
+#define EXTRA
+
+//a name that expands to a function-like macro, which is called by the tokens after it
+#define INC(x) ((x) + 1)
+#define ALIAS INC
+
+//these are painted blue, so are not expanded again
+#define self self
+#define ping pong
+#define pong ping
+
+//the C11 example, where `g` is expanded but the `f` it makes is not
+#define f(a) a*g
+#define g(a) f(a)
+
+int main() {
+    int total = ADD(1,
+#ifdef EXTRA
+        100
+#else
+        2
+#endif
+    );
+    if (total != 101) {return 1;}
+
+    int next_line = ADD
+    (3, 4);
+    if (next_line != 7) {return 2;}
+
+    if (ALIAS(1) != 2) {return 3;}
+
+    int self = 5;
+    if (self != 5) {return 4;}
+
+    int ping = 6;
+    if (ping != 6) {return 5;}
+
+    int g = 3;
+    if (f(2)(9) != 54) {return 6;}
+
+    return 0;
+}
\ No newline at end of file
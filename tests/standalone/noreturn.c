@@ -0,0 +1,20 @@
+#include <stdlib.h>
+#include <stdnoreturn.h>
+
+noreturn void finish(int code);
+
+void finish(int code) {
+    exit(code);
+}
+
+int pick(int x) {
+    if (x > 2) {
+        return x;
+    }
+    finish(7);
+}
+
+int main() {
+    pick(1);
+    return 0;
+}
\ No newline at end of file
@@ -0,0 +1,86 @@
+//pragmas that aren't known are ignored
+#pragma unknown_to_this_compiler
+#pragma GCC diagnostic push
+#pragma GCC diagnostic ignored "-Wunknown-pragmas"
+#pragma also_unknown
+#pragma GCC diagnostic pop
+
+#pragma pack(push, 1)
+struct Packed {
+    char c;
+    int i;
+};
+#pragma pack(pop)
+
+struct Natural {
+    char c;
+    int i;
+};
+
+#pragma pack(2)
+struct PackedTwo {
+    char c;
+    int i;
+};
+#pragma pack()
+
+//_Pragma can come from a macro
+#define PACKED(definition) _Pragma("pack(push, 1)") definition _Pragma("pack(pop)")
+PACKED(struct PackedLong {
+    char c;
+    long l;
+};)
+
+//the packing comes from where the struct starts, not where its body ends
+#pragma pack(push, 1)
+struct PoppedBeforeBody
+#pragma pack(pop)
+{
+    char c;
+    int i;
+};
+
+//unions are padded to their most aligned member, unless packed
+union NaturalUnion {
+    int i;
+    char bytes[5];
+};
+#pragma pack(push, 1)
+union PackedUnion {
+    int i;
+    char bytes[5];
+};
+#pragma pack(pop)
+
+int main() {
+    struct Packed packed;
+    packed.c = 1;
+    packed.i = 1000;
+
+    if (sizeof(struct Packed) != 5) {
+        return 1;
+    }
+    if (sizeof(struct Natural) != 8) {
+        return 2;
+    }
+    if (sizeof(struct PackedTwo) != 6) {
+        return 3;
+    }
+    if (sizeof(struct PackedLong) != 9) {
+        return 4;
+    }
+    if (packed.c + packed.i != 1001) {
+        return 5;
+    }
+    if (sizeof(struct PoppedBeforeBody) != 5) {
+        return 6;
+    }
+    if (sizeof(union NaturalUnion) != 8) {
+        return 7;
+    }
+    if (sizeof(union PackedUnion) != 5) {
+        return 8;
+    }
+
+    return 0;
+}
\ No newline at end of file
@@ -0,0 +1,40 @@
+#define STR(x) #x
+#define XSTR(x) STR(x)
+#define CAT(a, b) a ## b
+#define VALUE 42
+
+int same(char *a, char *b) {
+    while (*a && *a == *b) {
+        a = a + 1;
+        b = b + 1;
+    }
+    return *a == *b;
+}
+
+int main() {
+    int xy = 5;
+    if (same(STR(hello), "hello") == 0) {return 1;}
+    if (same(STR(  a   +  b  ), "a + b") == 0) {return 2;}
+    if (same(STR(a+b), "a+b") == 0) {return 3;}
+    if (same(STR("quote\n"), "\"quote\\n\"") == 0) {return 4;}
+    if (same(STR(VALUE), "VALUE") == 0) {return 5;}//stringified args are not expanded
+    if (same(XSTR(VALUE), "42") == 0) {return 6;}
+    if (CAT(x, y) != 5) {return 7;}
+    if (CAT(4, 2) != 42) {return 8;}
+    if (CAT(VAL, UE) != 42) {return 9;}//the pasted token is expanded
+    if (CAT(, xy) != 5) {return 10;}//an empty arg pastes as nothing
+    if (same(STR(), "") == 0) {return 11;}
+
+    //numbers and chars keep how they were written
+    if (same(STR(0x10), "0x10") == 0) {return 12;}
+    if (same(STR(1.5), "1.5") == 0) {return 13;}
+    if (same(STR(1.5e3f), "1.5e3f") == 0) {return 14;}
+    if (same(STR(10UL), "10UL") == 0) {return 15;}
+    if (same(STR('a'), "'a'") == 0) {return 16;}
+    if (same(STR('\n'), "'\\n'") == 0) {return 17;}
+    if (CAT(0x1, 0) != 16) {return 18;}
+    if (CAT(1, 0u) != 10) {return 19;}
+    if (CAT(1., 5) != 1.5) {return 20;}
+
+    return 0;
+}
\ No newline at end of file
@@ -0,0 +1,48 @@
+//small structs are passed in registers, whether they are read through a pointer or from a global
+struct pair {
+    int first;
+    long second;
+};
+struct mixed {
+    char tag;
+    double value;
+};
+
+struct pair global_pair;
+struct mixed global_mixed;
+
+int check_pair(struct pair p, int expected_first, long expected_second) {
+    return p.first == expected_first && p.second == expected_second;
+}
+
+int check_mixed(struct mixed m) {
+    return m.tag == 'm' && m.value == 2.5;
+}
+
+int main() {
+    global_pair.first = 3;
+    global_pair.second = 4000000000;
+    global_mixed.tag = 'm';
+    global_mixed.value = 2.5;
+
+    struct pair local;
+    local.first = 5;
+    local.second = 6;
+    struct pair* p = &local;
+
+    if (check_pair(*p, 5, 6) == 0) {
+        return 1;
+    }
+    if (check_pair(global_pair, 3, 4000000000) == 0) {
+        return 2;
+    }
+    if (check_mixed(global_mixed) == 0) {
+        return 3;
+    }
+
+    struct mixed* m = &global_mixed;
+    if (check_mixed(*m) == 0) {
+        return 4;
+    }
+    return 0;
+}
\ No newline at end of file
@@ -0,0 +1,123 @@
+
+/* Few cases, so this uses a chain of comparisons */
+int sparse(int x) {
+    switch (x) {
+        case 1:
+            return 10;
+        case 1000:
+            return 20;
+        case -50:
+            return 30;
+    }
+    return 40;
+}
+
+/* Dense cases, so this uses a jump table */
+int dense(int x) {
+    int result = 0;
+    switch (x) {
+        case 0: result = 5; break;
+        case 1: result = 6; break;
+        case 2: result = 7; break;
+        case 4: result = 8; break;
+        case 5: result = 9; break;
+        default: result = 100; break;
+    }
+    return result;
+}
+
+/* Dense cases with negative values and no default */
+int dense_negative(int x) {
+    int result = 1;
+    switch (x) {
+        case -2: result = 2; break;
+        case -1: result = 3; break;
+        case 0: result = 4; break;
+        case 1: result = 5; break;
+    }
+    return result;
+}
+
+/* Cases without a break fall through to the next one */
+int fallthrough(int x) {
+    int total = 0;
+    switch (x) {
+        case 1:
+            total = total + 1;
+        case 2:
+            total = total + 2;
+        default:
+            total = total + 4;
+        case 3:
+            total = total + 8;
+    }
+    return total;
+}
+
+/* Case labels are constant expressions */
+int folded(char c) {
+    switch (c) {
+        case 'a':
+            return 1;
+        case 2 * 3 + 1:
+            return 2;
+        case 1 ? 20 : 30:
+            return 3;
+    }
+    return 0;
+}
+
+/* break inside a switch inside a loop only leaves the switch */
+int in_loop(void) {
+    int sum = 0;
+    int i;
+    for (i = 0; i < 5; i = i + 1) {
+        switch (i) {
+            case 2:
+                break;
+            default:
+                sum = sum + i;
+        }
+    }
+    return sum;
+}
+
+/* Nested switch statements own their own cases */
+int nested(int x, int y) {
+    switch (x) {
+        case 1:
+            switch (y) {
+                case 1: return 11;
+                default: return 19;
+            }
+        case 2:
+            return 20;
+        default:
+            return 0;
+    }
+}
+
+int main() {
+    if (sparse(1) != 10 || sparse(1000) != 20 || sparse(-50) != 30 || sparse(7) != 40) {
+        return 1;
+    }
+    if (dense(0) != 5 || dense(2) != 7 || dense(3) != 100 || dense(5) != 9 || dense(6) != 100 || dense(-1) != 100) {
+        return 2;
+    }
+    if (dense_negative(-2) != 2 || dense_negative(1) != 5 || dense_negative(-3) != 1 || dense_negative(2) != 1) {
+        return 3;
+    }
+    if (fallthrough(1) != 15 || fallthrough(2) != 14 || fallthrough(3) != 8 || fallthrough(9) != 12) {
+        return 4;
+    }
+    if (folded('a') != 1 || folded(7) != 2 || folded(20) != 3 || folded(30) != 0) {
+        return 5;
+    }
+    if (in_loop() != 8) {
+        return 6;
+    }
+    if (nested(1, 1) != 11 || nested(1, 2) != 19 || nested(2, 1) != 20 || nested(3, 3) != 0) {
+        return 7;
+    }
+    return 0;
+}
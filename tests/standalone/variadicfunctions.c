@@ -0,0 +1,139 @@
+#include <stdarg.h>
+#include <stdio.h>
+
+/* sums `count` ints passed after it */
+int sum(int count, ...) {
+    va_list args;
+    va_start(args, count);
+
+    int total = 0;
+    for (int i = 0; i < count; i += 1) {
+        total += va_arg(args, int);
+    }
+
+    va_end(args);
+    return total;
+}
+
+/* reads from a va_list that was passed as a param */
+long nth_from_list(int n, va_list args) {
+    long result = 0;
+    for (int i = 0; i <= n; i += 1) {
+        result = va_arg(args, long);
+    }
+    return result;
+}
+
+long nth(int n, ...) {
+    va_list args;
+    va_start(args, n);
+    long result = nth_from_list(n, args);
+    va_end(args);
+    return result;
+}
+
+/* walks the args twice, using a copy of the va_list */
+int sum_and_max(int count, ...) {
+    va_list args;
+    va_list copy;
+    va_start(args, count);
+    va_copy(copy, args);
+
+    int total = 0;
+    for (int i = 0; i < count; i += 1) {
+        total += va_arg(args, int);
+    }
+
+    int max = va_arg(copy, int);
+    for (int i = 1; i < count; i += 1) {
+        int next = va_arg(copy, int);
+        if (next > max) max = next;
+    }
+
+    va_end(copy);
+    va_end(args);
+    return total * 100 + max;
+}
+
+/* pointers and negative numbers come out unchanged */
+long deref_sum(char first, ...) {
+    va_list args;
+    va_start(args, first);
+    int* a = va_arg(args, int*);
+    int* b = va_arg(args, int*);
+    long c = va_arg(args, long);
+    va_end(args);
+    return first + *a + *b + c;
+}
+
+/* doubles are read from the vector register save area, then the stack once the 8 vector registers run out */
+double sum_doubles(int count, ...) {
+    va_list args;
+    va_start(args, count);
+
+    double total = 0.0;
+    for (int i = 0; i < count; i += 1) {
+        total += va_arg(args, double);
+    }
+
+    va_end(args);
+    return total;
+}
+
+/* ints and doubles overflow onto the stack separately, but are stored there in the order they were passed */
+double weighted_sum(int count, ...) {
+    va_list args;
+    va_start(args, count);
+
+    double total = 0.0;
+    for (int i = 0; i < count; i += 1) {
+        int weight = va_arg(args, int);
+        double value = va_arg(args, double);
+        total += weight * value;
+    }
+
+    va_end(args);
+    return total;
+}
+
+/* a logging wrapper that forwards its args to libc */
+int log_value(char* format, ...) {
+    va_list args;
+    va_start(args, format);
+    int printed = vprintf(format, args);
+    va_end(args);
+    return printed;
+}
+
+int main() {
+    if (sum(0) != 0) return 1;
+    if (sum(3, 1, 2, 3) != 6) return 2;
+    if (sum(5, 10, -20, 30, -40, 50) != 30) return 3;
+
+    if (nth(0, 7L, 8L) != 7) return 4;
+    if (nth(2, 1L, 2L, 10000000000) != 10000000000) return 5;
+
+    if (sum_and_max(4, 3, 9, -2, 5) != 1509) return 6;
+
+    int x = 40;
+    int y = 2;
+    if (deref_sum(-1, &x, &y, -3L) != 38) return 7;
+
+    char format[6] = {'x', '=', '%', 'd', '\n', 0};
+    if (log_value(format, 42) != 5) return 8;
+
+    //only the first 5 varargs fit in the GP registers left after count
+    if (sum(10, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10) != 55) return 9;
+
+    if (sum_doubles(3, 1.5, 2.25, 0.25) != 4.0) return 10;
+    //the last 2 doubles are passed on the stack
+    if (sum_doubles(10, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0) != 55.0) return 11;
+
+    //4 ints and 1 double are passed on the stack
+    if (weighted_sum(9, 1, 0.5, 2, 0.5, 3, 0.5, 4, 0.5, 5, 0.5, 6, 0.5, 7, 0.5, 8, 0.5, 9, 0.25) != 20.25) return 12;
+
+    //printf only reads the vector registers if al says they are used
+    printf("%f %f\n", 1.5, sum_doubles(2, 0.5, 0.25));
+
+    return 0;
+}
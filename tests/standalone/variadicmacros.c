@@ -0,0 +1,29 @@
+#include <stdio.h>
+
+#define LOG(fmt, ...) printf(fmt, __VA_ARGS__)
+#define GNU_LOG(fmt, ...) printf(fmt, ## __VA_ARGS__)
+#define NAMED_LOG(fmt, args...) printf(fmt, ## args)
+#define OPT_LOG(fmt, ...) printf(fmt __VA_OPT__(,) __VA_ARGS__)
+#define STR(...) #__VA_ARGS__
+#define FIRST(x, ...) x
+#define SUM(...) sum3(__VA_ARGS__)
+
+int sum3(int a, int b, int c) {
+    return a + b + c;
+}
+
+int main() {
+    LOG("%d %d\n", 1, 2);
+    GNU_LOG("no args\n");
+    GNU_LOG("%d\n", 3);
+    NAMED_LOG("named\n");
+    NAMED_LOG("%s\n", "named args");
+    OPT_LOG("opt\n");
+    OPT_LOG("%d %d\n", 4, 5);
+    LOG("%s\n", STR(a, b));
+
+    if (FIRST(6, 7, 8) != 6) {return 1;}
+    if (SUM(1, 2, 3) != 6) {return 2;}
+
+    return 0;
+}
\ No newline at end of file